@@ -119,6 +119,7 @@ pub enum TokenKind {
     If,
     Implement,
     Import,
+    Inline,
     Integer,
     Invalid,
     InvalidUnicodeEscape,
@@ -218,6 +219,7 @@ impl TokenKind {
             TokenKind::If => "the 'if' keyword",
             TokenKind::Implement => "the 'impl' keyword",
             TokenKind::Import => "the 'import' keyword",
+            TokenKind::Inline => "the 'inline' keyword",
             TokenKind::Integer => "an integer",
             TokenKind::Invalid => "an invalid token",
             TokenKind::InvalidUnicodeEscape => {
@@ -342,6 +344,7 @@ impl Token {
                 | TokenKind::Case
                 | TokenKind::Enum
                 | TokenKind::Extern
+                | TokenKind::Inline
         )
     }
 
@@ -997,6 +1000,7 @@ impl Lexer {
                 "return" => TokenKind::Return,
                 "static" => TokenKind::Static,
                 "extern" => TokenKind::Extern,
+                "inline" => TokenKind::Inline,
                 _ => TokenKind::Identifier,
             },
             7 => match value.as_str() {
@@ -1437,6 +1441,7 @@ mod tests {
         assert!(tok(TokenKind::If, "", 1..=1, 1..=1).is_keyword());
         assert!(tok(TokenKind::Implement, "", 1..=1, 1..=1).is_keyword());
         assert!(tok(TokenKind::Import, "", 1..=1, 1..=1).is_keyword());
+        assert!(tok(TokenKind::Inline, "", 1..=1, 1..=1).is_keyword());
         assert!(tok(TokenKind::Let, "", 1..=1, 1..=1).is_keyword());
         assert!(tok(TokenKind::Loop, "", 1..=1, 1..=1).is_keyword());
         assert!(tok(TokenKind::Match, "", 1..=1, 1..=1).is_keyword());
@@ -2076,6 +2081,7 @@ mod tests {
         assert_token!("return", Return, "return", 1..=1, 1..=6);
         assert_token!("static", Static, "static", 1..=1, 1..=6);
         assert_token!("extern", Extern, "extern", 1..=1, 1..=6);
+        assert_token!("inline", Inline, "inline", 1..=1, 1..=6);
 
         assert_token!("builtin", Builtin, "builtin", 1..=1, 1..=7);
         assert_token!("recover", Recover, "recover", 1..=1, 1..=7);
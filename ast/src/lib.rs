@@ -2,4 +2,5 @@
 pub mod lexer;
 pub mod nodes;
 pub mod parser;
+pub mod printer;
 pub mod source_location;
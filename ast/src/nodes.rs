@@ -397,6 +397,7 @@ pub enum MethodKind {
 #[derive(Debug, PartialEq, Eq)]
 pub struct DefineMethod {
     pub public: bool,
+    pub tail: bool,
     pub kind: MethodKind,
     pub operator: bool,
     pub name: Identifier,
@@ -453,6 +454,7 @@ pub enum ClassKind {
     Enum,
     Regular,
     Extern,
+    Inline,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -461,6 +463,7 @@ pub struct DefineClass {
     pub kind: ClassKind,
     pub name: Constant,
     pub type_parameters: Option<TypeParameters>,
+    pub derive: Option<TypeNames>,
     pub body: ClassExpressions,
     pub location: SourceLocation,
 }
@@ -697,6 +700,7 @@ pub enum Expression {
     Match(Box<Match>),
     Loop(Box<Loop>),
     While(Box<While>),
+    For(Box<For>),
     True(Box<True>),
     False(Box<False>),
     Nil(Box<Nil>),
@@ -766,6 +770,7 @@ impl Node for Expression {
             Expression::Tuple(ref typ) => typ.location(),
             Expression::TypeCast(ref typ) => typ.location(),
             Expression::While(ref typ) => typ.location(),
+            Expression::For(ref typ) => typ.location(),
             Expression::Mut(ref typ) => typ.location(),
             Expression::Recover(ref typ) => typ.location(),
         }
@@ -1482,6 +1487,20 @@ impl Node for While {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct For {
+    pub pattern: Pattern,
+    pub iterator: Expression,
+    pub body: Expressions,
+    pub location: SourceLocation,
+}
+
+impl Node for For {
+    fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Module {
     pub expressions: Vec<TopLevelExpression>,
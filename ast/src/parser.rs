@@ -348,25 +348,78 @@ impl Parser {
         &mut self,
         start: Token,
     ) -> Result<Expression, ParseError> {
-        let mut left = self.const_value(start)?;
+        let mut left = self.const_postfix(start)?;
 
-        while let Some(operator) = self.binary_operator() {
-            let rhs_token = self.require()?;
-            let right = self.const_value(rhs_token)?;
-            let location =
-                SourceLocation::start_end(left.location(), right.location());
+        loop {
+            if let Some(operator) = self.binary_operator() {
+                let rhs_token = self.require()?;
+                let right = self.const_postfix(rhs_token)?;
+                let location = SourceLocation::start_end(
+                    left.location(),
+                    right.location(),
+                );
 
-            left = Expression::Binary(Box::new(Binary {
-                operator,
-                left,
-                right,
-                location,
-            }));
+                left = Expression::Binary(Box::new(Binary {
+                    operator,
+                    left,
+                    right,
+                    location,
+                }));
+            } else if self.peek().kind == TokenKind::As {
+                self.next();
+
+                let cast_token = self.require()?;
+                let cast_to = self.type_reference(cast_token)?;
+                let location = SourceLocation::start_end(
+                    left.location(),
+                    cast_to.location(),
+                );
+
+                left = Expression::TypeCast(Box::new(TypeCast {
+                    value: left,
+                    cast_to,
+                    location,
+                }));
+            } else {
+                break;
+            }
         }
 
         Ok(left)
     }
 
+    fn const_postfix(
+        &mut self,
+        start: Token,
+    ) -> Result<Expression, ParseError> {
+        let mut node = self.const_value(start)?;
+
+        while self.peek().kind == TokenKind::Dot {
+            node = self.const_call(node)?;
+        }
+
+        Ok(node)
+    }
+
+    fn const_call(
+        &mut self,
+        receiver: Expression,
+    ) -> Result<Expression, ParseError> {
+        self.next();
+
+        let name = Identifier::from(self.expect(TokenKind::Identifier)?);
+        let arguments = self.arguments(name.location())?;
+        let end_loc = location!(arguments).unwrap_or_else(|| name.location());
+        let location = SourceLocation::start_end(receiver.location(), end_loc);
+
+        Ok(Expression::Call(Box::new(Call {
+            receiver: Some(receiver),
+            name,
+            arguments,
+            location,
+        })))
+    }
+
     fn const_value(&mut self, start: Token) -> Result<Expression, ParseError> {
         let value = match start.kind {
             TokenKind::Float => self.float_literal(start),
@@ -412,6 +465,35 @@ impl Parser {
     fn const_group(&mut self, start: Token) -> Result<Expression, ParseError> {
         let value_token = self.require()?;
         let value = self.const_expression(value_token)?;
+
+        if self.peek().kind == TokenKind::Comma {
+            let mut values = vec![value];
+
+            self.next();
+
+            loop {
+                let token = self.require()?;
+
+                if token.kind == TokenKind::ParenClose {
+                    let location = SourceLocation::start_end(
+                        &start.location,
+                        &token.location,
+                    );
+
+                    return Ok(Expression::Tuple(Box::new(Tuple {
+                        values,
+                        location,
+                    })));
+                }
+
+                values.push(self.const_expression(token)?);
+
+                if self.peek().kind != TokenKind::ParenClose {
+                    self.expect(TokenKind::Comma)?;
+                }
+            }
+        }
+
         let end = self.expect(TokenKind::ParenClose)?;
         let location =
             SourceLocation::start_end(&start.location, &end.location);
@@ -855,8 +937,14 @@ impl Parser {
             _ => MethodKind::Instance,
         };
 
-        let name_token = self.require()?;
-        let (name, operator) = self.method_name(name_token)?;
+        let (name, operator, tail) = if let MethodKind::Extern = kind {
+            let name_token = self.require()?;
+            let (name, operator) = self.method_name(name_token)?;
+
+            (name, operator, false)
+        } else {
+            self.method_name_with_tail()?
+        };
         let type_parameters = if let MethodKind::Extern = kind {
             None
         } else {
@@ -882,6 +970,7 @@ impl Parser {
 
         Ok(TopLevelExpression::DefineMethod(Box::new(DefineMethod {
             public,
+            tail,
             operator,
             name,
             type_parameters,
@@ -923,8 +1012,7 @@ impl Parser {
             }
             _ => MethodKind::Instance,
         };
-        let name_token = self.require()?;
-        let (name, operator) = self.method_name(name_token)?;
+        let (name, operator, tail) = self.method_name_with_tail()?;
         let type_parameters = self.optional_type_parameter_definitions()?;
         let arguments = self.optional_method_arguments(false)?;
         let return_type = self.optional_return_type()?;
@@ -935,6 +1023,7 @@ impl Parser {
 
         Ok(DefineMethod {
             public,
+            tail,
             operator,
             name,
             type_parameters,
@@ -962,8 +1051,7 @@ impl Parser {
             }
             _ => MethodKind::Instance,
         };
-        let name_token = self.require()?;
-        let (name, operator) = self.method_name(name_token)?;
+        let (name, operator, tail) = self.method_name_with_tail()?;
         let type_parameters = self.optional_type_parameter_definitions()?;
         let arguments = self.optional_method_arguments(false)?;
         let return_type = self.optional_return_type()?;
@@ -974,6 +1062,7 @@ impl Parser {
 
         Ok(DefineMethod {
             public,
+            tail,
             operator,
             name,
             type_parameters,
@@ -985,6 +1074,37 @@ impl Parser {
         })
     }
 
+    fn method_name_with_tail(
+        &mut self,
+    ) -> Result<(Identifier, bool, bool), ParseError> {
+        let start = self.require()?;
+
+        // `tail` isn't a keyword, so we only treat it as one if it's followed
+        // by the method name on the same line. This way methods can still be
+        // named `tail`, including required methods without a body.
+        let tail =
+            start.kind == TokenKind::Identifier && start.value == "tail" && {
+                let next = self.peek();
+
+                next.same_line_as(&start)
+                    && (next.kind == TokenKind::Identifier
+                        || next.kind == TokenKind::Constant
+                        || next.is_keyword()
+                        || next.is_operator())
+            };
+
+        if tail {
+            let name_token = self.require()?;
+            let (name, operator) = self.method_name(name_token)?;
+
+            Ok((name, operator, true))
+        } else {
+            let (name, operator) = self.method_name(start)?;
+
+            Ok((name, operator, false))
+        }
+    }
+
     fn method_name(
         &mut self,
         start: Token,
@@ -1042,6 +1162,10 @@ impl Parser {
                 self.next();
                 ClassKind::Extern
             }
+            TokenKind::Inline => {
+                self.next();
+                ClassKind::Inline
+            }
             _ => ClassKind::Regular,
         };
 
@@ -1052,6 +1176,12 @@ impl Parser {
             self.optional_type_parameter_definitions()?
         };
 
+        let derive = if let ClassKind::Extern = kind {
+            None
+        } else {
+            self.optional_derive()?
+        };
+
         let body = if let ClassKind::Extern = kind {
             self.extern_class_expressions()?
         } else {
@@ -1066,11 +1196,30 @@ impl Parser {
             kind,
             name,
             type_parameters,
+            derive,
             body,
             location,
         })))
     }
 
+    fn optional_derive(&mut self) -> Result<Option<TypeNames>, ParseError> {
+        let token = self.peek();
+
+        if token.kind != TokenKind::Identifier || token.value != "derive" {
+            return Ok(None);
+        }
+
+        self.next();
+
+        let (values, location) = self.list(
+            TokenKind::ParenOpen,
+            TokenKind::ParenClose,
+            |parser, token| parser.type_name_with_optional_namespace(token),
+        )?;
+
+        Ok(Some(TypeNames { values, location }))
+    }
+
     fn define_variant(
         &mut self,
         start: Token,
@@ -1464,6 +1613,7 @@ impl Parser {
 
         Ok(DefineMethod {
             public,
+            tail: false,
             operator,
             name,
             type_parameters,
@@ -1666,6 +1816,7 @@ impl Parser {
             TokenKind::Nil => self.nil_literal(start),
             TokenKind::Try => self.try_expression(start)?,
             TokenKind::While => self.while_expression(start)?,
+            TokenKind::For => self.for_expression(start)?,
             TokenKind::Let => self.define_variable(start)?,
             _ => {
                 error!(start.location, "'{}' can't be used here", start.value)
@@ -2549,6 +2700,7 @@ impl Parser {
             | TokenKind::Field
             | TokenKind::Float
             | TokenKind::Fn
+            | TokenKind::For
             | TokenKind::Identifier
             | TokenKind::If
             | TokenKind::Integer
@@ -2936,6 +3088,29 @@ impl Parser {
         Ok(Expression::While(Box::new(While { condition, body, location })))
     }
 
+    fn for_expression(
+        &mut self,
+        start: Token,
+    ) -> Result<Expression, ParseError> {
+        let pattern = self.pattern()?;
+        let token = self.require()?;
+
+        if token.kind != TokenKind::Identifier || token.value != "in" {
+            error!(
+                token.location,
+                "Expected 'in', found '{}' instead", token.value
+            );
+        }
+
+        let iterator = self.expression_without_trailing_block()?;
+        let body_token = self.expect(TokenKind::CurlyOpen)?;
+        let body = self.expressions(body_token)?;
+        let location =
+            SourceLocation::start_end(&start.location, body.location());
+
+        Ok(Expression::For(Box::new(For { pattern, iterator, body, location })))
+    }
+
     fn if_condition(&mut self) -> Result<IfCondition, ParseError> {
         let condition = self.expression_without_trailing_block()?;
         let token = self.expect(TokenKind::CurlyOpen)?;
@@ -3707,9 +3882,110 @@ mod tests {
 
     #[test]
     fn test_invalid_constants() {
-        assert_error!("let A = B.new", cols(10, 10));
+        assert_error!("let A = B.C", cols(11, 11));
         assert_error!("let A = B { }", cols(11, 11));
-        assert_error!("let A = (B.new)", cols(11, 11));
+        assert_error!("let A = (B.C)", cols(12, 12));
+        assert_error!("let A = (10, 20", cols(15, 15));
+        assert_error!("let A = (10, 20 30)", cols(17, 18));
+        assert_error!("let A = 10 as", cols(13, 13));
+    }
+
+    #[test]
+    fn test_constant_with_tuple() {
+        assert_eq!(
+            top(parse("let A = (10, 20)")),
+            TopLevelExpression::DefineConstant(Box::new(DefineConstant {
+                public: false,
+                name: Constant {
+                    source: None,
+                    name: "A".to_string(),
+                    location: cols(5, 5)
+                },
+                value: Expression::Tuple(Box::new(Tuple {
+                    values: vec![
+                        Expression::Int(Box::new(IntLiteral {
+                            value: "10".to_string(),
+                            location: cols(10, 11)
+                        })),
+                        Expression::Int(Box::new(IntLiteral {
+                            value: "20".to_string(),
+                            location: cols(14, 15)
+                        })),
+                    ],
+                    location: cols(9, 16)
+                })),
+                location: cols(1, 16)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_constant_with_method_call() {
+        assert_eq!(
+            top(parse("let A = B.foo(10)")),
+            TopLevelExpression::DefineConstant(Box::new(DefineConstant {
+                public: false,
+                name: Constant {
+                    source: None,
+                    name: "A".to_string(),
+                    location: cols(5, 5)
+                },
+                value: Expression::Call(Box::new(Call {
+                    receiver: Some(Expression::Constant(Box::new(Constant {
+                        source: None,
+                        name: "B".to_string(),
+                        location: cols(9, 9)
+                    }))),
+                    name: Identifier {
+                        name: "foo".to_string(),
+                        location: cols(11, 13)
+                    },
+                    arguments: Some(Arguments {
+                        values: vec![Argument::Positional(Expression::Int(
+                            Box::new(IntLiteral {
+                                value: "10".to_string(),
+                                location: cols(15, 16)
+                            })
+                        ))],
+                        location: cols(14, 17)
+                    }),
+                    location: cols(9, 17)
+                })),
+                location: cols(1, 17)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_constant_with_type_cast() {
+        assert_eq!(
+            top(parse("let A = 10 as Float")),
+            TopLevelExpression::DefineConstant(Box::new(DefineConstant {
+                public: false,
+                name: Constant {
+                    source: None,
+                    name: "A".to_string(),
+                    location: cols(5, 5)
+                },
+                value: Expression::TypeCast(Box::new(TypeCast {
+                    value: Expression::Int(Box::new(IntLiteral {
+                        value: "10".to_string(),
+                        location: cols(9, 10)
+                    })),
+                    cast_to: Type::Named(Box::new(TypeName {
+                        name: Constant {
+                            source: None,
+                            name: "Float".to_string(),
+                            location: cols(15, 19)
+                        },
+                        arguments: None,
+                        location: cols(15, 19)
+                    })),
+                    location: cols(9, 19)
+                })),
+                location: cols(1, 19)
+            }))
+        );
     }
 
     #[test]
@@ -4069,6 +4345,7 @@ mod tests {
             top(parse("fn foo {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4090,6 +4367,7 @@ mod tests {
             top(parse("fn FOO {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4111,6 +4389,7 @@ mod tests {
             top(parse("fn pub foo {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: true,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4132,6 +4411,7 @@ mod tests {
             top(parse("fn 123 {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4153,6 +4433,7 @@ mod tests {
             top(parse("fn ab= {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4174,6 +4455,7 @@ mod tests {
             top(parse("fn 12= {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4195,6 +4477,7 @@ mod tests {
             top(parse("fn let {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4219,6 +4502,7 @@ mod tests {
             top(parse("fn foo [T] {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4251,6 +4535,7 @@ mod tests {
             top(parse("fn foo [T: A + B] {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4308,6 +4593,7 @@ mod tests {
             top(parse("fn foo (a: A, b: B) {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4369,6 +4655,7 @@ mod tests {
             top(parse("fn foo -> A {}")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4401,6 +4688,7 @@ mod tests {
             top(parse("fn foo { 10 }")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Instance,
                 name: Identifier {
@@ -4422,12 +4710,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tail_recursive_method() {
+        assert_eq!(
+            top(parse("fn pub tail foo {}")),
+            TopLevelExpression::DefineMethod(Box::new(DefineMethod {
+                public: true,
+                tail: true,
+                operator: false,
+                kind: MethodKind::Instance,
+                name: Identifier {
+                    name: "foo".to_string(),
+                    location: cols(13, 15)
+                },
+                type_parameters: None,
+                arguments: None,
+                return_type: None,
+                body: Some(Expressions {
+                    values: Vec::new(),
+                    location: cols(17, 18)
+                }),
+                location: cols(1, 18),
+            }))
+        );
+
+        assert_eq!(
+            top(parse("fn tail {}")),
+            TopLevelExpression::DefineMethod(Box::new(DefineMethod {
+                public: false,
+                tail: false,
+                operator: false,
+                kind: MethodKind::Instance,
+                name: Identifier {
+                    name: "tail".to_string(),
+                    location: cols(4, 7)
+                },
+                type_parameters: None,
+                arguments: None,
+                return_type: None,
+                body: Some(Expressions {
+                    values: Vec::new(),
+                    location: cols(9, 10)
+                }),
+                location: cols(1, 10),
+            }))
+        );
+
+        assert_eq!(
+            top(parse("fn tail tail {}")),
+            TopLevelExpression::DefineMethod(Box::new(DefineMethod {
+                public: false,
+                tail: true,
+                operator: false,
+                kind: MethodKind::Instance,
+                name: Identifier {
+                    name: "tail".to_string(),
+                    location: cols(9, 12)
+                },
+                type_parameters: None,
+                arguments: None,
+                return_type: None,
+                body: Some(Expressions {
+                    values: Vec::new(),
+                    location: cols(14, 15)
+                }),
+                location: cols(1, 15),
+            }))
+        );
+
+        // `tail` followed by a name on the next line is the method name.
+        assert_error!("fn tail\nfoo {}", location(2..=2, 1..=3));
+    }
+
     #[test]
     fn test_extern_method() {
         assert_eq!(
             top(parse("fn extern foo")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Extern,
                 name: Identifier {
@@ -4446,6 +4807,7 @@ mod tests {
             top(parse("fn extern foo(...)")),
             TopLevelExpression::DefineMethod(Box::new(DefineMethod {
                 public: false,
+                tail: false,
                 operator: false,
                 kind: MethodKind::Extern,
                 name: Identifier {
@@ -4490,6 +4852,7 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(9, 10)
@@ -4509,6 +4872,7 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(13, 14)
@@ -4531,6 +4895,7 @@ mod tests {
                 },
                 kind: ClassKind::Extern,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(16, 17)
@@ -4599,6 +4964,7 @@ mod tests {
                 },
                 kind: ClassKind::Async,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(15, 16)
@@ -4608,6 +4974,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_inline_class() {
+        assert_eq!(
+            top(parse("class inline A {}")),
+            TopLevelExpression::DefineClass(Box::new(DefineClass {
+                public: false,
+                name: Constant {
+                    source: None,
+                    name: "A".to_string(),
+                    location: cols(14, 14)
+                },
+                kind: ClassKind::Inline,
+                type_parameters: None,
+                derive: None,
+                body: ClassExpressions {
+                    values: Vec::new(),
+                    location: cols(16, 17)
+                },
+                location: cols(1, 17)
+            }))
+        );
+    }
+
     #[test]
     fn test_class_with_async_method() {
         assert_eq!(
@@ -4621,10 +5010,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: false,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::Async,
                             name: Identifier {
@@ -4658,10 +5049,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: false,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::AsyncMutable,
                             name: Identifier {
@@ -4731,6 +5124,7 @@ mod tests {
                     ],
                     location: cols(8, 16)
                 }),
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(18, 19)
@@ -4775,6 +5169,7 @@ mod tests {
                     },],
                     location: cols(8, 15)
                 }),
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(17, 18)
@@ -4784,6 +5179,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_class_with_derive() {
+        assert_eq!(
+            top(parse("class A derive(B, c.D) {}")),
+            TopLevelExpression::DefineClass(Box::new(DefineClass {
+                public: false,
+                name: Constant {
+                    source: None,
+                    name: "A".to_string(),
+                    location: cols(7, 7)
+                },
+                kind: ClassKind::Regular,
+                type_parameters: None,
+                derive: Some(TypeNames {
+                    values: vec![
+                        TypeName {
+                            name: Constant {
+                                source: None,
+                                name: "B".to_string(),
+                                location: cols(16, 16)
+                            },
+                            arguments: None,
+                            location: cols(16, 16)
+                        },
+                        TypeName {
+                            name: Constant {
+                                source: Some(Identifier {
+                                    name: "c".to_string(),
+                                    location: cols(19, 19)
+                                }),
+                                name: "D".to_string(),
+                                location: cols(21, 21)
+                            },
+                            arguments: None,
+                            location: cols(19, 21)
+                        }
+                    ],
+                    location: cols(15, 22)
+                }),
+                body: ClassExpressions {
+                    values: Vec::new(),
+                    location: cols(24, 25)
+                },
+                location: cols(1, 25)
+            }))
+        );
+    }
+
     #[test]
     fn test_class_with_instance_method() {
         assert_eq!(
@@ -4797,10 +5240,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: false,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::Instance,
                             name: Identifier {
@@ -4834,10 +5279,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: true,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::Instance,
                             name: Identifier {
@@ -4874,10 +5321,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: false,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::Moving,
                             name: Identifier {
@@ -4914,10 +5363,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: false,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::Mutable,
                             name: Identifier {
@@ -4954,10 +5405,12 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineMethod(Box::new(
                         DefineMethod {
                             public: false,
+                            tail: false,
                             operator: false,
                             kind: MethodKind::Static,
                             name: Identifier {
@@ -4981,6 +5434,48 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_class_with_tail_recursive_static_method() {
+        assert_eq!(
+            top(parse("class A { fn static tail foo {} }")),
+            TopLevelExpression::DefineClass(Box::new(DefineClass {
+                public: false,
+                name: Constant {
+                    source: None,
+                    name: "A".to_string(),
+                    location: cols(7, 7)
+                },
+                kind: ClassKind::Regular,
+                type_parameters: None,
+                derive: None,
+                body: ClassExpressions {
+                    values: vec![ClassExpression::DefineMethod(Box::new(
+                        DefineMethod {
+                            public: false,
+                            tail: true,
+                            operator: false,
+                            kind: MethodKind::Static,
+                            name: Identifier {
+                                name: "foo".to_string(),
+                                location: cols(26, 28)
+                            },
+                            type_parameters: None,
+                            arguments: None,
+                            return_type: None,
+                            body: Some(Expressions {
+                                values: Vec::new(),
+                                location: cols(30, 31)
+                            }),
+                            location: cols(11, 31)
+                        }
+                    ))],
+                    location: cols(9, 33)
+                },
+                location: cols(1, 33)
+            }))
+        )
+    }
+
     #[test]
     fn test_class_with_field() {
         assert_eq!(
@@ -4994,6 +5489,7 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineField(Box::new(
                         DefineField {
@@ -5031,6 +5527,7 @@ mod tests {
                 },
                 kind: ClassKind::Regular,
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: vec![ClassExpression::DefineField(Box::new(
                         DefineField {
@@ -5065,6 +5562,8 @@ mod tests {
         assert_error!("class A {", cols(9, 9));
         assert_error!("class extern A[T] {", cols(15, 15));
         assert_error!("class extern A { fn foo {  } }", cols(18, 19));
+        assert_error!("class extern A derive(B) {}", cols(16, 21));
+        assert_error!("class A derive {}", cols(16, 16));
     }
 
     #[test]
@@ -5236,6 +5735,7 @@ mod tests {
                 body: ImplementationExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5289,6 +5789,7 @@ mod tests {
                 body: ImplementationExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5322,6 +5823,7 @@ mod tests {
                 body: ImplementationExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Async,
                         name: Identifier {
@@ -5389,6 +5891,7 @@ mod tests {
                 body: ImplementationExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Static,
                         name: Identifier {
@@ -5608,6 +6111,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5643,6 +6147,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5678,6 +6183,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5721,6 +6227,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5775,6 +6282,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5821,6 +6329,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Instance,
                         name: Identifier {
@@ -5859,6 +6368,7 @@ mod tests {
                 body: TraitExpressions {
                     values: vec![DefineMethod {
                         public: false,
+                        tail: false,
                         operator: false,
                         kind: MethodKind::Moving,
                         name: Identifier {
@@ -5902,6 +6412,7 @@ mod tests {
                     location: cols(15, 15)
                 },
                 type_parameters: None,
+                derive: None,
                 body: ClassExpressions {
                     values: Vec::new(),
                     location: cols(17, 18)
@@ -9230,6 +9741,43 @@ mod tests {
         assert_error_expr!("while 10 20 }", cols(10, 11));
     }
 
+    #[test]
+    fn test_for_expression() {
+        assert_eq!(
+            expr("for x in y { 10 }"),
+            Expression::For(Box::new(For {
+                pattern: Pattern::Identifier(Box::new(IdentifierPattern {
+                    name: Identifier {
+                        name: "x".to_string(),
+                        location: cols(5, 5)
+                    },
+                    mutable: false,
+                    value_type: None,
+                    location: cols(5, 5)
+                })),
+                iterator: Expression::Identifier(Box::new(Identifier {
+                    name: "y".to_string(),
+                    location: cols(10, 10)
+                })),
+                body: Expressions {
+                    values: vec![Expression::Int(Box::new(IntLiteral {
+                        value: "10".to_string(),
+                        location: cols(14, 15)
+                    }))],
+                    location: cols(12, 17)
+                },
+                location: cols(1, 17)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_invalid_for_expression() {
+        assert_error_expr!("for x y { 10 }", cols(7, 7));
+        assert_error_expr!("for x in y 10 }", cols(12, 13));
+        assert_error_expr!("for in y {}", cols(8, 8));
+    }
+
     #[test]
     fn test_enum_class() {
         assert_eq!(
@@ -9254,6 +9802,7 @@ mod tests {
                     }],
                     location: cols(18, 20)
                 }),
+                derive: None,
                 body: ClassExpressions {
                     values: vec![
                         ClassExpression::DefineVariant(Box::new(
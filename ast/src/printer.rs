@@ -0,0 +1,991 @@
+//! Pretty-printing of ASTs as Inko source code.
+//!
+//! The output is formatted consistently rather than preserving the formatting
+//! of the original source code, and comments aren't included as they aren't
+//! part of the AST. Parsing the output again produces the same AST, minus
+//! source locations.
+use crate::nodes::*;
+
+/// The number of spaces to indent nested code with.
+const INDENT: usize = 2;
+
+/// Returns a String containing the source code of a module.
+pub fn to_text(module: &Module) -> String {
+    let mut printer = Printer::new();
+
+    printer.module(module);
+    printer.buffer
+}
+
+fn operator(kind: &OperatorKind) -> &'static str {
+    match kind {
+        OperatorKind::Add => "+",
+        OperatorKind::BitAnd => "&",
+        OperatorKind::BitOr => "|",
+        OperatorKind::BitXor => "^",
+        OperatorKind::Div => "/",
+        OperatorKind::Eq => "==",
+        OperatorKind::Ge => ">=",
+        OperatorKind::Gt => ">",
+        OperatorKind::Le => "<=",
+        OperatorKind::Lt => "<",
+        OperatorKind::Mod => "%",
+        OperatorKind::Mul => "*",
+        OperatorKind::Ne => "!=",
+        OperatorKind::Pow => "**",
+        OperatorKind::Shl => "<<",
+        OperatorKind::Shr => ">>",
+        OperatorKind::Sub => "-",
+        OperatorKind::UnsignedShr => ">>>",
+    }
+}
+
+struct Printer {
+    buffer: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn new() -> Printer {
+        Printer { buffer: String::new(), indent: 0 }
+    }
+
+    fn push(&mut self, value: &str) {
+        self.buffer.push_str(value);
+    }
+
+    fn newline(&mut self) {
+        self.buffer.push('\n');
+
+        for _ in 0..self.indent {
+            self.buffer.push(' ');
+        }
+    }
+
+    fn list<T, F: FnMut(&mut Self, &T)>(&mut self, values: &[T], mut func: F) {
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 {
+                self.push(", ");
+            }
+
+            func(self, value);
+        }
+    }
+
+    /// Prints a sequence of items on separate lines and wrapped in curly
+    /// braces, separating items with an empty line if `separate` returns
+    /// `true` for two consecutive items.
+    fn items<T, F, S>(&mut self, values: &[T], mut func: F, separate: S)
+    where
+        F: FnMut(&mut Self, &T),
+        S: Fn(&T, &T) -> bool,
+    {
+        if values.is_empty() {
+            self.push("{}");
+            return;
+        }
+
+        self.push("{");
+        self.indent += INDENT;
+
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 && separate(&values[index - 1], value) {
+                self.buffer.push('\n');
+            }
+
+            self.newline();
+            func(self, value);
+        }
+
+        self.indent -= INDENT;
+        self.newline();
+        self.push("}");
+    }
+
+    fn module(&mut self, module: &Module) {
+        for (index, expr) in module.expressions.iter().enumerate() {
+            if index > 0 {
+                let imports = matches!(
+                    (&module.expressions[index - 1], expr),
+                    (
+                        TopLevelExpression::Import(_)
+                            | TopLevelExpression::ExternImport(_),
+                        TopLevelExpression::Import(_)
+                            | TopLevelExpression::ExternImport(_)
+                    )
+                );
+
+                self.push(if imports { "\n" } else { "\n\n" });
+            }
+
+            self.top_level_expression(expr);
+        }
+
+        if !module.expressions.is_empty() {
+            self.push("\n");
+        }
+    }
+
+    fn top_level_expression(&mut self, node: &TopLevelExpression) {
+        match node {
+            TopLevelExpression::DefineConstant(ref n) => {
+                self.define_constant(n)
+            }
+            TopLevelExpression::DefineMethod(ref n) => self.define_method(n),
+            TopLevelExpression::DefineClass(ref n) => self.define_class(n),
+            TopLevelExpression::DefineTrait(ref n) => self.define_trait(n),
+            TopLevelExpression::ReopenClass(ref n) => self.reopen_class(n),
+            TopLevelExpression::ImplementTrait(ref n) => {
+                self.implement_trait(n)
+            }
+            TopLevelExpression::Import(ref n) => self.import(n),
+            TopLevelExpression::ExternImport(ref n) => {
+                self.push("import extern ");
+                self.single_string(&n.path.path);
+            }
+        }
+    }
+
+    fn import(&mut self, node: &Import) {
+        self.push("import ");
+
+        for (index, step) in node.path.steps.iter().enumerate() {
+            if index > 0 {
+                self.push(".");
+            }
+
+            self.push(&step.name);
+        }
+
+        if let Some(symbols) = &node.symbols {
+            self.push(".(");
+            self.list(&symbols.values, |p, sym| {
+                p.push(&sym.name);
+
+                if let Some(alias) = &sym.alias {
+                    p.push(" as ");
+                    p.push(&alias.name);
+                }
+            });
+            self.push(")");
+        }
+
+        if let Some(tags) = &node.tags {
+            self.push(" if ");
+
+            for (index, tag) in tags.values.iter().enumerate() {
+                if index > 0 {
+                    self.push(" and ");
+                }
+
+                self.push(&tag.name);
+            }
+        }
+    }
+
+    fn define_constant(&mut self, node: &DefineConstant) {
+        self.push("let ");
+
+        if node.public {
+            self.push("pub ");
+        }
+
+        self.push(&node.name.name);
+        self.push(" = ");
+        self.expression(&node.value);
+    }
+
+    fn define_method(&mut self, node: &DefineMethod) {
+        self.push("fn ");
+
+        if node.public {
+            self.push("pub ");
+        }
+
+        self.push(match node.kind {
+            MethodKind::Instance => "",
+            MethodKind::Static => "static ",
+            MethodKind::Async => "async ",
+            MethodKind::Moving => "move ",
+            MethodKind::Mutable => "mut ",
+            MethodKind::AsyncMutable => "async mut ",
+            MethodKind::Extern => "extern ",
+        });
+
+        if node.tail {
+            self.push("tail ");
+        }
+
+        self.push(&node.name.name);
+        self.type_parameters(node.type_parameters.as_ref());
+
+        if let Some(args) = &node.arguments {
+            self.push("(");
+            self.list(&args.values, |p, arg| {
+                p.push(&arg.name.name);
+                p.push(": ");
+                p.type_reference(&arg.value_type);
+            });
+
+            if args.variadic {
+                self.push(if args.values.is_empty() { "..." } else { ", ..." });
+            }
+
+            self.push(")");
+        }
+
+        self.return_type(node.return_type.as_ref());
+
+        if let Some(body) = &node.body {
+            self.push(" ");
+            self.body(&body.values);
+        }
+    }
+
+    fn define_class(&mut self, node: &DefineClass) {
+        self.push("class ");
+
+        if node.public {
+            self.push("pub ");
+        }
+
+        self.push(match node.kind {
+            ClassKind::Async => "async ",
+            ClassKind::Builtin => "builtin ",
+            ClassKind::Enum => "enum ",
+            ClassKind::Regular => "",
+            ClassKind::Extern => "extern ",
+            ClassKind::Inline => "inline ",
+        });
+
+        self.push(&node.name.name);
+        self.type_parameters(node.type_parameters.as_ref());
+
+        if let Some(derive) = &node.derive {
+            self.push(" derive(");
+            self.list(&derive.values, |p, n| p.type_name(n));
+            self.push(")");
+        }
+
+        self.push(" ");
+        self.items(
+            &node.body.values,
+            |p, expr| match expr {
+                ClassExpression::DefineMethod(ref n) => p.define_method(n),
+                ClassExpression::DefineField(ref n) => {
+                    p.push("let ");
+
+                    if n.public {
+                        p.push("pub ");
+                    }
+
+                    p.push("@");
+                    p.push(&n.name.name);
+                    p.push(": ");
+                    p.type_reference(&n.value_type);
+                }
+                ClassExpression::DefineVariant(ref n) => {
+                    p.push("case ");
+                    p.push(&n.name.name);
+
+                    if let Some(members) = &n.members {
+                        p.push("(");
+                        p.list(&members.values, |p, n| p.type_reference(n));
+                        p.push(")");
+                    }
+                }
+            },
+            |prev, next| {
+                !matches!(
+                    (prev, next),
+                    (
+                        ClassExpression::DefineField(_),
+                        ClassExpression::DefineField(_)
+                    ) | (
+                        ClassExpression::DefineVariant(_),
+                        ClassExpression::DefineVariant(_)
+                    )
+                )
+            },
+        );
+    }
+
+    fn define_trait(&mut self, node: &DefineTrait) {
+        self.push("trait ");
+
+        if node.public {
+            self.push("pub ");
+        }
+
+        self.push(&node.name.name);
+        self.type_parameters(node.type_parameters.as_ref());
+
+        if let Some(reqs) = &node.requirements {
+            self.push(": ");
+
+            for (index, req) in reqs.values.iter().enumerate() {
+                if index > 0 {
+                    self.push(" + ");
+                }
+
+                self.type_name(req);
+            }
+        }
+
+        self.push(" ");
+        self.items(&node.body.values, |p, n| p.define_method(n), |_, _| true);
+    }
+
+    fn reopen_class(&mut self, node: &ReopenClass) {
+        self.push("impl ");
+        self.push(&node.class_name.name);
+        self.type_bounds(node.bounds.as_ref());
+        self.push(" ");
+        self.items(&node.body.values, |p, n| p.define_method(n), |_, _| true);
+    }
+
+    fn implement_trait(&mut self, node: &ImplementTrait) {
+        self.push("impl ");
+        self.type_name(&node.trait_name);
+        self.push(" for ");
+        self.push(&node.class_name.name);
+        self.type_bounds(node.bounds.as_ref());
+        self.push(" ");
+        self.items(&node.body.values, |p, n| p.define_method(n), |_, _| true);
+    }
+
+    fn type_parameters(&mut self, node: Option<&TypeParameters>) {
+        let Some(params) = node else { return };
+
+        self.push("[");
+        self.list(&params.values, |p, param| {
+            p.push(&param.name.name);
+
+            if let Some(reqs) = &param.requirements {
+                p.requirements(reqs);
+            }
+        });
+        self.push("]");
+    }
+
+    fn type_bounds(&mut self, node: Option<&TypeBounds>) {
+        let Some(bounds) = node else { return };
+
+        self.push(" if ");
+        self.list(&bounds.values, |p, bound| {
+            p.push(&bound.name.name);
+            p.requirements(&bound.requirements);
+        });
+    }
+
+    fn requirements(&mut self, node: &Requirements) {
+        self.push(": ");
+
+        for (index, req) in node.values.iter().enumerate() {
+            if index > 0 {
+                self.push(" + ");
+            }
+
+            match req {
+                Requirement::Trait(n) => self.type_name(n),
+                Requirement::Mutable(_) => self.push("mut"),
+            }
+        }
+    }
+
+    fn return_type(&mut self, node: Option<&Type>) {
+        if let Some(typ) = node {
+            self.push(" -> ");
+            self.type_reference(typ);
+        }
+    }
+
+    fn type_reference(&mut self, node: &Type) {
+        match node {
+            Type::Named(ref n) => self.type_name(n),
+            Type::Ref(ref n) => {
+                self.push("ref ");
+                self.referrable_type(&n.type_reference);
+            }
+            Type::Mut(ref n) => {
+                self.push("mut ");
+                self.referrable_type(&n.type_reference);
+            }
+            Type::Uni(ref n) => {
+                self.push("uni ");
+                self.referrable_type(&n.type_reference);
+            }
+            Type::Closure(ref n) => self.closure_type(n),
+            Type::Tuple(ref n) => self.tuple_type(n),
+        }
+    }
+
+    fn referrable_type(&mut self, node: &ReferrableType) {
+        match node {
+            ReferrableType::Named(ref n) => self.type_name(n),
+            ReferrableType::Closure(ref n) => self.closure_type(n),
+            ReferrableType::Tuple(ref n) => self.tuple_type(n),
+        }
+    }
+
+    fn type_name(&mut self, node: &TypeName) {
+        self.constant(&node.name);
+
+        if let Some(args) = &node.arguments {
+            self.push("[");
+            self.list(&args.values, |p, n| p.type_reference(n));
+            self.push("]");
+        }
+    }
+
+    fn closure_type(&mut self, node: &ClosureType) {
+        self.push("fn");
+
+        if let Some(args) = &node.arguments {
+            self.push(" (");
+            self.list(&args.values, |p, n| p.type_reference(n));
+            self.push(")");
+        }
+
+        self.return_type(node.return_type.as_ref());
+    }
+
+    fn tuple_type(&mut self, node: &TupleType) {
+        self.push("(");
+        self.list(&node.values, |p, n| p.type_reference(n));
+        self.push(")");
+    }
+
+    fn constant(&mut self, node: &Constant) {
+        if let Some(source) = &node.source {
+            self.push(&source.name);
+            self.push(".");
+        }
+
+        self.push(&node.name);
+    }
+
+    fn body(&mut self, nodes: &[Expression]) {
+        self.items(nodes, |p, n| p.expression(n), |_, _| false);
+    }
+
+    fn expression(&mut self, node: &Expression) {
+        match node {
+            Expression::Int(ref n) => self.push(&n.value),
+            Expression::Float(ref n) => self.push(&n.value),
+            Expression::SingleString(ref n) => {
+                self.single_string(n.value.as_ref().map_or("", |v| &v.value));
+            }
+            Expression::DoubleString(ref n) => {
+                self.push("\"");
+
+                for value in &n.values {
+                    match value {
+                        DoubleStringValue::Text(ref n) => {
+                            self.double_string_text(&n.value)
+                        }
+                        DoubleStringValue::Expression(ref n) => {
+                            self.push("{");
+                            self.expression(&n.value);
+                            self.push("}");
+                        }
+                    }
+                }
+
+                self.push("\"");
+            }
+            Expression::Binary(ref n) => {
+                self.expression(&n.left);
+                self.push(" ");
+                self.push(operator(&n.operator.kind));
+                self.push(" ");
+                self.expression(&n.right);
+            }
+            Expression::Field(ref n) => {
+                self.push("@");
+                self.push(&n.name);
+            }
+            Expression::Constant(ref n) => self.constant(n),
+            Expression::Identifier(ref n) => self.push(&n.name),
+            Expression::Call(ref n) => self.call(n),
+            Expression::AssignVariable(ref n) => {
+                self.push(&n.variable.name);
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::ReplaceVariable(ref n) => {
+                self.push(&n.variable.name);
+                self.push(" := ");
+                self.expression(&n.value);
+            }
+            Expression::AssignField(ref n) => {
+                self.push("@");
+                self.push(&n.field.name);
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::ReplaceField(ref n) => {
+                self.push("@");
+                self.push(&n.field.name);
+                self.push(" := ");
+                self.expression(&n.value);
+            }
+            Expression::AssignSetter(ref n) => {
+                self.expression(&n.receiver);
+                self.push(".");
+                self.push(&n.name.name);
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::BinaryAssignVariable(ref n) => {
+                self.push(&n.variable.name);
+                self.binary_assign(&n.operator.kind, &n.value);
+            }
+            Expression::BinaryAssignField(ref n) => {
+                self.push("@");
+                self.push(&n.field.name);
+                self.binary_assign(&n.operator.kind, &n.value);
+            }
+            Expression::BinaryAssignSetter(ref n) => {
+                self.expression(&n.receiver);
+                self.push(".");
+                self.push(&n.name.name);
+                self.binary_assign(&n.operator.kind, &n.value);
+            }
+            Expression::Closure(ref n) => self.closure(n),
+            Expression::DefineVariable(ref n) => {
+                self.push(if n.mutable { "let mut " } else { "let " });
+                self.push(&n.name.name);
+
+                if let Some(typ) = &n.value_type {
+                    self.push(": ");
+                    self.type_reference(typ);
+                }
+
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::SelfObject(_) => self.push("self"),
+            Expression::Group(ref n) => {
+                self.push("(");
+                self.expression(&n.value);
+                self.push(")");
+            }
+            Expression::Next(_) => self.push("next"),
+            Expression::Break(_) => self.push("break"),
+            Expression::Ref(ref n) => {
+                self.push("ref ");
+                self.expression(&n.value);
+            }
+            Expression::Mut(ref n) => {
+                self.push("mut ");
+                self.expression(&n.value);
+            }
+            Expression::Recover(ref n) => {
+                self.push("recover ");
+                self.body(&n.body.values);
+            }
+            Expression::And(ref n) => {
+                self.expression(&n.left);
+                self.push(" and ");
+                self.expression(&n.right);
+            }
+            Expression::Or(ref n) => {
+                self.expression(&n.left);
+                self.push(" or ");
+                self.expression(&n.right);
+            }
+            Expression::TypeCast(ref n) => {
+                self.expression(&n.value);
+                self.push(" as ");
+                self.type_reference(&n.cast_to);
+            }
+            Expression::Throw(ref n) => {
+                self.push("throw ");
+                self.expression(&n.value);
+            }
+            Expression::Return(ref n) => {
+                self.push("return");
+
+                if let Some(value) = &n.value {
+                    self.push(" ");
+                    self.expression(value);
+                }
+            }
+            Expression::Try(ref n) => {
+                self.push("try ");
+                self.expression(&n.expression);
+            }
+            Expression::If(ref n) => {
+                self.push("if ");
+                self.expression(&n.if_true.condition);
+                self.push(" ");
+                self.body(&n.if_true.body.values);
+
+                for cond in &n.else_if {
+                    self.push(" else if ");
+                    self.expression(&cond.condition);
+                    self.push(" ");
+                    self.body(&cond.body.values);
+                }
+
+                if let Some(body) = &n.else_body {
+                    self.push(" else ");
+                    self.body(&body.values);
+                }
+            }
+            Expression::Match(ref n) => self.match_expression(n),
+            Expression::Loop(ref n) => {
+                self.push("loop ");
+                self.body(&n.body.values);
+            }
+            Expression::While(ref n) => {
+                self.push("while ");
+                self.expression(&n.condition);
+                self.push(" ");
+                self.body(&n.body.values);
+            }
+            Expression::For(ref n) => {
+                self.push("for ");
+                self.pattern(&n.pattern);
+                self.push(" in ");
+                self.expression(&n.iterator);
+                self.push(" ");
+                self.body(&n.body.values);
+            }
+            Expression::True(_) => self.push("true"),
+            Expression::False(_) => self.push("false"),
+            Expression::Nil(_) => self.push("nil"),
+            Expression::ClassLiteral(ref n) => {
+                self.constant(&n.class_name);
+
+                if n.fields.is_empty() {
+                    self.push(" {}");
+                } else {
+                    self.push(" { ");
+                    self.list(&n.fields, |p, field| {
+                        p.push("@");
+                        p.push(&field.field.name);
+                        p.push(" = ");
+                        p.expression(&field.value);
+                    });
+                    self.push(" }");
+                }
+            }
+            Expression::Scope(ref n) => self.body(&n.body.values),
+            Expression::Array(ref n) => {
+                self.push("[");
+                self.list(&n.values, |p, n| p.expression(n));
+                self.push("]");
+            }
+            Expression::Tuple(ref n) => {
+                self.push("(");
+                self.list(&n.values, |p, n| p.expression(n));
+                self.push(if n.values.len() == 1 { ",)" } else { ")" });
+            }
+        }
+    }
+
+    fn binary_assign(&mut self, kind: &OperatorKind, value: &Expression) {
+        self.push(" ");
+        self.push(operator(kind));
+        self.push("= ");
+        self.expression(value);
+    }
+
+    fn call(&mut self, node: &Call) {
+        if let Some(rec) = &node.receiver {
+            self.expression(rec);
+            self.push(".");
+        }
+
+        self.push(&node.name.name);
+
+        let Some(args) = &node.arguments else { return };
+        let mut values = args.values.as_slice();
+        let mut trailing = None;
+
+        // A closure passed after named arguments can only be specified as a
+        // trailing block.
+        if let [rest @ .., Argument::Named(_), Argument::Positional(last)] =
+            values
+        {
+            values = &values[..rest.len() + 1];
+            trailing = Some(last);
+        }
+
+        if !values.is_empty() || trailing.is_none() {
+            self.push("(");
+            self.list(values, |p, arg| match arg {
+                Argument::Positional(ref n) => p.expression(n),
+                Argument::Named(ref n) => {
+                    p.push(&n.name.name);
+                    p.push(": ");
+                    p.expression(&n.value);
+                }
+            });
+            self.push(")");
+        }
+
+        if let Some(value) = trailing {
+            self.push(" ");
+            self.expression(value);
+        }
+    }
+
+    fn closure(&mut self, node: &Closure) {
+        self.push(if node.moving { "fn move" } else { "fn" });
+
+        if let Some(args) = &node.arguments {
+            self.push(" (");
+            self.list(&args.values, |p, arg| {
+                p.push(&arg.name.name);
+
+                if let Some(typ) = &arg.value_type {
+                    p.push(": ");
+                    p.type_reference(typ);
+                }
+            });
+            self.push(")");
+        }
+
+        self.return_type(node.return_type.as_ref());
+        self.push(" ");
+        self.body(&node.body.values);
+    }
+
+    fn match_expression(&mut self, node: &Match) {
+        self.push("match ");
+        self.expression(&node.expression);
+        self.push(" ");
+        self.items(
+            &node.cases,
+            |p, case| {
+                p.push("case ");
+                p.pattern(&case.pattern);
+
+                if let Some(guard) = &case.guard {
+                    p.push(" if ");
+                    p.expression(guard);
+                }
+
+                p.push(" -> ");
+
+                match case.body.values.as_slice() {
+                    [expr] => p.expression(expr),
+                    values => p.body(values),
+                }
+            },
+            |_, _| false,
+        );
+    }
+
+    fn pattern(&mut self, node: &Pattern) {
+        match node {
+            Pattern::Constant(ref n) => self.constant(n),
+            Pattern::Variant(ref n) => {
+                self.constant(&n.name);
+                self.push("(");
+                self.list(&n.values, |p, n| p.pattern(n));
+                self.push(")");
+            }
+            Pattern::Class(ref n) => {
+                if n.values.is_empty() {
+                    self.push("{}");
+                } else {
+                    self.push("{ ");
+                    self.list(&n.values, |p, field| {
+                        p.push("@");
+                        p.push(&field.field.name);
+                        p.push(" = ");
+                        p.pattern(&field.pattern);
+                    });
+                    self.push(" }");
+                }
+            }
+            Pattern::Expression(ref n) => self.expression(n),
+            Pattern::Identifier(ref n) => {
+                if n.mutable {
+                    self.push("mut ");
+                }
+
+                self.push(&n.name.name);
+
+                if let Some(typ) = &n.value_type {
+                    self.push(": ");
+                    self.type_reference(typ);
+                }
+            }
+            Pattern::Tuple(ref n) => {
+                self.push("(");
+                self.list(&n.values, |p, n| p.pattern(n));
+                self.push(")");
+            }
+            Pattern::Wildcard(_) => self.push("_"),
+            Pattern::Or(ref n) => {
+                for (index, pat) in n.patterns.iter().enumerate() {
+                    if index > 0 {
+                        self.push(" or ");
+                    }
+
+                    self.pattern(pat);
+                }
+            }
+            Pattern::String(ref n) => self.single_string(&n.value),
+        }
+    }
+
+    fn single_string(&mut self, value: &str) {
+        self.push("'");
+
+        for chr in value.chars() {
+            match chr {
+                '\'' => self.push("\\'"),
+                '\\' => self.push("\\\\"),
+                _ => self.buffer.push(chr),
+            }
+        }
+
+        self.push("'");
+    }
+
+    fn double_string_text(&mut self, value: &str) {
+        for chr in value.chars() {
+            match chr {
+                '"' => self.push("\\\""),
+                '\\' => self.push("\\\\"),
+                '{' => self.push("\\{"),
+                '\0' => self.push("\\0"),
+                '\x1b' => self.push("\\e"),
+                '\n' => self.push("\\n"),
+                '\r' => self.push("\\r"),
+                '\t' => self.push("\\t"),
+                _ if chr.is_control() => {
+                    self.push(&format!("\\u{{{:X}}}", chr as u32))
+                }
+                _ => self.buffer.push(chr),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use similar_asserts::assert_eq;
+
+    #[track_caller]
+    fn print(input: &str) -> String {
+        to_text(&Parser::new(input.into(), "test.inko".into()).parse().unwrap())
+    }
+
+    #[track_caller]
+    fn assert_round_trip(input: &str) {
+        assert_eq!(print(input), input);
+    }
+
+    #[test]
+    fn test_imports_and_constants() {
+        assert_round_trip(
+            "import std.foo
+import std.bar.(self, Foo as Bar, baz) if linux and amd64
+import extern 'm'
+
+let pub A = 10
+",
+        );
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_round_trip(
+            "class pub Foo[T: Equal[T] + mut] derive(Clone) {
+  let @a: Int
+  let pub @b: ref Array[(Int, fn (T) -> uni T)]
+
+  fn pub static tail new(a: Int) -> Foo[T] {
+    Foo { @a = a, @b = [] }
+  }
+
+  fn async mut foo {}
+}
+
+class enum Letter {
+  case A
+  case B(Int, String)
+}
+
+class extern Point {
+  let @x: Int32
+}
+",
+        );
+    }
+
+    #[test]
+    fn test_traits_and_implementations() {
+        assert_round_trip(
+            "trait pub Foo: Bar + baz.Baz {
+  fn foo -> Int
+
+  fn move bar(a: Int) {
+    a
+  }
+}
+
+impl Foo for Int if T: Bar + mut, U: Baz {
+  fn foo -> Int {
+    42
+  }
+}
+
+impl Array if T: Foo {}
+
+fn extern printf(format: Pointer[UInt8], ...) -> Int32
+",
+        );
+    }
+
+    #[test]
+    fn test_expressions() {
+        assert_round_trip(
+            "fn example {
+  let mut a: Int = 1 + (2 * 3) as Int
+  let b = 'it\\'s' and \"a\\n{a.to_string}\\{\"
+  a += 10
+  @b := [1, 2.5, (a,), (a, b)]
+  foo.bar = ref self
+  foo.bar(1, name: a) fn move (x, y: Int) -> Int {
+    x
+  }
+  baz(fn {})
+  if a {
+    return
+  } else if b {
+    throw 10
+  } else {
+    recover {
+      try foo
+    }
+  }
+  while true {
+    break
+  }
+  for (a, b) in c {
+    next
+  }
+  loop {}
+  match a {
+    case Some(mut a: Int) or None if a -> {}
+    case { @a = 10, @b = 'x' } -> {
+      a
+      b
+    }
+    case foo.BAR or _ -> nil
+  }
+}
+",
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Caching of compiled executables.
+//!
+//! Executables are stored in a directory named after a hash of the compiler
+//! version, the compiler settings (including the name of the output), the
+//! runtime library, and the source code of all modules that make up the
+//! program. If none of these change, the
+//! executable is reused instead of compiling the program again.
+//!
+//! Programs are compiled into a staging directory unique to the current
+//! process, which is renamed to the final entry directory once compiling
+//! finishes. This way an entry directory only exists if it contains a complete
+//! executable, and concurrent compilations of the same program don't overwrite
+//! each other's files.
+use crate::config::Config;
+use crate::linker::runtime_library;
+use crate::modules_parser::ParsedModule;
+use blake2::{digest::consts::U16, Blake2b, Digest};
+use std::cmp::Reverse;
+use std::fs::{read, read_dir, remove_dir_all, rename, write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime};
+
+/// The name of the file used to track when a cache entry was last used.
+const USED: &str = "used";
+
+/// The prefix of the directories programs are compiled into before they're
+/// moved into place.
+const STAGING_PREFIX: &str = "tmp-";
+
+/// The maximum number of executables to keep in the cache.
+const MAX_ENTRIES: usize = 32;
+
+/// The amount of time after its last use during which an entry is never
+/// removed.
+///
+/// This prevents us from removing executables that another process is about
+/// to run, and from removing the staging directories of compilations that are
+/// still in progress.
+const GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Returns a key that uniquely identifies the executable produced for the
+/// given modules.
+pub(crate) fn key(config: &Config, modules: &[ParsedModule]) -> String {
+    let mut hasher: Blake2b<U16> = Blake2b::new();
+    let settings = format!(
+        "{}\0{}\0{}\0{}\0{:?}\0{:?}\0{:?}\0{:?}\0{:?}",
+        env!("CARGO_PKG_VERSION"),
+        config.target,
+        config.opt.directory_name().unwrap_or("balanced"),
+        config.static_linking,
+        config.build_tags,
+        config.libraries,
+        config.linker_settings(),
+        config.output,
+        config.library,
+    );
+
+    hasher.update(settings);
+
+    // Hashing the runtime library itself is expensive due to its size, so
+    // instead we hash its path, size and modification time. This way
+    // rebuilding the runtime in place still results in a different key.
+    if let Some(path) = runtime_library(config) {
+        hasher.update(b"\0");
+        hasher.update(path.to_string_lossy().as_bytes());
+
+        if let Ok(meta) = path.metadata() {
+            let time = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .unwrap_or_default();
+
+            hasher.update(format!("\0{}\0{}", meta.len(), time.as_nanos()));
+        }
+    }
+
+    // Modules are sorted by their names, so the order in which we hash them
+    // is stable.
+    for module in modules {
+        hasher.update(b"\0");
+        hasher.update(module.name.as_str());
+        hasher.update(b"\0");
+        hasher.update(module.ast.file.to_string_lossy().as_bytes());
+        hasher.update(b"\0");
+
+        if let Ok(data) = read(&module.ast.file) {
+            hasher.update(data);
+        }
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// Returns the directory to compile a program into before moving it to the
+/// entry directory `entry`.
+pub(crate) fn staging_directory(cache: &Path, entry: &Path) -> PathBuf {
+    let name = entry.file_name().unwrap_or_default().to_string_lossy();
+
+    cache.join(format!("{}{}-{}", STAGING_PREFIX, process::id(), name))
+}
+
+/// Moves a staging directory into place, making its executable available to
+/// future compilations.
+///
+/// The `executable` argument is the path of the executable in the entry
+/// directory. If another process finished compiling the same program first,
+/// its entry is kept and the staging directory is removed. If the existing
+/// entry doesn't contain the executable, it's replaced.
+pub(crate) fn commit(
+    staging: &Path,
+    entry: &Path,
+    executable: &Path,
+) -> Result<(), String> {
+    if rename(staging, entry).is_ok() {
+        return Ok(());
+    }
+
+    if executable.is_file() {
+        let _ = remove_dir_all(staging);
+        return Ok(());
+    }
+
+    if entry.is_dir() {
+        let _ = remove_dir_all(entry);
+    }
+
+    rename(staging, entry).map_err(|err| {
+        format!(
+            "Failed to move {} to {}: {}",
+            staging.display(),
+            entry.display(),
+            err
+        )
+    })
+}
+
+/// Records that the cache entry in the given directory is used.
+pub(crate) fn mark_used(directory: &Path) {
+    // If this fails the entry is just evicted sooner, so we ignore any errors.
+    let _ = write(directory.join(USED), "");
+}
+
+/// Removes the least recently used entries from the cache, such that no more
+/// than `MAX_ENTRIES` entries remain, and removes staging directories left
+/// behind by interrupted compilations.
+pub(crate) fn evict(cache: &Path) {
+    let entries: Vec<(SystemTime, PathBuf)> = read_dir(cache)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| {
+            let time = path
+                .join(USED)
+                .metadata()
+                .or_else(|_| path.metadata())
+                .and_then(|meta| meta.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+
+            (time, path)
+        })
+        .collect();
+
+    for path in evictable(entries, SystemTime::now()) {
+        // Another process may be using the entry at the same time, so
+        // failing to remove it isn't an error.
+        let _ = remove_dir_all(path);
+    }
+}
+
+/// Returns the entries to remove, given the time each entry was last used.
+fn evictable(
+    entries: Vec<(SystemTime, PathBuf)>,
+    now: SystemTime,
+) -> Vec<PathBuf> {
+    let expired = |time: SystemTime| {
+        now.duration_since(time).map_or(false, |age| age > GRACE_PERIOD)
+    };
+    let (staging, mut entries): (Vec<_>, Vec<_>) =
+        entries.into_iter().partition(|(_, path)| {
+            path.file_name().map_or(false, |n| {
+                n.to_string_lossy().starts_with(STAGING_PREFIX)
+            })
+        });
+
+    let mut remove: Vec<PathBuf> = staging
+        .into_iter()
+        .filter(|(time, _)| expired(*time))
+        .map(|(_, path)| path)
+        .collect();
+
+    entries.sort_by_key(|(time, _)| Reverse(*time));
+    remove.extend(
+        entries
+            .into_iter()
+            .skip(MAX_ENTRIES)
+            .filter(|(time, _)| expired(*time))
+            .map(|(_, path)| path),
+    );
+
+    remove
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Library, Output};
+    use std::env::temp_dir;
+    use std::fs::create_dir_all;
+
+    fn cache_directory(name: &str) -> PathBuf {
+        let dir =
+            temp_dir().join(format!("inko-cache-{}-{}", name, process::id()));
+
+        let _ = remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_evictable() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let old = now - GRACE_PERIOD - Duration::from_secs(10);
+        let mut entries = Vec::new();
+
+        for i in 0..(MAX_ENTRIES + 2) {
+            let time = old - Duration::from_secs(i as u64);
+
+            entries.push((time, PathBuf::from(i.to_string())));
+        }
+
+        // A recently used entry isn't removed, even if it exceeds the limit.
+        entries.push((now - Duration::from_secs(5), PathBuf::from("recent")));
+        entries.push((old, PathBuf::from(format!("{}1-abc", STAGING_PREFIX))));
+        entries.push((now, PathBuf::from(format!("{}2-abc", STAGING_PREFIX))));
+
+        let mut remove = evictable(entries, now);
+
+        remove.sort();
+
+        assert_eq!(
+            remove,
+            vec![
+                PathBuf::from((MAX_ENTRIES - 1).to_string()),
+                PathBuf::from(MAX_ENTRIES.to_string()),
+                PathBuf::from((MAX_ENTRIES + 1).to_string()),
+                PathBuf::from(format!("{}1-abc", STAGING_PREFIX)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evict_keeps_recent_entries() {
+        let cache = cache_directory("evict");
+
+        for i in 0..(MAX_ENTRIES + 2) {
+            let dir = cache.join(i.to_string());
+
+            create_dir_all(&dir).unwrap();
+            mark_used(&dir);
+        }
+
+        evict(&cache);
+
+        assert_eq!(read_dir(&cache).unwrap().count(), MAX_ENTRIES + 2);
+        remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn test_commit() {
+        let cache = cache_directory("commit");
+        let entry = cache.join("abc");
+        let staging1 = staging_directory(&cache, &entry);
+        let staging2 = cache.join(format!("{}0-abc", STAGING_PREFIX));
+
+        create_dir_all(&staging1).unwrap();
+        create_dir_all(&staging2).unwrap();
+        write(staging1.join("exe"), "1").unwrap();
+        write(staging2.join("exe"), "2").unwrap();
+
+        assert!(commit(&staging1, &entry, &entry.join("exe")).is_ok());
+        assert!(commit(&staging2, &entry, &entry.join("exe")).is_ok());
+        assert!(!staging1.exists());
+        assert!(!staging2.exists());
+        assert_eq!(read(entry.join("exe")).unwrap(), b"1");
+
+        remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn test_commit_with_incomplete_entry() {
+        let cache = cache_directory("commit-incomplete");
+        let entry = cache.join("abc");
+        let staging = staging_directory(&cache, &entry);
+
+        create_dir_all(&staging).unwrap();
+        create_dir_all(&entry).unwrap();
+        write(staging.join("new"), "1").unwrap();
+        write(entry.join("old"), "2").unwrap();
+
+        assert!(commit(&staging, &entry, &entry.join("new")).is_ok());
+        assert!(!staging.exists());
+        assert!(!entry.join("old").exists());
+        assert_eq!(read(entry.join("new")).unwrap(), b"1");
+
+        remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn test_key_with_output() {
+        let mut config = Config::new();
+        let derive = key(&config, &[]);
+
+        config.output = Output::File("foo".to_string());
+
+        let foo = key(&config, &[]);
+
+        config.output = Output::File("bar".to_string());
+
+        let bar = key(&config, &[]);
+
+        config.library = Some(Library::Static);
+
+        let lib = key(&config, &[]);
+
+        assert_ne!(derive, foo);
+        assert_ne!(foo, bar);
+        assert_ne!(bar, lib);
+    }
+}
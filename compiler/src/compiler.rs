@@ -1,29 +1,34 @@
-use crate::config::{BuildDirectories, Output};
+use crate::cache;
+use crate::config::{BuildDirectories, Emit, Library, Opt, Output};
 use crate::config::{Config, SOURCE, SOURCE_EXT, TESTS};
+use crate::header;
 use crate::hir;
 use crate::linker::link;
 use crate::llvm;
+use crate::mir::escape::stack_allocate;
 use crate::mir::passes as mir;
-use crate::mir::printer::to_dot;
+use crate::mir::printer::{to_dot, to_text};
 use crate::mir::specialize::Specialize;
+use crate::mir::tail_calls::tail_calls;
 use crate::mir::Mir;
 use crate::modules_parser::{ModulesParser, ParsedModule};
 use crate::state::State;
+use crate::target::OperatingSystem;
 use crate::type_check::define_types::{
-    CheckTraitImplementations, CheckTraitRequirements, CheckTypeParameters,
-    DefineFields, DefineTraitRequirements, DefineTypeParameterRequirements,
-    DefineTypeParameters, DefineTypes, DefineVariants, ImplementTraits,
-    InsertPrelude,
+    CheckDerivedTraits, CheckTraitImplementations, CheckTraitRequirements,
+    CheckTypeParameters, DefineFields, DefineTraitRequirements,
+    DefineTypeParameterRequirements, DefineTypeParameters, DefineTypes,
+    DefineVariants, DeriveTraits, ImplementTraits, InsertPrelude,
 };
 use crate::type_check::expressions::{DefineConstants, Expressions};
 use crate::type_check::imports::{CollectExternImports, DefineImportedTypes};
 use crate::type_check::methods::{
-    CheckMainMethod, DefineMethods, DefineModuleMethodNames,
-    ImplementTraitMethods,
+    CheckMainMethod, DefineExportedMethods, DefineMethods,
+    DefineModuleMethodNames, ImplementTraitMethods,
 };
 use std::env::current_dir;
 use std::ffi::OsStr;
-use std::fs::write;
+use std::fs::{remove_dir_all, write};
 use std::path::{Path, PathBuf};
 use types::module_name::ModuleName;
 
@@ -72,27 +77,92 @@ impl Compiler {
         let main_mod = self.state.db.main_module().unwrap().clone();
         let ast = ModulesParser::new(&mut self.state)
             .run(vec![(main_mod, file.clone())]);
+        let dirs = BuildDirectories::new(&self.state.config);
 
-        let hir = self.compile_hir(ast)?;
-        let mut mir = self.compile_mir(hir)?;
+        self.compile(&dirs, ast, file)
+    }
 
-        self.optimise_mir(&mut mir);
+    /// Compiles a source file into an executable, storing the executable in a
+    /// cache directory.
+    ///
+    /// If an executable for the same source code and settings already exists
+    /// in the cache, it's reused instead of compiling the program again.
+    pub fn build_cached(
+        &mut self,
+        file: Option<PathBuf>,
+        cache: &Path,
+    ) -> Result<PathBuf, CompileError> {
+        let file = self.main_module_path(file)?;
+        let main_mod = self.state.db.main_module().unwrap().clone();
+        let ast = ModulesParser::new(&mut self.state)
+            .run(vec![(main_mod, file.clone())]);
+        let dir = cache.join(cache::key(&self.state.config, &ast));
+
+        self.state.config.build = dir.clone();
 
         let dirs = BuildDirectories::new(&self.state.config);
+        let exe = self.executable_path(&dirs, &file);
 
-        dirs.create().map_err(CompileError::Internal)?;
+        if !self.state.diagnostics.has_errors() && exe.is_file() {
+            cache::mark_used(&dir);
+            return Ok(exe);
+        }
+
+        // The program is compiled into a separate directory first, such that
+        // an interrupted compilation doesn't leave behind an incomplete
+        // executable that we'd then reuse.
+        let staging = cache::staging_directory(cache, &dir);
+
+        self.state.config.build = staging.clone();
 
-        if self.state.config.dot {
-            self.write_dot(&dirs, &mir)?;
+        let staging_dirs = BuildDirectories::new(&self.state.config);
+        let result = self.compile(&staging_dirs, ast, file);
+
+        self.state.config.build = dir.clone();
+
+        if let Err(err) = result {
+            let _ = remove_dir_all(&staging);
+            return Err(err);
         }
 
-        self.compile_machine_code(&dirs, &mir, file)
+        cache::commit(&staging, &dir, &exe).map_err(CompileError::Internal)?;
+        cache::mark_used(&dir);
+        cache::evict(cache);
+        Ok(exe)
     }
 
     pub fn print_diagnostics(&self) {
         self.state.config.presenter.present(&self.state.diagnostics);
     }
 
+    fn compile(
+        &mut self,
+        dirs: &BuildDirectories,
+        ast: Vec<ParsedModule>,
+        file: PathBuf,
+    ) -> Result<PathBuf, CompileError> {
+        dirs.create().map_err(CompileError::Internal)?;
+
+        if self.state.config.emits(Emit::Ast) {
+            self.emit_ast(dirs, &ast)?;
+        }
+
+        let hir = self.compile_hir(ast)?;
+        let mut mir = self.compile_mir(hir)?;
+
+        self.optimise_mir(&mut mir)?;
+
+        if self.state.config.emits(Emit::Mir) {
+            self.emit_mir(dirs, &mir, Emit::Mir)?;
+        }
+
+        if self.state.config.emits(Emit::Dot) {
+            self.emit_mir(dirs, &mir, Emit::Dot)?;
+        }
+
+        self.compile_machine_code(dirs, &mir, file)
+    }
+
     fn main_module_path(
         &mut self,
         file: Option<PathBuf>,
@@ -132,6 +202,10 @@ impl Compiler {
             return Err(CompileError::Invalid);
         }
 
+        if self.state.config.emits(Emit::Hir) {
+            self.emit_hir(&modules)?;
+        }
+
         let mut mir = Mir::new();
         let state = &mut self.state;
 
@@ -170,6 +244,7 @@ impl Compiler {
             && DefineModuleMethodNames::run_all(state, modules)
             && DefineImportedTypes::run_all(state, modules)
             && InsertPrelude::run_all(state, modules)
+            && DeriveTraits::run_all(state, modules)
             && DefineTypeParameters::run_all(state, modules)
             && DefineTypeParameterRequirements::run_all(state, modules)
             && DefineTraitRequirements::run_all(state, modules)
@@ -179,40 +254,97 @@ impl Compiler {
             && CheckTypeParameters::run_all(state, modules)
             && DefineVariants::run_all(state, modules)
             && DefineFields::run_all(state, modules)
+            && CheckDerivedTraits::run_all(state, modules)
             && DefineMethods::run_all(state, modules)
             && CheckMainMethod::run(state)
+            && DefineExportedMethods::run_all(state, modules)
             && ImplementTraitMethods::run_all(state, modules)
             && DefineConstants::run_all(state, modules)
             && Expressions::run_all(state, modules)
     }
 
-    fn optimise_mir(&mut self, mir: &mut Mir) {
+    fn optimise_mir(&mut self, mir: &mut Mir) -> Result<(), CompileError> {
         Specialize::run_all(&mut self.state, mir);
+        tail_calls(&mut self.state, mir);
+
+        if self.state.diagnostics.has_errors() {
+            return Err(CompileError::Invalid);
+        }
+
+        if !matches!(self.state.config.opt, Opt::None) {
+            stack_allocate(&self.state.db, mir);
+        }
+
         mir::clean_up_basic_blocks(mir);
+        Ok(())
     }
 
-    fn write_dot(
+    fn emit_ast(
+        &self,
+        directories: &BuildDirectories,
+        modules: &[ParsedModule],
+    ) -> Result<(), CompileError> {
+        for module in modules {
+            if !self.state.config.emit_module(&module.name) {
+                continue;
+            }
+
+            let output = ast::printer::to_text(&module.ast);
+
+            write_emit(directories, &module.name, Emit::Ast, output)?;
+        }
+
+        Ok(())
+    }
+
+    fn emit_hir(&self, modules: &[hir::Module]) -> Result<(), CompileError> {
+        let directories = BuildDirectories::new(&self.state.config);
+
+        for module in modules {
+            let name = module.module_id.name(&self.state.db);
+
+            if !self.state.config.emit_module(name) {
+                continue;
+            }
+
+            let output = hir::printer::to_text(&self.state.db, module);
+
+            write_emit(&directories, name, Emit::Hir, output)?;
+        }
+
+        Ok(())
+    }
+
+    fn emit_mir(
         &self,
         directories: &BuildDirectories,
         mir: &Mir,
+        emit: Emit,
     ) -> Result<(), CompileError> {
-        directories.create_dot().map_err(CompileError::Internal)?;
+        let db = &self.state.db;
+        let filter = self.state.config.emit_filter.as_ref();
 
         for module in mir.modules.values() {
-            let methods: Vec<_> =
-                module.methods.iter().map(|m| &mir.methods[m]).collect();
+            let name = module.id.name(db);
 
-            let output = to_dot(&self.state.db, mir, &methods);
-            let name = module.id.name(&self.state.db).normalized_name();
-            let path = directories.dot.join(format!("{}.dot", name));
+            if !self.state.config.emit_module(name) {
+                continue;
+            }
 
-            write(&path, output).map_err(|err| {
-                CompileError::Internal(format!(
-                    "Failed to write {}: {}",
-                    path.display(),
-                    err
-                ))
-            })?;
+            let methods: Vec<_> = module
+                .methods
+                .iter()
+                .filter(|&&m| filter.map_or(true, |f| f.includes_method(db, m)))
+                .map(|m| &mir.methods[m])
+                .collect();
+
+            let output = if let Emit::Dot = emit {
+                to_dot(db, mir, &methods)
+            } else {
+                to_text(db, mir, &methods)
+            };
+
+            write_emit(directories, name, emit, output)?;
         }
 
         Ok(())
@@ -224,25 +356,64 @@ impl Compiler {
         mir: &Mir,
         main_file: PathBuf,
     ) -> Result<PathBuf, CompileError> {
-        let exe = match &self.state.config.output {
+        let exe = self.executable_path(directories, &main_file);
+        let objects =
+            llvm::passes::Compile::run_all(&self.state, directories, mir)
+                .map_err(CompileError::Internal)?;
+
+        link(&self.state, directories, &exe, &objects)
+            .map_err(CompileError::Internal)?;
+
+        if self.state.config.library.is_some() {
+            self.write_header(&exe)?;
+        }
+
+        Ok(exe)
+    }
+
+    fn executable_path(
+        &self,
+        directories: &BuildDirectories,
+        main_file: &Path,
+    ) -> PathBuf {
+        match &self.state.config.output {
             Output::Derive => {
                 let name = main_file
                     .file_stem()
                     .map(|s| s.to_string_lossy().into_owned())
                     .unwrap_or_else(|| "main".to_string());
 
-                directories.bin.join(name)
+                directories.bin.join(self.output_name(name))
             }
             Output::File(name) => directories.bin.join(name),
             Output::Path(path) => path.clone(),
+        }
+    }
+
+    fn output_name(&self, name: String) -> String {
+        let ext = match self.state.config.target.os {
+            OperatingSystem::Mac => "dylib",
+            _ => "so",
         };
 
-        let objects =
-            llvm::passes::Compile::run_all(&self.state, directories, mir)
-                .map_err(CompileError::Internal)?;
+        match self.state.config.library {
+            Some(Library::Static) => format!("lib{}.a", name),
+            Some(Library::Shared) => format!("lib{}.{}", name, ext),
+            None => name,
+        }
+    }
 
-        link(&self.state, &exe, &objects).map_err(CompileError::Internal)?;
-        Ok(exe)
+    fn write_header(&self, library: &Path) -> Result<(), CompileError> {
+        let name = self.state.db.main_module().unwrap().normalized_name();
+        let path = library.with_file_name(format!("{}.h", name));
+
+        write(&path, header::generate(&self.state.db)).map_err(|err| {
+            CompileError::Internal(format!(
+                "Failed to write {}: {}",
+                path.display(),
+                err
+            ))
+        })
     }
 
     fn module_name_from_path(&self, file: &Path) -> ModuleName {
@@ -315,3 +486,21 @@ impl Compiler {
         Ok(modules)
     }
 }
+
+fn write_emit(
+    directories: &BuildDirectories,
+    module: &ModuleName,
+    emit: Emit,
+    output: String,
+) -> Result<(), CompileError> {
+    let path = directories.emit_path(module, emit);
+
+    directories
+        .create_emit()
+        .and_then(|_| {
+            write(&path, output).map_err(|err| {
+                format!("Failed to write {}: {}", path.display(), err)
+            })
+        })
+        .map_err(CompileError::Internal)
+}
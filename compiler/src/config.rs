@@ -1,28 +1,41 @@
 //! Configuration for the compiler.
+use crate::pkg::manifest::{Manifest, Setting, MANIFEST_FILE};
 use crate::presenters::{JSONPresenter, Presenter, TextPresenter};
 use crate::target::Target;
+use std::collections::HashMap;
 use std::env;
 use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 use types::module_name::ModuleName;
+use types::{Database, MethodId, TypeId};
 
 /// The extension to use for source files.
-pub(crate) const SOURCE_EXT: &str = "inko";
+pub const SOURCE_EXT: &str = "inko";
 
 /// The name of the module to compile if no explicit file/module is provided.
-pub(crate) const MAIN_MODULE: &str = "main";
+pub const MAIN_MODULE: &str = "main";
 
 /// The name of the directory containing a project's source code.
-pub(crate) const SOURCE: &str = "src";
+pub const SOURCE: &str = "src";
 
 /// The name of the directory containing third-party dependencies.
 pub const DEP: &str = "dep";
 
+/// The name of the directory containing vendored third-party dependencies.
+pub const VENDOR: &str = "vendor";
+
 /// The name of the directory containing a project's unit tests.
-pub(crate) const TESTS: &str = "test";
+pub const TESTS: &str = "test";
 
 /// The name of the directory to store build files in.
-const BUILD: &str = "build";
+pub const BUILD: &str = "build";
+
+/// The error produced when limiting the assembly output to a single method.
+///
+/// Assembly is produced by LLVM for entire modules, and extracting a single
+/// function from it depends on the object file format.
+const ASM_METHOD_FILTER: &str =
+    "Assembly can only be emitted for entire modules, not for individual methods";
 
 fn create_directory(path: &Path) -> Result<(), String> {
     if path.is_dir() {
@@ -41,14 +54,12 @@ pub(crate) struct BuildDirectories {
     /// The directory to store object files in.
     pub(crate) objects: PathBuf,
 
-    /// The directory to store LLVM IR files in.
-    pub(crate) llvm_ir: PathBuf,
-
     /// The directory to place executable files in.
     pub(crate) bin: PathBuf,
 
-    /// The directory to write DOT files to.
-    pub(crate) dot: PathBuf,
+    /// The directory to write intermediate representations to, as requested
+    /// using the `--emit` option.
+    pub(crate) emit: PathBuf,
 }
 
 impl BuildDirectories {
@@ -59,11 +70,10 @@ impl BuildDirectories {
             .map_or(config.build.clone(), |p| config.build.join(p));
 
         let objects = build.join("objects");
-        let llvm_ir = build.join("llvm");
-        let dot = build.join("dot");
+        let emit = build.join("emit");
         let bin = build.clone();
 
-        BuildDirectories { build, objects, llvm_ir, bin, dot }
+        BuildDirectories { build, objects, bin, emit }
     }
 
     pub(crate) fn create(&self) -> Result<(), String> {
@@ -72,12 +82,18 @@ impl BuildDirectories {
             .and_then(|_| create_directory(&self.bin))
     }
 
-    pub(crate) fn create_dot(&self) -> Result<(), String> {
-        create_directory(&self.dot)
+    pub(crate) fn create_emit(&self) -> Result<(), String> {
+        create_directory(&self.emit)
     }
 
-    pub(crate) fn create_llvm(&self) -> Result<(), String> {
-        create_directory(&self.llvm_ir)
+    /// Returns the path to write an intermediate representation of a module
+    /// to.
+    pub(crate) fn emit_path(&self, module: &ModuleName, emit: Emit) -> PathBuf {
+        self.emit.join(format!(
+            "{}.{}",
+            module.normalized_name(),
+            emit.extension()
+        ))
     }
 }
 
@@ -107,6 +123,7 @@ impl Opt {
 }
 
 /// A type describing where to write the executable to.
+#[derive(Debug)]
 pub enum Output {
     /// Derive the output path from the main module, and place it in the default
     /// output directory.
@@ -120,6 +137,128 @@ pub enum Output {
     Path(PathBuf),
 }
 
+/// A type describing the kind of library to produce, instead of an executable.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Library {
+    /// A static library (e.g. `libfoo.a`).
+    Static,
+
+    /// A shared library (e.g. `libfoo.so`).
+    Shared,
+}
+
+/// A type describing an intermediate representation to write to disk.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Emit {
+    /// The AST of a module, as produced by the parser.
+    Ast,
+
+    /// The HIR of a module, after type-checking it.
+    Hir,
+
+    /// A textual listing of the MIR of a module.
+    Mir,
+
+    /// The MIR of a module as a Graphviz/DOT graph.
+    Dot,
+
+    /// The LLVM IR of a module.
+    Llvm,
+
+    /// The assembly generated for a module.
+    Asm,
+
+    /// The command used for linking the program, as a JSON file in the same
+    /// format as `compile_commands.json`.
+    Link,
+}
+
+impl Emit {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Emit::Ast => "ast",
+            Emit::Hir => "hir",
+            Emit::Mir => "mir",
+            Emit::Dot => "dot",
+            Emit::Llvm => "ll",
+            Emit::Asm => "s",
+            Emit::Link => "json",
+        }
+    }
+}
+
+/// Settings used when linking executables and shared libraries.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+pub struct LinkerSettings {
+    /// The program to use for linking, instead of the system's C compiler.
+    pub linker: Option<PathBuf>,
+
+    /// Additional arguments to pass to the linker.
+    pub arguments: Vec<String>,
+
+    /// Additional directories to search for C libraries.
+    pub library_paths: Vec<PathBuf>,
+}
+
+impl LinkerSettings {
+    fn merge(&mut self, other: &LinkerSettings) {
+        if other.linker.is_some() {
+            self.linker = other.linker.clone();
+        }
+
+        self.arguments.extend(other.arguments.iter().cloned());
+        self.library_paths.extend(other.library_paths.iter().cloned());
+    }
+}
+
+/// A type describing which parts of a program to emit intermediate
+/// representations for.
+pub struct EmitFilter {
+    /// The name of the module to emit.
+    pub(crate) module: ModuleName,
+
+    /// The name of the method to emit, if only a single method is to be
+    /// emitted.
+    ///
+    /// Module methods are specified using just their name (e.g. `main`), while
+    /// methods defined on classes and traits use the format `Type.method`.
+    pub(crate) method: Option<String>,
+}
+
+impl EmitFilter {
+    pub(crate) fn includes_module(&self, name: &ModuleName) -> bool {
+        &self.module == name
+    }
+
+    pub(crate) fn includes_method(&self, db: &Database, id: MethodId) -> bool {
+        let name = if let Some(name) = &self.method {
+            name
+        } else {
+            return true;
+        };
+
+        let receiver = match id.receiver_id(db) {
+            TypeId::Class(cls) if !cls.kind(db).is_module() => {
+                Some(cls.name(db))
+            }
+            TypeId::ClassInstance(ins)
+                if !ins.instance_of().kind(db).is_module() =>
+            {
+                Some(ins.instance_of().name(db))
+            }
+            TypeId::Trait(id) => Some(id.name(db)),
+            TypeId::TraitInstance(ins) => Some(ins.instance_of().name(db)),
+            _ => None,
+        };
+
+        if let Some(rec) = receiver {
+            *name == format!("{}.{}", rec, id.name(db))
+        } else {
+            name == id.name(db)
+        }
+    }
+}
+
 /// A type for storing compiler configuration, such as the source directories to
 /// search for modules.
 pub struct Config {
@@ -136,6 +275,12 @@ pub struct Config {
     /// The directory containing the project's dependencies.
     pub dependencies: PathBuf,
 
+    /// The directory containing the project's vendored dependencies.
+    ///
+    /// If this directory exists, it's used instead of the `dependencies`
+    /// directory.
+    pub vendor: PathBuf,
+
     /// The directory containing the project's unit tests.
     pub tests: PathBuf,
 
@@ -161,19 +306,46 @@ pub struct Config {
     pub(crate) target: Target,
 
     /// If MIR should be printed to DOT files.
+    #[deprecated(note = "use `Config::add_emit(\"dot\")` instead")]
     pub dot: bool,
 
     /// If LLVM IR should be verified as part of code generation.
     pub verify_llvm: bool,
 
     /// If LLVM IR should be written to disk.
+    #[deprecated(note = "use `Config::add_emit(\"llvm\")` instead")]
     pub write_llvm: bool,
 
+    /// The intermediate representations to write to disk.
+    pub emit: Vec<Emit>,
+
+    /// The module or method to limit the emitted output to.
+    pub emit_filter: Option<EmitFilter>,
+
     /// If C libraries should be linked statically or not.
     pub static_linking: bool,
+
+    /// Additional build tags to define, on top of those derived from the
+    /// target.
+    pub build_tags: Vec<String>,
+
+    /// Additional C libraries to link against.
+    pub libraries: Vec<String>,
+
+    /// The linker settings that apply to all targets.
+    pub linker: LinkerSettings,
+
+    /// The linker settings that only apply to specific targets, keyed by the
+    /// target name.
+    pub target_linker: HashMap<String, LinkerSettings>,
+
+    /// The kind of library to produce, if a library is to be produced instead
+    /// of an executable.
+    pub library: Option<Library>,
 }
 
 impl Config {
+    #[allow(deprecated)]
     pub(crate) fn new() -> Self {
         let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::new());
         let std = PathBuf::from(env!("INKO_STD"));
@@ -185,6 +357,7 @@ impl Config {
             tests: cwd.join(TESTS),
             build: cwd.join(BUILD),
             dependencies: cwd.join(DEP),
+            vendor: cwd.join(VENDOR),
             sources: Vec::new(),
             presenter: Box::new(TextPresenter::with_colors()),
             implicit_imports: vec![],
@@ -194,7 +367,14 @@ impl Config {
             dot: false,
             verify_llvm: false,
             write_llvm: false,
+            emit: Vec::new(),
+            emit_filter: None,
             static_linking: false,
+            build_tags: Vec::new(),
+            libraries: Vec::new(),
+            linker: LinkerSettings::default(),
+            target_linker: HashMap::new(),
+            library: None,
         }
     }
 
@@ -248,6 +428,192 @@ impl Config {
         Ok(())
     }
 
+    pub fn set_library(&mut self, name: &str) -> Result<(), String> {
+        self.library = match name {
+            "static" => Some(Library::Static),
+            "shared" => Some(Library::Shared),
+            _ => {
+                return Err(format!(
+                    "The library type '{}' isn't supported",
+                    name
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Applies the build settings from the package manifest in the current
+    /// working directory, if there is such a manifest.
+    ///
+    /// This method should be called before applying any command-line options,
+    /// such that these options take precedence.
+    pub fn load_manifest(&mut self) -> Result<(), String> {
+        let path = PathBuf::from(MANIFEST_FILE);
+
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        let manifest = Manifest::load(&path)?;
+
+        for setting in manifest.settings() {
+            match setting {
+                Setting::Opt(val) => self.set_opt(val),
+                Setting::Static => {
+                    self.static_linking = true;
+                    Ok(())
+                }
+                Setting::Include(val) => {
+                    self.add_source_directory(val.clone());
+                    Ok(())
+                }
+                Setting::Define(val) => self.add_build_tag(val),
+                Setting::Link(val) => {
+                    self.libraries.push(val.clone());
+                    Ok(())
+                }
+                Setting::Linker(_)
+                | Setting::LinkerArgument(_)
+                | Setting::LibraryPath(_) => self.linker_setting(None, setting),
+                Setting::Target(target, setting) => {
+                    self.linker_setting(Some(target), setting)
+                }
+                Setting::Output(val) => {
+                    self.output = Output::File(val.clone());
+                    Ok(())
+                }
+            }
+            .map_err(|err| format!("{}: {}", MANIFEST_FILE, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies a linker setting, either to all targets or only to the given
+    /// target.
+    pub fn linker_setting(
+        &mut self,
+        target: Option<&str>,
+        setting: &Setting,
+    ) -> Result<(), String> {
+        let settings = if let Some(name) = target {
+            let target = Target::from_str(name).ok_or_else(|| {
+                format!("The target '{}' isn't supported", name)
+            })?;
+
+            self.target_linker.entry(target.to_string()).or_default()
+        } else {
+            &mut self.linker
+        };
+
+        match setting {
+            Setting::Linker(val) => settings.linker = Some(val.clone()),
+            Setting::LinkerArgument(val) => {
+                settings.arguments.push(val.clone())
+            }
+            Setting::LibraryPath(val) => {
+                settings.library_paths.push(val.clone())
+            }
+            _ => {
+                return Err(format!(
+                    "The setting '{}' can't be used as a linker setting",
+                    setting
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_build_tag(&mut self, name: &str) -> Result<(), String> {
+        let valid = name
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_ascii_lowercase() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if !valid {
+            return Err(format!("The build tag '{}' is invalid", name));
+        }
+
+        if !self.build_tags.iter().any(|v| v == name) {
+            self.build_tags.push(name.to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn add_emit(&mut self, name: &str) -> Result<(), String> {
+        let emit = match name {
+            "ast" => Emit::Ast,
+            "hir" => Emit::Hir,
+            "mir" => Emit::Mir,
+            "dot" => Emit::Dot,
+            "llvm" => Emit::Llvm,
+            "asm" => Emit::Asm,
+            "link" => Emit::Link,
+            _ => {
+                return Err(format!(
+                    "The intermediate representation '{}' isn't supported",
+                    name
+                ))
+            }
+        };
+
+        if emit == Emit::Asm && self.emit_filter_has_method() {
+            return Err(ASM_METHOD_FILTER.to_string());
+        }
+
+        if !self.emit.contains(&emit) {
+            self.emit.push(emit);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_emit_filter(&mut self, value: &str) -> Result<(), String> {
+        let (module, method) = match value.split_once(':') {
+            Some((module, method)) => (module, Some(method.to_string())),
+            None => (value, None),
+        };
+
+        if method.is_some() && self.emits(Emit::Asm) {
+            return Err(ASM_METHOD_FILTER.to_string());
+        }
+
+        self.emit_filter =
+            Some(EmitFilter { module: ModuleName::new(module), method });
+
+        Ok(())
+    }
+
+    fn emit_filter_has_method(&self) -> bool {
+        self.emit_filter.as_ref().map_or(false, |f| f.method.is_some())
+    }
+
+    #[allow(deprecated)]
+    pub(crate) fn emits(&self, emit: Emit) -> bool {
+        self.emit.contains(&emit)
+            || (emit == Emit::Dot && self.dot)
+            || (emit == Emit::Llvm && self.write_llvm)
+    }
+
+    /// Returns the linker settings to use for the current target.
+    pub(crate) fn linker_settings(&self) -> LinkerSettings {
+        let mut settings = self.linker.clone();
+
+        if let Some(target) = self.target_linker.get(&self.target.to_string()) {
+            settings.merge(target);
+        }
+
+        settings
+    }
+
+    pub(crate) fn emit_module(&self, name: &ModuleName) -> bool {
+        self.emit_filter.as_ref().map_or(true, |f| f.includes_module(name))
+    }
+
     pub(crate) fn main_source_module(&self) -> PathBuf {
         let mut main_file = self.source.join(MAIN_MODULE);
 
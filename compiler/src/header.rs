@@ -0,0 +1,188 @@
+//! Generating C header files for libraries.
+use crate::symbol_names::library_symbol;
+use std::fmt::Write as _;
+use types::{Block, ClassId, Database, ForeignType, TypeId, TypeRef};
+
+fn c_type_name(id: TypeId) -> Option<String> {
+    let name = match id {
+        TypeId::Foreign(ForeignType::Int(size, true)) => {
+            format!("int{}_t", size)
+        }
+        TypeId::Foreign(ForeignType::Int(size, false)) => {
+            format!("uint{}_t", size)
+        }
+        TypeId::Foreign(ForeignType::Float(32)) => "float".to_string(),
+        TypeId::Foreign(ForeignType::Float(_)) => "double".to_string(),
+        TypeId::ClassInstance(ins) if ins.instance_of() == ClassId::int() => {
+            "int64_t".to_string()
+        }
+        TypeId::ClassInstance(ins) if ins.instance_of() == ClassId::float() => {
+            "double".to_string()
+        }
+        TypeId::ClassInstance(ins) if ins.instance_of() == ClassId::nil() => {
+            "void".to_string()
+        }
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Returns the C type to use for an Inko type.
+///
+/// The type is expected to be compatible with C, i.e.
+/// `TypeRef::is_c_compatible()` returns `true` for it.
+pub(crate) fn c_type(db: &Database, typ: TypeRef) -> String {
+    match typ {
+        // Extern classes aren't included in the header, so pointers to such
+        // types are exposed as void pointers.
+        TypeRef::Pointer(id) => {
+            format!("{}*", c_type_name(id).unwrap_or_else(|| "void".into()))
+        }
+        _ => typ
+            .type_id(db)
+            .ok()
+            .and_then(c_type_name)
+            .unwrap_or_else(|| "void".to_string()),
+    }
+}
+
+/// Returns the C header for a library, declaring the functions it exports.
+pub(crate) fn generate(db: &Database) -> String {
+    let guard = format!(
+        "INKO_{}_H",
+        db.main_module().unwrap().normalized_name().to_uppercase()
+    );
+    let mut buf = String::new();
+
+    let _ = write!(
+        buf,
+        "// This file is generated by the Inko compiler, don't edit it.\n\
+        #ifndef {guard}\n\
+        #define {guard}\n\
+        \n\
+        #include <stdint.h>\n\
+        \n\
+        #ifdef __cplusplus\n\
+        extern \"C\" {{\n\
+        #endif\n\
+        \n\
+        typedef struct InkoRuntime InkoRuntime;\n\
+        \n\
+        // Starts the Inko runtime. This function must be called before calling\n\
+        // any of the other functions, and only once.\n\
+        InkoRuntime *{}(int argc, char **argv);\n\
+        \n\
+        // Stops the Inko runtime and releases its resources.\n\
+        void {}(InkoRuntime *runtime);\n",
+        library_symbol(db, "init"),
+        library_symbol(db, "shutdown"),
+    );
+
+    for &method in db.exported_methods() {
+        let name = library_symbol(db, method.name(db));
+        let ret = c_type(db, method.return_type(db));
+        let mut args = vec!["InkoRuntime *runtime".to_string()];
+
+        for arg in method.arguments(db) {
+            args.push(format!("{} {}", c_type(db, arg.value_type), arg.name));
+        }
+
+        let _ = write!(buf, "\n{} {}({});\n", ret, name, args.join(", "));
+    }
+
+    let _ = write!(
+        buf,
+        "\n\
+        #ifdef __cplusplus\n\
+        }}\n\
+        #endif\n\
+        \n\
+        #endif\n"
+    );
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::module_name::ModuleName;
+    use types::{ClassInstance, Method, MethodKind, Module, Visibility};
+
+    fn int() -> TypeRef {
+        TypeRef::Owned(TypeId::ClassInstance(
+            ClassInstance::new(ClassId::int()),
+        ))
+    }
+
+    #[test]
+    fn test_c_type() {
+        let db = Database::new();
+
+        assert_eq!(c_type(&db, int()), "int64_t");
+        assert_eq!(c_type(&db, TypeRef::foreign_signed_int(8)), "int8_t");
+        assert_eq!(c_type(&db, TypeRef::foreign_unsigned_int(32)), "uint32_t");
+        assert_eq!(c_type(&db, TypeRef::foreign_float(32)), "float");
+        assert_eq!(c_type(&db, TypeRef::foreign_float(64)), "double");
+        assert_eq!(
+            c_type(
+                &db,
+                TypeRef::pointer(TypeId::Foreign(ForeignType::Int(8, false)))
+            ),
+            "uint8_t*"
+        );
+        assert_eq!(c_type(&db, TypeRef::nil()), "void");
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut db = Database::new();
+        let name = ModuleName::new("foo.bar");
+        let module = Module::alloc(&mut db, name.clone(), "bar.inko".into());
+        let method = Method::alloc(
+            &mut db,
+            module,
+            "add".to_string(),
+            Visibility::Public,
+            MethodKind::Static,
+        );
+
+        method.new_argument(&mut db, "a".to_string(), int(), int());
+        method.new_argument(&mut db, "b".to_string(), int(), int());
+        method.set_return_type(&mut db, int());
+        db.set_main_module(name);
+        db.add_exported_method(method);
+
+        assert_eq!(
+            generate(&db),
+            "// This file is generated by the Inko compiler, don't edit it.
+#ifndef INKO_FOO_BAR_H
+#define INKO_FOO_BAR_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+typedef struct InkoRuntime InkoRuntime;
+
+// Starts the Inko runtime. This function must be called before calling
+// any of the other functions, and only once.
+InkoRuntime *foo_bar_init(int argc, char **argv);
+
+// Stops the Inko runtime and releases its resources.
+void foo_bar_shutdown(InkoRuntime *runtime);
+
+int64_t foo_bar_add(InkoRuntime *runtime, int64_t a, int64_t b);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+"
+        );
+    }
+}
@@ -3,6 +3,8 @@
 //! HIR is generated from the AST, and share many similarities with it. Unlike
 //! the AST it stores type information, and some AST nodes are desugared into
 //! different HIR nodes.
+pub(crate) mod printer;
+
 use crate::diagnostics::DiagnosticId;
 use crate::modules_parser::ParsedModule;
 use crate::state::State;
@@ -12,10 +14,13 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use types::{
     ARRAY_INTERNAL_NAME, ARRAY_LIMIT, ARRAY_PUSH, ARRAY_WITH_CAPACITY,
+    OPTION_NONE, OPTION_SOME,
 };
 
 const BUILTIN_RECEIVER: &str = "_INKO";
 const ARRAY_LIT_VAR: &str = "$array";
+const FOR_ITER_VAR: &str = "$iter";
+const FOR_NEXT_METHOD: &str = "next";
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct IntLiteral {
@@ -210,6 +215,7 @@ impl MethodKind {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct DefineInstanceMethod {
     pub(crate) public: bool,
+    pub(crate) tail: bool,
     pub(crate) kind: MethodKind,
     pub(crate) name: Identifier,
     pub(crate) type_parameters: Vec<TypeParameter>,
@@ -223,6 +229,7 @@ pub(crate) struct DefineInstanceMethod {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct DefineModuleMethod {
     pub(crate) public: bool,
+    pub(crate) tail: bool,
     pub(crate) name: Identifier,
     pub(crate) type_parameters: Vec<TypeParameter>,
     pub(crate) arguments: Vec<MethodArgument>,
@@ -258,6 +265,7 @@ pub(crate) struct DefineRequiredMethod {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct DefineStaticMethod {
     pub(crate) public: bool,
+    pub(crate) tail: bool,
     pub(crate) name: Identifier,
     pub(crate) type_parameters: Vec<TypeParameter>,
     pub(crate) arguments: Vec<MethodArgument>,
@@ -303,6 +311,7 @@ pub(crate) enum ClassKind {
     Async,
     Builtin,
     Enum,
+    Inline,
     Regular,
 }
 
@@ -313,6 +322,8 @@ pub(crate) struct DefineClass {
     pub(crate) kind: ClassKind,
     pub(crate) name: Constant,
     pub(crate) type_parameters: Vec<TypeParameter>,
+    /// The traits to derive an implementation for.
+    pub(crate) derive: Vec<TypeName>,
     pub(crate) body: Vec<ClassExpression>,
     pub(crate) location: SourceLocation,
 }
@@ -539,6 +550,11 @@ pub(crate) enum ConstExpression {
     Binary(Box<ConstBinary>),
     ConstantRef(Box<ConstantRef>),
     Array(Box<ConstArray>),
+    Tuple(Box<ConstTuple>),
+    Cast(Box<ConstCast>),
+    Call(Box<ConstCall>),
+    BuiltinCall(Box<ConstBuiltinCall>),
+    Interpolation(Box<ConstInterpolation>),
     Invalid(Box<SourceLocation>),
     True(Box<True>),
     False(Box<False>),
@@ -553,6 +569,11 @@ impl ConstExpression {
             Self::Binary(ref n) => &n.location,
             Self::ConstantRef(ref n) => &n.location,
             Self::Array(ref n) => &n.location,
+            Self::Tuple(ref n) => &n.location,
+            Self::Cast(ref n) => &n.location,
+            Self::Call(ref n) => &n.location,
+            Self::BuiltinCall(ref n) => &n.location,
+            Self::Interpolation(ref n) => &n.location,
             Self::Invalid(ref l) => l,
             Self::True(ref n) => &n.location,
             Self::False(ref n) => &n.location,
@@ -738,6 +759,55 @@ pub(crate) struct ConstArray {
     pub(crate) location: SourceLocation,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConstTuple {
+    pub(crate) resolved_type: types::TypeRef,
+    pub(crate) values: Vec<ConstExpression>,
+    pub(crate) location: SourceLocation,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConstCast {
+    pub(crate) resolved_type: types::TypeRef,
+    pub(crate) value: ConstExpression,
+    pub(crate) cast_to: Type,
+    pub(crate) location: SourceLocation,
+}
+
+/// The operation performed by a method call in a constant expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ConstCallKind {
+    Unknown,
+    Opposite,
+    Absolute,
+    Builtin(types::BuiltinFunction),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConstCall {
+    pub(crate) kind: ConstCallKind,
+    pub(crate) resolved_type: types::TypeRef,
+    pub(crate) receiver: ConstExpression,
+    pub(crate) name: Identifier,
+    pub(crate) arguments: Vec<ConstExpression>,
+    pub(crate) location: SourceLocation,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConstBuiltinCall {
+    pub(crate) info: Option<types::BuiltinCallInfo>,
+    pub(crate) name: Identifier,
+    pub(crate) arguments: Vec<ConstExpression>,
+    pub(crate) location: SourceLocation,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConstInterpolation {
+    pub(crate) resolved_type: types::TypeRef,
+    pub(crate) values: Vec<ConstExpression>,
+    pub(crate) location: SourceLocation,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Field {
     pub(crate) name: String,
@@ -831,6 +901,8 @@ pub(crate) struct Ref {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Mut {
+    /// The method to take the address of, when using `mut` on a module method.
+    pub(crate) pointer_to_method: Option<types::MethodId>,
     pub(crate) resolved_type: types::TypeRef,
     pub(crate) value: Expression,
     pub(crate) location: SourceLocation,
@@ -1131,6 +1203,7 @@ impl<'a> LowerToHir<'a> {
         } else {
             TopLevelExpression::ModuleMethod(Box::new(DefineModuleMethod {
                 public: node.public,
+                tail: node.tail,
                 name: self.identifier(node.name),
                 type_parameters: self
                     .optional_type_parameters(node.type_parameters),
@@ -1174,11 +1247,13 @@ impl<'a> LowerToHir<'a> {
                 ast::ClassKind::Async => ClassKind::Async,
                 ast::ClassKind::Enum => ClassKind::Enum,
                 ast::ClassKind::Builtin => ClassKind::Builtin,
+                ast::ClassKind::Inline => ClassKind::Inline,
                 _ => ClassKind::Regular,
             },
             name: self.constant(node.name),
             type_parameters: self
                 .optional_type_parameters(node.type_parameters),
+            derive: self.optional_type_names(node.derive),
             body: self.class_expressions(node.body),
             location: node.location,
         }))
@@ -1273,6 +1348,7 @@ impl<'a> LowerToHir<'a> {
 
         Box::new(DefineStaticMethod {
             public: node.public,
+            tail: node.tail,
             name: self.identifier(node.name),
             type_parameters: self
                 .optional_type_parameters(node.type_parameters),
@@ -1290,6 +1366,15 @@ impl<'a> LowerToHir<'a> {
     ) -> Box<DefineAsyncMethod> {
         self.operator_method_not_allowed(node.operator, &node.location);
 
+        if node.tail {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidMethod,
+                "async methods can't be tail recursive",
+                self.file(),
+                node.location.clone(),
+            );
+        }
+
         Box::new(DefineAsyncMethod {
             mutable: node.kind == ast::MethodKind::AsyncMutable,
             public: node.public,
@@ -1310,6 +1395,7 @@ impl<'a> LowerToHir<'a> {
     ) -> DefineInstanceMethod {
         DefineInstanceMethod {
             public: node.public,
+            tail: node.tail,
             kind: match node.kind {
                 ast::MethodKind::Moving => MethodKind::Moving,
                 ast::MethodKind::Mutable => MethodKind::Mutable,
@@ -1724,6 +1810,15 @@ impl<'a> LowerToHir<'a> {
             ast::Expression::Array(node) => {
                 ConstExpression::Array(self.const_array(*node))
             }
+            ast::Expression::Tuple(node) => {
+                ConstExpression::Tuple(self.const_tuple(*node))
+            }
+            ast::Expression::TypeCast(node) => {
+                ConstExpression::Cast(self.const_cast(*node))
+            }
+            ast::Expression::Call(node) if node.receiver.is_some() => {
+                self.const_call(*node)
+            }
             node => {
                 self.state.diagnostics.error(
                     DiagnosticId::InvalidConstExpr,
@@ -1975,31 +2070,53 @@ impl<'a> LowerToHir<'a> {
         &mut self,
         node: ast::DoubleStringLiteral,
     ) -> ConstExpression {
-        let mut value = String::new();
+        let mut values = Vec::new();
+        let mut interpolated = false;
 
-        // While we could in theory support string interpolation, for the sake
-        // of simplicity we don't. This ensures we don't have to main two
-        // versions of string conversion for constant types: one in the standard
-        // library, and one here in the compiler.
         for val in node.values {
             match val {
-                ast::DoubleStringValue::Text(node) => value += &node.value,
+                ast::DoubleStringValue::Text(node) => {
+                    if let Some(ConstExpression::String(prev)) =
+                        values.last_mut()
+                    {
+                        prev.value += &node.value;
+                        prev.location = SourceLocation::start_end(
+                            &prev.location,
+                            &node.location,
+                        );
+                    } else {
+                        values.push(ConstExpression::String(Box::new(
+                            ConstStringLiteral {
+                                value: node.value,
+                                resolved_type: types::TypeRef::Unknown,
+                                location: node.location,
+                            },
+                        )));
+                    }
+                }
                 ast::DoubleStringValue::Expression(node) => {
-                    self.state.diagnostics.error(
-                        DiagnosticId::InvalidConstExpr,
-                        "constant values don't support string interpolation",
-                        self.file(),
-                        node.location.clone(),
-                    );
-
-                    return ConstExpression::Invalid(Box::new(node.location));
+                    interpolated = true;
+                    values.push(self.const_value(node.value));
                 }
             }
         }
 
-        ConstExpression::String(Box::new(ConstStringLiteral {
-            value,
+        if !interpolated {
+            let value = match values.pop() {
+                Some(ConstExpression::String(n)) => n.value,
+                _ => String::new(),
+            };
+
+            return ConstExpression::String(Box::new(ConstStringLiteral {
+                value,
+                resolved_type: types::TypeRef::Unknown,
+                location: node.location,
+            }));
+        }
+
+        ConstExpression::Interpolation(Box::new(ConstInterpolation {
             resolved_type: types::TypeRef::Unknown,
+            values,
             location: node.location,
         }))
     }
@@ -2025,6 +2142,81 @@ impl<'a> LowerToHir<'a> {
         })
     }
 
+    fn const_tuple(&mut self, node: ast::Tuple) -> Box<ConstTuple> {
+        let values =
+            node.values.into_iter().map(|n| self.const_value(n)).collect();
+
+        Box::new(ConstTuple {
+            resolved_type: types::TypeRef::Unknown,
+            values,
+            location: node.location,
+        })
+    }
+
+    fn const_cast(&mut self, node: ast::TypeCast) -> Box<ConstCast> {
+        Box::new(ConstCast {
+            resolved_type: types::TypeRef::Unknown,
+            value: self.const_value(node.value),
+            cast_to: self.type_reference(node.cast_to),
+            location: node.location,
+        })
+    }
+
+    fn const_call(&mut self, node: ast::Call) -> ConstExpression {
+        if self.is_builtin_call(&node) {
+            if !self.module.is_std(&self.state.db) {
+                self.state.diagnostics.invalid_builtin_function(
+                    self.file(),
+                    node.location.clone(),
+                );
+            }
+
+            return ConstExpression::BuiltinCall(Box::new(ConstBuiltinCall {
+                info: None,
+                name: self.identifier(node.name),
+                arguments: self.const_call_arguments(node.arguments),
+                location: node.location,
+            }));
+        }
+
+        ConstExpression::Call(Box::new(ConstCall {
+            kind: ConstCallKind::Unknown,
+            resolved_type: types::TypeRef::Unknown,
+            receiver: self.const_value(node.receiver.unwrap()),
+            name: self.identifier(node.name),
+            arguments: self.const_call_arguments(node.arguments),
+            location: node.location,
+        }))
+    }
+
+    fn const_call_arguments(
+        &mut self,
+        arguments: Option<ast::Arguments>,
+    ) -> Vec<ConstExpression> {
+        let mut exprs = Vec::new();
+
+        if let Some(args) = arguments {
+            for n in args.values.into_iter() {
+                exprs.push(match n {
+                    ast::Argument::Positional(n) => self.const_value(n),
+                    ast::Argument::Named(node) => {
+                        self.state.diagnostics.error(
+                            DiagnosticId::InvalidConstExpr,
+                            "constant method calls don't support named \
+                            arguments",
+                            self.file(),
+                            node.location,
+                        );
+
+                        self.const_value(node.value)
+                    }
+                });
+            }
+        }
+
+        exprs
+    }
+
     fn binary_operator(&self, operator: &ast::Operator) -> Operator {
         // This isn't ideal, but I also don't want to introduce a standalone
         // Operator enum in its own module _just_ so we don't need this match.
@@ -2179,6 +2371,9 @@ impl<'a> LowerToHir<'a> {
             ast::Expression::While(node) => {
                 Expression::Loop(self.while_expression(*node))
             }
+            ast::Expression::For(node) => {
+                Expression::Scope(self.for_expression(*node))
+            }
             ast::Expression::Scope(node) => {
                 Expression::Scope(self.scope(*node))
             }
@@ -2583,6 +2778,7 @@ impl<'a> LowerToHir<'a> {
 
     fn mut_reference(&mut self, node: ast::Mut) -> Box<Mut> {
         Box::new(Mut {
+            pointer_to_method: None,
             resolved_type: types::TypeRef::Unknown,
             value: self.expression(node.value),
             location: node.location,
@@ -2775,6 +2971,109 @@ impl<'a> LowerToHir<'a> {
         Box::new(Loop { body, location: node.location })
     }
 
+    /// Desugars a `for` loop into a regular `loop`.
+    ///
+    /// Loops like this:
+    ///
+    ///     for pattern in iter {
+    ///       body
+    ///     }
+    ///
+    /// Are desugared into this:
+    ///
+    ///     {
+    ///       let $iter = iter
+    ///
+    ///       loop {
+    ///         match $iter.next {
+    ///           case Some(pattern) -> body
+    ///           case None -> break
+    ///         }
+    ///       }
+    ///     }
+    fn for_expression(&mut self, node: ast::For) -> Box<Scope> {
+        let iter_loc = node.iterator.location().clone();
+        let pattern_loc = node.pattern.location().clone();
+        let def_var = Expression::DefineVariable(Box::new(DefineVariable {
+            resolved_type: types::TypeRef::Unknown,
+            variable_id: None,
+            mutable: false,
+            name: Identifier {
+                name: FOR_ITER_VAR.to_string(),
+                location: iter_loc.clone(),
+            },
+            value_type: None,
+            value: self.expression(node.iterator),
+            location: iter_loc.clone(),
+        }));
+        let next = Expression::Call(Box::new(Call {
+            kind: types::CallKind::Unknown,
+            receiver: Some(Expression::IdentifierRef(Box::new(
+                IdentifierRef {
+                    name: FOR_ITER_VAR.to_string(),
+                    kind: types::IdentifierKind::Unknown,
+                    location: iter_loc.clone(),
+                },
+            ))),
+            name: Identifier {
+                name: FOR_NEXT_METHOD.to_string(),
+                location: iter_loc.clone(),
+            },
+            arguments: Vec::new(),
+            location: iter_loc.clone(),
+        }));
+        let body = vec![Expression::Match(Box::new(Match {
+            resolved_type: types::TypeRef::Unknown,
+            expression: next,
+            cases: vec![
+                MatchCase {
+                    variable_ids: Vec::new(),
+                    pattern: Pattern::Variant(Box::new(VariantPattern {
+                        variant_id: None,
+                        name: Constant {
+                            name: OPTION_SOME.to_string(),
+                            location: pattern_loc.clone(),
+                        },
+                        values: vec![self.pattern(node.pattern)],
+                        location: pattern_loc.clone(),
+                    })),
+                    guard: None,
+                    body: self.expressions(node.body),
+                    location: pattern_loc,
+                },
+                MatchCase {
+                    variable_ids: Vec::new(),
+                    pattern: Pattern::Variant(Box::new(VariantPattern {
+                        variant_id: None,
+                        name: Constant {
+                            name: OPTION_NONE.to_string(),
+                            location: iter_loc.clone(),
+                        },
+                        values: Vec::new(),
+                        location: iter_loc.clone(),
+                    })),
+                    guard: None,
+                    body: vec![self.break_expression(iter_loc.clone())],
+                    location: iter_loc,
+                },
+            ],
+            location: node.location.clone(),
+            write_result: true,
+        }))];
+
+        Box::new(Scope {
+            resolved_type: types::TypeRef::Unknown,
+            body: vec![
+                def_var,
+                Expression::Loop(Box::new(Loop {
+                    body,
+                    location: node.location.clone(),
+                })),
+            ],
+            location: node.location,
+        })
+    }
+
     fn scope(&mut self, node: ast::Scope) -> Box<Scope> {
         Box::new(Scope {
             resolved_type: types::TypeRef::Unknown,
@@ -3075,7 +3374,43 @@ mod tests {
 
     #[test]
     fn test_lower_constant_with_string_interpolation() {
-        let (hir, diags) = lower_top_expr("let A = \"{10}\"");
+        let (hir, diags) = lower_top_expr("let A = \"a{10}\"");
+
+        assert_eq!(diags, 0);
+        assert_eq!(
+            hir,
+            TopLevelExpression::Constant(Box::new(DefineConstant {
+                public: false,
+                constant_id: None,
+                name: Constant { name: "A".to_string(), location: cols(5, 5) },
+                value: ConstExpression::Interpolation(Box::new(
+                    ConstInterpolation {
+                        resolved_type: types::TypeRef::Unknown,
+                        values: vec![
+                            ConstExpression::String(Box::new(
+                                ConstStringLiteral {
+                                    value: "a".to_string(),
+                                    resolved_type: types::TypeRef::Unknown,
+                                    location: cols(10, 10)
+                                }
+                            )),
+                            ConstExpression::Int(Box::new(IntLiteral {
+                                value: 10,
+                                resolved_type: types::TypeRef::Unknown,
+                                location: cols(12, 13)
+                            }))
+                        ],
+                        location: cols(9, 15)
+                    }
+                )),
+                location: cols(1, 15)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_lower_constant_with_invalid_string_interpolation() {
+        let (hir, diags) = lower_top_expr("let A = \"{a}\"");
 
         assert_eq!(diags, 1);
         assert_eq!(
@@ -3084,8 +3419,16 @@ mod tests {
                 public: false,
                 constant_id: None,
                 name: Constant { name: "A".to_string(), location: cols(5, 5) },
-                value: ConstExpression::Invalid(Box::new(cols(10, 13))),
-                location: cols(1, 14)
+                value: ConstExpression::Interpolation(Box::new(
+                    ConstInterpolation {
+                        resolved_type: types::TypeRef::Unknown,
+                        values: vec![ConstExpression::Invalid(Box::new(cols(
+                            11, 11
+                        )))],
+                        location: cols(9, 13)
+                    }
+                )),
+                location: cols(1, 13)
             }))
         );
     }
@@ -3188,6 +3531,143 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lower_constant_with_tuple() {
+        let (hir, diags) = lower_top_expr("let A = (10, 20)");
+
+        assert_eq!(diags, 0);
+        assert_eq!(
+            hir,
+            TopLevelExpression::Constant(Box::new(DefineConstant {
+                public: false,
+                constant_id: None,
+                name: Constant { name: "A".to_string(), location: cols(5, 5) },
+                value: ConstExpression::Tuple(Box::new(ConstTuple {
+                    resolved_type: types::TypeRef::Unknown,
+                    values: vec![
+                        ConstExpression::Int(Box::new(IntLiteral {
+                            value: 10,
+                            resolved_type: types::TypeRef::Unknown,
+                            location: cols(10, 11)
+                        })),
+                        ConstExpression::Int(Box::new(IntLiteral {
+                            value: 20,
+                            resolved_type: types::TypeRef::Unknown,
+                            location: cols(14, 15)
+                        }))
+                    ],
+                    location: cols(9, 16)
+                })),
+                location: cols(1, 16)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_lower_constant_with_cast() {
+        let (hir, diags) = lower_top_expr("let A = 10 as Float");
+
+        assert_eq!(diags, 0);
+        assert_eq!(
+            hir,
+            TopLevelExpression::Constant(Box::new(DefineConstant {
+                public: false,
+                constant_id: None,
+                name: Constant { name: "A".to_string(), location: cols(5, 5) },
+                value: ConstExpression::Cast(Box::new(ConstCast {
+                    resolved_type: types::TypeRef::Unknown,
+                    value: ConstExpression::Int(Box::new(IntLiteral {
+                        value: 10,
+                        resolved_type: types::TypeRef::Unknown,
+                        location: cols(9, 10)
+                    })),
+                    cast_to: Type::Named(Box::new(TypeName {
+                        source: None,
+                        resolved_type: types::TypeRef::Unknown,
+                        name: Constant {
+                            name: "Float".to_string(),
+                            location: cols(15, 19)
+                        },
+                        arguments: Vec::new(),
+                        location: cols(15, 19)
+                    })),
+                    location: cols(9, 19)
+                })),
+                location: cols(1, 19)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_lower_constant_with_method_call() {
+        let (hir, diags) = lower_top_expr("let A = 10.rotate_left(2)");
+
+        assert_eq!(diags, 0);
+        assert_eq!(
+            hir,
+            TopLevelExpression::Constant(Box::new(DefineConstant {
+                public: false,
+                constant_id: None,
+                name: Constant { name: "A".to_string(), location: cols(5, 5) },
+                value: ConstExpression::Call(Box::new(ConstCall {
+                    kind: ConstCallKind::Unknown,
+                    resolved_type: types::TypeRef::Unknown,
+                    receiver: ConstExpression::Int(Box::new(IntLiteral {
+                        value: 10,
+                        resolved_type: types::TypeRef::Unknown,
+                        location: cols(9, 10)
+                    })),
+                    name: Identifier {
+                        name: "rotate_left".to_string(),
+                        location: cols(12, 22)
+                    },
+                    arguments: vec![ConstExpression::Int(Box::new(
+                        IntLiteral {
+                            value: 2,
+                            resolved_type: types::TypeRef::Unknown,
+                            location: cols(24, 24)
+                        }
+                    ))],
+                    location: cols(9, 25)
+                })),
+                location: cols(1, 25)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_lower_constant_with_builtin_call() {
+        let (hir, diags) = lower_top_expr("let A = _INKO.int_bit_not(2)");
+
+        assert_eq!(diags, 0);
+        assert_eq!(
+            hir,
+            TopLevelExpression::Constant(Box::new(DefineConstant {
+                public: false,
+                constant_id: None,
+                name: Constant { name: "A".to_string(), location: cols(5, 5) },
+                value: ConstExpression::BuiltinCall(Box::new(
+                    ConstBuiltinCall {
+                        info: None,
+                        name: Identifier {
+                            name: "int_bit_not".to_string(),
+                            location: cols(15, 25)
+                        },
+                        arguments: vec![ConstExpression::Int(Box::new(
+                            IntLiteral {
+                                value: 2,
+                                resolved_type: types::TypeRef::Unknown,
+                                location: cols(27, 27)
+                            }
+                        ))],
+                        location: cols(9, 28)
+                    }
+                )),
+                location: cols(1, 28)
+            }))
+        );
+    }
+
     #[test]
     fn test_lower_constant_with_boolean_array() {
         let (hir, diags) = lower_top_expr("let A = [true, false]");
@@ -3373,6 +3853,7 @@ mod tests {
             hir,
             TopLevelExpression::ModuleMethod(Box::new(DefineModuleMethod {
                 public: false,
+                tail: false,
                 name: Identifier {
                     name: "foo".to_string(),
                     location: cols(4, 6)
@@ -3434,6 +3915,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lower_tail_recursive_module_method() {
+        let (hir, diags) = lower_top_expr("fn tail foo { 10 }");
+
+        assert_eq!(diags, 0);
+        assert_eq!(
+            hir,
+            TopLevelExpression::ModuleMethod(Box::new(DefineModuleMethod {
+                public: false,
+                tail: true,
+                name: Identifier {
+                    name: "foo".to_string(),
+                    location: cols(9, 11)
+                },
+                type_parameters: Vec::new(),
+                arguments: Vec::new(),
+                return_type: None,
+                body: vec![Expression::Int(Box::new(IntLiteral {
+                    value: 10,
+                    resolved_type: types::TypeRef::Unknown,
+                    location: cols(15, 16)
+                }))],
+                method_id: None,
+                location: cols(1, 18),
+            })),
+        );
+    }
+
     #[test]
     fn test_lower_extern_function() {
         let (hir, diags) = lower_top_expr("fn extern foo");
@@ -3512,6 +4021,7 @@ mod tests {
                     mutable: false,
                     location: cols(9, 12)
                 }],
+                derive: Vec::new(),
                 body: vec![ClassExpression::Field(Box::new(DefineField {
                     public: false,
                     field_id: None,
@@ -3536,6 +4046,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lower_class_with_derive() {
+        let hir = lower_top_expr("class A derive(B) {}").0;
+
+        assert_eq!(
+            hir,
+            TopLevelExpression::Class(Box::new(DefineClass {
+                public: false,
+                kind: ClassKind::Regular,
+                class_id: None,
+                name: Constant { name: "A".to_string(), location: cols(7, 7) },
+                type_parameters: Vec::new(),
+                derive: vec![TypeName {
+                    source: None,
+                    resolved_type: types::TypeRef::Unknown,
+                    name: Constant {
+                        name: "B".to_string(),
+                        location: cols(16, 16)
+                    },
+                    arguments: Vec::new(),
+                    location: cols(16, 16)
+                }],
+                body: Vec::new(),
+                location: cols(1, 20)
+            })),
+        );
+    }
+
     #[test]
     fn test_lower_extern_class() {
         let hir = lower_top_expr("class extern A { let @a: B }").0;
@@ -3588,6 +4126,7 @@ mod tests {
                     location: cols(11, 11)
                 },
                 type_parameters: Vec::new(),
+                derive: Vec::new(),
                 body: Vec::new(),
                 location: cols(1, 14)
             })),
@@ -3606,6 +4145,7 @@ mod tests {
                 class_id: None,
                 name: Constant { name: "A".to_string(), location: cols(7, 7) },
                 type_parameters: Vec::new(),
+                derive: Vec::new(),
                 body: vec![ClassExpression::Field(Box::new(DefineField {
                     public: true,
                     field_id: None,
@@ -3663,6 +4203,7 @@ mod tests {
                     mutable: false,
                     location: cols(17, 20)
                 }],
+                derive: Vec::new(),
                 body: vec![ClassExpression::Field(Box::new(DefineField {
                     public: false,
                     field_id: None,
@@ -3702,6 +4243,7 @@ mod tests {
                     location: cols(13, 13)
                 },
                 type_parameters: Vec::new(),
+                derive: Vec::new(),
                 body: Vec::new(),
                 location: cols(1, 16)
             })),
@@ -3721,9 +4263,11 @@ mod tests {
                 kind: ClassKind::Regular,
                 name: Constant { name: "A".to_string(), location: cols(7, 7) },
                 type_parameters: Vec::new(),
+                derive: Vec::new(),
                 body: vec![ClassExpression::StaticMethod(Box::new(
                     DefineStaticMethod {
                         public: false,
+                        tail: false,
                         name: Identifier {
                             name: "a".to_string(),
                             location: cols(21, 21)
@@ -3792,6 +4336,7 @@ mod tests {
                 kind: ClassKind::Regular,
                 name: Constant { name: "A".to_string(), location: cols(7, 7) },
                 type_parameters: Vec::new(),
+                derive: Vec::new(),
                 body: vec![ClassExpression::AsyncMethod(Box::new(
                     DefineAsyncMethod {
                         mutable: false,
@@ -3851,6 +4396,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lower_class_with_tail_recursive_async_method() {
+        let diags = lower_top_expr("class A { fn async tail a {} }").1;
+
+        assert_eq!(diags, 1);
+    }
+
     #[test]
     fn test_lower_class_with_instance_method() {
         let hir = lower_top_expr("class A { fn a[A](b: B) -> D { 10 } }").0;
@@ -3863,9 +4415,11 @@ mod tests {
                 kind: ClassKind::Regular,
                 name: Constant { name: "A".to_string(), location: cols(7, 7) },
                 type_parameters: Vec::new(),
+                derive: Vec::new(),
                 body: vec![ClassExpression::InstanceMethod(Box::new(
                     DefineInstanceMethod {
                         public: false,
+                        tail: false,
                         kind: MethodKind::Regular,
                         name: Identifier {
                             name: "a".to_string(),
@@ -4106,6 +4660,7 @@ mod tests {
                 body: vec![TraitExpression::InstanceMethod(Box::new(
                     DefineInstanceMethod {
                         public: false,
+                        tail: false,
                         kind: MethodKind::Moving,
                         name: Identifier {
                             name: "a".to_string(),
@@ -4139,6 +4694,7 @@ mod tests {
                 body: vec![TraitExpression::InstanceMethod(Box::new(
                     DefineInstanceMethod {
                         public: false,
+                        tail: false,
                         kind: MethodKind::Regular,
                         name: Identifier {
                             name: "a".to_string(),
@@ -4249,6 +4805,7 @@ mod tests {
                 body: vec![ReopenClassExpression::InstanceMethod(Box::new(
                     DefineInstanceMethod {
                         public: false,
+                        tail: false,
                         kind: MethodKind::Regular,
                         name: Identifier {
                             name: "foo".to_string(),
@@ -4283,6 +4840,7 @@ mod tests {
                 body: vec![ReopenClassExpression::StaticMethod(Box::new(
                     DefineStaticMethod {
                         public: false,
+                        tail: false,
                         name: Identifier {
                             name: "foo".to_string(),
                             location: cols(20, 22)
@@ -4509,6 +5067,7 @@ mod tests {
                 bounds: Vec::new(),
                 body: vec![DefineInstanceMethod {
                     public: false,
+                    tail: false,
                     kind: MethodKind::Regular,
                     name: Identifier {
                         name: "foo".to_string(),
@@ -4552,6 +5111,7 @@ mod tests {
                 bounds: Vec::new(),
                 body: vec![DefineInstanceMethod {
                     public: false,
+                    tail: false,
                     kind: MethodKind::Moving,
                     name: Identifier {
                         name: "foo".to_string(),
@@ -5582,6 +6142,7 @@ mod tests {
         assert_eq!(
             hir,
             Expression::Mut(Box::new(Mut {
+                pointer_to_method: None,
                 resolved_type: types::TypeRef::Unknown,
                 value: Expression::Int(Box::new(IntLiteral {
                     value: 10,
@@ -5892,6 +6453,117 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_lower_for_expression() {
+        let hir = lower_expr("fn a { for x in 10 { 20 } }").0;
+
+        assert_eq!(
+            hir,
+            Expression::Scope(Box::new(Scope {
+                resolved_type: types::TypeRef::Unknown,
+                body: vec![
+                    Expression::DefineVariable(Box::new(DefineVariable {
+                        resolved_type: types::TypeRef::Unknown,
+                        variable_id: None,
+                        mutable: false,
+                        name: Identifier {
+                            name: FOR_ITER_VAR.to_string(),
+                            location: cols(17, 18)
+                        },
+                        value_type: None,
+                        value: Expression::Int(Box::new(IntLiteral {
+                            value: 10,
+                            resolved_type: types::TypeRef::Unknown,
+                            location: cols(17, 18)
+                        })),
+                        location: cols(17, 18)
+                    })),
+                    Expression::Loop(Box::new(Loop {
+                        body: vec![Expression::Match(Box::new(Match {
+                            resolved_type: types::TypeRef::Unknown,
+                            expression: Expression::Call(Box::new(Call {
+                                kind: types::CallKind::Unknown,
+                                receiver: Some(Expression::IdentifierRef(
+                                    Box::new(IdentifierRef {
+                                        name: FOR_ITER_VAR.to_string(),
+                                        kind: types::IdentifierKind::Unknown,
+                                        location: cols(17, 18)
+                                    })
+                                )),
+                                name: Identifier {
+                                    name: FOR_NEXT_METHOD.to_string(),
+                                    location: cols(17, 18)
+                                },
+                                arguments: Vec::new(),
+                                location: cols(17, 18)
+                            })),
+                            cases: vec![
+                                MatchCase {
+                                    variable_ids: Vec::new(),
+                                    pattern: Pattern::Variant(Box::new(
+                                        VariantPattern {
+                                            variant_id: None,
+                                            name: Constant {
+                                                name: OPTION_SOME.to_string(),
+                                                location: cols(12, 12)
+                                            },
+                                            values: vec![Pattern::Identifier(
+                                                Box::new(IdentifierPattern {
+                                                    variable_id: None,
+                                                    name: Identifier {
+                                                        name: "x".to_string(),
+                                                        location: cols(12, 12)
+                                                    },
+                                                    mutable: false,
+                                                    value_type: None,
+                                                    location: cols(12, 12)
+                                                })
+                                            )],
+                                            location: cols(12, 12)
+                                        }
+                                    )),
+                                    guard: None,
+                                    body: vec![Expression::Int(Box::new(
+                                        IntLiteral {
+                                            value: 20,
+                                            resolved_type:
+                                                types::TypeRef::Unknown,
+                                            location: cols(22, 23)
+                                        }
+                                    ))],
+                                    location: cols(12, 12)
+                                },
+                                MatchCase {
+                                    variable_ids: Vec::new(),
+                                    pattern: Pattern::Variant(Box::new(
+                                        VariantPattern {
+                                            variant_id: None,
+                                            name: Constant {
+                                                name: OPTION_NONE.to_string(),
+                                                location: cols(17, 18)
+                                            },
+                                            values: Vec::new(),
+                                            location: cols(17, 18)
+                                        }
+                                    )),
+                                    guard: None,
+                                    body: vec![Expression::Break(Box::new(
+                                        Break { location: cols(17, 18) }
+                                    ))],
+                                    location: cols(17, 18)
+                                }
+                            ],
+                            location: cols(8, 25),
+                            write_result: true,
+                        }))],
+                        location: cols(8, 25)
+                    })),
+                ],
+                location: cols(8, 25)
+            }))
+        );
+    }
+
     #[test]
     fn test_lower_scope_expression() {
         let hir = lower_expr("fn a { { 10 } }").0;
@@ -6005,6 +6677,7 @@ mod tests {
                     mutable: false,
                     location: cols(19, 19)
                 }],
+                derive: Vec::new(),
                 body: vec![
                     ClassExpression::Variant(Box::new(DefineVariant {
                         method_id: None,
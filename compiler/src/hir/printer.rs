@@ -0,0 +1,1033 @@
+//! Pretty-printing of HIR for debugging purposes.
+//!
+//! The output uses Inko's syntax, but shows the desugared form of expressions
+//! (e.g. `if` and `while` expressions are printed as `match` and `loop`
+//! expressions). Where known, the inferred types of variables, closure
+//! arguments and closure return types are included as type annotations.
+use crate::hir::*;
+use types::format::format_type;
+use types::{Block, Database, TypeRef};
+
+/// The number of spaces to indent nested code with.
+const INDENT: usize = 2;
+
+/// Returns a String containing the HIR of a module.
+pub(crate) fn to_text(db: &Database, module: &Module) -> String {
+    let mut printer = Printer { db, buffer: String::new(), indent: 0 };
+
+    printer.module(module);
+    printer.buffer
+}
+
+struct Printer<'a> {
+    db: &'a Database,
+    buffer: String,
+    indent: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn push(&mut self, value: &str) {
+        self.buffer.push_str(value);
+    }
+
+    fn newline(&mut self) {
+        self.buffer.push('\n');
+
+        for _ in 0..self.indent {
+            self.buffer.push(' ');
+        }
+    }
+
+    fn list<T, F: FnMut(&mut Self, &T)>(&mut self, values: &[T], mut func: F) {
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 {
+                self.push(", ");
+            }
+
+            func(self, value);
+        }
+    }
+
+    /// Prints a sequence of items on separate lines and wrapped in curly
+    /// braces, separating items with an empty line if `separate` returns
+    /// `true` for two consecutive items.
+    fn items<T, F, S>(&mut self, values: &[T], mut func: F, separate: S)
+    where
+        F: FnMut(&mut Self, &T),
+        S: Fn(&T, &T) -> bool,
+    {
+        if values.is_empty() {
+            self.push("{}");
+            return;
+        }
+
+        self.push("{");
+        self.indent += INDENT;
+
+        for (index, value) in values.iter().enumerate() {
+            if index > 0 && separate(&values[index - 1], value) {
+                self.buffer.push('\n');
+            }
+
+            self.newline();
+            func(self, value);
+        }
+
+        self.indent -= INDENT;
+        self.newline();
+        self.push("}");
+    }
+
+    /// Prints an inferred type as a type annotation, unless the type isn't
+    /// known (e.g. because type checking didn't run).
+    fn inferred_type(&mut self, typ: TypeRef) {
+        if let TypeRef::Unknown = typ {
+            return;
+        }
+
+        self.push(": ");
+        self.push(&format_type(self.db, typ));
+    }
+
+    fn module(&mut self, module: &Module) {
+        for (index, expr) in module.expressions.iter().enumerate() {
+            if index > 0 {
+                let imports = matches!(
+                    (&module.expressions[index - 1], expr),
+                    (
+                        TopLevelExpression::Import(_)
+                            | TopLevelExpression::ExternImport(_),
+                        TopLevelExpression::Import(_)
+                            | TopLevelExpression::ExternImport(_)
+                    )
+                );
+
+                self.push(if imports { "\n" } else { "\n\n" });
+            }
+
+            self.top_level_expression(expr);
+        }
+
+        if !module.expressions.is_empty() {
+            self.push("\n");
+        }
+    }
+
+    fn top_level_expression(&mut self, node: &TopLevelExpression) {
+        match node {
+            TopLevelExpression::Class(ref n) => self.define_class(n),
+            TopLevelExpression::ExternClass(ref n) => {
+                self.push("class ");
+                self.public(n.public);
+                self.push("extern ");
+                self.push(&n.name.name);
+                self.push(" ");
+                self.items(&n.fields, |p, n| p.define_field(n), |_, _| false);
+            }
+            TopLevelExpression::Constant(ref n) => {
+                self.push("let ");
+                self.public(n.public);
+                self.push(&n.name.name);
+                self.push(" = ");
+                self.const_expression(&n.value);
+            }
+            TopLevelExpression::ModuleMethod(ref n) => {
+                self.method_header(
+                    n.public,
+                    "",
+                    n.tail,
+                    &n.name,
+                    &n.type_parameters,
+                    &n.arguments,
+                );
+                self.return_type(n.return_type.as_ref());
+                self.push(" ");
+                self.body(&n.body);
+            }
+            TopLevelExpression::ExternFunction(ref n) => {
+                self.push("fn ");
+                self.public(n.public);
+                self.push("extern ");
+                self.push(&n.name.name);
+                self.push("(");
+                self.list(&n.arguments, |p, n| p.method_argument(n));
+
+                if n.variadic {
+                    self.push(if n.arguments.is_empty() {
+                        "..."
+                    } else {
+                        ", ..."
+                    });
+                }
+
+                self.push(")");
+                self.return_type(n.return_type.as_ref());
+            }
+            TopLevelExpression::Trait(ref n) => self.define_trait(n),
+            TopLevelExpression::Implement(ref n) => {
+                self.push("impl ");
+                self.type_name(&n.trait_name);
+                self.push(" for ");
+                self.push(&n.class_name.name);
+                self.type_bounds(&n.bounds);
+                self.push(" ");
+                self.items(&n.body, |p, n| p.instance_method(n), |_, _| true);
+            }
+            TopLevelExpression::Import(ref n) => self.import(n),
+            TopLevelExpression::Reopen(ref n) => {
+                self.push("impl ");
+                self.push(&n.class_name.name);
+                self.type_bounds(&n.bounds);
+                self.push(" ");
+                self.items(
+                    &n.body,
+                    |p, expr| match expr {
+                        ReopenClassExpression::InstanceMethod(ref n) => {
+                            p.instance_method(n)
+                        }
+                        ReopenClassExpression::StaticMethod(ref n) => {
+                            p.static_method(n)
+                        }
+                        ReopenClassExpression::AsyncMethod(ref n) => {
+                            p.async_method(n)
+                        }
+                    },
+                    |_, _| true,
+                );
+            }
+            TopLevelExpression::ExternImport(ref n) => {
+                self.push("import extern ");
+                self.single_string(&n.source);
+            }
+        }
+    }
+
+    fn public(&mut self, public: bool) {
+        if public {
+            self.push("pub ");
+        }
+    }
+
+    fn import(&mut self, node: &Import) {
+        self.push("import ");
+
+        for (index, step) in node.source.iter().enumerate() {
+            if index > 0 {
+                self.push(".");
+            }
+
+            self.push(&step.name);
+        }
+
+        if node.symbols.is_empty() {
+            return;
+        }
+
+        self.push(".(");
+        self.list(&node.symbols, |p, sym| {
+            p.push(&sym.name.name);
+
+            if sym.import_as.name != sym.name.name {
+                p.push(" as ");
+                p.push(&sym.import_as.name);
+            }
+        });
+        self.push(")");
+    }
+
+    fn define_class(&mut self, node: &DefineClass) {
+        self.push("class ");
+        self.public(node.public);
+        self.push(match node.kind {
+            ClassKind::Async => "async ",
+            ClassKind::Builtin => "builtin ",
+            ClassKind::Enum => "enum ",
+            ClassKind::Inline => "inline ",
+            ClassKind::Regular => "",
+        });
+
+        self.push(&node.name.name);
+        self.type_parameters(&node.type_parameters);
+
+        if !node.derive.is_empty() {
+            self.push(" derive(");
+            self.list(&node.derive, |p, n| p.type_name(n));
+            self.push(")");
+        }
+
+        self.push(" ");
+        self.items(
+            &node.body,
+            |p, expr| match expr {
+                ClassExpression::InstanceMethod(ref n) => p.instance_method(n),
+                ClassExpression::StaticMethod(ref n) => p.static_method(n),
+                ClassExpression::AsyncMethod(ref n) => p.async_method(n),
+                ClassExpression::Field(ref n) => p.define_field(n),
+                ClassExpression::Variant(ref n) => {
+                    p.push("case ");
+                    p.push(&n.name.name);
+
+                    if !n.members.is_empty() {
+                        p.push("(");
+                        p.list(&n.members, |p, n| p.type_reference(n));
+                        p.push(")");
+                    }
+                }
+            },
+            |prev, next| {
+                !matches!(
+                    (prev, next),
+                    (ClassExpression::Field(_), ClassExpression::Field(_))
+                        | (
+                            ClassExpression::Variant(_),
+                            ClassExpression::Variant(_)
+                        )
+                )
+            },
+        );
+    }
+
+    fn define_field(&mut self, node: &DefineField) {
+        self.push("let ");
+        self.public(node.public);
+        self.push("@");
+        self.push(&node.name.name);
+        self.push(": ");
+        self.type_reference(&node.value_type);
+    }
+
+    fn define_trait(&mut self, node: &DefineTrait) {
+        self.push("trait ");
+        self.public(node.public);
+        self.push(&node.name.name);
+        self.type_parameters(&node.type_parameters);
+
+        if !node.requirements.is_empty() {
+            self.push(": ");
+            self.requirements(&node.requirements, false);
+        }
+
+        self.push(" ");
+        self.items(
+            &node.body,
+            |p, expr| match expr {
+                TraitExpression::InstanceMethod(ref n) => p.instance_method(n),
+                TraitExpression::RequiredMethod(ref n) => {
+                    p.method_header(
+                        n.public,
+                        method_kind(n.kind),
+                        false,
+                        &n.name,
+                        &n.type_parameters,
+                        &n.arguments,
+                    );
+                    p.return_type(n.return_type.as_ref());
+                }
+            },
+            |_, _| true,
+        );
+    }
+
+    fn instance_method(&mut self, node: &DefineInstanceMethod) {
+        self.method_header(
+            node.public,
+            method_kind(node.kind),
+            node.tail,
+            &node.name,
+            &node.type_parameters,
+            &node.arguments,
+        );
+        self.return_type(node.return_type.as_ref());
+        self.push(" ");
+        self.body(&node.body);
+    }
+
+    fn static_method(&mut self, node: &DefineStaticMethod) {
+        self.method_header(
+            node.public,
+            "static ",
+            node.tail,
+            &node.name,
+            &node.type_parameters,
+            &node.arguments,
+        );
+        self.return_type(node.return_type.as_ref());
+        self.push(" ");
+        self.body(&node.body);
+    }
+
+    fn async_method(&mut self, node: &DefineAsyncMethod) {
+        self.method_header(
+            node.public,
+            if node.mutable { "async mut " } else { "async " },
+            false,
+            &node.name,
+            &node.type_parameters,
+            &node.arguments,
+        );
+        self.return_type(node.return_type.as_ref());
+        self.push(" ");
+        self.body(&node.body);
+    }
+
+    fn method_header(
+        &mut self,
+        public: bool,
+        kind: &str,
+        tail: bool,
+        name: &Identifier,
+        type_parameters: &[TypeParameter],
+        arguments: &[MethodArgument],
+    ) {
+        self.push("fn ");
+        self.public(public);
+        self.push(kind);
+
+        if tail {
+            self.push("tail ");
+        }
+
+        self.push(&name.name);
+        self.type_parameters(type_parameters);
+
+        if !arguments.is_empty() {
+            self.push("(");
+            self.list(arguments, |p, n| p.method_argument(n));
+            self.push(")");
+        }
+    }
+
+    fn method_argument(&mut self, node: &MethodArgument) {
+        self.push(&node.name.name);
+        self.push(": ");
+        self.type_reference(&node.value_type);
+    }
+
+    fn type_parameters(&mut self, nodes: &[TypeParameter]) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        self.push("[");
+        self.list(nodes, |p, param| {
+            p.push(&param.name.name);
+
+            if !param.requirements.is_empty() || param.mutable {
+                p.push(": ");
+                p.requirements(&param.requirements, param.mutable);
+            }
+        });
+        self.push("]");
+    }
+
+    fn type_bounds(&mut self, nodes: &[TypeBound]) {
+        if nodes.is_empty() {
+            return;
+        }
+
+        self.push(" if ");
+        self.list(nodes, |p, bound| {
+            p.push(&bound.name.name);
+            p.push(": ");
+            p.requirements(&bound.requirements, bound.mutable);
+        });
+    }
+
+    fn requirements(&mut self, nodes: &[TypeName], mutable: bool) {
+        for (index, req) in nodes.iter().enumerate() {
+            if index > 0 {
+                self.push(" + ");
+            }
+
+            self.type_name(req);
+        }
+
+        if mutable {
+            self.push(if nodes.is_empty() { "mut" } else { " + mut" });
+        }
+    }
+
+    fn return_type(&mut self, node: Option<&Type>) {
+        if let Some(typ) = node {
+            self.push(" -> ");
+            self.type_reference(typ);
+        }
+    }
+
+    fn type_reference(&mut self, node: &Type) {
+        match node {
+            Type::Named(ref n) => self.type_name(n),
+            Type::Ref(ref n) => {
+                self.push("ref ");
+                self.referrable_type(&n.type_reference);
+            }
+            Type::Mut(ref n) => {
+                self.push("mut ");
+                self.referrable_type(&n.type_reference);
+            }
+            Type::Uni(ref n) => {
+                self.push("uni ");
+                self.referrable_type(&n.type_reference);
+            }
+            Type::Closure(ref n) => self.closure_type(n),
+            Type::Tuple(ref n) => self.tuple_type(n),
+        }
+    }
+
+    fn referrable_type(&mut self, node: &ReferrableType) {
+        match node {
+            ReferrableType::Named(ref n) => self.type_name(n),
+            ReferrableType::Closure(ref n) => self.closure_type(n),
+            ReferrableType::Tuple(ref n) => self.tuple_type(n),
+        }
+    }
+
+    fn type_name(&mut self, node: &TypeName) {
+        if let Some(source) = &node.source {
+            self.push(&source.name);
+            self.push(".");
+        }
+
+        self.push(&node.name.name);
+
+        if !node.arguments.is_empty() {
+            self.push("[");
+            self.list(&node.arguments, |p, n| p.type_reference(n));
+            self.push("]");
+        }
+    }
+
+    fn closure_type(&mut self, node: &ClosureType) {
+        self.push("fn");
+
+        if !node.arguments.is_empty() {
+            self.push(" (");
+            self.list(&node.arguments, |p, n| p.type_reference(n));
+            self.push(")");
+        }
+
+        self.return_type(node.return_type.as_ref());
+    }
+
+    fn tuple_type(&mut self, node: &TupleType) {
+        self.push("(");
+        self.list(&node.values, |p, n| p.type_reference(n));
+        self.push(")");
+    }
+
+    fn body(&mut self, nodes: &[Expression]) {
+        self.items(nodes, |p, n| p.expression(n), |_, _| false);
+    }
+
+    fn expression(&mut self, node: &Expression) {
+        match node {
+            Expression::And(ref n) => {
+                self.expression(&n.left);
+                self.push(" and ");
+                self.expression(&n.right);
+            }
+            Expression::Or(ref n) => {
+                self.expression(&n.left);
+                self.push(" or ");
+                self.expression(&n.right);
+            }
+            Expression::AssignField(ref n) => {
+                self.push("@");
+                self.push(&n.field.name);
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::ReplaceField(ref n) => {
+                self.push("@");
+                self.push(&n.field.name);
+                self.push(" := ");
+                self.expression(&n.value);
+            }
+            Expression::AssignSetter(ref n) => {
+                self.expression(&n.receiver);
+                self.push(".");
+                self.push(&n.name.name);
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::AssignVariable(ref n) => {
+                self.push(&n.variable.name);
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::ReplaceVariable(ref n) => {
+                self.push(&n.variable.name);
+                self.push(" := ");
+                self.expression(&n.value);
+            }
+            Expression::Break(_) => self.push("break"),
+            Expression::Next(_) => self.push("next"),
+            Expression::BuiltinCall(ref n) => {
+                self.push(BUILTIN_RECEIVER);
+                self.push(".");
+                self.push(&n.name.name);
+                self.push("(");
+                self.list(&n.arguments, |p, n| p.expression(n));
+                self.push(")");
+            }
+            Expression::Call(ref n) => self.call(n),
+            Expression::Closure(ref n) => self.closure(n),
+            Expression::ConstantRef(ref n) => {
+                self.constant_ref(n.source.as_ref(), &n.name)
+            }
+            Expression::DefineVariable(ref n) => {
+                self.push(if n.mutable { "let mut " } else { "let " });
+                self.push(&n.name.name);
+
+                match &n.value_type {
+                    Some(typ) if n.resolved_type == TypeRef::Unknown => {
+                        self.push(": ");
+                        self.type_reference(typ);
+                    }
+                    _ => self.inferred_type(n.resolved_type),
+                }
+
+                self.push(" = ");
+                self.expression(&n.value);
+            }
+            Expression::True(_) => self.push("true"),
+            Expression::False(_) => self.push("false"),
+            Expression::Nil(_) => self.push("nil"),
+            Expression::FieldRef(ref n) => {
+                self.push("@");
+                self.push(&n.name);
+            }
+            Expression::Float(ref n) => self.push(&format!("{:?}", n.value)),
+            Expression::Int(ref n) => self.push(&n.value.to_string()),
+            Expression::IdentifierRef(ref n) => self.push(&n.name),
+            Expression::ClassLiteral(ref n) => {
+                self.push(&n.class_name.name);
+
+                if n.fields.is_empty() {
+                    self.push(" {}");
+                } else {
+                    self.push(" { ");
+                    self.list(&n.fields, |p, field| {
+                        p.push("@");
+                        p.push(&field.field.name);
+                        p.push(" = ");
+                        p.expression(&field.value);
+                    });
+                    self.push(" }");
+                }
+            }
+            Expression::Loop(ref n) => {
+                self.push("loop ");
+                self.body(&n.body);
+            }
+            Expression::Match(ref n) => self.match_expression(n),
+            Expression::Mut(ref n) => {
+                self.push("mut ");
+                self.expression(&n.value);
+            }
+            Expression::Ref(ref n) => {
+                self.push("ref ");
+                self.expression(&n.value);
+            }
+            Expression::Recover(ref n) => {
+                self.push("recover ");
+                self.body(&n.body);
+            }
+            Expression::Return(ref n) => {
+                self.push("return");
+
+                if let Some(value) = &n.value {
+                    self.push(" ");
+                    self.expression(value);
+                }
+            }
+            Expression::Throw(ref n) => {
+                self.push("throw ");
+                self.expression(&n.value);
+            }
+            Expression::Try(ref n) => {
+                self.push("try ");
+                self.expression(&n.expression);
+            }
+            Expression::Scope(ref n) => self.body(&n.body),
+            Expression::SelfObject(_) => self.push("self"),
+            Expression::String(ref n) => {
+                self.push("\"");
+
+                for value in &n.values {
+                    match value {
+                        StringValue::Text(ref n) => {
+                            self.double_string_text(&n.value)
+                        }
+                        StringValue::Expression(ref n) => {
+                            self.push("{");
+                            self.call(n);
+                            self.push("}");
+                        }
+                    }
+                }
+
+                self.push("\"");
+            }
+            Expression::Tuple(ref n) => {
+                self.push("(");
+                self.list(&n.values, |p, n| p.expression(n));
+                self.push(if n.values.len() == 1 { ",)" } else { ")" });
+            }
+            Expression::TypeCast(ref n) => {
+                self.expression(&n.value);
+                self.push(" as ");
+                self.type_reference(&n.cast_to);
+            }
+        }
+    }
+
+    fn call(&mut self, node: &Call) {
+        if let Some(rec) = &node.receiver {
+            self.expression(rec);
+            self.push(".");
+        }
+
+        self.push(&node.name.name);
+
+        if node.arguments.is_empty() {
+            return;
+        }
+
+        self.push("(");
+        self.list(&node.arguments, |p, arg| match arg {
+            Argument::Positional(ref n) => p.expression(n),
+            Argument::Named(ref n) => {
+                p.push(&n.name.name);
+                p.push(": ");
+                p.expression(&n.value);
+            }
+        });
+        self.push(")");
+    }
+
+    fn closure(&mut self, node: &Closure) {
+        self.push(if node.moving { "fn move" } else { "fn" });
+
+        if !node.arguments.is_empty() {
+            let types = node
+                .closure_id
+                .map(|id| id.arguments(self.db))
+                .unwrap_or_default();
+
+            self.push(" (");
+
+            for (index, arg) in node.arguments.iter().enumerate() {
+                if index > 0 {
+                    self.push(", ");
+                }
+
+                self.push(&arg.name.name);
+
+                match (types.get(index), &arg.value_type) {
+                    (Some(typ), _) => self.inferred_type(typ.value_type),
+                    (None, Some(typ)) => {
+                        self.push(": ");
+                        self.type_reference(typ);
+                    }
+                    _ => {}
+                }
+            }
+
+            self.push(")");
+        }
+
+        match (node.closure_id, &node.return_type) {
+            (Some(id), _) => {
+                self.push(" -> ");
+                self.push(&format_type(self.db, id.return_type(self.db)));
+            }
+            (None, typ) => self.return_type(typ.as_ref()),
+        }
+
+        self.push(" ");
+        self.body(&node.body);
+    }
+
+    fn match_expression(&mut self, node: &Match) {
+        self.push("match ");
+        self.expression(&node.expression);
+        self.push(" ");
+        self.items(
+            &node.cases,
+            |p, case| {
+                p.push("case ");
+                p.pattern(&case.pattern);
+
+                if let Some(guard) = &case.guard {
+                    p.push(" if ");
+                    p.expression(guard);
+                }
+
+                p.push(" -> ");
+
+                match case.body.as_slice() {
+                    [expr] => p.expression(expr),
+                    values => p.body(values),
+                }
+            },
+            |_, _| false,
+        );
+    }
+
+    fn pattern(&mut self, node: &Pattern) {
+        match node {
+            Pattern::Class(ref n) => {
+                if n.values.is_empty() {
+                    self.push("{}");
+                } else {
+                    self.push("{ ");
+                    self.list(&n.values, |p, field| {
+                        p.push("@");
+                        p.push(&field.field.name);
+                        p.push(" = ");
+                        p.pattern(&field.pattern);
+                    });
+                    self.push(" }");
+                }
+            }
+            Pattern::Constant(ref n) => {
+                self.constant_ref(n.source.as_ref(), &n.name)
+            }
+            Pattern::Identifier(ref n) => {
+                if n.mutable {
+                    self.push("mut ");
+                }
+
+                self.push(&n.name.name);
+
+                match (n.variable_id, &n.value_type) {
+                    (Some(id), _) => self.inferred_type(id.value_type(self.db)),
+                    (None, Some(typ)) => {
+                        self.push(": ");
+                        self.type_reference(typ);
+                    }
+                    _ => {}
+                }
+            }
+            Pattern::Int(ref n) => self.push(&n.value.to_string()),
+            Pattern::String(ref n) => self.single_string(&n.value),
+            Pattern::Tuple(ref n) => {
+                self.push("(");
+                self.list(&n.values, |p, n| p.pattern(n));
+                self.push(")");
+            }
+            Pattern::Variant(ref n) => {
+                self.push(&n.name.name);
+
+                if !n.values.is_empty() {
+                    self.push("(");
+                    self.list(&n.values, |p, n| p.pattern(n));
+                    self.push(")");
+                }
+            }
+            Pattern::Wildcard(_) => self.push("_"),
+            Pattern::True(_) => self.push("true"),
+            Pattern::False(_) => self.push("false"),
+            Pattern::Or(ref n) => {
+                for (index, pat) in n.patterns.iter().enumerate() {
+                    if index > 0 {
+                        self.push(" or ");
+                    }
+
+                    self.pattern(pat);
+                }
+            }
+        }
+    }
+
+    fn const_expression(&mut self, node: &ConstExpression) {
+        match node {
+            ConstExpression::Int(ref n) => self.push(&n.value.to_string()),
+            ConstExpression::Float(ref n) => {
+                self.push(&format!("{:?}", n.value))
+            }
+            ConstExpression::String(ref n) => self.single_string(&n.value),
+            ConstExpression::True(_) => self.push("true"),
+            ConstExpression::False(_) => self.push("false"),
+            ConstExpression::Binary(ref n) => {
+                self.const_expression(&n.left);
+                self.push(" ");
+                self.push(n.operator.method_name());
+                self.push(" ");
+                self.const_expression(&n.right);
+            }
+            ConstExpression::ConstantRef(ref n) => {
+                self.constant_ref(n.source.as_ref(), &n.name)
+            }
+            ConstExpression::Array(ref n) => {
+                self.push("[");
+                self.list(&n.values, |p, n| p.const_expression(n));
+                self.push("]");
+            }
+            ConstExpression::Tuple(ref n) => {
+                self.push("(");
+                self.list(&n.values, |p, n| p.const_expression(n));
+                self.push(if n.values.len() == 1 { ",)" } else { ")" });
+            }
+            ConstExpression::Cast(ref n) => {
+                self.const_expression(&n.value);
+                self.push(" as ");
+                self.type_reference(&n.cast_to);
+            }
+            ConstExpression::Call(ref n) => {
+                self.const_expression(&n.receiver);
+                self.push(".");
+                self.push(&n.name.name);
+
+                if !n.arguments.is_empty() {
+                    self.push("(");
+                    self.list(&n.arguments, |p, n| p.const_expression(n));
+                    self.push(")");
+                }
+            }
+            ConstExpression::BuiltinCall(ref n) => {
+                self.push(BUILTIN_RECEIVER);
+                self.push(".");
+                self.push(&n.name.name);
+                self.push("(");
+                self.list(&n.arguments, |p, n| p.const_expression(n));
+                self.push(")");
+            }
+            ConstExpression::Interpolation(ref n) => {
+                self.push("\"");
+
+                for value in &n.values {
+                    match value {
+                        ConstExpression::String(ref n) => {
+                            self.double_string_text(&n.value)
+                        }
+                        value => {
+                            self.push("{");
+                            self.const_expression(value);
+                            self.push("}");
+                        }
+                    }
+                }
+
+                self.push("\"");
+            }
+            ConstExpression::Invalid(_) => self.push("<invalid>"),
+        }
+    }
+
+    fn constant_ref(&mut self, source: Option<&Identifier>, name: &str) {
+        if let Some(source) = source {
+            self.push(&source.name);
+            self.push(".");
+        }
+
+        self.push(name);
+    }
+
+    fn single_string(&mut self, value: &str) {
+        self.push("'");
+
+        for chr in value.chars() {
+            match chr {
+                '\'' => self.push("\\'"),
+                '\\' => self.push("\\\\"),
+                _ => self.buffer.push(chr),
+            }
+        }
+
+        self.push("'");
+    }
+
+    fn double_string_text(&mut self, value: &str) {
+        for chr in value.chars() {
+            match chr {
+                '"' => self.push("\\\""),
+                '\\' => self.push("\\\\"),
+                '{' => self.push("\\{"),
+                '\0' => self.push("\\0"),
+                '\x1b' => self.push("\\e"),
+                '\n' => self.push("\\n"),
+                '\r' => self.push("\\r"),
+                '\t' => self.push("\\t"),
+                _ if chr.is_control() => {
+                    self.push(&format!("\\u{{{:X}}}", chr as u32))
+                }
+                _ => self.buffer.push(chr),
+            }
+        }
+    }
+}
+
+fn method_kind(kind: MethodKind) -> &'static str {
+    match kind {
+        MethodKind::Regular => "",
+        MethodKind::Moving => "move ",
+        MethodKind::Mutable => "mut ",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::modules_parser::ParsedModule;
+    use crate::state::State;
+    use ::ast::parser::Parser;
+    use similar_asserts::assert_eq;
+    use types::module_name::ModuleName;
+
+    #[track_caller]
+    fn print(input: &str) -> String {
+        let mut state = State::new(Config::new());
+        let ast = Parser::new(input.into(), "test.inko".into())
+            .parse()
+            .expect("failed to parse the module");
+        let name = ModuleName::new("std.foo");
+        let hir =
+            LowerToHir::run_all(&mut state, vec![ParsedModule { ast, name }]);
+
+        to_text(&state.db, &hir[0])
+    }
+
+    #[test]
+    fn test_desugared_expressions() {
+        assert_eq!(
+            print(
+                "import std.foo.(Foo as Bar)
+
+class Foo {
+  let @a: Int
+
+  fn pub mut foo(values: Array[Int]) {
+    for v in values {
+      if v > 1 { @a = v }
+    }
+  }
+}
+"
+            ),
+            "import std.foo.(Foo as Bar)
+
+class Foo {
+  let @a: Int
+
+  fn pub mut foo(values: Array[Int]) {
+    {
+      let $iter = values
+      loop {
+        match $iter.next {
+          case Some(v) -> match v.>(1) {
+            case true -> @a = v
+            case _ -> nil
+          }
+          case None -> break
+        }
+      }
+    }
+  }
+}
+"
+        );
+    }
+}
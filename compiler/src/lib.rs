@@ -1,7 +1,9 @@
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::new_without_default))]
 #![cfg_attr(feature = "cargo-clippy", allow(clippy::enum_variant_names))]
 
+mod cache;
 mod diagnostics;
+mod header;
 mod hir;
 mod linker;
 mod llvm;
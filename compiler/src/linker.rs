@@ -1,10 +1,14 @@
-use crate::config::Config;
+use crate::config::{BuildDirectories, Config, Emit, Library};
 use crate::state::State;
-use crate::target::OperatingSystem;
+use crate::target::{Architecture, OperatingSystem};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs::{remove_file, write};
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-fn runtime_library(config: &Config) -> Option<PathBuf> {
+pub(crate) fn runtime_library(config: &Config) -> Option<PathBuf> {
     let mut files = vec![format!("libinko-{}.a", &config.target)];
 
     // When compiling for the native target we also support DIR/libinko.a, as
@@ -36,19 +40,247 @@ fn lld_is_available() -> bool {
         .map_or(false, |status| status.success())
 }
 
+/// The command used for linking, recorded such that it can be written to disk.
+struct LinkCommand {
+    program: OsString,
+    arguments: Vec<OsString>,
+}
+
+impl LinkCommand {
+    fn new<S: AsRef<OsStr>>(program: S) -> LinkCommand {
+        LinkCommand {
+            program: program.as_ref().to_owned(),
+            arguments: Vec::new(),
+        }
+    }
+
+    fn arg<S: AsRef<OsStr>>(&mut self, argument: S) -> &mut LinkCommand {
+        self.arguments.push(argument.as_ref().to_owned());
+        self
+    }
+
+    fn args<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
+        &mut self,
+        arguments: I,
+    ) -> &mut LinkCommand {
+        for arg in arguments {
+            self.arg(arg);
+        }
+
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+
+        cmd.args(&self.arguments);
+        cmd
+    }
+}
+
+/// Appends a string to a buffer as a JSON string literal.
+fn write_json_string(buffer: &mut String, value: &str) {
+    buffer.push('"');
+
+    for chr in value.chars() {
+        match chr {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => {
+                buffer.push_str(&format!("\\u{:04x}", chr as u32));
+            }
+            chr => buffer.push(chr),
+        }
+    }
+
+    buffer.push('"');
+}
+
+/// Returns the linker command as JSON, using the same format as
+/// `compile_commands.json`.
+///
+/// A link command doesn't have a source file, so the `file` key is set to the
+/// output file instead.
+fn command_json(directory: &Path, cmd: &LinkCommand, output: &Path) -> String {
+    let mut json = String::from("[{\"directory\": ");
+
+    write_json_string(&mut json, &directory.to_string_lossy());
+    json.push_str(", \"arguments\": [");
+
+    for (index, arg) in
+        Some(&cmd.program).into_iter().chain(&cmd.arguments).enumerate()
+    {
+        if index > 0 {
+            json.push_str(", ");
+        }
+
+        write_json_string(&mut json, &arg.to_string_lossy());
+    }
+
+    json.push_str("], \"file\": ");
+    write_json_string(&mut json, &output.to_string_lossy());
+    json.push_str(", \"output\": ");
+    write_json_string(&mut json, &output.to_string_lossy());
+    json.push_str("}]\n");
+    json
+}
+
+/// Writes the linker command to a JSON file, using the same format as
+/// `compile_commands.json`.
+fn write_command(
+    directories: &BuildDirectories,
+    cmd: &LinkCommand,
+    output: &Path,
+) -> Result<(), String> {
+    let dir = env::current_dir().unwrap_or_else(|_| PathBuf::new());
+    let path =
+        directories.emit.join(format!("link.{}", Emit::Link.extension()));
+    let json = command_json(&dir, cmd, output);
+
+    directories.create_emit().and_then(|_| {
+        write(&path, json).map_err(|err| {
+            format!("Failed to write {}: {}", path.display(), err)
+        })
+    })
+}
+
+fn run(mut cmd: Command, input: Option<String>) -> Result<(), String> {
+    cmd.stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() });
+    cmd.stderr(Stdio::piped());
+    cmd.stdout(Stdio::null());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|err| format!("Failed to start the linker: {err}"))?;
+
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|err| format!("Failed to write to the linker: {err}"))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to wait for the linker: {err}"))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "The linker exited with status code {}:\n{}",
+            output.status.code().unwrap_or(0),
+            String::from_utf8_lossy(&output.stderr),
+        ))
+    }
+}
+
+/// Combines the object files and the runtime library into a single static
+/// library.
+fn archive(
+    state: &State,
+    output: &Path,
+    paths: &[PathBuf],
+    runtime: &Path,
+) -> Result<(), String> {
+    // Archives can't be nested, so we have to add the object files of the
+    // runtime library instead of the library itself. macOS' version of ar
+    // doesn't support this, but libtool does.
+    if let OperatingSystem::Mac = state.config.target.os {
+        let mut cmd = Command::new("libtool");
+
+        cmd.arg("-static");
+        cmd.arg("-o");
+        cmd.arg(output);
+        cmd.args(paths);
+        cmd.arg(runtime);
+
+        return run(cmd, None);
+    }
+
+    // Existing archives aren't replaced, instead ar would add the object files
+    // to the existing archive.
+    if output.is_file() {
+        remove_file(output).map_err(|err| {
+            format!("Failed to remove {}: {}", output.display(), err)
+        })?;
+    }
+
+    // GNU ar and llvm-ar support adding the contents of an archive using an
+    // MRI script, which we provide using STDIN.
+    let mut script =
+        format!("CREATE {}\nADDLIB {}\n", output.display(), runtime.display());
+
+    for path in paths {
+        script.push_str(&format!("ADDMOD {}\n", path.display()));
+    }
+
+    script.push_str("SAVE\nEND\n");
+
+    let mut cmd = Command::new("ar");
+
+    cmd.arg("-M");
+    run(cmd, Some(script))
+}
+
 pub(crate) fn link(
     state: &State,
+    directories: &BuildDirectories,
     output: &Path,
     paths: &[PathBuf],
 ) -> Result<(), String> {
+    let rt_path = runtime_library(&state.config).ok_or_else(|| {
+        format!("No runtime is available for target '{}'", state.config.target)
+    })?;
+
+    if let Some(Library::Static) = state.config.library {
+        return archive(state, output, paths, &rt_path);
+    }
+
+    let target = &state.config.target;
+
+    if target.static_only() {
+        if let Some(Library::Shared) = state.config.library {
+            return Err(format!(
+                "Shared libraries aren't supported for target '{}'",
+                target
+            ));
+        }
+    }
+
+    let settings = state.config.linker_settings();
+
     // On Unix systems the necessary libraries/object files are all over the
     // place. Instead of re-implementing the logic necessary to find these
-    // files, we rely on the system's compiler to do this for us.
+    // files, we rely on the system's compiler to do this for us, unless a
+    // custom linker is specified.
     //
     // As we only use this executable for linking it doesn't really matter
     // if this ends up using gcc, clang or something else, because we only
     // use it as a wrapper around the linker executable.
-    let mut cmd = Command::new("cc");
+    //
+    // When targeting musl from a platform that doesn't use musl, the system
+    // compiler would link against the wrong libc. In this case we default to
+    // the musl-gcc wrapper provided by musl. This wrapper only produces
+    // executables for the host architecture, so when also targeting a
+    // different architecture a linker must be specified explicitly.
+    let mut cmd = match &settings.linker {
+        Some(path) => LinkCommand::new(path),
+        None if target.static_only() && !target.is_native() => {
+            if target.arch != Architecture::native() {
+                return Err(format!(
+                    "No default linker is available for target '{}', \
+                    specify one using the --linker option",
+                    target
+                ));
+            }
+
+            LinkCommand::new("musl-gcc")
+        }
+        None => LinkCommand::new("cc"),
+    };
 
     // Object files must come before any of the libraries to link against, as
     // certain linkers are very particular about the order of flags such as
@@ -57,10 +289,6 @@ pub(crate) fn link(
         cmd.arg(path);
     }
 
-    let rt_path = runtime_library(&state.config).ok_or_else(|| {
-        format!("No runtime is available for target '{}'", state.config.target)
-    })?;
-
     cmd.arg(&rt_path);
 
     // Include any extra platform specific libraries, such as libm on the
@@ -75,6 +303,9 @@ pub(crate) fn link(
     // libm _without_ statically linking libc. See
     // https://bugzilla.redhat.com/show_bug.cgi?id=1433347 for some extra
     // details.
+    //
+    // musl on the other hand does support static linking, and executables
+    // for musl targets are always linked statically, including libc itself.
     match state.config.target.os {
         OperatingSystem::Linux => {
             // Certain versions of Linux (e.g. Debian 11) also need libdl and
@@ -110,11 +341,19 @@ pub(crate) fn link(
         _ => (),
     }
 
-    if static_linking {
+    for path in &settings.library_paths {
+        cmd.arg(format!("-L{}", path.display()));
+    }
+
+    // For musl targets we produce fully static executables, so there's no
+    // need to switch between static and dynamic linking.
+    let toggle_static = static_linking && !target.static_only();
+
+    if toggle_static {
         cmd.arg("-Wl,-Bstatic");
     }
 
-    for lib in &state.libraries {
+    for lib in state.libraries.iter().chain(&state.config.libraries) {
         // These libraries are already included if needed, and we can't
         // statically link against them (if static linking is desired), so we
         // skip them here.
@@ -125,10 +364,21 @@ pub(crate) fn link(
         cmd.arg(&(format!("-l{}", lib)));
     }
 
-    if static_linking {
+    if toggle_static {
         cmd.arg("-Wl,-Bdynamic");
     }
 
+    if target.static_only() {
+        cmd.arg("-static");
+    }
+
+    if let Some(Library::Shared) = state.config.library {
+        match state.config.target.os {
+            OperatingSystem::Mac => cmd.arg("-dynamiclib"),
+            _ => cmd.arg("-shared"),
+        };
+    }
+
     cmd.arg("-o");
     cmd.arg(output);
 
@@ -138,31 +388,49 @@ pub(crate) fn link(
         cmd.arg("-static-libgcc");
 
         // On platforms where lld isn't the default (e.g. Linux), we'll use it
-        // if available, speeding up the linking process.
-        if lld_is_available() {
+        // if available, speeding up the linking process. If a different
+        // linker is requested explicitly, we leave the choice to the user.
+        let custom = settings.linker.is_some()
+            || settings.arguments.iter().any(|a| a.starts_with("-fuse-ld="));
+
+        if !custom && lld_is_available() {
             cmd.arg("-fuse-ld=lld");
         }
     }
 
-    cmd.stdin(Stdio::null());
-    cmd.stderr(Stdio::piped());
-    cmd.stdout(Stdio::null());
+    // User provided arguments come last, such that they can override any of
+    // the arguments we pass by default.
+    cmd.args(&settings.arguments);
 
-    let child = cmd
-        .spawn()
-        .map_err(|err| format!("Failed to start the linker: {err}"))?;
+    if state.config.emits(Emit::Link) {
+        write_command(directories, &cmd, output)?;
+    }
 
-    let output = child
-        .wait_with_output()
-        .map_err(|err| format!("Failed to wait for the linker: {err}"))?;
+    run(cmd.command(), None)
+}
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "The linker exited with status code {}:\n{}",
-            output.status.code().unwrap_or(0),
-            String::from_utf8_lossy(&output.stderr),
-        ))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_json_string() {
+        let mut buffer = String::new();
+
+        write_json_string(&mut buffer, "a\"b\\c\nd\u{1b}");
+        assert_eq!(buffer, "\"a\\\"b\\\\c\\nd\\u001b\"");
+    }
+
+    #[test]
+    fn test_command_json() {
+        let mut cmd = LinkCommand::new("cc");
+
+        cmd.arg("-o").arg("a\"b");
+
+        assert_eq!(
+            command_json(Path::new("/tmp"), &cmd, Path::new("a\"b")),
+            "[{\"directory\": \"/tmp\", \"arguments\": [\"cc\", \"-o\", \
+            \"a\\\"b\"], \"file\": \"a\\\"b\", \"output\": \"a\\\"b\"}]\n"
+        );
     }
 }
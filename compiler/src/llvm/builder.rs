@@ -232,6 +232,14 @@ impl<'ctx> Builder<'ctx> {
             .unwrap()
     }
 
+    pub(crate) fn direct_call(
+        &self,
+        function: FunctionValue<'ctx>,
+        arguments: &[BasicMetadataValueEnum<'ctx>],
+    ) -> CallSiteValue<'ctx> {
+        self.inner.build_call(function, arguments, "")
+    }
+
     pub(crate) fn indirect_call(
         &self,
         typ: FunctionType<'ctx>,
@@ -361,6 +369,38 @@ impl<'ctx> Builder<'ctx> {
         self.inner.build_int_compare(IntPredicate::SLE, lhs, rhs, "")
     }
 
+    pub(crate) fn unsigned_int_gt(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        self.inner.build_int_compare(IntPredicate::UGT, lhs, rhs, "")
+    }
+
+    pub(crate) fn unsigned_int_ge(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        self.inner.build_int_compare(IntPredicate::UGE, lhs, rhs, "")
+    }
+
+    pub(crate) fn unsigned_int_lt(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        self.inner.build_int_compare(IntPredicate::ULT, lhs, rhs, "")
+    }
+
+    pub(crate) fn unsigned_int_le(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        self.inner.build_int_compare(IntPredicate::ULE, lhs, rhs, "")
+    }
+
     pub(crate) fn int_sub(
         &self,
         lhs: IntValue<'ctx>,
@@ -401,6 +441,22 @@ impl<'ctx> Builder<'ctx> {
         self.inner.build_int_signed_rem(lhs, rhs, "")
     }
 
+    pub(crate) fn unsigned_int_div(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        self.inner.build_int_unsigned_div(lhs, rhs, "")
+    }
+
+    pub(crate) fn unsigned_int_rem(
+        &self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        self.inner.build_int_unsigned_rem(lhs, rhs, "")
+    }
+
     pub(crate) fn bit_and(
         &self,
         lhs: IntValue<'ctx>,
@@ -457,16 +513,20 @@ impl<'ctx> Builder<'ctx> {
         &self,
         value: IntValue<'ctx>,
         size: u32,
+        signed: bool,
     ) -> FloatValue<'ctx> {
         let typ = if size == 32 {
             self.context.f32_type()
         } else {
             self.context.f64_type()
         };
+        let op = if signed {
+            InstructionOpcode::SIToFP
+        } else {
+            InstructionOpcode::UIToFP
+        };
 
-        self.inner
-            .build_cast(InstructionOpcode::SIToFP, value, typ, "")
-            .into_float_value()
+        self.inner.build_cast(op, value, typ, "").into_float_value()
     }
 
     pub(crate) fn int_to_int(
@@ -41,6 +41,10 @@ impl Context {
         self.inner.create_enum_attribute(id, value)
     }
 
+    pub(crate) fn string_attribute(&self, key: &str, value: &str) -> Attribute {
+        self.inner.create_string_attribute(key, value)
+    }
+
     pub(crate) fn pointer_type(&self) -> PointerType<'_> {
         self.inner.i8_type().ptr_type(AddressSpace::default())
     }
@@ -154,6 +158,17 @@ impl Context {
         self.inner.create_module(name)
     }
 
+    pub(crate) fn foreign_type(&self, typ: ForeignType) -> BasicTypeEnum {
+        match typ {
+            ForeignType::Int(8, _) => self.i8_type().as_basic_type_enum(),
+            ForeignType::Int(16, _) => self.i16_type().as_basic_type_enum(),
+            ForeignType::Int(32, _) => self.i32_type().as_basic_type_enum(),
+            ForeignType::Int(_, _) => self.i64_type().as_basic_type_enum(),
+            ForeignType::Float(32) => self.f32_type().as_basic_type_enum(),
+            ForeignType::Float(_) => self.f64_type().as_basic_type_enum(),
+        }
+    }
+
     pub(crate) fn llvm_type<'a>(
         &'a self,
         db: &Database,
@@ -162,26 +177,12 @@ impl Context {
     ) -> BasicTypeEnum<'a> {
         if let Ok(id) = type_ref.type_id(db) {
             let base = match id {
-                TypeId::Foreign(ForeignType::Int(8, _)) => {
-                    self.i8_type().as_basic_type_enum()
-                }
-                TypeId::Foreign(ForeignType::Int(16, _)) => {
-                    self.i16_type().as_basic_type_enum()
-                }
-                TypeId::Foreign(ForeignType::Int(32, _)) => {
-                    self.i32_type().as_basic_type_enum()
-                }
-                TypeId::Foreign(ForeignType::Int(_, _)) => {
-                    self.i64_type().as_basic_type_enum()
-                }
-                TypeId::Foreign(ForeignType::Float(32)) => {
-                    self.f32_type().as_basic_type_enum()
-                }
-                TypeId::Foreign(ForeignType::Float(_)) => {
-                    self.f64_type().as_basic_type_enum()
+                TypeId::Foreign(typ) => self.foreign_type(typ),
+                TypeId::ClassInstance(ins) if ins.instance_of().is_sized() => {
+                    self.foreign_type(ins.instance_of().sized_type().unwrap())
                 }
                 TypeId::ClassInstance(ins)
-                    if ins.instance_of().kind(db).is_extern() =>
+                    if ins.instance_of().kind(db).is_stack_allocated() =>
                 {
                     layouts.instances[&ins.instance_of()].as_basic_type_enum()
                 }
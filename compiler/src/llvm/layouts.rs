@@ -13,7 +13,8 @@ use std::cmp::max;
 use std::collections::HashMap;
 use types::{
     ClassId, Database, MethodId, MethodSource, Shape, BOOL_ID, BYTE_ARRAY_ID,
-    CALL_METHOD, DROPPER_METHOD, FLOAT_ID, INT_ID, NIL_ID, STRING_ID,
+    CALL_METHOD, DROPPER_METHOD, ENUM_TAG_INDEX, FLOAT_ID, INT_ID, NIL_ID,
+    STRING_ID,
 };
 
 /// The size of an object header.
@@ -58,7 +59,7 @@ fn round_methods(mut value: usize) -> usize {
 
 fn hash_key(db: &Database, method: MethodId, shapes: &[Shape]) -> String {
     shapes.iter().fold(method.name(db).clone(), |mut name, shape| {
-        name.push_str(shape.identifier());
+        name.push_str(&shape.identifier());
         name
     })
 }
@@ -198,6 +199,11 @@ impl<'ctx> Layouts<'ctx> {
                     header,
                     context.f64_type().into(),
                 ),
+                _ if id.is_sized() => context.builtin_type(
+                    &name,
+                    header,
+                    context.foreign_type(id.sized_type().unwrap()),
+                ),
                 BOOL_ID | NIL_ID => {
                     let typ = context.opaque_struct(&name);
 
@@ -244,6 +250,8 @@ impl<'ctx> Layouts<'ctx> {
             _ => 128,
         };
 
+        let mut enums = Vec::new();
+
         for id in mir.classes.keys() {
             // String is a built-in class, but it's defined like a regular one,
             // so we _don't_ want to skip it here.
@@ -259,7 +267,14 @@ impl<'ctx> Layouts<'ctx> {
             let kind = id.kind(db);
             let mut fields = Vec::new();
 
-            if kind.is_extern() {
+            // The members of enums may be instances of inline classes, so the
+            // size of enums is only known once the inline classes are defined.
+            if kind.is_enum() {
+                enums.push(*id);
+                continue;
+            }
+
+            if kind.is_stack_allocated() {
                 for field in id.fields(db) {
                     let typ =
                         context.llvm_type(db, &layouts, field.value_type(db));
@@ -293,6 +308,49 @@ impl<'ctx> Layouts<'ctx> {
             layout.set_body(&fields, false);
         }
 
+        for id in enums {
+            let layout = layouts.instances[&id];
+            let variants = id.variants(db);
+            let mut fields = vec![header.into()];
+
+            for field in id.fields(db) {
+                let index = field.index(db);
+
+                if index == ENUM_TAG_INDEX {
+                    fields.push(context.llvm_type(
+                        db,
+                        &layouts,
+                        field.value_type(db),
+                    ));
+
+                    continue;
+                }
+
+                // Each member slot must be large enough to store the largest
+                // value of the variants that use the slot.
+                let size = variants
+                    .iter()
+                    .filter_map(|v| v.members(db).get(index - 1).cloned())
+                    .map(|t| {
+                        target_data
+                            .get_abi_size(&context.llvm_type(db, &layouts, t))
+                    })
+                    .max()
+                    .unwrap_or(8);
+
+                fields.push(if size <= 8 {
+                    context.i64_type().into()
+                } else {
+                    context
+                        .i64_type()
+                        .array_type(((size + 7) / 8) as u32)
+                        .into()
+                });
+            }
+
+            layout.set_body(&fields, false);
+        }
+
         // We need to define the method information for trait methods, as
         // this information is necessary when generating dynamic dispatch code.
         //
@@ -106,6 +106,14 @@ impl<'a, 'ctx> Module<'a, 'ctx> {
             let info = &self.layouts.methods[&method];
             let func = self.inner.add_function(name, info.signature, None);
 
+            // Frame pointers are required by the CPU profiler to walk the
+            // stack, as this can't be done using an unwinder from within a
+            // signal handler.
+            func.add_attribute(
+                AttributeLoc::Function,
+                self.context.string_attribute("frame-pointer", "all"),
+            );
+
             if let Some(typ) = info.struct_return {
                 let sret = self.context.type_attribute("sret", typ.into());
                 let noalias = self.context.enum_attribute("noalias", 0);
@@ -1,4 +1,4 @@
-use crate::config::BuildDirectories;
+use crate::config::{BuildDirectories, Emit};
 use crate::llvm::builder::Builder;
 use crate::llvm::constants::{
     ARRAY_BUF_INDEX, ARRAY_CAPA_INDEX, ARRAY_LENGTH_INDEX,
@@ -13,10 +13,11 @@ use crate::llvm::layouts::Layouts;
 use crate::llvm::module::Module;
 use crate::llvm::runtime_function::RuntimeFunction;
 use crate::mir::{
-    CastType, Constant, Instruction, LocationId, Method, Mir, RegisterId,
+    CallBuiltin, CastType, Constant, Instruction, LocationId, Method, Mir,
+    RegisterId,
 };
 use crate::state::State;
-use crate::symbol_names::SymbolNames;
+use crate::symbol_names::{library_symbol, SymbolNames};
 use crate::target::Architecture;
 use inkwell::basic_block::BasicBlock;
 use inkwell::module::Linkage;
@@ -28,7 +29,7 @@ use inkwell::types::{
     BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType,
 };
 use inkwell::values::{
-    BasicMetadataValueEnum, BasicValue, BasicValueEnum, FloatValue,
+    AnyValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, FloatValue,
     FunctionValue, GlobalValue, IntValue, PointerValue,
 };
 use inkwell::AddressSpace;
@@ -38,10 +39,52 @@ use std::path::Path;
 use std::path::PathBuf;
 use types::module_name::ModuleName;
 use types::{
-    BuiltinFunction, ClassId, Database, Shape, TypeRef, BYTE_ARRAY_ID,
-    STRING_ID,
+    BuiltinFunction, ClassId, Database, ForeignType, MethodId, Shape, TypeRef,
+    BYTE_ARRAY_ID, STRING_ID,
 };
 
+/// Writes the LLVM IR of a module to disk.
+///
+/// If the emitted output is limited to a single method, only the LLVM IR of
+/// that method is written.
+fn emit_llvm(
+    state: &State,
+    directories: &BuildDirectories,
+    mir: &Mir,
+    names: &SymbolNames,
+    module: &Module,
+) -> Result<(), String> {
+    let path = directories.emit_path(&module.name, Emit::Llvm);
+    let filter = state.config.emit_filter.as_ref();
+
+    // The generated main module doesn't have a corresponding MIR module, so
+    // for that module we always emit the entire module.
+    let mir_mod = mir
+        .modules
+        .keys()
+        .find(|id| id.name(&state.db) == &module.name)
+        .and_then(|id| mir.modules.get(id));
+
+    directories.create_emit()?;
+
+    match (filter, mir_mod) {
+        (Some(filter), Some(mir_mod)) if filter.method.is_some() => {
+            let output = mir_mod
+                .methods
+                .iter()
+                .filter(|&&id| filter.includes_method(&state.db, id))
+                .filter_map(|id| module.inner.get_function(&names.methods[id]))
+                .map(|func| func.print_to_string().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            std::fs::write(&path, output).map_err(|err| err.to_string())
+        }
+        _ => module.print_to_file(&path).map_err(|err| err.to_string()),
+    }
+    .map_err(|err| format!("Failed to create {}: {}", path.display(), err))
+}
+
 /// A compiler pass that compiles Inko MIR into object files using LLVM.
 pub(crate) struct Compile<'a, 'b, 'ctx> {
     db: &'a Database,
@@ -121,15 +164,27 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
             Path::new("$main.inko"),
         );
 
-        GenerateMain::new(
-            &state.db,
-            mir,
-            &types,
-            &names,
-            &context,
-            &main_module,
-        )
-        .run();
+        if state.config.library.is_some() {
+            GenerateLibrary::new(
+                &state.db,
+                mir,
+                &types,
+                &names,
+                &context,
+                &main_module,
+            )
+            .run();
+        } else {
+            GenerateMain::new(
+                &state.db,
+                mir,
+                &types,
+                &names,
+                &context,
+                &main_module,
+            )
+            .run();
+        }
 
         modules.push(main_module);
 
@@ -149,18 +204,11 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
 
         let mut paths = Vec::with_capacity(modules.len());
 
-        if state.config.write_llvm {
-            directories.create_llvm()?;
-        }
-
-        if state.config.write_llvm {
+        if state.config.emits(Emit::Llvm) {
             for module in &modules {
-                let name = module.name.normalized_name();
-                let path = directories.llvm_ir.join(format!("{}.ll", name));
-
-                module.print_to_file(&path).map_err(|err| {
-                    format!("Failed to create {}: {}", path.display(), err)
-                })?;
+                if state.config.emit_module(&module.name) {
+                    emit_llvm(state, directories, mir, &names, module)?;
+                }
             }
         }
 
@@ -178,6 +226,27 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
             }
         }
 
+        if state.config.emits(Emit::Asm) {
+            for module in &modules {
+                if !state.config.emit_module(&module.name) {
+                    continue;
+                }
+
+                let path = directories.emit_path(&module.name, Emit::Asm);
+
+                directories.create_emit()?;
+                target_machine
+                    .write_to_file(
+                        &module.inner,
+                        FileType::Assembly,
+                        path.as_path(),
+                    )
+                    .map_err(|err| {
+                        format!("Failed to create {}: {}", path.display(), err)
+                    })?;
+            }
+        }
+
         for module in &modules {
             let name = module.name.normalized_name();
             let path = directories.objects.join(format!("{}.o", name));
@@ -405,27 +474,17 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
                 builder.i64_literal(0).as_basic_value_enum()
             }
             Constant::Array(values) => {
-                let (shape, val_typ) = match values.first() {
-                    Some(Constant::Int(_)) => (
-                        Shape::Int,
-                        builder.context.i64_type().as_basic_type_enum(),
-                    ),
-                    Some(Constant::Bool(_)) => (
-                        Shape::Boolean,
-                        builder.context.i64_type().as_basic_type_enum(),
-                    ),
-                    Some(Constant::Float(_)) => (
-                        Shape::Float,
-                        builder.context.f64_type().as_basic_type_enum(),
-                    ),
-                    Some(Constant::String(_)) => (
-                        Shape::String,
-                        builder.context.pointer_type().as_basic_type_enum(),
-                    ),
-                    _ => (
-                        Shape::Owned,
-                        builder.context.pointer_type().as_basic_type_enum(),
-                    ),
+                let shape = values
+                    .first()
+                    .map_or(Shape::Owned, |v| self.constant_shape(v));
+                let val_typ = match shape {
+                    Shape::Int | Shape::Boolean => {
+                        builder.context.i64_type().as_basic_type_enum()
+                    }
+                    Shape::Float => {
+                        builder.context.f64_type().as_basic_type_enum()
+                    }
+                    _ => builder.context.pointer_type().as_basic_type_enum(),
                 };
 
                 let class_id =
@@ -474,6 +533,51 @@ impl<'a, 'b, 'ctx> Compile<'a, 'b, 'ctx> {
                 builder.store_field(layout, array, ARRAY_BUF_INDEX, buf_ptr);
                 array.as_basic_value_enum()
             }
+            Constant::Tuple(values) => {
+                let shapes: Vec<_> =
+                    values.iter().map(|v| self.constant_shape(v)).collect();
+                let class_id = ClassId::tuple(values.len())
+                    .unwrap()
+                    .specializations(self.db)[&shapes];
+                let layout = self.layouts.instances[&class_id];
+                let class_name = &self.names.classes[&class_id];
+                let class_global = self
+                    .module
+                    .add_class(class_id, class_name)
+                    .as_pointer_value();
+                let class = builder.load_untyped_pointer(class_global);
+                let alloc =
+                    self.module.runtime_function(RuntimeFunction::Allocate);
+                let tuple =
+                    builder.call(alloc, &[class.into()]).into_pointer_value();
+
+                for (index, arg) in values.iter().enumerate() {
+                    let val = self.permanent_value(builder, state_var, arg);
+
+                    builder.store_field(
+                        layout,
+                        tuple,
+                        (FIELD_OFFSET + index) as _,
+                        val,
+                    );
+                }
+
+                tuple.as_basic_value_enum()
+            }
+        }
+    }
+
+    /// Returns the shape of a constant value when stored in a generic type,
+    /// such as an array or a tuple.
+    fn constant_shape(&self, constant: &Constant) -> Shape {
+        match constant {
+            Constant::Int(_) => Shape::Int,
+            Constant::Float(_) => Shape::Float,
+            Constant::Bool(_) => Shape::Boolean,
+            Constant::String(_) => Shape::String,
+            // Constant arrays and tuples are typed as references, so nested
+            // values use the `Ref` shape.
+            Constant::Array(_) | Constant::Tuple(_) => Shape::Ref,
         }
     }
 
@@ -1279,6 +1383,38 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
 
                         self.builder.store(reg_var, state);
                     }
+                    BuiltinFunction::PointerValueSize => {
+                        let reg_var = self.variables[&ins.register];
+                        let val = match self.register_type(ins.arguments[0]) {
+                            TypeRef::Pointer(id) => TypeRef::Owned(id),
+                            _ => unreachable!(),
+                        };
+                        let typ = self.builder.context.llvm_type(
+                            self.db,
+                            self.layouts,
+                            val,
+                        );
+                        let size = typ.size_of().unwrap();
+
+                        self.builder.store(reg_var, size);
+                    }
+                    BuiltinFunction::SizedAdd
+                    | BuiltinFunction::SizedSub
+                    | BuiltinFunction::SizedMul
+                    | BuiltinFunction::SizedDiv
+                    | BuiltinFunction::SizedRem
+                    | BuiltinFunction::SizedEq
+                    | BuiltinFunction::SizedLt
+                    | BuiltinFunction::SizedLe
+                    | BuiltinFunction::SizedGt
+                    | BuiltinFunction::SizedGe
+                    | BuiltinFunction::SizedBitAnd
+                    | BuiltinFunction::SizedBitOr
+                    | BuiltinFunction::SizedBitXor
+                    | BuiltinFunction::SizedShl
+                    | BuiltinFunction::SizedShr => {
+                        self.sized_builtin(ins);
+                    }
                     BuiltinFunction::Moved => unreachable!(),
                 }
             }
@@ -1425,7 +1561,7 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                     args.push(self.builder.load(typ, var).into());
                 }
 
-                self.call(ins.register, func, &args);
+                self.call(ins.register, func, &args, ins.tail);
             }
             Instruction::CallInstance(ins) => {
                 self.set_debug_location(ins.location);
@@ -1449,7 +1585,7 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                     args.push(self.builder.load(typ, var).into());
                 }
 
-                self.call(ins.register, func, &args);
+                self.call(ins.register, func, &args, ins.tail);
             }
             Instruction::CallDynamic(ins) => {
                 self.set_debug_location(ins.location);
@@ -1759,7 +1895,7 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                 );
             }
             Instruction::GetField(ins)
-                if ins.class.kind(self.db).is_extern() =>
+                if ins.class.kind(self.db).is_stack_allocated() =>
             {
                 let reg_var = self.variables[&ins.register];
                 let rec_var = self.variables[&ins.receiver];
@@ -1783,7 +1919,7 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                 self.builder.store(reg_var, field);
             }
             Instruction::SetField(ins)
-                if ins.class.kind(self.db).is_extern() =>
+                if ins.class.kind(self.db).is_stack_allocated() =>
             {
                 let rec_var = self.variables[&ins.receiver];
                 let rec_typ = self.variable_types[&ins.receiver];
@@ -1817,11 +1953,16 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                 let index = (base + ins.field.index(self.db)) as u32;
                 let layout = self.layouts.instances[&ins.class];
                 let rec = self.builder.load(rec_typ, rec_var);
-                let field = self.builder.load_field(
+
+                // Enum member slots may be larger than the values stored in
+                // them, so we load the field using the type of the register.
+                let typ = self.variable_types[&ins.register];
+                let addr = self.builder.field_address(
                     layout,
                     rec.into_pointer_value(),
                     index,
                 );
+                let field = self.builder.load(typ, addr);
 
                 self.builder.store(reg_var, field);
             }
@@ -1943,12 +2084,39 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                 self.builder.branch(is_zero, drop_block, after_block);
             }
             Instruction::Allocate(ins)
-                if ins.class.kind(self.db).is_extern() =>
+                if ins.class.kind(self.db).is_stack_allocated() =>
             {
                 // Defining the alloca already reserves (uninitialised) memory,
                 // so there's nothing we actually need to do here. Setting the
                 // fields is done using separate instructions.
             }
+            Instruction::Allocate(ins) if ins.stack => {
+                let reg_var = self.variables[&ins.register];
+                let name = &self.names.classes[&ins.class];
+                let global =
+                    self.module.add_class(ins.class, name).as_pointer_value();
+                let class = self.builder.load_untyped_pointer(global);
+                let typ = self.layouts.instances[&ins.class];
+                let ptr = self.builder.new_stack_slot(typ);
+                let header = self.layouts.header;
+
+                // The object doesn't outlive the current method, so we only
+                // need to initialise the header the same way the runtime does
+                // for heap objects.
+                self.builder.store_field(
+                    header,
+                    ptr,
+                    HEADER_CLASS_INDEX,
+                    class,
+                );
+                self.builder.store_field(
+                    header,
+                    ptr,
+                    HEADER_REFS_INDEX,
+                    self.builder.u32_literal(0),
+                );
+                self.builder.store(reg_var, ptr);
+            }
             Instruction::Allocate(ins) => {
                 let reg_var = self.variables[&ins.register];
                 let name = &self.names.classes[&ins.class];
@@ -2028,11 +2196,11 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
                             .int_to_int(src.into_int_value(), size, signed)
                             .as_basic_value_enum()
                     }
-                    (CastType::Int(_, _), CastType::Float(size)) => {
+                    (CastType::Int(_, signed), CastType::Float(size)) => {
                         let src = self.builder.load(src_typ, src_var);
 
                         self.builder
-                            .int_to_float(src.into_int_value(), size)
+                            .int_to_float(src.into_int_value(), size, signed)
                             .as_basic_value_enum()
                     }
                     (
@@ -2046,10 +2214,10 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
 
                         self.builder.int_to_pointer(src).as_basic_value_enum()
                     }
-                    (CastType::Float(_), CastType::Int(size, _)) => {
+                    (CastType::Float(_), CastType::Int(size, signed)) => {
                         let src = self.builder.load(src_typ, src_var);
 
-                        self.float_to_int(src.into_float_value(), size)
+                        self.float_to_int(src.into_float_value(), size, signed)
                             .as_basic_value_enum()
                     }
                     (CastType::Float(_), CastType::Float(size)) => {
@@ -2111,11 +2279,78 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
 
                 self.builder.store(reg_var, val_var);
             }
+            Instruction::MethodPointer(ins) => {
+                let reg_var = self.variables[&ins.register];
+                let func = if ins.method.is_extern(self.db) {
+                    self.module.add_method(ins.method.name(self.db), ins.method)
+                } else {
+                    self.callback(ins.method)
+                };
+
+                self.builder
+                    .store(reg_var, func.as_global_value().as_pointer_value());
+            }
             Instruction::Reference(_) => unreachable!(),
             Instruction::Drop(_) => unreachable!(),
         }
     }
 
+    /// Returns a C function that calls the given Inko method.
+    ///
+    /// C doesn't know about the state and process arguments Inko methods
+    /// expect, so the function obtains these from the process running on the
+    /// current thread.
+    fn callback(&mut self, method: MethodId) -> FunctionValue<'ctx> {
+        let target = &self.names.methods[&method];
+        let name = format!("{}$callback", target);
+
+        if let Some(func) = self.module.get_function(&name) {
+            return func;
+        }
+
+        let context = self.builder.context;
+        let params: Vec<BasicMetadataTypeEnum> = method
+            .arguments(self.db)
+            .into_iter()
+            .map(|a| {
+                context.llvm_type(self.db, self.layouts, a.value_type).into()
+            })
+            .collect();
+        let ret = context.return_type(self.db, self.layouts, method);
+        let typ = ret
+            .map(|t| t.fn_type(&params, false))
+            .unwrap_or_else(|| context.void_type().fn_type(&params, false));
+        let func = self.module.add_function(&name, typ, Some(Linkage::Private));
+        let target = self.module.add_method(target, method);
+        let builder = Builder::new(context, func);
+        let state_func =
+            self.module.runtime_function(RuntimeFunction::ProcessCurrentState);
+        let proc_func =
+            self.module.runtime_function(RuntimeFunction::ProcessCurrent);
+
+        builder.switch_to_block(builder.add_block());
+
+        let mut args: Vec<BasicMetadataValueEnum> = vec![
+            builder.call(state_func, &[]).into(),
+            builder.call(proc_func, &[]).into(),
+        ];
+
+        for arg in builder.arguments() {
+            args.push(arg.into());
+        }
+
+        if ret.is_some() {
+            let val = builder.call(target, &args);
+
+            builder.return_value(Some(&val));
+        } else {
+            builder.call_void(target, &args);
+            builder.return_value(None);
+        }
+
+        func
+    }
+
     fn define_register_variables(&mut self) {
         for index in 0..self.method.registers.len() {
             let id = RegisterId(index as _);
@@ -2137,14 +2372,20 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
         register: RegisterId,
         function: FunctionValue<'ctx>,
         arguments: &[BasicMetadataValueEnum],
+        tail: bool,
     ) {
         let var = self.variables[&register];
+        let call = self.builder.direct_call(function, arguments);
+
+        // LLVM 15 doesn't expose "musttail" through its C API, so the best we
+        // can do is mark the call as a tail call and let LLVM eliminate it
+        // where possible.
+        call.set_tail_call(tail);
 
         if self.register_type(register).is_never(self.db) {
-            self.builder.call_void(function, arguments);
             self.builder.unreachable();
         } else {
-            self.builder.store(var, self.builder.call(function, arguments));
+            self.builder.store(var, call.try_as_basic_value().left().unwrap());
         }
     }
 
@@ -2180,10 +2421,166 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
         self.builder.set_debug_location(loc);
     }
 
+    fn sized_builtin(&mut self, ins: &CallBuiltin) {
+        let reg_var = self.variables[&ins.register];
+        let lhs_var = self.variables[&ins.arguments[0]];
+        let lhs_typ = self.variable_types[&ins.arguments[0]];
+        let rhs_var = self.variables[&ins.arguments[1]];
+        let rhs_typ = self.variable_types[&ins.arguments[1]];
+        let lhs = self.builder.load(lhs_typ, lhs_var);
+        let rhs = self.builder.load(rhs_typ, rhs_var);
+        let sized = self
+            .register_type(ins.arguments[0])
+            .class_id(self.db)
+            .and_then(|id| id.sized_type());
+
+        let res = match sized {
+            Some(ForeignType::Float(_)) => {
+                let lhs = lhs.into_float_value();
+                let rhs = rhs.into_float_value();
+
+                match ins.name {
+                    BuiltinFunction::SizedAdd => {
+                        self.builder.float_add(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedSub => {
+                        self.builder.float_sub(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedMul => {
+                        self.builder.float_mul(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedDiv => {
+                        self.builder.float_div(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedRem => {
+                        self.builder.float_rem(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedEq => {
+                        let raw = self.builder.float_eq(lhs, rhs);
+
+                        self.builder.bool_to_int(raw).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedLt => {
+                        let raw = self.builder.float_lt(lhs, rhs);
+
+                        self.builder.bool_to_int(raw).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedLe => {
+                        let raw = self.builder.float_le(lhs, rhs);
+
+                        self.builder.bool_to_int(raw).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedGt => {
+                        let raw = self.builder.float_gt(lhs, rhs);
+
+                        self.builder.bool_to_int(raw).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedGe => {
+                        let raw = self.builder.float_ge(lhs, rhs);
+
+                        self.builder.bool_to_int(raw).as_basic_value_enum()
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            Some(ForeignType::Int(size, signed)) => {
+                let lhs = lhs.into_int_value();
+
+                // The shift amount is an Int, so we need to convert it to the
+                // same size as the value to shift.
+                let rhs =
+                    self.builder.int_to_int(rhs.into_int_value(), size, false);
+
+                match ins.name {
+                    BuiltinFunction::SizedAdd => {
+                        self.builder.int_add(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedSub => {
+                        self.builder.int_sub(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedMul => {
+                        self.builder.int_mul(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedDiv if signed => {
+                        self.builder.int_div(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedDiv => self
+                        .builder
+                        .unsigned_int_div(lhs, rhs)
+                        .as_basic_value_enum(),
+                    BuiltinFunction::SizedRem if signed => {
+                        self.builder.int_rem(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedRem => self
+                        .builder
+                        .unsigned_int_rem(lhs, rhs)
+                        .as_basic_value_enum(),
+                    BuiltinFunction::SizedBitAnd => {
+                        self.builder.bit_and(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedBitOr => {
+                        self.builder.bit_or(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedBitXor => {
+                        self.builder.bit_xor(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedShl => {
+                        self.builder.left_shift(lhs, rhs).as_basic_value_enum()
+                    }
+                    BuiltinFunction::SizedShr if signed => self
+                        .builder
+                        .signed_right_shift(lhs, rhs)
+                        .as_basic_value_enum(),
+                    BuiltinFunction::SizedShr => {
+                        self.builder.right_shift(lhs, rhs).as_basic_value_enum()
+                    }
+                    name => {
+                        let raw = match name {
+                            BuiltinFunction::SizedEq => {
+                                self.builder.int_eq(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedLt if signed => {
+                                self.builder.int_lt(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedLt => {
+                                self.builder.unsigned_int_lt(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedLe if signed => {
+                                self.builder.int_le(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedLe => {
+                                self.builder.unsigned_int_le(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedGt if signed => {
+                                self.builder.int_gt(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedGt => {
+                                self.builder.unsigned_int_gt(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedGe if signed => {
+                                self.builder.int_ge(lhs, rhs)
+                            }
+                            BuiltinFunction::SizedGe => {
+                                self.builder.unsigned_int_ge(lhs, rhs)
+                            }
+                            _ => unreachable!(),
+                        };
+
+                        self.builder.bool_to_int(raw).as_basic_value_enum()
+                    }
+                }
+            }
+            None => unreachable!(),
+        };
+
+        self.builder.store(reg_var, res);
+    }
+
     fn float_to_int(
         &mut self,
         source: FloatValue<'ctx>,
         size: u32,
+        signed: bool,
     ) -> IntValue<'ctx> {
         let target = match size {
             8 => self.builder.context.i8_type(),
@@ -2192,15 +2589,65 @@ impl<'a, 'b, 'ctx> LowerMethod<'a, 'b, 'ctx> {
             _ => self.builder.context.i64_type(),
         };
 
-        let func = self.module.intrinsic(
-            "llvm.fptosi.sat",
-            &[target.into(), source.get_type().into()],
-        );
+        let name = if signed { "llvm.fptosi.sat" } else { "llvm.fptoui.sat" };
+        let func = self
+            .module
+            .intrinsic(name, &[target.into(), source.get_type().into()]);
 
         self.builder.call(func, &[source.into()]).into_int_value()
     }
 }
 
+/// Generates the code for creating a runtime and setting up the classes and
+/// constants of all modules, returning a pointer to the runtime.
+fn setup_runtime<'ctx>(
+    mir: &Mir,
+    layouts: &Layouts<'ctx>,
+    names: &SymbolNames,
+    module: &Module<'_, 'ctx>,
+    builder: &Builder<'ctx>,
+    argc: BasicValueEnum<'ctx>,
+    argv: BasicValueEnum<'ctx>,
+) -> PointerValue<'ctx> {
+    let counts = builder.alloca(layouts.method_counts);
+
+    for class in [ClassId::string(), ClassId::byte_array()] {
+        let methods = builder
+            .context
+            .i16_type()
+            .const_int(layouts.methods(class) as _, false);
+
+        builder.store_field(layouts.method_counts, counts, class.0, methods);
+    }
+
+    let rt_new = module.runtime_function(RuntimeFunction::RuntimeNew);
+    let rt_state = module.runtime_function(RuntimeFunction::RuntimeState);
+    let runtime = builder
+        .call(rt_new, &[counts.into(), argc.into(), argv.into()])
+        .into_pointer_value();
+    let state = builder.call(rt_state, &[runtime.into()]).into_pointer_value();
+
+    // Allocate and store all the classes in their corresponding globals.
+    for &id in mir.modules.keys() {
+        let name = &names.setup_classes[&id];
+        let func = module.add_setup_function(name);
+
+        builder.call_void(func, &[state.into()]);
+    }
+
+    // Constants need to be defined in a separate pass, as they may depends
+    // on the classes (e.g. array constants need the Array class to be set
+    // up).
+    for &id in mir.modules.keys() {
+        let name = &names.setup_constants[&id];
+        let func = module.add_setup_function(name);
+
+        builder.call_void(func, &[state.into()]);
+    }
+
+    runtime
+}
+
 /// A pass for generating the entry module and method (i.e. `main()`).
 pub(crate) struct GenerateMain<'a, 'ctx> {
     db: &'a Database,
@@ -2251,43 +2698,19 @@ impl<'a, 'ctx> GenerateMain<'a, 'ctx> {
 
         let argc = self.builder.load(argc_typ, argc_var);
         let argv = self.builder.load(argv_typ, argv_var);
-        let layout = self.layouts.method_counts;
-        let counts = self.builder.alloca(layout);
-
-        self.set_method_count(counts, ClassId::string());
-        self.set_method_count(counts, ClassId::byte_array());
-
-        let rt_new = self.module.runtime_function(RuntimeFunction::RuntimeNew);
         let rt_start =
             self.module.runtime_function(RuntimeFunction::RuntimeStart);
-        let rt_state =
-            self.module.runtime_function(RuntimeFunction::RuntimeState);
         let rt_drop =
             self.module.runtime_function(RuntimeFunction::RuntimeDrop);
-        let runtime = self
-            .builder
-            .call(rt_new, &[counts.into(), argc.into(), argv.into()])
-            .into_pointer_value();
-        let state =
-            self.builder.call(rt_state, &[runtime.into()]).into_pointer_value();
-
-        // Allocate and store all the classes in their corresponding globals.
-        for &id in self.mir.modules.keys() {
-            let name = &self.names.setup_classes[&id];
-            let func = self.module.add_setup_function(name);
-
-            self.builder.call_void(func, &[state.into()]);
-        }
-
-        // Constants need to be defined in a separate pass, as they may depends
-        // on the classes (e.g. array constants need the Array class to be set
-        // up).
-        for &id in self.mir.modules.keys() {
-            let name = &self.names.setup_constants[&id];
-            let func = self.module.add_setup_function(name);
-
-            self.builder.call_void(func, &[state.into()]);
-        }
+        let runtime = setup_runtime(
+            self.mir,
+            self.layouts,
+            self.names,
+            self.module,
+            &self.builder,
+            argc,
+            argv,
+        );
 
         let main_class_id = self.db.main_class().unwrap();
         let main_method_id = self.db.main_method().unwrap();
@@ -2326,14 +2749,193 @@ impl<'a, 'ctx> GenerateMain<'a, 'ctx> {
         self.builder.call_void(rt_drop, &[runtime.into()]);
         self.builder.return_value(Some(&self.builder.u32_literal(0)));
     }
+}
 
-    fn methods(&self, id: ClassId) -> IntValue<'ctx> {
-        self.context.i16_type().const_int(self.layouts.methods(id) as _, false)
+/// A pass for generating the entry module of a library.
+///
+/// Instead of a `main()` function, this module defines functions for starting
+/// and stopping the runtime, and a C function for every exported method. These
+/// functions run the exported methods on a new process, blocking the calling
+/// thread until the method returns.
+pub(crate) struct GenerateLibrary<'a, 'ctx> {
+    db: &'a Database,
+    mir: &'a Mir,
+    layouts: &'a Layouts<'ctx>,
+    names: &'a SymbolNames,
+    context: &'ctx Context,
+    module: &'a Module<'a, 'ctx>,
+}
+
+impl<'a, 'ctx> GenerateLibrary<'a, 'ctx> {
+    fn new(
+        db: &'a Database,
+        mir: &'a Mir,
+        layouts: &'a Layouts<'ctx>,
+        names: &'a SymbolNames,
+        context: &'ctx Context,
+        module: &'a Module<'a, 'ctx>,
+    ) -> GenerateLibrary<'a, 'ctx> {
+        GenerateLibrary { db, mir, layouts, names, context, module }
+    }
+
+    fn run(self) {
+        self.generate_init();
+        self.generate_shutdown();
+
+        for &method in self.db.exported_methods() {
+            self.generate_export(method);
+        }
+    }
+
+    fn generate_init(&self) {
+        let space = AddressSpace::default();
+        let typ = self.context.pointer_type().fn_type(
+            &[
+                self.context.i32_type().into(),
+                self.context.i8_type().ptr_type(space).into(),
+            ],
+            false,
+        );
+        let name = library_symbol(self.db, "init");
+        let function = self.module.add_function(&name, typ, None);
+        let builder = Builder::new(self.context, function);
+
+        builder.switch_to_block(builder.add_block());
+
+        let runtime = setup_runtime(
+            self.mir,
+            self.layouts,
+            self.names,
+            self.module,
+            &builder,
+            builder.argument(0),
+            builder.argument(1),
+        );
+        let rt_spawn =
+            self.module.runtime_function(RuntimeFunction::RuntimeSpawn);
+
+        builder.call_void(rt_spawn, &[runtime.into()]);
+        builder.return_value(Some(&runtime));
     }
 
-    fn set_method_count(&self, counts: PointerValue<'ctx>, class: ClassId) {
-        let layout = self.layouts.method_counts;
+    fn generate_shutdown(&self) {
+        let typ = self
+            .context
+            .void_type()
+            .fn_type(&[self.context.pointer_type().into()], false);
+        let name = library_symbol(self.db, "shutdown");
+        let function = self.module.add_function(&name, typ, None);
+        let builder = Builder::new(self.context, function);
+        let rt_shutdown =
+            self.module.runtime_function(RuntimeFunction::RuntimeShutdown);
+
+        builder.switch_to_block(builder.add_block());
+        builder.call_void(rt_shutdown, &[builder.argument(0).into()]);
+        builder.return_value(None);
+    }
+
+    fn generate_export(&self, method: MethodId) {
+        let space = AddressSpace::default();
+        let name = library_symbol(self.db, method.name(self.db));
+        let func = self.module.add_method(&self.names.methods[&method], method);
+        let ret = self.context.return_type(self.db, self.layouts, method);
+        let mut fields: Vec<BasicTypeEnum> = method
+            .arguments(self.db)
+            .into_iter()
+            .map(|a| {
+                self.context.llvm_type(self.db, self.layouts, a.value_type)
+            })
+            .collect();
+        let nargs = fields.len() as u32;
+
+        if let Some(typ) = ret {
+            fields.push(typ);
+        }
+
+        // The arguments and return value are passed using a structure stored
+        // on the stack of the calling thread.
+        let data_type = self.context.struct_type(&fields);
+
+        // The function that runs on the process, calling the exported method.
+        let call_type = self.context.void_type().fn_type(
+            &[
+                self.layouts.state.ptr_type(space).into(),
+                self.context.pointer_type().into(),
+                self.context.pointer_type().into(),
+            ],
+            false,
+        );
+        let call = self.module.add_function(
+            &format!("{}$call", name),
+            call_type,
+            Some(Linkage::Private),
+        );
+        let builder = Builder::new(self.context, call);
+
+        builder.switch_to_block(builder.add_block());
+
+        let data = builder.argument(2).into_pointer_value();
+        let mut args: Vec<BasicMetadataValueEnum> =
+            vec![builder.argument(0).into(), builder.argument(1).into()];
+
+        for index in 0..nargs {
+            args.push(builder.load_field(data_type, data, index).into());
+        }
+
+        if ret.is_some() {
+            let val = builder.call(func, &args);
+
+            builder.store_field(data_type, data, nargs, val);
+        } else {
+            builder.call_void(func, &args);
+        }
+
+        builder.return_value(None);
+
+        // The C function, taking the runtime as its first argument.
+        let mut params: Vec<BasicMetadataTypeEnum> =
+            vec![self.context.pointer_type().into()];
 
-        self.builder.store_field(layout, counts, class.0, self.methods(class));
+        for &typ in fields.iter().take(nargs as usize) {
+            params.push(typ.into());
+        }
+
+        let typ = ret.map(|t| t.fn_type(&params, false)).unwrap_or_else(|| {
+            self.context.void_type().fn_type(&params, false)
+        });
+        let export = self.module.add_function(&name, typ, None);
+        let builder = Builder::new(self.context, export);
+        let rt_call =
+            self.module.runtime_function(RuntimeFunction::RuntimeCall);
+
+        builder.switch_to_block(builder.add_block());
+
+        let data = builder.alloca(data_type);
+
+        for index in 0..nargs {
+            builder.store_field(
+                data_type,
+                data,
+                index,
+                builder.argument(index + 1),
+            );
+        }
+
+        builder.call_void(
+            rt_call,
+            &[
+                builder.argument(0).into(),
+                call.as_global_value().as_pointer_value().into(),
+                data.into(),
+            ],
+        );
+
+        if ret.is_some() {
+            let val = builder.load_field(data_type, data, nargs);
+
+            builder.return_value(Some(&val));
+        } else {
+            builder.return_value(None);
+        }
     }
 }
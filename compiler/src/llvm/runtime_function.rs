@@ -11,13 +11,18 @@ pub(crate) enum RuntimeFunction {
     MessageNew,
     Allocate,
     AllocateAtomic,
+    ProcessCurrent,
+    ProcessCurrentState,
     ProcessFinishMessage,
     ProcessNew,
     ProcessPanic,
     ProcessSendMessage,
     Reduce,
+    RuntimeCall,
     RuntimeDrop,
     RuntimeNew,
+    RuntimeShutdown,
+    RuntimeSpawn,
     RuntimeStart,
     RuntimeState,
     StringConcat,
@@ -34,6 +39,10 @@ impl RuntimeFunction {
             RuntimeFunction::MessageNew => "inko_message_new",
             RuntimeFunction::Allocate => "inko_alloc",
             RuntimeFunction::AllocateAtomic => "inko_alloc_atomic",
+            RuntimeFunction::ProcessCurrent => "inko_process_current",
+            RuntimeFunction::ProcessCurrentState => {
+                "inko_process_current_state"
+            }
             RuntimeFunction::ProcessFinishMessage => {
                 "inko_process_finish_message"
             }
@@ -41,8 +50,11 @@ impl RuntimeFunction {
             RuntimeFunction::ProcessPanic => "inko_process_panic",
             RuntimeFunction::ProcessSendMessage => "inko_process_send_message",
             RuntimeFunction::Reduce => "inko_reduce",
+            RuntimeFunction::RuntimeCall => "inko_runtime_call",
             RuntimeFunction::RuntimeDrop => "inko_runtime_drop",
             RuntimeFunction::RuntimeNew => "inko_runtime_new",
+            RuntimeFunction::RuntimeShutdown => "inko_runtime_shutdown",
+            RuntimeFunction::RuntimeSpawn => "inko_runtime_spawn",
             RuntimeFunction::RuntimeStart => "inko_runtime_start",
             RuntimeFunction::RuntimeState => "inko_runtime_state",
             RuntimeFunction::StringConcat => "inko_string_concat",
@@ -106,12 +118,22 @@ impl RuntimeFunction {
 
                 ret.fn_type(&[counts, argc, argv], false)
             }
-            RuntimeFunction::RuntimeDrop => {
+            RuntimeFunction::RuntimeDrop
+            | RuntimeFunction::RuntimeShutdown
+            | RuntimeFunction::RuntimeSpawn => {
                 let runtime = context.pointer_type().into();
                 let ret = context.void_type();
 
                 ret.fn_type(&[runtime], false)
             }
+            RuntimeFunction::RuntimeCall => {
+                let runtime = context.pointer_type().into();
+                let function = context.pointer_type().into();
+                let data = context.pointer_type().into();
+                let ret = context.void_type();
+
+                ret.fn_type(&[runtime, function, data], false)
+            }
             RuntimeFunction::RuntimeStart => {
                 let runtime = context.pointer_type().into();
                 let class = context.pointer_type().into();
@@ -150,6 +172,12 @@ impl RuntimeFunction {
 
                 ret.fn_type(&[state, sender, receiver, message], false)
             }
+            RuntimeFunction::ProcessCurrent => {
+                context.pointer_type().fn_type(&[], false)
+            }
+            RuntimeFunction::ProcessCurrentState => {
+                module.layouts.state.ptr_type(space).fn_type(&[], false)
+            }
             RuntimeFunction::ProcessNew => {
                 let process = context.pointer_type().into();
                 let class = context.pointer_type().into();
@@ -0,0 +1,1123 @@
+//! Escape analysis for allocating objects on the stack.
+//!
+//! Objects are allocated on the heap by default. If we can prove that an object
+//! doesn't outlive the method that allocates it, we can instead allocate it on
+//! the stack, removing the need for a heap allocation.
+//!
+//! An object escapes if it's returned, stored in a field (which includes being
+//! captured by a closure), sent to a process, passed to C, or passed to a method
+//! that does any of these things. To determine the latter, we compute a summary
+//! for every method, recording which of its arguments may escape.
+//!
+//! For objects that don't escape, calls to their droppers are replaced with the
+//! body of the dropper, minus the instruction that releases the object's
+//! memory.
+use crate::mir::{
+    Block, BlockId, Goto, Instruction, Method, Mir, MoveRegister, RegisterId,
+};
+use std::collections::{HashMap, HashSet};
+use std::mem::take;
+use types::{ClassId, Database, MethodId, DROPPER_METHOD};
+
+/// A map that records for each argument of a method whether it may escape.
+type Summaries = HashMap<MethodId, Vec<bool>>;
+
+/// Allocates objects on the stack if they don't escape the method that
+/// allocates them.
+pub(crate) fn stack_allocate(db: &Database, mir: &mut Mir) {
+    let summaries = summaries(db, mir);
+    let mut stack = HashMap::new();
+
+    for (&id, method) in &mir.methods {
+        let regs = stack_registers(db, method, &summaries);
+
+        if !regs.0.is_empty() {
+            stack.insert(id, regs);
+        }
+    }
+
+    for (id, (roots, registers)) in stack {
+        let mut method = mir.methods.remove(&id).unwrap();
+
+        // Tail calls can't access the stack frame of the caller, so we can't
+        // use them if objects are allocated on the stack.
+        for block in &mut method.body.blocks {
+            for ins in &mut block.instructions {
+                match ins {
+                    Instruction::Allocate(ins)
+                        if roots.contains(&ins.register) =>
+                    {
+                        ins.stack = true;
+                    }
+                    Instruction::CallStatic(ins) => ins.tail = false,
+                    Instruction::CallInstance(ins) => ins.tail = false,
+                    _ => {}
+                }
+            }
+        }
+
+        inline_droppers(db, mir, &mut method, &registers);
+
+        // The inlined droppers and partially moved objects try to release
+        // the memory of the objects, which isn't necessary for objects on the
+        // stack.
+        for block in &mut method.body.blocks {
+            block.instructions.retain(|ins| match ins {
+                Instruction::Free(ins) => !registers.contains(&ins.register),
+                _ => true,
+            });
+        }
+
+        mir.methods.insert(id, method);
+    }
+}
+
+/// Computes the escape summaries of all methods.
+///
+/// Arguments start out as not escaping, and are updated until no more changes
+/// are produced. This way recursive methods are handled correctly. When the
+/// summary of a method changes, only the methods that call it are analysed
+/// again.
+fn summaries(db: &Database, mir: &Mir) -> Summaries {
+    let mut summaries: Summaries = mir
+        .methods
+        .iter()
+        .map(|(&id, method)| (id, vec![false; method.arguments.len()]))
+        .collect();
+    let mut callers: HashMap<MethodId, HashSet<MethodId>> = HashMap::new();
+
+    for (&id, method) in &mir.methods {
+        for ins in method.body.blocks.iter().flat_map(|b| &b.instructions) {
+            let callee = match ins {
+                Instruction::CallStatic(ins) => ins.method,
+                Instruction::CallInstance(ins) => ins.method,
+                _ => continue,
+            };
+
+            callers.entry(callee).or_default().insert(id);
+        }
+    }
+
+    let mut work: Vec<MethodId> = mir.methods.keys().cloned().collect();
+    let mut queued: HashSet<MethodId> = work.iter().cloned().collect();
+
+    while let Some(id) = work.pop() {
+        let method = &mir.methods[&id];
+        let dropper = id.name(db) == DROPPER_METHOD;
+        let mut changed = false;
+
+        queued.remove(&id);
+
+        for (index, &arg) in method.arguments.iter().enumerate() {
+            if summaries[&id][index] {
+                continue;
+            }
+
+            // Droppers are the only methods that may release the receiver.
+            let analysis = Escape {
+                db,
+                method,
+                summaries: &summaries,
+                owned: dropper && index == 0,
+            };
+
+            if analysis.escapes(&analysis.aliases(arg)) {
+                summaries.get_mut(&id).unwrap()[index] = true;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            continue;
+        }
+
+        for &caller in callers.get(&id).into_iter().flatten() {
+            if queued.insert(caller) {
+                work.push(caller);
+            }
+        }
+    }
+
+    summaries
+}
+
+/// Returns the registers of the objects in a method that can be allocated on
+/// the stack.
+///
+/// The return value is a tuple containing the registers that allocate the
+/// objects, and all the registers that refer to these objects.
+fn stack_registers(
+    db: &Database,
+    method: &Method,
+    summaries: &Summaries,
+) -> (HashSet<RegisterId>, HashSet<RegisterId>) {
+    let analysis = Escape { db, method, summaries, owned: true };
+    let mut roots = HashSet::new();
+    let mut registers = HashSet::new();
+
+    for (index, block) in method.body.blocks.iter().enumerate() {
+        for ins in &block.instructions {
+            let ins = match ins {
+                Instruction::Allocate(ins) if allow_stack(db, ins.class) => ins,
+                _ => continue,
+            };
+
+            // Allocations in loops reuse the same stack memory for every
+            // iteration, so we can only use the stack if the object is dropped
+            // before the next iteration. This is difficult to prove, so we
+            // just use the heap instead.
+            if in_loop(method, BlockId(index)) {
+                continue;
+            }
+
+            let aliases = analysis.aliases(ins.register);
+
+            if !analysis.only_assigned(ins.register, &aliases)
+                || analysis.escapes(&aliases)
+            {
+                continue;
+            }
+
+            roots.insert(ins.register);
+            registers.extend(aliases);
+        }
+    }
+
+    (roots, registers)
+}
+
+fn allow_stack(db: &Database, class: ClassId) -> bool {
+    let kind = class.kind(db);
+
+    // Processes and atomic values are shared between processes, and extern
+    // and inline classes are already allocated on the stack.
+    !kind.is_async()
+        && !kind.is_stack_allocated()
+        && !class.is_atomic(db)
+        && class.method(db, DROPPER_METHOD).is_some()
+}
+
+fn in_loop(method: &Method, block: BlockId) -> bool {
+    let mut visited = HashSet::new();
+    let mut work = method.body.successors(block);
+
+    while let Some(id) = work.pop() {
+        if id == block {
+            return true;
+        }
+
+        if visited.insert(id) {
+            work.extend(method.body.successors(id));
+        }
+    }
+
+    false
+}
+
+struct Escape<'a> {
+    db: &'a Database,
+    method: &'a Method,
+    summaries: &'a Summaries,
+
+    /// A flag indicating that the registers analysed own the object, meaning
+    /// they're allowed to drop and release it.
+    owned: bool,
+}
+
+impl<'a> Escape<'a> {
+    /// Returns the given register and all the registers it's moved into.
+    fn aliases(&self, register: RegisterId) -> HashSet<RegisterId> {
+        let mut aliases = HashSet::new();
+
+        aliases.insert(register);
+
+        loop {
+            let size = aliases.len();
+
+            for ins in self.instructions() {
+                match ins {
+                    Instruction::MoveRegister(ins)
+                        if aliases.contains(&ins.source) =>
+                    {
+                        aliases.insert(ins.target);
+                    }
+                    Instruction::Reference(ins)
+                        if aliases.contains(&ins.value) =>
+                    {
+                        aliases.insert(ins.register);
+                    }
+                    _ => {}
+                }
+            }
+
+            if aliases.len() == size {
+                return aliases;
+            }
+        }
+    }
+
+    /// Returns `true` if the aliases of an object are only ever assigned that
+    /// object.
+    ///
+    /// This is needed as registers may be reassigned, in which case we can't
+    /// determine statically which object is dropped when dropping the register.
+    fn only_assigned(
+        &self,
+        root: RegisterId,
+        aliases: &HashSet<RegisterId>,
+    ) -> bool {
+        let mut allocations = 0;
+
+        for ins in self.instructions() {
+            let ok = match ins {
+                Instruction::Allocate(ins) if ins.register == root => {
+                    allocations += 1;
+                    true
+                }
+                Instruction::MoveRegister(ins) => {
+                    !aliases.contains(&ins.target)
+                        || aliases.contains(&ins.source)
+                }
+                Instruction::Reference(ins) => {
+                    !aliases.contains(&ins.register)
+                        || aliases.contains(&ins.value)
+                }
+                _ => defined_register(ins)
+                    .map_or(true, |reg| !aliases.contains(&reg)),
+            };
+
+            if !ok {
+                return false;
+            }
+        }
+
+        allocations == 1 && !self.method.arguments.contains(&root)
+    }
+
+    fn escapes(&self, registers: &HashSet<RegisterId>) -> bool {
+        self.instructions().any(|ins| self.escapes_through(ins, registers))
+    }
+
+    fn escapes_through(
+        &self,
+        instruction: &Instruction,
+        regs: &HashSet<RegisterId>,
+    ) -> bool {
+        let any =
+            |values: &[RegisterId]| values.iter().any(|r| regs.contains(r));
+
+        match instruction {
+            Instruction::Return(ins) => regs.contains(&ins.register),
+            Instruction::SetField(ins) => regs.contains(&ins.value),
+            Instruction::WritePointer(ins) => regs.contains(&ins.value),
+            Instruction::Pointer(ins) => regs.contains(&ins.value),
+            Instruction::FieldPointer(ins) => regs.contains(&ins.receiver),
+            Instruction::Cast(ins) => regs.contains(&ins.source),
+            Instruction::Free(ins) => {
+                !self.owned && regs.contains(&ins.register)
+            }
+            Instruction::CallDropper(ins) => regs.contains(&ins.receiver),
+            Instruction::CallExtern(ins) => any(&ins.arguments),
+            Instruction::CallBuiltin(ins) => any(&ins.arguments),
+            Instruction::CallClosure(ins) => {
+                regs.contains(&ins.receiver) || any(&ins.arguments)
+            }
+            Instruction::CallDynamic(ins) => {
+                regs.contains(&ins.receiver) || any(&ins.arguments)
+            }
+            Instruction::Send(ins) => {
+                regs.contains(&ins.receiver) || any(&ins.arguments)
+            }
+            Instruction::CallStatic(ins) => {
+                self.call_escapes(ins.method, None, &ins.arguments, regs)
+            }
+            Instruction::CallInstance(ins) => self.call_escapes(
+                ins.method,
+                Some(ins.receiver),
+                &ins.arguments,
+                regs,
+            ),
+            _ => false,
+        }
+    }
+
+    fn call_escapes(
+        &self,
+        method: MethodId,
+        receiver: Option<RegisterId>,
+        arguments: &[RegisterId],
+        regs: &HashSet<RegisterId>,
+    ) -> bool {
+        let summary = match self.summaries.get(&method) {
+            Some(summary) => summary,
+            None => {
+                return receiver.map_or(false, |r| regs.contains(&r))
+                    || arguments.iter().any(|r| regs.contains(r));
+            }
+        };
+
+        let offset = if let Some(rec) = receiver {
+            if regs.contains(&rec) {
+                let escapes = if method.name(self.db) == DROPPER_METHOD {
+                    !self.owned || summary[0]
+                } else {
+                    method.is_moving(self.db) || summary[0]
+                };
+
+                if escapes {
+                    return true;
+                }
+            }
+
+            1
+        } else {
+            0
+        };
+
+        arguments.iter().enumerate().any(|(index, reg)| {
+            // Owned values are moved into the method, which then drops them.
+            regs.contains(reg)
+                && (self
+                    .method
+                    .registers
+                    .value_type(*reg)
+                    .is_owned_or_uni(self.db)
+                    || summary.get(index + offset).copied().unwrap_or(true))
+        })
+    }
+
+    fn instructions(&self) -> impl Iterator<Item = &'a Instruction> {
+        self.method.body.blocks.iter().flat_map(|b| b.instructions.iter())
+    }
+}
+
+/// Replaces calls to the droppers of the given registers with the bodies of
+/// these droppers.
+fn inline_droppers(
+    db: &Database,
+    mir: &Mir,
+    method: &mut Method,
+    registers: &HashSet<RegisterId>,
+) {
+    let mut block_idx = 0;
+
+    // Inlining introduces new blocks, so we can't use a fixed range here.
+    while block_idx < method.body.blocks.len() {
+        let block_id = BlockId(block_idx);
+        let position =
+            method.body.blocks[block_idx].instructions.iter().position(|ins| {
+                matches!(
+                    ins,
+                    Instruction::CallInstance(ins)
+                        if registers.contains(&ins.receiver)
+                            && ins.method.name(db) == DROPPER_METHOD
+                )
+            });
+
+        let index = if let Some(index) = position {
+            index
+        } else {
+            block_idx += 1;
+            continue;
+        };
+
+        let (call, remaining) = {
+            let block = &mut method.body.blocks[block_idx];
+
+            if let Instruction::CallInstance(ins) =
+                block.instructions.remove(index)
+            {
+                (ins, block.instructions.split_off(index))
+            } else {
+                unreachable!()
+            }
+        };
+
+        let after_id = method.body.add_block();
+
+        for succ in take(&mut method.body.blocks[block_idx].successors) {
+            method.body.remove_predecessor(succ, block_id);
+            method.body.add_edge(after_id, succ);
+        }
+
+        method.body.blocks[after_id.0].instructions = remaining;
+
+        let dropper = &mir.methods[&call.method];
+        let start_id = inline_method(
+            method,
+            dropper,
+            call.receiver,
+            call.register,
+            after_id,
+        );
+
+        method.body.block_mut(block_id).goto(start_id, call.location);
+        method.body.add_edge(block_id, start_id);
+        block_idx += 1;
+    }
+}
+
+/// Copies the body of `source` into `method`, returning the ID of the first
+/// inlined block.
+///
+/// The receiver of `source` is replaced with `receiver`, and returning is
+/// replaced with storing the return value in `result` and jumping to the block
+/// `after`.
+fn inline_method(
+    method: &mut Method,
+    source: &Method,
+    receiver: RegisterId,
+    result: RegisterId,
+    after: BlockId,
+) -> BlockId {
+    let self_reg = source.arguments[0];
+    let regs: Vec<RegisterId> = (0..source.registers.len())
+        .map(|index| {
+            let reg = RegisterId(index as u32);
+
+            if reg == self_reg {
+                receiver
+            } else {
+                method.registers.alloc(source.registers.value_type(reg))
+            }
+        })
+        .collect();
+
+    let offset = method.body.blocks.len();
+    let map_reg = |reg: RegisterId| regs[reg.0 as usize];
+    let map_block = |block: BlockId| BlockId(block.0 + offset);
+
+    for (index, block) in source.body.blocks.iter().enumerate() {
+        let mut new_block = Block::new();
+
+        new_block.predecessors =
+            block.predecessors.iter().map(|&b| map_block(b)).collect();
+        new_block.successors =
+            block.successors.iter().map(|&b| map_block(b)).collect();
+
+        for ins in &block.instructions {
+            if let Instruction::Return(ins) = ins {
+                new_block.instructions.push(Instruction::MoveRegister(
+                    Box::new(MoveRegister {
+                        source: map_reg(ins.register),
+                        target: result,
+                        location: ins.location,
+                    }),
+                ));
+                new_block.instructions.push(Instruction::Goto(Box::new(
+                    Goto { block: after, location: ins.location },
+                )));
+                new_block.successors.push(after);
+                method.body.blocks[after.0]
+                    .predecessors
+                    .push(map_block(BlockId(index)));
+
+                continue;
+            }
+
+            let mut ins = ins.clone();
+
+            remap_instruction(&mut ins, &map_reg, &map_block);
+            new_block.instructions.push(ins);
+        }
+
+        method.body.blocks.push(new_block);
+    }
+
+    map_block(source.body.start_id)
+}
+
+/// Returns the register an instruction assigns a value to, if any.
+pub(crate) fn defined_register(
+    instruction: &Instruction,
+) -> Option<RegisterId> {
+    match instruction {
+        Instruction::False(ins) => Some(ins.register),
+        Instruction::True(ins) => Some(ins.register),
+        Instruction::Nil(ins) => Some(ins.register),
+        Instruction::Int(ins) => Some(ins.register),
+        Instruction::Float(ins) => Some(ins.register),
+        Instruction::String(ins) => Some(ins.register),
+        Instruction::MoveRegister(ins) => Some(ins.target),
+        Instruction::CallStatic(ins) => Some(ins.register),
+        Instruction::CallInstance(ins) => Some(ins.register),
+        Instruction::CallExtern(ins) => Some(ins.register),
+        Instruction::CallDynamic(ins) => Some(ins.register),
+        Instruction::CallClosure(ins) => Some(ins.register),
+        Instruction::CallDropper(ins) => Some(ins.register),
+        Instruction::CallBuiltin(ins) => Some(ins.register),
+        Instruction::GetField(ins) => Some(ins.register),
+        Instruction::Reference(ins) => Some(ins.register),
+        Instruction::Allocate(ins) => Some(ins.register),
+        Instruction::Spawn(ins) => Some(ins.register),
+        Instruction::GetConstant(ins) => Some(ins.register),
+        Instruction::Cast(ins) => Some(ins.register),
+        Instruction::Pointer(ins) => Some(ins.register),
+        Instruction::ReadPointer(ins) => Some(ins.register),
+        Instruction::FieldPointer(ins) => Some(ins.register),
+        Instruction::MethodPointer(ins) => Some(ins.register),
+        _ => None,
+    }
+}
+
+pub(crate) fn remap_instruction(
+    instruction: &mut Instruction,
+    reg: &impl Fn(RegisterId) -> RegisterId,
+    block: &impl Fn(BlockId) -> BlockId,
+) {
+    let regs = |values: &mut Vec<RegisterId>| {
+        for value in values.iter_mut() {
+            *value = reg(*value);
+        }
+    };
+
+    match instruction {
+        Instruction::Branch(ins) => {
+            ins.condition = reg(ins.condition);
+            ins.if_true = block(ins.if_true);
+            ins.if_false = block(ins.if_false);
+        }
+        Instruction::Switch(ins) => {
+            ins.register = reg(ins.register);
+
+            for id in ins.blocks.iter_mut() {
+                *id = block(*id);
+            }
+        }
+        Instruction::Goto(ins) => ins.block = block(ins.block),
+        Instruction::False(ins) => ins.register = reg(ins.register),
+        Instruction::True(ins) => ins.register = reg(ins.register),
+        Instruction::Nil(ins) => ins.register = reg(ins.register),
+        Instruction::Int(ins) => ins.register = reg(ins.register),
+        Instruction::Float(ins) => ins.register = reg(ins.register),
+        Instruction::String(ins) => ins.register = reg(ins.register),
+        Instruction::Return(ins) => ins.register = reg(ins.register),
+        Instruction::MoveRegister(ins) => {
+            ins.source = reg(ins.source);
+            ins.target = reg(ins.target);
+        }
+        Instruction::CallStatic(ins) => {
+            ins.register = reg(ins.register);
+            regs(&mut ins.arguments);
+        }
+        Instruction::CallInstance(ins) => {
+            ins.register = reg(ins.register);
+            ins.receiver = reg(ins.receiver);
+            regs(&mut ins.arguments);
+        }
+        Instruction::CallExtern(ins) => {
+            ins.register = reg(ins.register);
+            regs(&mut ins.arguments);
+        }
+        Instruction::CallDynamic(ins) => {
+            ins.register = reg(ins.register);
+            ins.receiver = reg(ins.receiver);
+            regs(&mut ins.arguments);
+        }
+        Instruction::CallClosure(ins) => {
+            ins.register = reg(ins.register);
+            ins.receiver = reg(ins.receiver);
+            regs(&mut ins.arguments);
+        }
+        Instruction::CallDropper(ins) => {
+            ins.register = reg(ins.register);
+            ins.receiver = reg(ins.receiver);
+        }
+        Instruction::CallBuiltin(ins) => {
+            ins.register = reg(ins.register);
+            regs(&mut ins.arguments);
+        }
+        Instruction::Send(ins) => {
+            ins.receiver = reg(ins.receiver);
+            regs(&mut ins.arguments);
+        }
+        Instruction::GetField(ins) => {
+            ins.register = reg(ins.register);
+            ins.receiver = reg(ins.receiver);
+        }
+        Instruction::SetField(ins) => {
+            ins.receiver = reg(ins.receiver);
+            ins.value = reg(ins.value);
+        }
+        Instruction::CheckRefs(ins) => ins.register = reg(ins.register),
+        Instruction::Drop(ins) => ins.register = reg(ins.register),
+        Instruction::Free(ins) => ins.register = reg(ins.register),
+        Instruction::Reference(ins) => {
+            ins.register = reg(ins.register);
+            ins.value = reg(ins.value);
+        }
+        Instruction::Increment(ins) => ins.register = reg(ins.register),
+        Instruction::Decrement(ins) => ins.register = reg(ins.register),
+        Instruction::IncrementAtomic(ins) => ins.register = reg(ins.register),
+        Instruction::DecrementAtomic(ins) => {
+            ins.register = reg(ins.register);
+            ins.if_true = block(ins.if_true);
+            ins.if_false = block(ins.if_false);
+        }
+        Instruction::Allocate(ins) => ins.register = reg(ins.register),
+        Instruction::Spawn(ins) => ins.register = reg(ins.register),
+        Instruction::GetConstant(ins) => ins.register = reg(ins.register),
+        Instruction::Reduce(_) | Instruction::Finish(_) => {}
+        Instruction::Cast(ins) => {
+            ins.register = reg(ins.register);
+            ins.source = reg(ins.source);
+        }
+        Instruction::Pointer(ins) => {
+            ins.register = reg(ins.register);
+            ins.value = reg(ins.value);
+        }
+        Instruction::ReadPointer(ins) => {
+            ins.register = reg(ins.register);
+            ins.pointer = reg(ins.pointer);
+        }
+        Instruction::WritePointer(ins) => {
+            ins.pointer = reg(ins.pointer);
+            ins.value = reg(ins.value);
+        }
+        Instruction::FieldPointer(ins) => {
+            ins.register = reg(ins.register);
+            ins.receiver = reg(ins.receiver);
+        }
+        Instruction::MethodPointer(ins) => ins.register = reg(ins.register),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mir::LocationId;
+    use types::module_name::ModuleName;
+    use types::{
+        Class, ClassInstance, ClassKind, FieldId, MethodKind, Module, TypeId,
+        TypeRef, Visibility,
+    };
+
+    fn method() -> Method {
+        Method::new(MethodId(0), LocationId(0))
+    }
+
+    struct Setup {
+        db: Database,
+        module: types::ModuleId,
+        class: ClassId,
+        dropper: MethodId,
+    }
+
+    impl Setup {
+        /// Returns a database containing a class with a dropper.
+        fn new() -> Setup {
+            let mut db = Database::new();
+            let module = Module::alloc(
+                &mut db,
+                ModuleName::new("test"),
+                "test.inko".into(),
+            );
+            let class = Class::alloc(
+                &mut db,
+                "Foo".to_string(),
+                ClassKind::Regular,
+                Visibility::Public,
+                module,
+            );
+            let dropper = types::Method::alloc(
+                &mut db,
+                module,
+                DROPPER_METHOD.to_string(),
+                Visibility::Public,
+                MethodKind::Mutable,
+            );
+
+            class.add_method(&mut db, DROPPER_METHOD.to_string(), dropper);
+            Setup { db, module, class, dropper }
+        }
+
+        fn method(&mut self, name: &str) -> MethodId {
+            types::Method::alloc(
+                &mut self.db,
+                self.module,
+                name.to_string(),
+                Visibility::Public,
+                MethodKind::Static,
+            )
+        }
+
+        fn owned(&self) -> TypeRef {
+            TypeRef::Owned(TypeId::ClassInstance(ClassInstance::new(
+                self.class,
+            )))
+        }
+
+        fn borrowed(&self) -> TypeRef {
+            TypeRef::Ref(TypeId::ClassInstance(ClassInstance::new(self.class)))
+        }
+
+        /// Returns the MIR of the dropper, which releases the receiver.
+        fn dropper_mir(&self) -> Method {
+            let loc = LocationId(0);
+            let mut method = Method::new(self.dropper, loc);
+            let b0 = method.body.add_start_block();
+            let r0 = method.registers.alloc(self.owned());
+            let r1 = method.registers.alloc(TypeRef::nil());
+
+            method.arguments = vec![r0];
+            method.body.block_mut(b0).free(r0, loc);
+            method.body.block_mut(b0).nil_literal(r1, loc);
+            method.body.block_mut(b0).return_value(r1, loc);
+            method
+        }
+    }
+
+    #[test]
+    fn test_in_loop() {
+        let mut method = method();
+        let b0 = method.body.add_start_block();
+        let b1 = method.body.add_block();
+        let b2 = method.body.add_block();
+        let b3 = method.body.add_block();
+
+        method.body.add_edge(b0, b1);
+        method.body.add_edge(b1, b2);
+        method.body.add_edge(b2, b1);
+        method.body.add_edge(b2, b3);
+
+        assert!(!in_loop(&method, b0));
+        assert!(in_loop(&method, b1));
+        assert!(in_loop(&method, b2));
+        assert!(!in_loop(&method, b3));
+    }
+
+    #[test]
+    fn test_aliases() {
+        let db = Database::new();
+        let summaries = Summaries::new();
+        let mut method = method();
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+        let r3 = method.registers.alloc(TypeRef::int());
+
+        method.body.block_mut(b0).move_register(r1, r0, loc);
+        method.body.block_mut(b0).reference(r2, r1, loc);
+        method.body.block_mut(b0).move_register(r2, r3, loc);
+
+        let analysis = Escape {
+            db: &db,
+            method: &method,
+            summaries: &summaries,
+            owned: true,
+        };
+        let aliases = analysis.aliases(r0);
+
+        assert_eq!(aliases, vec![r0, r1, r2].into_iter().collect());
+        assert!(!analysis.only_assigned(r0, &aliases));
+    }
+
+    #[test]
+    fn test_escapes() {
+        let db = Database::new();
+        let summaries = Summaries::new();
+        let mut method = method();
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+
+        method.body.block_mut(b0).move_register(r1, r0, loc);
+        method.body.block_mut(b0).increment(r1, loc);
+        method.body.block_mut(b0).free(r1, loc);
+        method.body.block_mut(b0).return_value(r2, loc);
+
+        let owned = Escape {
+            db: &db,
+            method: &method,
+            summaries: &summaries,
+            owned: true,
+        };
+        let borrowed = Escape { owned: false, ..owned };
+
+        assert!(!owned.escapes(&owned.aliases(r0)));
+        assert!(borrowed.escapes(&borrowed.aliases(r0)));
+        assert!(owned.escapes(&owned.aliases(r2)));
+    }
+
+    #[test]
+    fn test_summaries() {
+        let mut setup = Setup::new();
+        let loc = LocationId(0);
+        let mut mir = Mir::new();
+        let ret = setup.method("ret");
+        let pass = setup.method("pass");
+        let keep = setup.method("keep");
+
+        // fn ret(a) { a }
+        let mut method = Method::new(ret, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(setup.borrowed());
+
+        method.arguments = vec![r0];
+        method.body.block_mut(b0).return_value(r0, loc);
+        mir.methods.insert(ret, method);
+
+        // fn pass(a) { ret(a) }
+        let mut method = Method::new(pass, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(setup.borrowed());
+        let r1 = method.registers.alloc(setup.borrowed());
+
+        method.arguments = vec![r0];
+        method.body.block_mut(b0).call_static(r1, ret, vec![r0], None, loc);
+        method.body.block_mut(b0).return_value(r1, loc);
+        mir.methods.insert(pass, method);
+
+        // fn keep(a, b) { keep(a, b) }
+        let mut method = Method::new(keep, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(setup.borrowed());
+        let r1 = method.registers.alloc(setup.borrowed());
+        let r2 = method.registers.alloc(TypeRef::nil());
+
+        method.arguments = vec![r0, r1];
+        method.body.block_mut(b0).call_static(
+            r2,
+            keep,
+            vec![r0, r1],
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).return_value(r2, loc);
+        mir.methods.insert(keep, method);
+
+        let summaries = summaries(&setup.db, &mir);
+
+        assert_eq!(summaries[&ret], vec![true]);
+        assert_eq!(summaries[&pass], vec![true]);
+        assert_eq!(summaries[&keep], vec![false, false]);
+    }
+
+    #[test]
+    fn test_stack_registers() {
+        let mut setup = Setup::new();
+        let loc = LocationId(0);
+        let store = setup.method("store");
+        let mut summaries = Summaries::new();
+        let mut method = method();
+        let b0 = method.body.add_start_block();
+        let b1 = method.body.add_block();
+        let b2 = method.body.add_block();
+        let dropped = method.registers.alloc(setup.owned());
+        let alias = method.registers.alloc(setup.owned());
+        let returned = method.registers.alloc(setup.owned());
+        let stored = method.registers.alloc(setup.owned());
+        let passed = method.registers.alloc(setup.owned());
+        let looped = method.registers.alloc(setup.owned());
+        let nil = method.registers.alloc(TypeRef::nil());
+
+        summaries.insert(setup.dropper, vec![false]);
+        summaries.insert(store, vec![true]);
+
+        method.body.block_mut(b0).allocate(dropped, setup.class, loc);
+        method.body.block_mut(b0).allocate(stored, setup.class, loc);
+        method.body.block_mut(b0).set_field(
+            dropped,
+            setup.class,
+            FieldId(0),
+            stored,
+            loc,
+        );
+        method.body.block_mut(b0).move_register(alias, dropped, loc);
+        method.body.block_mut(b0).call_instance(
+            nil,
+            alias,
+            setup.dropper,
+            Vec::new(),
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).allocate(passed, setup.class, loc);
+        method.body.block_mut(b0).call_static(
+            nil,
+            store,
+            vec![passed],
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).goto(b1, loc);
+        method.body.block_mut(b1).allocate(looped, setup.class, loc);
+        method.body.block_mut(b1).call_instance(
+            nil,
+            looped,
+            setup.dropper,
+            Vec::new(),
+            None,
+            loc,
+        );
+        method.body.block_mut(b1).branch(nil, b1, b2, loc);
+        method.body.block_mut(b2).allocate(returned, setup.class, loc);
+        method.body.block_mut(b2).return_value(returned, loc);
+        method.body.add_edge(b0, b1);
+        method.body.add_edge(b1, b1);
+        method.body.add_edge(b1, b2);
+
+        let (roots, registers) =
+            stack_registers(&setup.db, &method, &summaries);
+
+        assert_eq!(roots, [dropped].into_iter().collect());
+        assert_eq!(registers, [dropped, alias].into_iter().collect());
+    }
+
+    #[test]
+    fn test_inline_droppers() {
+        let setup = Setup::new();
+        let loc = LocationId(0);
+        let mut mir = Mir::new();
+        let mut method = method();
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(setup.owned());
+        let r1 = method.registers.alloc(TypeRef::nil());
+
+        mir.methods.insert(setup.dropper, setup.dropper_mir());
+        method.body.block_mut(b0).allocate(r0, setup.class, loc);
+        method.body.block_mut(b0).call_instance(
+            r1,
+            r0,
+            setup.dropper,
+            Vec::new(),
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).return_value(r1, loc);
+
+        inline_droppers(
+            &setup.db,
+            &mir,
+            &mut method,
+            &[r0].into_iter().collect(),
+        );
+
+        // The call is replaced with a jump to the inlined body, which jumps to
+        // the instructions that followed the call.
+        let after = BlockId(1);
+        let start = BlockId(2);
+
+        assert_eq!(method.body.blocks.len(), 3);
+        assert!(matches!(
+            method.body.blocks[b0.0].instructions.last(),
+            Some(Instruction::Goto(ins)) if ins.block == start
+        ));
+        assert_eq!(method.body.successors(b0), vec![start]);
+        assert!(matches!(
+            method.body.blocks[start.0].instructions[0],
+            Instruction::Free(ref ins) if ins.register == r0
+        ));
+        assert!(matches!(
+            method.body.blocks[start.0].instructions[2],
+            Instruction::MoveRegister(ref ins) if ins.target == r1
+        ));
+        assert_eq!(method.body.successors(start), vec![after]);
+        assert!(matches!(
+            method.body.blocks[after.0].instructions[0],
+            Instruction::Return(ref ins) if ins.register == r1
+        ));
+    }
+
+    #[test]
+    fn test_stack_allocate() {
+        let mut setup = Setup::new();
+        let loc = LocationId(0);
+        let mut mir = Mir::new();
+        let id = setup.method("main");
+        let other = setup.method("other");
+        let escaping = setup.method("escaping");
+
+        mir.methods.insert(setup.dropper, setup.dropper_mir());
+
+        // An object that doesn't escape, followed by a tail call.
+        let mut method = Method::new(id, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(setup.owned());
+        let r1 = method.registers.alloc(TypeRef::nil());
+        let r2 = method.registers.alloc(TypeRef::nil());
+
+        method.body.block_mut(b0).allocate(r0, setup.class, loc);
+        method.body.block_mut(b0).call_instance(
+            r1,
+            r0,
+            setup.dropper,
+            Vec::new(),
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).call_static(r2, other, Vec::new(), None, loc);
+        method.body.block_mut(b0).return_value(r2, loc);
+
+        if let Instruction::CallStatic(ins) =
+            &mut method.body.blocks[b0.0].instructions[2]
+        {
+            ins.tail = true;
+        }
+
+        mir.methods.insert(id, method);
+
+        // An object that escapes, followed by a tail call.
+        let mut method = Method::new(escaping, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(setup.owned());
+        let r1 = method.registers.alloc(TypeRef::nil());
+
+        method.body.block_mut(b0).allocate(r0, setup.class, loc);
+        method.body.block_mut(b0).call_static(r1, other, vec![r0], None, loc);
+        method.body.block_mut(b0).return_value(r1, loc);
+
+        if let Instruction::CallStatic(ins) =
+            &mut method.body.blocks[b0.0].instructions[1]
+        {
+            ins.tail = true;
+        }
+
+        mir.methods.insert(escaping, method);
+        stack_allocate(&setup.db, &mut mir);
+
+        let instructions: Vec<_> = mir.methods[&id]
+            .body
+            .blocks
+            .iter()
+            .flat_map(|b| b.instructions.iter())
+            .collect();
+
+        assert!(instructions.iter().any(|ins| matches!(
+            ins,
+            Instruction::Allocate(ins) if ins.stack
+        )));
+        assert!(!instructions
+            .iter()
+            .any(|ins| matches!(ins, Instruction::Free(_))));
+        assert!(!instructions.iter().any(|ins| matches!(
+            ins,
+            Instruction::CallInstance(ins) if ins.method == setup.dropper
+        )));
+        assert!(instructions.iter().any(|ins| matches!(
+            ins,
+            Instruction::CallStatic(ins) if ins.method == other && !ins.tail
+        )));
+
+        let escaping = &mir.methods[&escaping].body.blocks[0].instructions;
+
+        assert!(matches!(
+            &escaping[0],
+            Instruction::Allocate(ins) if !ins.stack
+        ));
+        assert!(matches!(
+            &escaping[1],
+            Instruction::CallStatic(ins) if ins.tail
+        ));
+
+        // The dropper itself still releases the objects it drops.
+        assert!(matches!(
+            mir.methods[&setup.dropper].body.blocks[0].instructions[0],
+            Instruction::Free(_)
+        ));
+    }
+}
@@ -2,10 +2,12 @@
 //!
 //! MIR is used for various optimisations, analysing moves of values, compiling
 //! pattern matching into decision trees, and more.
+pub(crate) mod escape;
 pub(crate) mod passes;
 pub(crate) mod pattern_matching;
 pub(crate) mod printer;
 pub(crate) mod specialize;
+pub(crate) mod tail_calls;
 
 use crate::symbol_names::{class_name, method_name};
 use ast::source_location::SourceLocation;
@@ -402,6 +404,7 @@ impl Block {
             method,
             arguments,
             type_arguments,
+            tail: false,
             location,
         })));
     }
@@ -422,6 +425,7 @@ impl Block {
                 method,
                 arguments,
                 type_arguments,
+                tail: false,
                 location,
             },
         )));
@@ -562,6 +566,17 @@ impl Block {
         })))
     }
 
+    pub(crate) fn method_pointer(
+        &mut self,
+        register: RegisterId,
+        method: types::MethodId,
+        location: LocationId,
+    ) {
+        self.instructions.push(Instruction::MethodPointer(Box::new(
+            MethodPointer { register, method, location },
+        )));
+    }
+
     pub(crate) fn field_pointer(
         &mut self,
         register: RegisterId,
@@ -607,6 +622,7 @@ impl Block {
             register,
             class,
             location,
+            stack: false,
         })));
     }
 
@@ -667,6 +683,7 @@ pub(crate) enum Constant {
     Float(f64),
     String(Rc<String>),
     Array(Rc<Vec<Constant>>),
+    Tuple(Rc<Vec<Constant>>),
     Bool(bool),
 }
 
@@ -686,6 +703,7 @@ impl PartialEq for Constant {
             }
             (Constant::String(a), Constant::String(b)) => a == b,
             (Constant::Array(a), Constant::Array(b)) => a == b,
+            (Constant::Tuple(a), Constant::Tuple(b)) => a == b,
             _ => false,
         }
     }
@@ -700,6 +718,7 @@ impl Hash for Constant {
             Constant::Float(v) => v.to_bits().hash(state),
             Constant::String(v) => v.hash(state),
             Constant::Array(v) => v.hash(state),
+            Constant::Tuple(v) => v.hash(state),
             Constant::Bool(v) => v.hash(state),
         }
     }
@@ -712,6 +731,11 @@ impl fmt::Display for Constant {
             Self::Float(v) => write!(f, "{}", v),
             Self::String(v) => write!(f, "{:?}", v),
             Self::Array(v) => write!(f, "{:?}", v),
+            Self::Tuple(v) => {
+                let vals: Vec<_> = v.iter().map(|v| v.to_string()).collect();
+
+                write!(f, "({})", vals.join(", "))
+            }
             Self::Bool(v) => write!(f, "{}", v),
         }
     }
@@ -873,6 +897,10 @@ pub(crate) struct CallStatic {
     pub(crate) arguments: Vec<RegisterId>,
     pub(crate) type_arguments: Option<usize>,
     pub(crate) location: LocationId,
+
+    /// A flag indicating the call is in a tail position, allowing the callee
+    /// to reuse the stack frame of the caller.
+    pub(crate) tail: bool,
 }
 
 #[derive(Clone)]
@@ -883,6 +911,10 @@ pub(crate) struct CallInstance {
     pub(crate) arguments: Vec<RegisterId>,
     pub(crate) type_arguments: Option<usize>,
     pub(crate) location: LocationId,
+
+    /// A flag indicating the call is in a tail position, allowing the callee
+    /// to reuse the stack frame of the caller.
+    pub(crate) tail: bool,
 }
 
 #[derive(Clone)]
@@ -958,6 +990,10 @@ pub(crate) struct Allocate {
     pub(crate) register: RegisterId,
     pub(crate) class: types::ClassId,
     pub(crate) location: LocationId,
+
+    /// A flag indicating the object is to be allocated on the stack instead of
+    /// the heap.
+    pub(crate) stack: bool,
 }
 
 #[derive(Clone)]
@@ -1026,7 +1062,13 @@ impl CastType {
                 Ok(TypeId::ClassInstance(ins)) => match ins.instance_of().0 {
                     INT_ID | NIL_ID | BOOL_ID => CastType::Int(64, true),
                     FLOAT_ID => CastType::Float(64),
-                    _ => CastType::Object,
+                    _ => match ins.instance_of().sized_type() {
+                        Some(ForeignType::Int(size, signed)) => {
+                            CastType::Int(size, signed)
+                        }
+                        Some(ForeignType::Float(size)) => CastType::Float(size),
+                        None => CastType::Object,
+                    },
                 },
                 _ => CastType::Object,
             }
@@ -1050,6 +1092,15 @@ pub(crate) struct FieldPointer {
     pub(crate) location: LocationId,
 }
 
+/// An instruction that produces a pointer to a method that can be called from
+/// C.
+#[derive(Clone, Debug, Copy)]
+pub(crate) struct MethodPointer {
+    pub(crate) register: RegisterId,
+    pub(crate) method: types::MethodId,
+    pub(crate) location: LocationId,
+}
+
 #[derive(Clone, Debug, Copy)]
 pub(crate) struct ReadPointer {
     pub(crate) register: RegisterId,
@@ -1109,6 +1160,7 @@ pub(crate) enum Instruction {
     ReadPointer(Box<ReadPointer>),
     WritePointer(Box<WritePointer>),
     FieldPointer(Box<FieldPointer>),
+    MethodPointer(Box<MethodPointer>),
 }
 
 impl Instruction {
@@ -1153,6 +1205,7 @@ impl Instruction {
             Instruction::ReadPointer(ref v) => v.location,
             Instruction::WritePointer(ref v) => v.location,
             Instruction::FieldPointer(ref v) => v.location,
+            Instruction::MethodPointer(ref v) => v.location,
         }
     }
 
@@ -1214,8 +1267,9 @@ impl Instruction {
             }
             Instruction::Allocate(ref v) => {
                 format!(
-                    "r{} = allocate {}",
+                    "r{} = {} {}",
                     v.register.0,
+                    if v.stack { "allocate_stack" } else { "allocate" },
                     class_name(db, v.class),
                 )
             }
@@ -1224,16 +1278,18 @@ impl Instruction {
             }
             Instruction::CallStatic(ref v) => {
                 format!(
-                    "r{} = call_static {}({})",
+                    "r{} = {} {}({})",
                     v.register.0,
+                    if v.tail { "tail_call_static" } else { "call_static" },
                     method_name(db, v.method),
                     join(&v.arguments),
                 )
             }
             Instruction::CallInstance(ref v) => {
                 format!(
-                    "r{} = call_instance r{}.{}({})",
+                    "r{} = {} r{}.{}({})",
                     v.register.0,
+                    if v.tail { "tail_call_instance" } else { "call_instance" },
                     v.receiver.0,
                     method_name(db, v.method),
                     join(&v.arguments),
@@ -1349,6 +1405,13 @@ impl Instruction {
                     v.field.name(db)
                 )
             }
+            Instruction::MethodPointer(ref v) => {
+                format!(
+                    "r{} = method_pointer {}",
+                    v.register.0,
+                    v.method.name(db)
+                )
+            }
         }
     }
 }
@@ -2,6 +2,7 @@
 use crate::diagnostics::DiagnosticId;
 use crate::hir;
 use crate::mir::pattern_matching as pmatch;
+use crate::mir::tail_calls::{can_merge, tail_call};
 use crate::mir::{
     Block, BlockId, CastType, Class, Constant, Goto, Instruction, LocationId,
     Method, Mir, Module, RegisterId, SELF_ID,
@@ -16,8 +17,9 @@ use std::rc::Rc;
 use types::format::format_type;
 use types::{
     self, Block as _, ClassId, ConstantId, MethodId, ModuleId, TypeBounds,
-    TypeRef, EQ_METHOD, FIELDS_LIMIT, OPTION_NONE, OPTION_SOME, RESULT_CLASS,
-    RESULT_ERROR, RESULT_MODULE, RESULT_OK,
+    TypeRef, EQ_METHOD, FIELDS_LIMIT, FUTURE_CLASS, FUTURE_HANDLE,
+    FUTURE_MODULE, FUTURE_NEW, FUTURE_SET, OPTION_NONE, OPTION_SOME,
+    RESULT_CLASS, RESULT_ERROR, RESULT_MODULE, RESULT_OK,
 };
 
 const SELF_NAME: &str = "self";
@@ -32,6 +34,71 @@ fn modulo(lhs: i64, rhs: i64) -> Option<i64> {
         .and_then(|res| res.checked_rem(rhs))
 }
 
+/// Evaluates a call to a builtin function at compile-time.
+///
+/// The results must be the same as those produced by the generated code for
+/// these functions.
+fn const_builtin(
+    id: types::BuiltinFunction,
+    arguments: &[Constant],
+) -> Option<Constant> {
+    use types::BuiltinFunction as Func;
+
+    let res = match (id, arguments) {
+        (Func::FloatCeil, [Constant::Float(v)]) => Constant::Float(v.ceil()),
+        (Func::FloatFloor, [Constant::Float(v)]) => Constant::Float(v.floor()),
+        (Func::FloatRound, [Constant::Float(v)]) => Constant::Float(v.round()),
+        (Func::FloatFromBits, [Constant::Int(v)]) => {
+            Constant::Float(f64::from_bits(*v as u64))
+        }
+        (Func::FloatToBits, [Constant::Float(v)]) => {
+            Constant::Int(v.to_bits() as i64)
+        }
+        (Func::FloatIsInf, [Constant::Float(v)]) => {
+            Constant::Bool(v.is_infinite())
+        }
+        (Func::FloatIsNan, [Constant::Float(v)]) => Constant::Bool(v.is_nan()),
+        (Func::IntBitNot, [Constant::Int(v)]) => Constant::Int(!v),
+        (Func::IntRotateLeft, [Constant::Int(a), Constant::Int(b)]) => {
+            Constant::Int(a.rotate_left(*b as u32))
+        }
+        (Func::IntRotateRight, [Constant::Int(a), Constant::Int(b)]) => {
+            Constant::Int(a.rotate_right(*b as u32))
+        }
+        (Func::IntWrappingAdd, [Constant::Int(a), Constant::Int(b)]) => {
+            Constant::Int(a.wrapping_add(*b))
+        }
+        (Func::IntWrappingMul, [Constant::Int(a), Constant::Int(b)]) => {
+            Constant::Int(a.wrapping_mul(*b))
+        }
+        (Func::IntWrappingSub, [Constant::Int(a), Constant::Int(b)]) => {
+            Constant::Int(a.wrapping_sub(*b))
+        }
+        _ => return None,
+    };
+
+    Some(res)
+}
+
+/// Converts a constant to a `String`, in the same way as the `to_string`
+/// methods of the standard library.
+fn const_to_string(value: &Constant) -> Option<String> {
+    let res = match value {
+        Constant::String(v) => v.as_ref().clone(),
+        Constant::Int(v) => v.to_string(),
+        Constant::Float(v) if v.is_infinite() && v.is_sign_positive() => {
+            "Infinity".to_string()
+        }
+        Constant::Float(v) if v.is_infinite() => "-Infinity".to_string(),
+        Constant::Float(v) if v.is_nan() => "NaN".to_string(),
+        Constant::Float(v) => format!("{:?}", v),
+        Constant::Bool(v) => v.to_string(),
+        _ => return None,
+    };
+
+    Some(res)
+}
+
 /// A compiler pass that verifies various global limits, such as the number of
 /// defined classes.
 pub(crate) fn check_global_limits(state: &mut State) -> Result<(), String> {
@@ -709,10 +776,113 @@ impl<'a> DefineConstants<'a> {
             hir::ConstExpression::Array(ref n) => Constant::Array(Rc::new(
                 n.values.iter().map(|n| self.expression(n)).collect(),
             )),
+            hir::ConstExpression::Tuple(ref n) => Constant::Tuple(Rc::new(
+                n.values.iter().map(|n| self.expression(n)).collect(),
+            )),
+            hir::ConstExpression::Cast(ref n) => self.cast(n),
+            hir::ConstExpression::Call(ref n) => self.call(n),
+            hir::ConstExpression::BuiltinCall(ref n) => self.builtin_call(n),
+            hir::ConstExpression::Interpolation(ref n) => self.interpolation(n),
             hir::ConstExpression::Invalid(_) => unreachable!(),
         }
     }
 
+    fn cast(&mut self, node: &hir::ConstCast) -> Constant {
+        let to_float = node.resolved_type.is_float(self.db());
+
+        // Float to Int casts saturate, the same as the generated code for such
+        // casts.
+        match self.expression(&node.value) {
+            Constant::Int(v) if to_float => Constant::Float(v as f64),
+            Constant::Float(v) if !to_float => Constant::Int(v as i64),
+            val => val,
+        }
+    }
+
+    fn call(&mut self, node: &hir::ConstCall) -> Constant {
+        let rec = self.expression(&node.receiver);
+        let args: Vec<_> =
+            node.arguments.iter().map(|n| self.expression(n)).collect();
+        let res = match (node.kind, &rec) {
+            (hir::ConstCallKind::Opposite, Constant::Int(v)) => {
+                v.checked_neg().map(Constant::Int)
+            }
+            (hir::ConstCallKind::Opposite, Constant::Float(v)) => {
+                Some(Constant::Float(-v))
+            }
+            (hir::ConstCallKind::Absolute, Constant::Int(v)) => {
+                v.checked_abs().map(Constant::Int)
+            }
+            (hir::ConstCallKind::Absolute, Constant::Float(v)) => {
+                Some(Constant::Float(v.abs()))
+            }
+            (hir::ConstCallKind::Builtin(id), _) => {
+                let mut all = vec![rec.clone()];
+
+                all.extend(args.iter().cloned());
+                const_builtin(id, &all)
+            }
+            _ => None,
+        };
+
+        res.unwrap_or_else(|| {
+            let args = if args.is_empty() {
+                String::new()
+            } else {
+                let vals: Vec<_> = args.iter().map(|v| v.to_string()).collect();
+
+                format!("({})", vals.join(", "))
+            };
+
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidConstExpr,
+                format!(
+                    "the constant expression '{}.{}{}' is invalid",
+                    rec, node.name.name, args
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            rec
+        })
+    }
+
+    fn builtin_call(&mut self, node: &hir::ConstBuiltinCall) -> Constant {
+        let info = node.info.as_ref().unwrap();
+        let args: Vec<_> =
+            node.arguments.iter().map(|n| self.expression(n)).collect();
+
+        const_builtin(info.id, &args).unwrap_or_else(|| {
+            let args: Vec<_> = args.iter().map(|v| v.to_string()).collect();
+
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidConstExpr,
+                format!(
+                    "the constant expression '{}({})' is invalid",
+                    node.name.name,
+                    args.join(", ")
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            Constant::Int(0)
+        })
+    }
+
+    fn interpolation(&mut self, node: &hir::ConstInterpolation) -> Constant {
+        let mut res = String::new();
+
+        for val in &node.values {
+            // The type checker only allows values that can be converted, so
+            // this never fails.
+            res += &const_to_string(&self.expression(val)).unwrap();
+        }
+
+        Constant::String(Rc::new(res))
+    }
+
     fn binary(&mut self, node: &hir::ConstBinary) -> Constant {
         let left = self.expression(&node.left);
         let right = self.expression(&node.right);
@@ -788,10 +958,10 @@ impl<'a> DefineConstants<'a> {
                     Constant::String(Rc::new(String::new()))
                 }
             }
-            Constant::Array(_) | Constant::Bool(_) => {
+            Constant::Array(_) | Constant::Tuple(_) | Constant::Bool(_) => {
                 self.state.diagnostics.error(
                     DiagnosticId::InvalidConstExpr,
-                    "constant Array and Bool values don't support \
+                    "constant Array, Tuple and Bool values don't support \
                     binary operations",
                     self.file(),
                     node.location.clone(),
@@ -1001,6 +1171,12 @@ impl<'a> LowerToMir<'a> {
         self.mir.add_methods(methods);
         self.add_class(id, class);
 
+        // Instances of inline classes are copied instead of dropped, so they
+        // don't need a dropper.
+        if id.kind(self.db()).is_inline() {
+            return;
+        }
+
         GenerateDropper {
             state: self.state,
             mir: self.mir,
@@ -1232,6 +1408,7 @@ impl<'a> LowerMethod<'a> {
     fn run(mut self, nodes: Vec<hir::Expression>, location: LocationId) {
         self.prepare(location);
         self.lower_method_body(nodes, location);
+        self.tail_calls();
     }
 
     fn run_with_captured_self(
@@ -1244,6 +1421,7 @@ impl<'a> LowerMethod<'a> {
         self.prepare(location);
         self.define_captured_self_register(self_field, self_type, location);
         self.lower_method_body(nodes, location);
+        self.tail_calls();
     }
 
     fn lower_method_body(
@@ -1299,6 +1477,93 @@ impl<'a> LowerMethod<'a> {
         }
     }
 
+    /// Flags calls in a tail position as tail calls.
+    ///
+    /// If the method is marked as tail recursive, this also checks that all
+    /// calls the method makes to itself are tail calls, and that the method
+    /// calls itself or another tail recursive method.
+    fn tail_calls(&mut self) {
+        let id = self.method.id;
+        let db = &self.state.db;
+        let ignore_ret = id.ignore_return_value(db);
+        let mut jumps = 0;
+        let mut invalid = Vec::new();
+
+        for index in 0..self.method.body.blocks.len() {
+            let block = BlockId(index);
+
+            if !self.method.body.is_connected(block) {
+                continue;
+            }
+
+            let tail = tail_call(db, self.method, block, ignore_ret);
+
+            for (pos, ins) in self.method.body.blocks[index]
+                .instructions
+                .iter_mut()
+                .enumerate()
+            {
+                let (method, flag, loc) = match ins {
+                    Instruction::CallStatic(ins) => {
+                        (ins.method, &mut ins.tail, ins.location)
+                    }
+                    Instruction::CallInstance(ins) => {
+                        (ins.method, &mut ins.tail, ins.location)
+                    }
+                    _ => continue,
+                };
+                let call = tail
+                    .as_ref()
+                    .filter(|(idx, _)| *idx == pos)
+                    .map(|(_, call)| call);
+                let jump = method == id || can_merge(db, id, method);
+
+                if let Some(call) = call {
+                    *flag = (jump && call.passed_on)
+                        || (call.returns && call.decrements.is_empty());
+                }
+
+                if jump && *flag {
+                    jumps += 1;
+                } else if method == id || (jump && call.is_some()) {
+                    invalid.push(loc);
+                }
+            }
+        }
+
+        if !id.is_tail_recursive(db) {
+            return;
+        }
+
+        let name = id.name(db).clone();
+
+        if jumps == 0 && invalid.is_empty() {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidMethod,
+                format!(
+                    "the method '{}' is marked as tail recursive, but \
+                    doesn't call itself or another tail recursive method",
+                    name
+                ),
+                self.file(),
+                self.mir.location(self.method.location).clone(),
+            );
+        }
+
+        for loc in invalid {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidMethod,
+                format!(
+                    "the method '{}' is marked as tail recursive, but this \
+                    call isn't in a tail position",
+                    name
+                ),
+                self.file(),
+                self.mir.location(loc).clone(),
+            );
+        }
+    }
+
     fn define_base_registers(&mut self, location: LocationId) {
         // The first register in a method is reserved for the receiver of the
         // method (e.g. `self`). For closures this points to the generated
@@ -1345,6 +1610,18 @@ impl<'a> LowerMethod<'a> {
         for reg in args {
             self.add_drop_flag(reg, location);
         }
+
+        // Async methods that return a value receive an extra argument: a
+        // handle to the future to resolve when the method finishes. This
+        // handle is consumed when resolving the future, so we don't track it
+        // like the other arguments.
+        if self.method.id.returns_future(self.db()) {
+            let ret = self.method.id.return_type(self.db());
+            let typ = TypeRef::future_type(self.db_mut(), ret);
+            let reg = self.new_untracked_register(typ);
+
+            self.method.arguments.push(reg);
+        }
     }
 
     fn define_captured_self_register(
@@ -1836,7 +2113,7 @@ impl<'a> LowerMethod<'a> {
         &mut self,
         info: types::CallInfo,
         receiver: Option<RegisterId>,
-        arguments: Vec<RegisterId>,
+        mut arguments: Vec<RegisterId>,
         location: LocationId,
     ) -> RegisterId {
         let result = self.new_register(info.returns);
@@ -1900,10 +2177,19 @@ impl<'a> LowerMethod<'a> {
             // otherwise we may end up scheduling the async dropper prematurely
             // (e.g. if new references are created before it runs).
             self.current_block_mut().increment_atomic(rec, location);
-            self.current_block_mut()
-                .send(rec, info.id, arguments, targs, location);
 
-            self.current_block_mut().nil_literal(result, location);
+            if info.id.returns_future(self.db()) {
+                let handle = self.new_future(result, location);
+
+                arguments.push(handle);
+                self.current_block_mut()
+                    .send(rec, info.id, arguments, targs, location);
+            } else {
+                self.current_block_mut()
+                    .send(rec, info.id, arguments, targs, location);
+
+                self.current_block_mut().nil_literal(result, location);
+            }
         } else if info.dynamic {
             self.current_block_mut()
                 .call_dynamic(result, rec, info.id, arguments, targs, location);
@@ -1917,6 +2203,65 @@ impl<'a> LowerMethod<'a> {
         result
     }
 
+    /// Creates a new future in the given register, returning a register
+    /// containing a second handle to the future.
+    ///
+    /// The second handle is passed to the async method that's called, which
+    /// uses it to resolve the future when it finishes.
+    fn new_future(
+        &mut self,
+        register: RegisterId,
+        location: LocationId,
+    ) -> RegisterId {
+        let typ = self.register_type(register);
+        let class = self.db().class_in_module(FUTURE_MODULE, FUTURE_CLASS);
+        let new = class.method(self.db(), FUTURE_NEW).unwrap();
+        let handle = class.method(self.db(), FUTURE_HANDLE).unwrap();
+        let targs = typ.type_arguments(self.db());
+        let targs = self.mir.add_type_arguments(targs);
+        let handle_reg = self.new_untracked_register(typ);
+
+        self.current_block_mut().call_static(
+            register,
+            new,
+            Vec::new(),
+            targs,
+            location,
+        );
+        self.current_block_mut().call_instance(
+            handle_reg,
+            register,
+            handle,
+            Vec::new(),
+            targs,
+            location,
+        );
+
+        handle_reg
+    }
+
+    /// Resolves the future of the current async method to the value in the
+    /// given register.
+    fn resolve_future(&mut self, register: RegisterId, location: LocationId) {
+        // The handle to the future is always passed as the last argument.
+        let future = *self.method.arguments.last().unwrap();
+        let typ = self.register_type(future);
+        let class = self.db().class_in_module(FUTURE_MODULE, FUTURE_CLASS);
+        let set = class.method(self.db(), FUTURE_SET).unwrap();
+        let targs = typ.type_arguments(self.db());
+        let targs = self.mir.add_type_arguments(targs);
+        let result = self.new_untracked_register(TypeRef::nil());
+
+        self.current_block_mut().call_instance(
+            result,
+            future,
+            set,
+            vec![register],
+            targs,
+            location,
+        );
+    }
+
     fn call_arguments(
         &mut self,
         info: &types::CallInfo,
@@ -2280,7 +2625,7 @@ impl<'a> LowerMethod<'a> {
                 self.current_block_mut().free(reg, loc);
 
                 self.drop_all_registers();
-                self.current_block_mut().return_value(ret_reg, loc);
+                self.return_register(ret_reg, loc);
                 ok_reg
             }
             types::ThrowKind::Result(ok_typ, err_typ) => {
@@ -2318,7 +2663,7 @@ impl<'a> LowerMethod<'a> {
                 self.current_block_mut().free(reg, loc);
 
                 self.drop_all_registers();
-                self.current_block_mut().return_value(ret_reg, loc);
+                self.return_register(ret_reg, loc);
                 ok_reg
             }
             _ => unreachable!(),
@@ -2351,8 +2696,7 @@ impl<'a> LowerMethod<'a> {
         self.mark_register_as_moved(reg);
         self.mark_register_as_moved(result_reg);
         self.drop_all_registers();
-
-        self.current_block_mut().return_value(result_reg, loc);
+        self.return_register(result_reg, loc);
 
         self.add_current_block();
         self.new_register(TypeRef::Never)
@@ -2362,6 +2706,10 @@ impl<'a> LowerMethod<'a> {
         if self.method.id.is_async(self.db()) {
             let terminate = self.method.id.is_main(self.db());
 
+            if self.method.id.returns_future(self.db()) {
+                self.resolve_future(register, location);
+            }
+
             // The reference count is incremented before sending a message, so
             // we must also decrement it when we finish, and (if needed)
             // schedule the async dropper.
@@ -2405,7 +2753,13 @@ impl<'a> LowerMethod<'a> {
     }
 
     fn mut_expression(&mut self, node: hir::Mut) -> RegisterId {
-        if node.resolved_type.is_pointer(self.db()) {
+        if let Some(id) = node.pointer_to_method {
+            let loc = self.add_location(node.location);
+            let reg = self.new_register(node.resolved_type);
+
+            self.current_block_mut().method_pointer(reg, id, loc);
+            reg
+        } else if node.resolved_type.is_pointer(self.db()) {
             let loc = self.add_location(node.location);
             let val = self.expression(node.value);
             let reg = self.new_register(node.resolved_type);
@@ -4488,3 +4842,222 @@ fn find_successor(blocks: &[Block], old_id: BlockId) -> BlockId {
 
     id
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::test::{cols, module_type};
+    use types::{MethodKind, Visibility};
+
+    fn method_type(
+        state: &mut State,
+        module: ModuleId,
+        name: &str,
+    ) -> MethodId {
+        let id = types::Method::alloc(
+            &mut state.db,
+            module,
+            name.to_string(),
+            Visibility::Private,
+            MethodKind::Static,
+        );
+
+        id.set_return_type(&mut state.db, TypeRef::int());
+        id
+    }
+
+    fn lower_tail_calls(
+        state: &mut State,
+        id: MethodId,
+        module: ModuleId,
+        body: impl FnOnce(&mut Method, LocationId),
+    ) -> Method {
+        let mut mir = Mir::new();
+        let loc = mir.add_location(cols(1, 1));
+        let mut method = Method::new(id, loc);
+        let mut lower = LowerMethod::new(state, &mut mir, module, &mut method);
+
+        body(lower.method, loc);
+        lower.tail_calls();
+        method
+    }
+
+    fn is_tail_call(method: &Method, index: usize) -> bool {
+        match &method.body.blocks[0].instructions[index] {
+            Instruction::CallStatic(ins) => ins.tail,
+            _ => false,
+        }
+    }
+
+    fn error_messages(state: &State) -> Vec<String> {
+        state.diagnostics.iter().map(|d| d.message().to_string()).collect()
+    }
+
+    #[test]
+    fn test_tail_recursive_method_with_tail_call() {
+        let mut state = State::new(Config::new());
+        let module = module_type(&mut state, "test");
+        let foo = method_type(&mut state, module, "foo");
+
+        foo.set_tail_recursive(&mut state.db);
+
+        let method = lower_tail_calls(&mut state, foo, module, |m, loc| {
+            let r0 = m.registers.alloc(TypeRef::int());
+            let r1 = m.registers.alloc(TypeRef::int());
+
+            m.arguments = vec![r0];
+            m.body.block_mut(BlockId(0)).call_static(
+                r1,
+                foo,
+                vec![r0],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).return_value(r1, loc);
+        });
+
+        assert!(error_messages(&state).is_empty());
+        assert!(is_tail_call(&method, 0));
+    }
+
+    #[test]
+    fn test_tail_recursive_method_without_recursion() {
+        let mut state = State::new(Config::new());
+        let module = module_type(&mut state, "test");
+        let foo = method_type(&mut state, module, "foo");
+        let bar = method_type(&mut state, module, "bar");
+
+        foo.set_tail_recursive(&mut state.db);
+
+        let method = lower_tail_calls(&mut state, foo, module, |m, loc| {
+            let r0 = m.registers.alloc(TypeRef::int());
+            let r1 = m.registers.alloc(TypeRef::int());
+
+            m.arguments = vec![r0];
+            m.body.block_mut(BlockId(0)).call_static(
+                r1,
+                bar,
+                vec![r0],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).return_value(r1, loc);
+        });
+
+        assert_eq!(
+            error_messages(&state),
+            vec!["the method 'foo' is marked as tail recursive, but doesn't \
+                call itself or another tail recursive method"
+                .to_string()]
+        );
+        assert!(is_tail_call(&method, 0));
+    }
+
+    #[test]
+    fn test_tail_recursive_method_with_call_in_non_tail_position() {
+        let mut state = State::new(Config::new());
+        let module = module_type(&mut state, "test");
+        let foo = method_type(&mut state, module, "foo");
+
+        foo.set_tail_recursive(&mut state.db);
+
+        let method = lower_tail_calls(&mut state, foo, module, |m, loc| {
+            let r0 = m.registers.alloc(TypeRef::int());
+            let r1 = m.registers.alloc(TypeRef::int());
+            let r2 = m.registers.alloc(TypeRef::int());
+
+            m.arguments = vec![r0];
+            m.body.block_mut(BlockId(0)).call_static(
+                r1,
+                foo,
+                vec![r0],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).call_static(
+                r2,
+                foo,
+                vec![r1],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).return_value(r2, loc);
+        });
+
+        assert_eq!(
+            error_messages(&state),
+            vec![
+                "the method 'foo' is marked as tail recursive, but this call \
+                isn't in a tail position"
+                    .to_string()
+            ]
+        );
+        assert!(!is_tail_call(&method, 0));
+        assert!(is_tail_call(&method, 1));
+    }
+
+    #[test]
+    fn test_tail_recursive_methods_calling_each_other() {
+        let mut state = State::new(Config::new());
+        let module = module_type(&mut state, "test");
+        let foo = method_type(&mut state, module, "foo");
+        let bar = method_type(&mut state, module, "bar");
+
+        foo.set_tail_recursive(&mut state.db);
+        bar.set_tail_recursive(&mut state.db);
+
+        let method = lower_tail_calls(&mut state, foo, module, |m, loc| {
+            let r0 = m.registers.alloc(TypeRef::int());
+            let r1 = m.registers.alloc(TypeRef::int());
+
+            m.arguments = vec![r0];
+            m.body.block_mut(BlockId(0)).call_static(
+                r1,
+                bar,
+                vec![r0],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).return_value(r1, loc);
+        });
+
+        assert!(error_messages(&state).is_empty());
+        assert!(is_tail_call(&method, 0));
+    }
+
+    #[test]
+    fn test_method_with_tail_call() {
+        let mut state = State::new(Config::new());
+        let module = module_type(&mut state, "test");
+        let foo = method_type(&mut state, module, "foo");
+        let bar = method_type(&mut state, module, "bar");
+
+        let method = lower_tail_calls(&mut state, foo, module, |m, loc| {
+            let r0 = m.registers.alloc(TypeRef::int());
+            let r1 = m.registers.alloc(TypeRef::int());
+            let r2 = m.registers.alloc(TypeRef::int());
+
+            m.arguments = vec![r0];
+            m.body.block_mut(BlockId(0)).call_static(
+                r1,
+                foo,
+                vec![r0],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).call_static(
+                r2,
+                bar,
+                vec![r1],
+                None,
+                loc,
+            );
+            m.body.block_mut(BlockId(0)).return_value(r2, loc);
+        });
+
+        assert!(error_messages(&state).is_empty());
+        assert!(!is_tail_call(&method, 0));
+        assert!(is_tail_call(&method, 1));
+    }
+}
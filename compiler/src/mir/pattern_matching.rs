@@ -859,7 +859,7 @@ impl<'a> Compiler<'a> {
 
                     Type::Finite(cons)
                 }
-                ClassKind::Regular | ClassKind::Extern => {
+                ClassKind::Regular | ClassKind::Extern | ClassKind::Inline => {
                     let fields = class_id.fields(self.db());
                     let args = fields
                         .iter()
@@ -2,7 +2,24 @@
 use crate::mir::{BlockId, Method, Mir};
 use crate::symbol_names::method_name;
 use std::fmt::Write;
-use types::{Database, TypeId};
+use types::format::format_type;
+use types::{Block, Database, TypeId};
+
+fn method_label(db: &Database, method: &Method) -> String {
+    let rec_name = match method.id.receiver_id(db) {
+        TypeId::Class(id) => id.name(db).clone(),
+        TypeId::Trait(id) => id.name(db).clone(),
+        TypeId::ClassInstance(ins) => ins.instance_of().name(db).clone(),
+        TypeId::TraitInstance(ins) => ins.instance_of().name(db).clone(),
+        _ => String::new(),
+    };
+
+    if rec_name.is_empty() {
+        method_name(db, method.id)
+    } else {
+        format!("{}.{}", rec_name, method_name(db, method.id))
+    }
+}
 
 /// Returns a String containing Dot/graphviz code for visualising the MIR of one
 /// or more methods.
@@ -18,21 +35,7 @@ pub(crate) fn to_dot(db: &Database, mir: &Mir, methods: &[&Method]) -> String {
         buffer.push_str("node[fontname=\"monospace\", fontsize=10];\n");
         buffer.push_str("edge[fontname=\"monospace\", fontsize=10];\n");
 
-        let rec_name = match method.id.receiver_id(db) {
-            TypeId::Class(id) => id.name(db).clone(),
-            TypeId::Trait(id) => id.name(db).clone(),
-            TypeId::ClassInstance(ins) => ins.instance_of().name(db).clone(),
-            TypeId::TraitInstance(ins) => ins.instance_of().name(db).clone(),
-            _ => String::new(),
-        };
-
-        let name = if rec_name.is_empty() {
-            format!("{}()", method_name(db, method.id),)
-        } else {
-            format!("{}.{}()", rec_name, method_name(db, method.id),)
-        };
-
-        let _ = writeln!(buffer, "label=\"{}\";", name);
+        let _ = writeln!(buffer, "label=\"{}()\";", method_label(db, method));
         let reachable_blocks = method.body.reachable();
 
         for (index, block) in method.body.blocks.iter().enumerate() {
@@ -91,3 +94,59 @@ pub(crate) fn to_dot(db: &Database, mir: &Mir, methods: &[&Method]) -> String {
     buffer.push_str("}\n");
     buffer
 }
+
+/// Returns a String containing a textual listing of the MIR of one or more
+/// methods.
+pub(crate) fn to_text(db: &Database, mir: &Mir, methods: &[&Method]) -> String {
+    let mut buffer = String::new();
+
+    for (method_index, method) in methods.iter().enumerate() {
+        if method_index > 0 {
+            buffer.push('\n');
+        }
+
+        let args = method
+            .arguments
+            .iter()
+            .map(|&reg| {
+                format!(
+                    "r{}: {}",
+                    reg.0,
+                    format_type(db, method.registers.value_type(reg))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let _ = writeln!(
+            buffer,
+            "fn {}({}) -> {} {{",
+            method_label(db, method),
+            args,
+            format_type(db, method.id.return_type(db))
+        );
+
+        let reachable_blocks = method.body.reachable();
+
+        for (index, block) in method.body.blocks.iter().enumerate() {
+            if reachable_blocks.contains(&BlockId(index)) {
+                let _ = writeln!(buffer, "b{}:", index);
+            } else {
+                let _ = writeln!(buffer, "b{}: # unreachable", index);
+            }
+
+            for ins in &block.instructions {
+                let _ = writeln!(
+                    buffer,
+                    "  {:<50} # line {}",
+                    ins.format(db),
+                    mir.location(ins.location()).line_range.start(),
+                );
+            }
+        }
+
+        buffer.push_str("}\n");
+    }
+
+    buffer
+}
@@ -21,6 +21,50 @@ fn argument_shape(
     arguments.get_recursive(db, parameter).unwrap().shape(db, shapes)
 }
 
+/// Returns `true` if the shapes of a class meet the bounds of the trait
+/// implementation that defines the given method.
+///
+/// Traits may be implemented only for certain type arguments, such as
+/// `impl Equal for Option if T: Equal`. Shapes such as Int and inline classes
+/// refer to a specific class, and specializing the methods of such an
+/// implementation for a class that doesn't meet the bounds results in calls to
+/// methods that don't exist.
+fn bounds_met(
+    db: &Database,
+    method: MethodId,
+    shapes: &HashMap<TypeParameterId, Shape>,
+) -> bool {
+    let trait_id = match method.source(db) {
+        MethodSource::Implementation(ins, _) => ins.instance_of(),
+        MethodSource::Direct => return true,
+    };
+
+    let bounds = match method
+        .receiver(db)
+        .class_id(db)
+        .and_then(|id| id.trait_implementation(db, trait_id))
+    {
+        Some(imp) => &imp.bounds,
+        None => return true,
+    };
+
+    bounds.iter().all(|(param, bound)| {
+        let class = match shapes.get(param) {
+            Some(Shape::Int) => ClassId::int(),
+            Some(Shape::Float) => ClassId::float(),
+            Some(Shape::Boolean) => ClassId::boolean(),
+            Some(Shape::String) => ClassId::string(),
+            Some(Shape::Nil) => ClassId::nil(),
+            Some(Shape::Inline(id) | Shape::Sized(id)) => *id,
+            _ => return true,
+        };
+
+        bound.requirements(db).into_iter().all(|req| {
+            class.trait_implementation(db, req.instance_of()).is_some()
+        })
+    })
+}
+
 fn specialize_constants(db: &mut Database, mir: &mut Mir) {
     let mut classes = Vec::new();
     let shapes = HashMap::new();
@@ -208,16 +252,28 @@ impl<'a, 'b> Specialize<'a, 'b> {
 
         let mut work = Work::new();
         let mut dcalls = DynamicCalls::new();
-        let main_class = state.db.main_class().unwrap();
-        let main_method = state.db.main_method().unwrap();
-        let main_mod = main_class.module(&state.db);
 
-        work.push(main_method, HashMap::new());
+        if let (Some(main_class), Some(main_method)) =
+            (state.db.main_class(), state.db.main_method())
+        {
+            let main_mod = main_class.module(&state.db);
+
+            work.push(main_method, HashMap::new());
+
+            // The main() method isn't called explicitly, so we have to manually
+            // record it in the main class.
+            mir.classes.get_mut(&main_class).unwrap().methods.push(main_method);
+            mir.modules.get_mut(&main_mod).unwrap().methods.push(main_method);
+        }
 
-        // The main() method isn't called explicitly, so we have to manually
-        // record it in the main class.
-        mir.classes.get_mut(&main_class).unwrap().methods.push(main_method);
-        mir.modules.get_mut(&main_mod).unwrap().methods.push(main_method);
+        // When compiling a library, the exported methods are the entry points
+        // instead, and they too aren't called explicitly.
+        for &method in state.db.exported_methods() {
+            let module = method.module(&state.db);
+
+            work.push(method, HashMap::new());
+            mir.modules.get_mut(&module).unwrap().methods.push(method);
+        }
 
         while let Some(job) = work.pop() {
             Specialize {
@@ -347,6 +403,17 @@ impl<'a, 'b> Specialize<'a, 'b> {
 
                         ins.method = self.call_static(cls, ins.method, targs);
                     }
+                    Instruction::MethodPointer(ins) => {
+                        if ins.method.is_extern(&self.state.db) {
+                            mir.extern_methods.insert(ins.method);
+                        } else {
+                            let rec = ins.method.receiver(&self.state.db);
+                            let cls = rec.class_id(&self.state.db).unwrap();
+
+                            ins.method =
+                                self.call_static(cls, ins.method, None);
+                        }
+                    }
                     Instruction::CallInstance(ins) => {
                         let rec = method.registers.value_type(ins.receiver);
                         let cls = rec.class_id(&self.state.db).unwrap();
@@ -491,6 +558,12 @@ impl<'a, 'b> Specialize<'a, 'b> {
                 continue;
             }
 
+            if kind.is_inline() {
+                // Inline classes are never dropped, and aren't generic, so
+                // there's nothing left to do.
+                continue;
+            }
+
             // New classes are only added for types to specialize, so the source
             // is always set at this point.
             let orig = class.specialization_source(&self.state.db).unwrap();
@@ -518,6 +591,10 @@ impl<'a, 'b> Specialize<'a, 'b> {
                 }
 
                 for call in calls {
+                    if !bounds_met(&self.state.db, call.method, &class_shapes) {
+                        continue;
+                    }
+
                     let mut shapes = class_shapes.clone();
 
                     for &(par, shape) in &call.shapes {
@@ -680,6 +757,10 @@ impl<'a, 'b> Specialize<'a, 'b> {
                         shapes.insert(param, shape);
                     }
 
+                    if !bounds_met(&self.state.db, method_impl, &shapes) {
+                        continue;
+                    }
+
                     // We have to repeat these two calls for every specialized
                     // class, because the shapes referred to through bounds or
                     // type arguments may differ per specialization.
@@ -762,6 +843,7 @@ impl<'a, 'b> Specialize<'a, 'b> {
             method: new,
             arguments: call.arguments.clone(),
             type_arguments: None,
+            tail: false,
             location: call.location,
         }))
     }
@@ -1099,7 +1181,12 @@ impl<'a, 'b, 'c> ExpandDrop<'a, 'b, 'c> {
         let typ = self.method.registers.value_type(val);
 
         match typ.shape(self.db, self.shapes) {
-            Shape::Int | Shape::Float | Shape::Nil | Shape::Boolean => {
+            Shape::Int
+            | Shape::Float
+            | Shape::Nil
+            | Shape::Boolean
+            | Shape::Inline(_)
+            | Shape::Sized(_) => {
                 self.ignore_value(block_id, after_id, loc);
             }
             Shape::Mut | Shape::Ref => {
@@ -1279,7 +1366,12 @@ impl<'a, 'b, 'c> ExpandReference<'a, 'b, 'c> {
             Shape::Owned if is_extern || typ.is_permanent(self.db) => {
                 // Extern and permanent values are to be left as-is.
             }
-            Shape::Int | Shape::Float | Shape::Nil | Shape::Boolean => {
+            Shape::Int
+            | Shape::Float
+            | Shape::Nil
+            | Shape::Boolean
+            | Shape::Inline(_)
+            | Shape::Sized(_) => {
                 // These are unboxed value types, or permanent types, both which
                 // we should leave as-is.
             }
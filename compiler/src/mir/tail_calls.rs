@@ -0,0 +1,876 @@
+//! Optimising of calls in a tail position.
+//!
+//! A call is in a tail position if the only thing a method does after the call
+//! is returning its result. Such calls are flagged when lowering methods to
+//! MIR, and optimised once specialization is done, as only then do we know
+//! exactly what code runs when dropping values.
+//!
+//! Calls a method makes to itself are turned into jumps to the start of the
+//! method, such that tail recursive methods run in constant stack space. Calls
+//! from one tail recursive method to another are turned into jumps as well, by
+//! copying the body of the callee into the caller. Other tail calls keep their
+//! flag, allowing LLVM to reuse the stack frame of the caller.
+//!
+//! Methods marked as tail recursive are checked when lowering them to MIR. If
+//! such a method contains a tail call that can't be turned into a jump after
+//! specialization, an error is produced, as the method would otherwise no
+//! longer run in constant stack space.
+use crate::diagnostics::DiagnosticId;
+use crate::mir::escape::{defined_register, remap_instruction};
+use crate::mir::{
+    Block, BlockId, Instruction, LocationId, Method, Mir, RegisterId,
+};
+use crate::state::State;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::mem::take;
+use types::{Block as _, Database, MethodId, Shape};
+
+/// A call in a tail position.
+pub(crate) struct TailCall {
+    /// The references to decrement after the call.
+    pub(crate) decrements: Vec<(RegisterId, LocationId)>,
+
+    /// A flag indicating the method returns the result of the call.
+    ///
+    /// This is `false` for methods that return `Nil` and ignore the result.
+    pub(crate) returns: bool,
+
+    /// A flag indicating the values the decremented references point to are
+    /// also passed to the call, using references of their own.
+    ///
+    /// The callee holds on to these references until it returns, so the
+    /// references can be decremented before the call without changing when
+    /// the values may be dropped.
+    pub(crate) passed_on: bool,
+}
+
+/// Returns the call in a tail position in the given block, if there is any.
+///
+/// The return value is the index of the call instruction, along with the
+/// instructions that follow it.
+pub(crate) fn tail_call(
+    db: &Database,
+    method: &Method,
+    block: BlockId,
+    ignore_return: bool,
+) -> Option<(usize, TailCall)> {
+    // Only the last call in a block can be in a tail position.
+    let (index, register, arguments) = method.body.blocks[block.0]
+        .instructions
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, ins)| match ins {
+            Instruction::CallStatic(ins) => {
+                Some((index, ins.register, ins.arguments.clone()))
+            }
+            Instruction::CallInstance(ins) => {
+                let mut args = vec![ins.receiver];
+
+                args.extend(ins.arguments.iter().cloned());
+                Some((index, ins.register, args))
+            }
+            _ => None,
+        })?;
+
+    let (decrements, returns) =
+        tail_position(db, method, block, index + 1, register, ignore_return)?;
+    let passed_on =
+        decrements.iter().all(|&(reg, _)| passed_on(method, reg, &arguments));
+
+    Some((index, TailCall { decrements, returns, passed_on }))
+}
+
+/// Returns `true` if a tail call from one method to another can be turned
+/// into a jump to a copy of the callee.
+pub(crate) fn can_merge(
+    db: &Database,
+    caller: MethodId,
+    callee: MethodId,
+) -> bool {
+    // The copied body returns from the caller, so both methods must return
+    // the same type.
+    caller.is_tail_recursive(db)
+        && callee.is_tail_recursive(db)
+        && caller.return_type(db) == callee.return_type(db)
+}
+
+/// Optimises the calls that are flagged as tail calls.
+pub(crate) fn tail_calls(state: &mut State, mir: &mut Mir) {
+    let db = &state.db;
+
+    // The bodies of tail recursive methods are copied into the methods that
+    // call them. We copy the bodies as they were before this pass, such that
+    // we don't also copy the bodies previously copied into them.
+    let originals: HashMap<MethodId, Method> = mir
+        .methods
+        .values()
+        .filter(|m| m.id.is_tail_recursive(db))
+        .map(|m| (m.id, m.clone()))
+        .collect();
+
+    let mut lost = Vec::new();
+
+    for method in mir.methods.values_mut() {
+        jumps(db, method, &originals, &mut lost);
+        calls(db, method);
+    }
+
+    for (method, loc) in lost {
+        state.diagnostics.error(
+            DiagnosticId::InvalidMethod,
+            format!(
+                "the method '{}' is marked as tail recursive, but this call \
+                can't be turned into a jump, as values are dropped after it",
+                method.name(&state.db)
+            ),
+            method.module(&state.db).file(&state.db),
+            mir.location(loc).clone(),
+        );
+    }
+}
+
+/// A call in a tail position to replace with a jump.
+struct Jump {
+    block: BlockId,
+    index: usize,
+    callee: MethodId,
+    call: TailCall,
+}
+
+/// Replaces calls in a tail position to the method itself, or to other tail
+/// recursive methods, with jumps.
+///
+/// Calls in tail recursive methods that should be turned into jumps but can't
+/// be are added to `lost`.
+fn jumps(
+    db: &Database,
+    method: &mut Method,
+    originals: &HashMap<MethodId, Method>,
+    lost: &mut Vec<(MethodId, LocationId)>,
+) {
+    // For every method we can jump to, the block to jump to and the registers
+    // to store the arguments in.
+    let mut targets = HashMap::new();
+    let mut work = find_jumps(db, method, originals, lost);
+
+    targets.insert(method.id, (method.body.start_id, method.arguments.clone()));
+
+    while let Some(jump) = work.pop() {
+        if let Entry::Vacant(entry) = targets.entry(jump.callee) {
+            let source = &originals[&jump.callee];
+            let (offset, regs) = copy_method(method, source);
+            let start = BlockId(source.body.start_id.0 + offset);
+            let params =
+                source.arguments.iter().map(|r| regs[r.0 as usize]).collect();
+
+            entry.insert((start, params));
+
+            // Jumps in the copied body are found using the original body, as
+            // the arguments of jumps to the copied body are stored in its
+            // parameters, and these stores would otherwise affect the results.
+            // Lost calls in the copied body are reported for the original
+            // method, so we don't report them again.
+            for mut jump in find_jumps(db, source, originals, &mut Vec::new()) {
+                jump.block = BlockId(jump.block.0 + offset);
+
+                for (reg, _) in &mut jump.call.decrements {
+                    *reg = regs[reg.0 as usize];
+                }
+
+                work.push(jump);
+            }
+        }
+
+        let (start, params) = &targets[&jump.callee];
+
+        jump_to(method, jump.block, jump.index, jump.call, *start, params);
+    }
+}
+
+fn find_jumps(
+    db: &Database,
+    method: &Method,
+    originals: &HashMap<MethodId, Method>,
+    lost: &mut Vec<(MethodId, LocationId)>,
+) -> Vec<Jump> {
+    let ignore_ret = method.id.ignore_return_value(db);
+    let mut jumps = Vec::new();
+
+    for index in 0..method.body.blocks.len() {
+        let block = BlockId(index);
+
+        if !method.body.is_connected(block) {
+            continue;
+        }
+
+        let (pos, callee) = match flagged_call(method, block) {
+            Some(val) => val,
+            None => continue,
+        };
+
+        if callee != method.id && !can_merge(db, method.id, callee) {
+            continue;
+        }
+
+        let known = callee == method.id || originals.contains_key(&callee);
+
+        match tail_call(db, method, block, ignore_ret) {
+            Some((idx, call)) if idx == pos && call.passed_on && known => {
+                jumps.push(Jump { block, index: pos, callee, call });
+            }
+            // The call is approved when lowering the method to MIR, but
+            // specialization may change what happens after the call (e.g.
+            // dropping a type parameter may require calling a dropper).
+            _ if method.id.is_tail_recursive(db) => {
+                lost.push((method.id, call_location(method, block, pos)));
+            }
+            _ => {}
+        }
+    }
+
+    jumps
+}
+
+/// Optimises the tail calls that aren't replaced with jumps.
+fn calls(db: &Database, method: &mut Method) {
+    let ignore_ret = method.id.ignore_return_value(db);
+
+    // Tail calls can't access the stack frame of the caller, so we can't keep
+    // the flag if pointers to data on the stack may be passed around.
+    let keep = !method.body.blocks.iter().any(|b| {
+        b.instructions.iter().any(|i| {
+            matches!(i, Instruction::Pointer(_) | Instruction::FieldPointer(_))
+        })
+    });
+
+    for index in 0..method.body.blocks.len() {
+        let block = BlockId(index);
+
+        if !method.body.is_connected(block) {
+            continue;
+        }
+
+        let pos = match flagged_call(method, block) {
+            Some((pos, _)) => pos,
+            None => continue,
+        };
+
+        // Dropping values may require more than just decrementing
+        // references, such as when calling a dropper, in which case the call
+        // is no longer in a tail position. Decrementing references before the
+        // call may allow the callee to drop the values they point to, so we
+        // don't optimise calls that are followed by decrements.
+        match tail_call(db, method, block, ignore_ret) {
+            Some((idx, call))
+                if idx == pos
+                    && keep
+                    && call.returns
+                    && call.decrements.is_empty() =>
+            {
+                return_directly(method, block, pos);
+            }
+            _ => unflag(method, block, pos),
+        }
+    }
+}
+
+/// Returns the index of the call flagged as a tail call in a block, and the
+/// method it calls.
+fn flagged_call(method: &Method, block: BlockId) -> Option<(usize, MethodId)> {
+    method.body.blocks[block.0].instructions.iter().enumerate().find_map(
+        |(pos, ins)| match ins {
+            Instruction::CallStatic(ins) if ins.tail => Some((pos, ins.method)),
+            Instruction::CallInstance(ins) if ins.tail => {
+                Some((pos, ins.method))
+            }
+            _ => None,
+        },
+    )
+}
+
+fn tail_position(
+    db: &Database,
+    method: &Method,
+    mut block: BlockId,
+    mut index: usize,
+    mut result: RegisterId,
+    ignore_return: bool,
+) -> Option<(Vec<(RegisterId, LocationId)>, bool)> {
+    let mut decrements = Vec::new();
+    let mut visited = HashSet::new();
+
+    loop {
+        let current = &method.body.blocks[block.0];
+        let ins = if let Some(ins) = current.instructions.get(index) {
+            ins
+        } else {
+            // Blocks without a terminator implicitly jump to their only
+            // successor.
+            match current.successors.as_slice() {
+                &[next] if visited.insert(next) => {
+                    block = next;
+                    index = 0;
+                    continue;
+                }
+                _ => return None,
+            }
+        };
+
+        index += 1;
+
+        match ins {
+            Instruction::Reduce(_) => {}
+            Instruction::Nil(ins) if ins.register != result => {}
+            Instruction::True(ins) if ins.register != result => {}
+            Instruction::False(ins) if ins.register != result => {}
+            Instruction::MoveRegister(ins) if ins.source == result => {
+                result = ins.target;
+            }
+            Instruction::MoveRegister(ins) if ins.target != result => {}
+            Instruction::Decrement(ins) if ins.register != result => {
+                decrements.push((ins.register, ins.location));
+            }
+            Instruction::Drop(ins)
+                if trivial_drop(db, method, ins.register) =>
+            {
+                // Before specialization, references are decremented by
+                // dropping them.
+                if matches!(
+                    method
+                        .registers
+                        .value_type(ins.register)
+                        .shape(db, &HashMap::new()),
+                    Shape::Mut | Shape::Ref
+                ) {
+                    decrements.push((ins.register, ins.location));
+                }
+            }
+            Instruction::Goto(ins) if visited.insert(ins.block) => {
+                block = ins.block;
+                index = 0;
+            }
+            Instruction::Return(ins) if ins.register == result => {
+                return Some((decrements, true));
+            }
+            Instruction::Return(_) if ignore_return => {
+                return Some((decrements, false));
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Returns `true` if the value a reference points to is also passed to a call,
+/// using a reference of its own.
+fn passed_on(
+    method: &Method,
+    register: RegisterId,
+    arguments: &[RegisterId],
+) -> bool {
+    let mut definitions = 0;
+    let mut incremented = false;
+    let mut copies = HashMap::new();
+
+    for ins in method.body.blocks.iter().flat_map(|b| b.instructions.iter()) {
+        if let Instruction::Increment(ins) = ins {
+            incremented |= ins.register == register;
+        }
+
+        let target = match defined_register(ins) {
+            Some(reg) => reg,
+            None => continue,
+        };
+
+        if target == register {
+            definitions += 1;
+        }
+
+        if !arguments.contains(&target) {
+            continue;
+        }
+
+        // Before specialization, new references are created using the
+        // Reference instruction. After specialization these are turned into
+        // an increment followed by a move.
+        let copy = match ins {
+            Instruction::Reference(ins) => Some(ins.value == register),
+            Instruction::MoveRegister(ins) if ins.source == register => None,
+            _ => Some(false),
+        };
+        let entry = copies.entry(target).or_insert(true);
+
+        *entry &= copy.unwrap_or(incremented);
+    }
+
+    // If the reference is assigned more than once, the copy passed to the
+    // call may point to a different value.
+    definitions <= 1 && copies.values().any(|&copy| copy)
+}
+
+/// Returns `true` if dropping a register never does more than decrementing a
+/// reference.
+fn trivial_drop(db: &Database, method: &Method, register: RegisterId) -> bool {
+    let typ = method.registers.value_type(register);
+
+    // The shapes of type parameters aren't known until specialization, and
+    // they may be assigned types that use atomic reference counting.
+    if typ.is_type_parameter(db) {
+        return false;
+    }
+
+    if typ.is_permanent(db) {
+        return true;
+    }
+
+    matches!(
+        typ.shape(db, &HashMap::new()),
+        Shape::Int
+            | Shape::Float
+            | Shape::Nil
+            | Shape::Boolean
+            | Shape::Inline(_)
+            | Shape::Sized(_)
+            | Shape::Mut
+            | Shape::Ref
+    )
+}
+
+/// Copies the body of `source` into `method`.
+///
+/// The return value is the offset of the copied blocks, and the registers the
+/// registers of `source` are mapped to.
+fn copy_method(
+    method: &mut Method,
+    source: &Method,
+) -> (usize, Vec<RegisterId>) {
+    let regs: Vec<RegisterId> = (0..source.registers.len())
+        .map(|index| {
+            let typ = source.registers.value_type(RegisterId(index as u32));
+
+            method.registers.alloc(typ)
+        })
+        .collect();
+
+    let offset = method.body.blocks.len();
+    let map_reg = |reg: RegisterId| regs[reg.0 as usize];
+    let map_block = |block: BlockId| BlockId(block.0 + offset);
+
+    for block in &source.body.blocks {
+        let mut new_block = Block::new();
+
+        new_block.predecessors =
+            block.predecessors.iter().map(|&b| map_block(b)).collect();
+        new_block.successors =
+            block.successors.iter().map(|&b| map_block(b)).collect();
+
+        for ins in &block.instructions {
+            let mut ins = ins.clone();
+
+            remap_instruction(&mut ins, &map_reg, &map_block);
+            new_block.instructions.push(ins);
+        }
+
+        method.body.blocks.push(new_block);
+    }
+
+    (offset, regs)
+}
+
+/// Replaces a call with a jump to the block `start`, storing the arguments in
+/// the registers `params`.
+fn jump_to(
+    method: &mut Method,
+    block: BlockId,
+    index: usize,
+    call: TailCall,
+    start: BlockId,
+    params: &[RegisterId],
+) {
+    let ins = method.body.blocks[block.0].instructions.split_off(index);
+    let (mut values, loc) = match &ins[0] {
+        Instruction::CallStatic(ins) => (ins.arguments.clone(), ins.location),
+        Instruction::CallInstance(ins) => {
+            let mut values = vec![ins.receiver];
+
+            values.extend(ins.arguments.iter().cloned());
+            (values, ins.location)
+        }
+        _ => unreachable!(),
+    };
+
+    // The values the references point to are also passed to the call, so it's
+    // fine to decrement them before jumping.
+    for (reg, loc) in call.decrements {
+        method.body.block_mut(block).decrement(reg, loc);
+    }
+
+    // Arguments may be passed to the call as-is (e.g. when swapping them), so
+    // such values are first moved into temporary registers, ensuring they
+    // aren't overwritten before they're used.
+    for (index, val) in values.iter_mut().enumerate() {
+        if *val != params[index] && params.contains(val) {
+            let typ = method.registers.value_type(*val);
+            let tmp = method.registers.alloc(typ);
+
+            method.body.block_mut(block).move_register(tmp, *val, loc);
+            *val = tmp;
+        }
+    }
+
+    for (&param, &val) in params.iter().zip(values.iter()) {
+        if param != val {
+            method.body.block_mut(block).move_register(param, val, loc);
+        }
+    }
+
+    let target = method.body.block_mut(block);
+
+    target.reduce_call(loc);
+    target.goto(start, loc);
+    replace_successors(method, block, Some(start));
+}
+
+/// Returns the result of a tail call directly.
+fn return_directly(method: &mut Method, block: BlockId, index: usize) {
+    let mut ins = method.body.blocks[block.0].instructions.split_off(index);
+    let (reg, loc) = match &ins[0] {
+        Instruction::CallStatic(ins) => (ins.register, ins.location),
+        Instruction::CallInstance(ins) => (ins.register, ins.location),
+        _ => unreachable!(),
+    };
+    let target = method.body.block_mut(block);
+
+    target.reduce_call(loc);
+    target.instructions.push(ins.swap_remove(0));
+    target.return_value(reg, loc);
+    replace_successors(method, block, None);
+}
+
+fn call_location(method: &Method, block: BlockId, index: usize) -> LocationId {
+    match &method.body.blocks[block.0].instructions[index] {
+        Instruction::CallStatic(ins) => ins.location,
+        Instruction::CallInstance(ins) => ins.location,
+        _ => unreachable!(),
+    }
+}
+
+fn unflag(method: &mut Method, block: BlockId, index: usize) {
+    match &mut method.body.blocks[block.0].instructions[index] {
+        Instruction::CallStatic(ins) => ins.tail = false,
+        Instruction::CallInstance(ins) => ins.tail = false,
+        _ => {}
+    }
+}
+
+/// Replaces the successors of a block, disconnecting the blocks that are no
+/// longer reachable as a result.
+fn replace_successors(
+    method: &mut Method,
+    block: BlockId,
+    successor: Option<BlockId>,
+) {
+    let body = &mut method.body;
+    let mut work = vec![block];
+
+    while let Some(id) = work.pop() {
+        for succ in take(&mut body.blocks[id.0].successors) {
+            body.remove_predecessor(succ, id);
+
+            if succ != body.start_id
+                && body.blocks[succ.0].predecessors.is_empty()
+            {
+                work.push(succ);
+            }
+        }
+    }
+
+    if let Some(succ) = successor {
+        body.add_edge(block, succ);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::mir::MoveRegister;
+    use crate::test::{cols, module_type};
+    use types::{MethodKind, TypeRef, Visibility};
+
+    fn method() -> Method {
+        Method::new(MethodId(0), LocationId(0))
+    }
+
+    #[test]
+    fn test_tail_call() {
+        let db = Database::new();
+        let mut method = method();
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let b1 = method.body.add_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+
+        method.body.block_mut(b0).call_static(
+            r1,
+            MethodId(1),
+            vec![r0],
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).reduce_call(loc);
+        method.body.block_mut(b0).goto(b1, loc);
+        method.body.block_mut(b1).decrement(r0, loc);
+        method.body.block_mut(b1).move_register(r2, r1, loc);
+        method.body.block_mut(b1).return_value(r2, loc);
+        method.body.add_edge(b0, b1);
+
+        let (index, call) = tail_call(&db, &method, b0, false).unwrap();
+
+        assert_eq!(index, 0);
+        assert_eq!(call.decrements.len(), 1);
+        assert!(call.returns);
+        assert!(tail_call(&db, &method, b1, false).is_none());
+    }
+
+    #[test]
+    fn test_tail_call_with_other_instructions() {
+        let db = Database::new();
+        let mut method = method();
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+
+        method.body.block_mut(b0).call_static(
+            r1,
+            MethodId(1),
+            vec![r0],
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).free(r0, loc);
+        method.body.block_mut(b0).return_value(r1, loc);
+
+        assert!(tail_call(&db, &method, b0, false).is_none());
+
+        method.body.block_mut(b0).instructions.truncate(1);
+        method.body.block_mut(b0).return_value(r2, loc);
+
+        assert!(tail_call(&db, &method, b0, false).is_none());
+        assert!(!tail_call(&db, &method, b0, true).unwrap().1.returns);
+    }
+
+    #[test]
+    fn test_tail_call_with_references() {
+        let db = Database::new();
+        let mut method = method();
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+        let r3 = method.registers.alloc(TypeRef::int());
+
+        method.arguments = vec![r0];
+        method.body.block_mut(b0).increment(r0, loc);
+        method.body.block_mut(b0).move_register(r1, r0, loc);
+        method.body.block_mut(b0).call_static(
+            r2,
+            MethodId(1),
+            vec![r1],
+            None,
+            loc,
+        );
+        method.body.block_mut(b0).decrement(r0, loc);
+        method.body.block_mut(b0).return_value(r2, loc);
+
+        assert!(tail_call(&db, &method, b0, false).unwrap().1.passed_on);
+
+        // Without the increment the callee doesn't have a reference of its
+        // own, so the value may be dropped while the callee runs.
+        method.body.block_mut(b0).instructions.remove(0);
+
+        assert!(!tail_call(&db, &method, b0, false).unwrap().1.passed_on);
+
+        // The same applies when the reference isn't passed to the callee.
+        method.body.block_mut(b0).instructions[0] =
+            Instruction::MoveRegister(Box::new(MoveRegister {
+                source: r3,
+                target: r1,
+                location: loc,
+            }));
+
+        assert!(!tail_call(&db, &method, b0, false).unwrap().1.passed_on);
+    }
+
+    fn tail_recursive_method(state: &mut State) -> MethodId {
+        let module = module_type(state, "test");
+        let id = types::Method::alloc(
+            &mut state.db,
+            module,
+            "foo".to_string(),
+            Visibility::Private,
+            MethodKind::Static,
+        );
+
+        id.set_return_type(&mut state.db, TypeRef::int());
+        id.set_tail_recursive(&mut state.db);
+        id
+    }
+
+    fn flag_tail_call(method: &mut Method, block: BlockId, index: usize) {
+        match &mut method.body.blocks[block.0].instructions[index] {
+            Instruction::CallStatic(ins) => ins.tail = true,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_tail_calls_with_jump() {
+        let mut state = State::new(Config::new());
+        let mut mir = Mir::new();
+        let loc = mir.add_location(cols(1, 1));
+        let id = tail_recursive_method(&mut state);
+        let mut method = Method::new(id, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+
+        method.arguments = vec![r0];
+        method.body.block_mut(b0).call_static(r1, id, vec![r0], None, loc);
+        method.body.block_mut(b0).return_value(r1, loc);
+        flag_tail_call(&mut method, b0, 0);
+        mir.methods.insert(id, method);
+        tail_calls(&mut state, &mut mir);
+
+        assert!(!state.diagnostics.has_errors());
+        assert!(matches!(
+            mir.methods[&id].body.blocks[b0.0].instructions.last(),
+            Some(Instruction::Goto(ins)) if ins.block == b0
+        ));
+    }
+
+    #[test]
+    fn test_tail_calls_with_lost_jump() {
+        let mut state = State::new(Config::new());
+        let mut mir = Mir::new();
+        let loc = mir.add_location(cols(1, 1));
+        let id = tail_recursive_method(&mut state);
+        let mut method = Method::new(id, loc);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+
+        // The reference in r0 isn't passed to the call, so it can't be
+        // decremented before jumping to the start of the method.
+        method.arguments = vec![r0, r1];
+        method.body.block_mut(b0).call_static(r2, id, vec![r1], None, loc);
+        method.body.block_mut(b0).decrement(r0, loc);
+        method.body.block_mut(b0).return_value(r2, loc);
+        flag_tail_call(&mut method, b0, 0);
+        mir.methods.insert(id, method);
+        tail_calls(&mut state, &mut mir);
+
+        let errors: Vec<_> =
+            state.diagnostics.iter().map(|d| d.message().to_string()).collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                "the method 'foo' is marked as tail recursive, but this call \
+                can't be turned into a jump, as values are dropped after it"
+                    .to_string()
+            ]
+        );
+        assert!(matches!(
+            &mir.methods[&id].body.blocks[b0.0].instructions[0],
+            Instruction::CallStatic(ins) if !ins.tail
+        ));
+    }
+
+    #[test]
+    fn test_copy_method() {
+        let mut method = method();
+        let mut source = Method::new(MethodId(1), LocationId(0));
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let s0 = source.body.add_start_block();
+        let s1 = source.body.add_block();
+        let sr0 = source.registers.alloc(TypeRef::int());
+
+        method.body.block_mut(b0).return_value(r0, loc);
+        source.arguments = vec![sr0];
+        source.body.block_mut(s0).goto(s1, loc);
+        source.body.block_mut(s1).return_value(sr0, loc);
+        source.body.add_edge(s0, s1);
+
+        let (offset, regs) = copy_method(&mut method, &source);
+
+        assert_eq!(offset, 1);
+        assert_eq!(regs, vec![RegisterId(1)]);
+        assert_eq!(method.body.blocks.len(), 3);
+        assert_eq!(method.body.successors(BlockId(1)), vec![BlockId(2)]);
+        assert!(matches!(
+            method.body.blocks[2].instructions[0],
+            Instruction::Return(ref ins) if ins.register == RegisterId(1)
+        ));
+    }
+
+    #[test]
+    fn test_jump_to() {
+        let mut method = method();
+        let loc = LocationId(0);
+        let b0 = method.body.add_start_block();
+        let b1 = method.body.add_block();
+        let b2 = method.body.add_block();
+        let r0 = method.registers.alloc(TypeRef::int());
+        let r1 = method.registers.alloc(TypeRef::int());
+        let r2 = method.registers.alloc(TypeRef::int());
+
+        method.arguments = vec![r0, r1];
+        method.body.block_mut(b0).goto(b1, loc);
+        method.body.block_mut(b1).call_static(
+            r2,
+            MethodId(0),
+            vec![r1, r0],
+            None,
+            loc,
+        );
+        method.body.block_mut(b1).goto(b2, loc);
+        method.body.block_mut(b2).return_value(r2, loc);
+        method.body.add_edge(b0, b1);
+        method.body.add_edge(b1, b2);
+
+        let (index, call) =
+            tail_call(&Database::new(), &method, b1, false).unwrap();
+
+        jump_to(&mut method, b1, index, call, b0, &[r0, r1]);
+
+        let moves: Vec<_> = method.body.blocks[b1.0]
+            .instructions
+            .iter()
+            .filter_map(|ins| match ins {
+                Instruction::MoveRegister(ins) => {
+                    Some((ins.target.0, ins.source.0))
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(moves, vec![(3, 1), (4, 0), (0, 3), (1, 4)]);
+        assert!(matches!(
+            method.body.blocks[b1.0].instructions.last(),
+            Some(Instruction::Goto(ins)) if ins.block == b0
+        ));
+        assert_eq!(method.body.successors(b1), vec![b0]);
+        assert_eq!(method.body.predecessors(b0), vec![b1]);
+        assert!(method.body.predecessors(b2).is_empty());
+    }
+}
@@ -3,10 +3,28 @@ use blake2::{digest::consts::U16, Blake2b, Digest};
 use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub const MANIFEST_FILE: &str = "inko.pkg";
 
+/// The keyword used for requiring a dependency using a local path.
+const PATH: &str = "path";
+
+fn import_name(tail: &str) -> String {
+    // For generic names like "http" or "sqlite3", creating a repository with
+    // such a name may be confusing, as one might think it's e.g. a fork of a
+    // project, or perhaps the name conflicts with an existing project.
+    //
+    // To handle that, if a project is called "inko-http", we strip the "inko-"
+    // prefix. This way within the code you can just use "http" as the module
+    // name.
+    if let Some(name) = tail.strip_prefix("inko-") {
+        name.to_string()
+    } else {
+        tail.to_string()
+    }
+}
+
 /// The URL of a package.
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub struct Url {
@@ -48,21 +66,7 @@ impl Url {
     }
 
     pub fn import_name(&self) -> String {
-        let tail = self.value.split('/').last().unwrap();
-
-        // For generic names like "http" or "sqlite3", creating a repository
-        // with such a name may be confusing, as one might think it's e.g. a
-        // fork of a project, or perhaps the name conflicts with an existing
-        // project.
-        //
-        // To handle that, if a project is called "inko-http", we strip the
-        // "inko-" prefix. This way within the code you can just use "http" as
-        // the module name.
-        if let Some(name) = tail.strip_prefix("inko-") {
-            name.to_string()
-        } else {
-            tail.to_string()
-        }
+        import_name(self.value.split('/').last().unwrap())
     }
 }
 
@@ -113,10 +117,143 @@ impl fmt::Display for Dependency {
     }
 }
 
+/// A dependency that uses a local directory, instead of a Git repository.
+///
+/// Local dependencies are used directly from their directory, meaning they
+/// don't have a version or checksum, and aren't installed into `./dep`.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct LocalDependency {
+    /// The path to the root directory of the dependency, relative to the
+    /// directory containing the manifest.
+    pub path: PathBuf,
+    pub name: String,
+}
+
+impl LocalDependency {
+    pub fn new(path: PathBuf) -> Self {
+        let name = import_name(
+            &path
+                .file_name()
+                .map(|v| v.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        );
+
+        Self { path, name }
+    }
+}
+
+impl fmt::Display for LocalDependency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "require {} {}", PATH, self.path.display())
+    }
+}
+
+/// A build setting specified in the manifest.
+///
+/// Build settings provide defaults for the compiler's configuration, and only
+/// apply to the project the manifest belongs to, not to projects that depend
+/// on it.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum Setting {
+    /// The optimisation level to use.
+    Opt(String),
+
+    /// C libraries are to be linked statically.
+    Static,
+
+    /// A directory to add to the list of source directories.
+    Include(PathBuf),
+
+    /// A build tag to define.
+    Define(String),
+
+    /// A C library to link against.
+    Link(String),
+
+    /// The program to use for linking.
+    Linker(PathBuf),
+
+    /// An extra argument to pass to the linker.
+    LinkerArgument(String),
+
+    /// A directory to search for C libraries.
+    LibraryPath(PathBuf),
+
+    /// A setting that only applies when compiling for the given target.
+    ///
+    /// Only the linker settings can be specified per target.
+    Target(String, Box<Setting>),
+
+    /// The name of the executable to produce.
+    Output(String),
+}
+
+impl Setting {
+    fn parse(command: &str, value: Option<&str>) -> Option<Setting> {
+        let setting = match (command, value) {
+            ("static", None) => Setting::Static,
+            ("opt", Some(val)) => Setting::Opt(val.to_string()),
+            ("include", Some(val)) => Setting::Include(PathBuf::from(val)),
+            ("define", Some(val)) => Setting::Define(val.to_string()),
+            ("link", Some(val)) => Setting::Link(val.to_string()),
+            ("linker", Some(val)) => Setting::Linker(PathBuf::from(val)),
+            ("linker-arg", Some(val)) => {
+                Setting::LinkerArgument(val.to_string())
+            }
+            ("library-path", Some(val)) => {
+                Setting::LibraryPath(PathBuf::from(val))
+            }
+            ("target", Some(val)) => {
+                let (target, rest) = val.split_once(' ')?;
+                let (cmd, val) = match rest.trim().split_once(' ') {
+                    Some((cmd, val)) => (cmd, Some(val.trim())),
+                    None => (rest.trim(), None),
+                };
+
+                match Setting::parse(cmd, val)? {
+                    setting @ (Setting::Linker(_)
+                    | Setting::LinkerArgument(_)
+                    | Setting::LibraryPath(_)) => {
+                        Setting::Target(target.to_string(), Box::new(setting))
+                    }
+                    _ => return None,
+                }
+            }
+            ("output", Some(val)) => Setting::Output(val.to_string()),
+            _ => return None,
+        };
+
+        Some(setting)
+    }
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Setting::Opt(val) => write!(f, "opt {}", val),
+            Setting::Static => write!(f, "static"),
+            Setting::Include(val) => write!(f, "include {}", val.display()),
+            Setting::Define(val) => write!(f, "define {}", val),
+            Setting::Link(val) => write!(f, "link {}", val),
+            Setting::Linker(val) => write!(f, "linker {}", val.display()),
+            Setting::LinkerArgument(val) => write!(f, "linker-arg {}", val),
+            Setting::LibraryPath(val) => {
+                write!(f, "library-path {}", val.display())
+            }
+            Setting::Target(target, setting) => {
+                write!(f, "target {} {}", target, setting)
+            }
+            Setting::Output(val) => write!(f, "output {}", val),
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub enum Entry {
     Comment(String),
     Dependency(Dependency),
+    LocalDependency(LocalDependency),
+    Setting(Setting),
     EmptyLine,
 }
 
@@ -126,6 +263,8 @@ impl fmt::Display for Entry {
             Entry::Comment(comment) => write!(f, "#{}", comment),
             Entry::EmptyLine => Ok(()),
             Entry::Dependency(dep) => dep.fmt(f),
+            Entry::LocalDependency(dep) => dep.fmt(f),
+            Entry::Setting(setting) => setting.fmt(f),
         }
     }
 }
@@ -173,16 +312,39 @@ impl Manifest {
 
             let chunks: Vec<_> = trimmed.split(' ').collect();
 
-            if chunks.len() != 4 {
-                return Err(format!("The entry on line {} is invalid", lnum));
+            if chunks[0] != "require" {
+                // The values of settings may contain spaces (e.g. an argument
+                // to pass to the linker), so we use the rest of the line as
+                // the value.
+                let (cmd, val) = match trimmed.split_once(' ') {
+                    Some((cmd, val)) => (cmd, Some(val.trim())),
+                    None => (trimmed, None),
+                };
+
+                let setting = Setting::parse(cmd, val).ok_or_else(|| {
+                    format!("The command '{}' on line {} is invalid", cmd, lnum)
+                })?;
+
+                manifest.entries.push(Entry::Setting(setting));
+                continue;
             }
 
-            // Currently this is the only action we support.
-            if chunks[0] != "require" {
-                return Err(format!(
-                    "Expected line {} to start with 'require', not '{}'",
-                    lnum, chunks[0]
-                ));
+            if chunks.len() == 3 && chunks[1] == PATH {
+                let dep = LocalDependency::new(PathBuf::from(chunks[2]));
+
+                if dep.name.is_empty() {
+                    return Err(format!(
+                        "The path on line {} is invalid",
+                        lnum
+                    ));
+                }
+
+                manifest.entries.push(Entry::LocalDependency(dep));
+                continue;
+            }
+
+            if chunks.len() != 4 {
+                return Err(format!("The entry on line {} is invalid", lnum));
             }
 
             let url = Url::parse(chunks[1]).ok_or_else(|| {
@@ -255,6 +417,26 @@ impl Manifest {
             .collect()
     }
 
+    pub fn local_dependencies(&self) -> Vec<&LocalDependency> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::LocalDependency(dep) => Some(dep),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn settings(&self) -> Vec<&Setting> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Setting(setting) => Some(setting),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn save<P: AsRef<Path>>(&self, path: &P) -> Result<(), String> {
         let path = path.as_ref();
 
@@ -334,6 +516,10 @@ mod tests {
         let missing_chunks = "# Ignore me
         require https://gitlab.com/inko-lang/foo 1.2.3";
 
+        let invalid_path = "require path ..";
+        let invalid_setting = "static foo";
+        let invalid_target = "target amd64-linux-gnu opt none";
+
         let invalid_cmd = "# Ignore me
         bla https://gitlab.com/inko-lang/foo 1.2.3 abcdef123";
 
@@ -346,10 +532,21 @@ mod tests {
             Manifest::parse(&mut missing_chunks.as_bytes()),
             Err("The entry on line 2 is invalid".to_string())
         );
+        assert_eq!(
+            Manifest::parse(&mut invalid_setting.as_bytes()),
+            Err("The command 'static' on line 1 is invalid".to_string())
+        );
+        assert_eq!(
+            Manifest::parse(&mut invalid_target.as_bytes()),
+            Err("The command 'target' on line 1 is invalid".to_string())
+        );
+        assert_eq!(
+            Manifest::parse(&mut invalid_path.as_bytes()),
+            Err("The path on line 1 is invalid".to_string())
+        );
         assert_eq!(
             Manifest::parse(&mut invalid_cmd.as_bytes()),
-            Err("Expected line 2 to start with 'require', not 'bla'"
-                .to_string())
+            Err("The command 'bla' on line 2 is invalid".to_string())
         );
         assert_eq!(
             Manifest::parse(&mut invalid_version.as_bytes()),
@@ -366,7 +563,12 @@ mod tests {
         let input = "# Ignore me
 #
 
-require https://gitlab.com/inko-lang/foo 1.2.3 633d02e92b2a96623c276b7d7fe09568f9f2e1ad";
+require https://gitlab.com/inko-lang/foo 1.2.3 633d02e92b2a96623c276b7d7fe09568f9f2e1ad
+require path ../inko-bar
+opt aggressive
+static
+linker-arg -Wl,--as-needed  -Wl,-z,now
+target arm64-linux-gnu linker aarch64-linux-gnu-gcc";
 
         assert_eq!(
             Manifest::parse(&mut input.as_bytes()),
@@ -382,7 +584,22 @@ require https://gitlab.com/inko-lang/foo 1.2.3 633d02e92b2a96623c276b7d7fe09568f
                         checksum: Checksum::new(
                             "633d02e92b2a96623c276b7d7fe09568f9f2e1ad"
                         ),
-                    })
+                    }),
+                    Entry::LocalDependency(LocalDependency {
+                        path: PathBuf::from("../inko-bar"),
+                        name: "bar".to_string(),
+                    }),
+                    Entry::Setting(Setting::Opt("aggressive".to_string())),
+                    Entry::Setting(Setting::Static),
+                    Entry::Setting(Setting::LinkerArgument(
+                        "-Wl,--as-needed  -Wl,-z,now".to_string()
+                    )),
+                    Entry::Setting(Setting::Target(
+                        "arm64-linux-gnu".to_string(),
+                        Box::new(Setting::Linker(PathBuf::from(
+                            "aarch64-linux-gnu-gcc"
+                        )))
+                    )),
                 ]
             })
         );
@@ -407,6 +624,15 @@ require https://gitlab.com/inko-lang/foo 1.2.3 633d02e92b2a96623c276b7d7fe09568f
                     version: Version::new(4, 5, 6),
                     checksum: Checksum::new("def"),
                 }),
+                Entry::LocalDependency(LocalDependency::new(PathBuf::from(
+                    "../baz",
+                ))),
+                Entry::Setting(Setting::Include(PathBuf::from("vendor"))),
+                Entry::Setting(Setting::Static),
+                Entry::Setting(Setting::Target(
+                    "amd64-linux-gnu".to_string(),
+                    Box::new(Setting::LibraryPath(PathBuf::from("/opt/lib"))),
+                )),
             ],
         };
 
@@ -415,6 +641,10 @@ require https://gitlab.com/inko-lang/foo 1.2.3 633d02e92b2a96623c276b7d7fe09568f
 
 require https://gitlab.com/inko-lang/foo 1.2.3 abc
 require https://github.com/inko-lang/bar 4.5.6 def
+require path ../baz
+include vendor
+static
+target amd64-linux-gnu library-path /opt/lib
 ";
 
         assert_eq!(manifest.to_string(), output);
@@ -517,4 +747,27 @@ require https://github.com/inko-lang/bar 4.5.6 def
             }]
         );
     }
+
+    #[test]
+    fn test_manifest_local_dependencies() {
+        let mut manifest = Manifest { entries: Vec::new() };
+
+        manifest.add_dependency(
+            Url::new("test"),
+            "test".to_string(),
+            Version::new(1, 2, 3),
+            Checksum::new("abc"),
+        );
+        manifest.entries.push(Entry::LocalDependency(LocalDependency::new(
+            PathBuf::from("../foo"),
+        )));
+
+        assert_eq!(
+            manifest.local_dependencies(),
+            vec![&LocalDependency {
+                path: PathBuf::from("../foo"),
+                name: "foo".to_string()
+            }]
+        );
+    }
 }
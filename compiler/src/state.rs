@@ -13,8 +13,8 @@ pub(crate) struct BuildTags {
 }
 
 impl BuildTags {
-    fn new(target: &Target) -> BuildTags {
-        let mut values = HashSet::new();
+    fn new(target: &Target, defines: &[String]) -> BuildTags {
+        let mut values: HashSet<_> = defines.iter().cloned().collect();
 
         values.insert(target.arch_name().to_string());
         values.insert(target.os_name().to_string());
@@ -114,7 +114,21 @@ impl Packages {
         let mut found = None;
 
         // At this stage we expect the manifests to be valid.
-        for dep in Manifest::load(&manifest_path).ok()?.into_dependencies() {
+        let manifest = Manifest::load(&manifest_path).ok()?;
+
+        // Local dependencies are used as-is, instead of being installed into
+        // the dependencies directory.
+        for dep in manifest.local_dependencies() {
+            let dir = root.join(&dep.path).join(SOURCE);
+
+            if dep.name == head && found.is_none() {
+                found = Some(dir.clone());
+            }
+
+            map.insert(dep.name.clone(), dir);
+        }
+
+        for dep in manifest.into_dependencies() {
             let dir = dependencies
                 .join(dep.url.directory_name())
                 .join(&format!("v{}", dep.version.major))
@@ -169,6 +183,9 @@ pub(crate) struct State {
     pub(crate) build_tags: BuildTags,
     pub(crate) libraries: HashSet<String>,
     packages: Packages,
+
+    /// The directory containing the installed third-party dependencies.
+    dependencies: PathBuf,
     exists: Exists,
 }
 
@@ -176,7 +193,16 @@ impl State {
     pub(crate) fn new(config: Config) -> Self {
         let diagnostics = Diagnostics::new();
         let db = Database::new();
-        let build_tags = BuildTags::new(&config.target);
+        let build_tags = BuildTags::new(&config.target, &config.build_tags);
+
+        // Vendored dependencies take priority over those installed using
+        // `inko pkg sync`, such that the former can be used without having
+        // to install dependencies first.
+        let dependencies = if config.vendor.is_dir() {
+            config.vendor.clone()
+        } else {
+            config.dependencies.clone()
+        };
 
         Self {
             config,
@@ -185,6 +211,7 @@ impl State {
             build_tags,
             libraries: HashSet::new(),
             packages: Packages::new(),
+            dependencies,
             exists: Exists::new(),
         }
     }
@@ -205,7 +232,7 @@ impl State {
 
             if let Some(p) = self
                 .packages
-                .source_directory(&self.config.dependencies, &root, module)
+                .source_directory(&self.dependencies, &root, module)
                 .and_then(|src| self.exists.check(src.join(&rel)))
             {
                 return Some(p);
@@ -234,23 +261,41 @@ mod tests {
 
     #[test]
     fn test_build_tags() {
-        let linux = BuildTags::new(&Target {
-            arch: Architecture::Amd64,
-            os: OperatingSystem::Linux,
-            abi: Abi::Native,
-        });
-
-        let bsd = BuildTags::new(&Target {
-            arch: Architecture::Amd64,
-            os: OperatingSystem::Freebsd,
-            abi: Abi::Native,
-        });
-
-        let mac = BuildTags::new(&Target {
-            arch: Architecture::Amd64,
-            os: OperatingSystem::Mac,
-            abi: Abi::Native,
-        });
+        let linux = BuildTags::new(
+            &Target {
+                arch: Architecture::Amd64,
+                os: OperatingSystem::Linux,
+                abi: Abi::Native,
+            },
+            &[],
+        );
+
+        let musl = BuildTags::new(
+            &Target {
+                arch: Architecture::Amd64,
+                os: OperatingSystem::Linux,
+                abi: Abi::Musl,
+            },
+            &[],
+        );
+
+        let bsd = BuildTags::new(
+            &Target {
+                arch: Architecture::Amd64,
+                os: OperatingSystem::Freebsd,
+                abi: Abi::Native,
+            },
+            &[],
+        );
+
+        let mac = BuildTags::new(
+            &Target {
+                arch: Architecture::Amd64,
+                os: OperatingSystem::Mac,
+                abi: Abi::Native,
+            },
+            &[],
+        );
 
         assert!(linux.is_defined("amd64"));
         assert!(linux.is_defined("linux"));
@@ -258,6 +303,10 @@ mod tests {
         assert!(linux.is_defined("gnu"));
         assert!(!linux.is_defined("bsd"));
 
+        assert!(musl.is_defined("linux"));
+        assert!(musl.is_defined("musl"));
+        assert!(!musl.is_defined("gnu"));
+
         assert!(bsd.is_defined("amd64"));
         assert!(bsd.is_defined("bsd"));
         assert!(bsd.is_defined("unix"));
@@ -269,4 +318,20 @@ mod tests {
         assert!(!mac.is_defined("bsd"));
         assert!(!mac.is_defined("linux"));
     }
+
+    #[test]
+    fn test_build_tags_with_defines() {
+        let tags = BuildTags::new(
+            &Target {
+                arch: Architecture::Amd64,
+                os: OperatingSystem::Linux,
+                abi: Abi::Native,
+            },
+            &["sqlite".to_string()],
+        );
+
+        assert!(tags.is_defined("sqlite"));
+        assert!(tags.is_defined("linux"));
+        assert!(!tags.is_defined("debug_logging"));
+    }
 }
@@ -35,6 +35,14 @@ fn mangled_method_name(db: &Database, method: MethodId) -> String {
     }
 }
 
+/// Returns the name of a C function exposed by a library.
+///
+/// The names are prefixed with the name of the main module, such that the
+/// functions of different libraries don't conflict with each other.
+pub(crate) fn library_symbol(db: &Database, name: &str) -> String {
+    format!("{}_{}", db.main_module().unwrap().normalized_name(), name)
+}
+
 /// A cache of mangled symbol names.
 pub(crate) struct SymbolNames {
     pub(crate) classes: HashMap<ClassId, String>,
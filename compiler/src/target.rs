@@ -63,6 +63,7 @@ impl OperatingSystem {
 pub(crate) enum Abi {
     Native,
     Gnu,
+    Musl,
 }
 
 impl Abi {
@@ -70,6 +71,7 @@ impl Abi {
         match input {
             "native" => Some(Abi::Native),
             "gnu" => Some(Abi::Gnu),
+            "musl" => Some(Abi::Musl),
             _ => None,
         }
     }
@@ -77,6 +79,8 @@ impl Abi {
     pub(crate) fn native() -> Abi {
         if cfg!(target_env = "gnu") {
             Abi::Gnu
+        } else if cfg!(target_env = "musl") {
+            Abi::Musl
         } else {
             Abi::Native
         }
@@ -95,13 +99,20 @@ pub struct Target {
 impl Target {
     /// Parses a target from a string.
     ///
-    /// If the target is invalid, a None is returned.
+    /// If the target is invalid, a None is returned. musl is only supported
+    /// when targeting Linux.
     pub(crate) fn from_str(input: &str) -> Option<Target> {
         let mut iter = input.split('-');
         let arch = iter.next().and_then(Architecture::from_str)?;
         let os = iter.next().and_then(OperatingSystem::from_str)?;
         let abi = iter.next().and_then(Abi::from_str)?;
 
+        if iter.next().is_some()
+            || (abi == Abi::Musl && os != OperatingSystem::Linux)
+        {
+            return None;
+        }
+
         Some(Target { arch, os, abi })
     }
 
@@ -124,6 +135,9 @@ impl Target {
         let os = match self.os {
             OperatingSystem::Freebsd => "unknown-freebsd",
             OperatingSystem::Mac => "apple-darwin",
+            OperatingSystem::Linux if self.abi == Abi::Musl => {
+                "unknown-linux-musl"
+            }
             OperatingSystem::Linux => "unknown-linux-gnu",
         };
 
@@ -152,9 +166,16 @@ impl Target {
                 _ => "native",
             },
             Abi::Gnu => "gnu",
+            Abi::Musl => "musl",
         }
     }
 
+    /// Returns `true` if executables for this target must be linked
+    /// statically.
+    pub(crate) fn static_only(&self) -> bool {
+        self.abi == Abi::Musl
+    }
+
     pub(crate) fn is_native(&self) -> bool {
         self == &Target::native()
     }
@@ -232,9 +253,21 @@ mod tests {
             )
         );
 
+        assert_eq!(
+            Target::from_str("amd64-linux-musl"),
+            Some(target(
+                Architecture::Amd64,
+                OperatingSystem::Linux,
+                Abi::Musl
+            ))
+        );
+
         assert_eq!(Target::from_str("bla-linux-native"), None);
         assert_eq!(Target::from_str("amd64-bla-native"), None);
         assert_eq!(Target::from_str("amd64-linux"), None);
+        assert_eq!(Target::from_str("amd64-linux-musl-foo"), None);
+        assert_eq!(Target::from_str("amd64-mac-musl"), None);
+        assert_eq!(Target::from_str("arm64-freebsd-musl"), None);
     }
 
     #[test]
@@ -252,6 +285,16 @@ mod tests {
                 .llvm_triple(),
             "x86_64-unknown-linux-gnu"
         );
+        assert_eq!(
+            target(Architecture::Amd64, OperatingSystem::Linux, Abi::Musl)
+                .llvm_triple(),
+            "x86_64-unknown-linux-musl"
+        );
+        assert_eq!(
+            target(Architecture::Arm64, OperatingSystem::Linux, Abi::Musl)
+                .llvm_triple(),
+            "aarch64-unknown-linux-musl"
+        );
         assert_eq!(
             target(Architecture::Amd64, OperatingSystem::Freebsd, Abi::Native)
                 .llvm_triple(),
@@ -271,6 +314,11 @@ mod tests {
                 .to_string(),
             "amd64-linux-gnu"
         );
+        assert_eq!(
+            target(Architecture::Arm64, OperatingSystem::Linux, Abi::Musl)
+                .to_string(),
+            "arm64-linux-musl"
+        );
         assert_eq!(
             target(Architecture::Amd64, OperatingSystem::Freebsd, Abi::Native)
                 .to_string(),
@@ -6,21 +6,35 @@ use crate::type_check::{
     define_type_bounds, CheckTypeSignature, DefineAndCheckTypeSignature,
     DefineTypeSignature, Rules, TypeScope,
 };
+use ::ast::source_location::SourceLocation;
 use std::path::PathBuf;
 use types::check::TypeChecker;
 use types::format::format_type;
 use types::{
     Class, ClassId, ClassInstance, ClassKind, Constant, Database, ModuleId,
-    Symbol, Trait, TraitId, TraitImplementation, TypeId, TypeRef, Visibility,
-    ARRAY_INTERNAL_NAME, ENUM_TAG_FIELD, ENUM_TAG_INDEX, FIELDS_LIMIT,
-    MAIN_CLASS, OPTION_CLASS, OPTION_MODULE, RESULT_CLASS, RESULT_MODULE,
-    VARIANTS_LIMIT,
+    Symbol, Trait, TraitId, TraitImplementation, TraitInstance, TypeId,
+    TypeRef, Visibility, ARRAY_INTERNAL_NAME, ENUM_TAG_FIELD, ENUM_TAG_INDEX,
+    EQ_METHOD, FIELDS_LIMIT, MAIN_CLASS, OPTION_CLASS, OPTION_MODULE,
+    RESULT_CLASS, RESULT_MODULE, STRING_MODULE, TO_STRING_METHOD,
+    TO_STRING_TRAIT, VARIANTS_LIMIT,
 };
 
 /// The maximum number of members a single variant can store. We subtract one as
 /// the tag is its own field.
 const MAX_MEMBERS: usize = FIELDS_LIMIT - 1;
 
+/// The name of the hidden symbol used to refer to `std.hash.Hasher` in derived
+/// implementations of `Hash`.
+const DERIVE_HASHER: &str = "$Hasher";
+
+/// The name of the type parameter used for the `Hasher` in derived
+/// implementations of `Hash`.
+const DERIVE_HASHER_PARAMETER: &str = "$H";
+
+/// The name of the hidden symbol used to refer to `std.fmt.Formatter` in
+/// derived implementations of `Format`.
+const DERIVE_FORMATTER: &str = "$Formatter";
+
 /// A compiler pass that defines classes and traits.
 ///
 /// This pass _only_ defines the types, it doesn't define their type parameters,
@@ -112,6 +126,24 @@ impl<'a> DefineTypes<'a> {
                 vis,
                 module,
             ),
+            hir::ClassKind::Inline => {
+                if !node.type_parameters.is_empty() {
+                    self.state.diagnostics.error(
+                        DiagnosticId::InvalidType,
+                        "inline classes can't define type parameters",
+                        self.file(),
+                        node.location.clone(),
+                    );
+                }
+
+                Class::alloc(
+                    self.db_mut(),
+                    name.clone(),
+                    ClassKind::Inline,
+                    vis,
+                    module,
+                )
+            }
         };
 
         if self.module.symbol_exists(self.db(), &name) {
@@ -317,6 +349,16 @@ impl<'a> ImplementTraits<'a> {
             }
 
             if instance.instance_of() == self.drop_trait {
+                if class_id.kind(self.db()).is_inline() {
+                    self.state.diagnostics.error(
+                        DiagnosticId::InvalidImplementation,
+                        "the trait 'std::drop::Drop' can't be implemented \
+                        for inline classes",
+                        self.file(),
+                        node.location.clone(),
+                    );
+                }
+
                 if !node.bounds.is_empty() {
                     self.state.diagnostics.error(
                         DiagnosticId::InvalidImplementation,
@@ -543,6 +585,7 @@ impl<'a> DefineFields<'a> {
         let class_id = node.class_id.unwrap();
         let mut id: usize = 0;
         let is_enum = class_id.kind(self.db()).is_enum();
+        let is_inline = class_id.kind(self.db()).is_inline();
         let scope = TypeScope::new(self.module, TypeId::Class(class_id), None);
         let is_main = self.main_module && node.name.name == MAIN_CLASS;
 
@@ -610,6 +653,26 @@ impl<'a> DefineFields<'a> {
                 );
             }
 
+            if is_inline && typ.class_id(self.db()) == Some(class_id) {
+                self.state.diagnostics.error(
+                    DiagnosticId::InvalidType,
+                    "inline classes can't store values of their own type",
+                    self.file(),
+                    node.value_type.location().clone(),
+                );
+            } else if is_inline && !typ.allow_in_inline_class(self.db()) {
+                self.state.diagnostics.error(
+                    DiagnosticId::InvalidType,
+                    format!(
+                        "values of type '{}' can't be stored in inline \
+                        classes",
+                        format_type(self.db(), typ)
+                    ),
+                    self.file(),
+                    node.value_type.location().clone(),
+                );
+            }
+
             let module = self.module;
             let field =
                 class_id.new_field(self.db_mut(), name, id, typ, vis, module);
@@ -980,6 +1043,884 @@ impl<'a> InsertPrelude<'a> {
     }
 }
 
+/// The traits for which an implementation can be derived.
+#[derive(Copy, Clone)]
+enum Derive {
+    Equal,
+    Clone,
+    Hash,
+    Format,
+    ToString,
+}
+
+impl Derive {
+    fn new(db: &Database, id: TraitId) -> Option<Derive> {
+        let module = id.module(db).name(db).as_str();
+
+        match (module, id.name(db).as_str()) {
+            ("std.cmp", "Equal") => Some(Derive::Equal),
+            ("std.clone", "Clone") => Some(Derive::Clone),
+            ("std.hash", "Hash") => Some(Derive::Hash),
+            ("std.fmt", "Format") => Some(Derive::Format),
+            (STRING_MODULE, TO_STRING_TRAIT) => Some(Derive::ToString),
+            _ => None,
+        }
+    }
+
+    fn is_generic(self) -> bool {
+        matches!(self, Derive::Equal | Derive::Clone)
+    }
+}
+
+/// A type that generates the HIR of a derived trait implementation.
+///
+/// The generated code is the same as the code one would write by hand, such as
+/// `@a == other.a and @b == other.b` for `Equal`.
+struct DeriveGenerator {
+    class_name: String,
+    type_parameters: Vec<(String, bool)>,
+    fields: Vec<String>,
+    variants: Vec<(String, usize)>,
+    is_enum: bool,
+    location: SourceLocation,
+}
+
+impl DeriveGenerator {
+    fn new(node: &hir::DefineClass, is_enum: bool) -> DeriveGenerator {
+        let mut fields = Vec::new();
+        let mut variants = Vec::new();
+
+        for expr in &node.body {
+            match expr {
+                hir::ClassExpression::Field(n) => {
+                    fields.push(n.name.name.clone());
+                }
+                hir::ClassExpression::Variant(n) => {
+                    variants.push((n.name.name.clone(), n.members.len()));
+                }
+                _ => {}
+            }
+        }
+
+        DeriveGenerator {
+            class_name: node.name.name.clone(),
+            type_parameters: node
+                .type_parameters
+                .iter()
+                .map(|n| (n.name.name.clone(), n.mutable))
+                .collect(),
+            fields,
+            variants,
+            is_enum,
+            location: node.location.clone(),
+        }
+    }
+
+    fn implementation(
+        &mut self,
+        derive: Derive,
+        trait_name: &hir::TypeName,
+    ) -> hir::ImplementTrait {
+        self.location = trait_name.location.clone();
+
+        let method = match derive {
+            Derive::Equal => self.equal(),
+            Derive::Clone => self.clone(),
+            Derive::Hash => self.hash(),
+            Derive::Format => self.format(),
+            Derive::ToString => self.to_string(),
+        };
+        let self_type = if derive.is_generic() {
+            vec![self.self_type()]
+        } else {
+            Vec::new()
+        };
+        let bounds = self
+            .type_parameters
+            .iter()
+            .map(|(name, mutable)| {
+                let args = if derive.is_generic() {
+                    vec![self.named_type(name, Vec::new())]
+                } else {
+                    Vec::new()
+                };
+
+                hir::TypeBound {
+                    name: self.constant(name),
+                    requirements: vec![self.trait_name(trait_name, args)],
+                    mutable: *mutable,
+                    location: self.location.clone(),
+                }
+            })
+            .collect();
+
+        hir::ImplementTrait {
+            trait_name: self.trait_name(trait_name, self_type),
+            class_name: self.constant(&self.class_name),
+            body: vec![method],
+            location: self.location.clone(),
+            bounds,
+            trait_instance: None,
+            class_instance: None,
+        }
+    }
+
+    fn equal(&self) -> hir::DefineInstanceMethod {
+        let body = if self.is_enum {
+            let cases = self
+                .variants
+                .iter()
+                .map(|(name, members)| {
+                    let mut cases = vec![self.case(
+                        self.variant_pattern(name, *members, "b"),
+                        vec![self.all((0..*members).map(|i| {
+                            self.call(
+                                Some(self.variable(&format!("a{}", i))),
+                                EQ_METHOD,
+                                vec![self.variable(&format!("b{}", i))],
+                            )
+                        }))],
+                    )];
+
+                    if self.variants.len() > 1 {
+                        cases.push(self.case(
+                            hir::Pattern::Wildcard(Box::new(
+                                hir::WildcardPattern {
+                                    location: self.location.clone(),
+                                },
+                            )),
+                            vec![hir::Expression::False(Box::new(
+                                hir::False {
+                                    resolved_type: TypeRef::Unknown,
+                                    location: self.location.clone(),
+                                },
+                            ))],
+                        ));
+                    }
+
+                    self.case(
+                        self.variant_pattern(name, *members, "a"),
+                        vec![self
+                            .match_expression(self.variable("other"), cases)],
+                    )
+                })
+                .collect();
+
+            self.match_expression(self.self_object(), cases)
+        } else {
+            self.all(self.fields.iter().map(|name| {
+                self.call(
+                    Some(self.field(name)),
+                    EQ_METHOD,
+                    vec![self.call(
+                        Some(self.variable("other")),
+                        name,
+                        Vec::new(),
+                    )],
+                )
+            }))
+        };
+
+        self.method(
+            EQ_METHOD,
+            Vec::new(),
+            vec![self.argument(
+                "other",
+                hir::Type::Ref(Box::new(hir::ReferenceType {
+                    type_reference: hir::ReferrableType::Named(Box::new(
+                        self.type_name(&self.class_name, self.type_arguments()),
+                    )),
+                    location: self.location.clone(),
+                })),
+            )],
+            Some(self.named_type("Bool", Vec::new())),
+            vec![body],
+        )
+    }
+
+    fn clone(&self) -> hir::DefineInstanceMethod {
+        let body = if self.is_enum {
+            let cases = self
+                .variants
+                .iter()
+                .map(|(name, members)| {
+                    let args = (0..*members)
+                        .map(|i| {
+                            self.call(
+                                Some(self.variable(&format!("a{}", i))),
+                                "clone",
+                                Vec::new(),
+                            )
+                        })
+                        .collect();
+
+                    self.case(
+                        self.variant_pattern(name, *members, "a"),
+                        vec![self.call(
+                            Some(hir::Expression::ConstantRef(Box::new(
+                                hir::ConstantRef {
+                                    kind: types::ConstantKind::Unknown,
+                                    source: None,
+                                    name: self.class_name.clone(),
+                                    resolved_type: TypeRef::Unknown,
+                                    location: self.location.clone(),
+                                },
+                            ))),
+                            name,
+                            args,
+                        )],
+                    )
+                })
+                .collect();
+
+            self.match_expression(self.self_object(), cases)
+        } else {
+            let fields = self
+                .fields
+                .iter()
+                .map(|name| hir::AssignClassLiteralField {
+                    resolved_type: TypeRef::Unknown,
+                    field_id: None,
+                    field: hir::Field {
+                        name: name.clone(),
+                        location: self.location.clone(),
+                    },
+                    value: self.call(
+                        Some(self.field(name)),
+                        "clone",
+                        Vec::new(),
+                    ),
+                    location: self.location.clone(),
+                })
+                .collect();
+
+            hir::Expression::ClassLiteral(Box::new(hir::ClassLiteral {
+                class_id: None,
+                resolved_type: TypeRef::Unknown,
+                class_name: self.constant(&self.class_name),
+                fields,
+                location: self.location.clone(),
+            }))
+        };
+
+        self.method(
+            "clone",
+            Vec::new(),
+            Vec::new(),
+            Some(self.self_type()),
+            vec![body],
+        )
+    }
+
+    fn hash(&self) -> hir::DefineInstanceMethod {
+        let hash = |value| {
+            self.call(Some(value), "hash", vec![self.variable("hasher")])
+        };
+        let body = if self.is_enum {
+            let cases = self
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(index, (name, members))| {
+                    let mut body = vec![self.call(
+                        Some(self.variable("hasher")),
+                        "write",
+                        vec![hir::Expression::Int(Box::new(hir::IntLiteral {
+                            value: index as i64,
+                            resolved_type: TypeRef::Unknown,
+                            location: self.location.clone(),
+                        }))],
+                    )];
+
+                    for i in 0..*members {
+                        body.push(hash(self.variable(&format!("a{}", i))));
+                    }
+
+                    self.case(self.variant_pattern(name, *members, "a"), body)
+                })
+                .collect();
+
+            vec![self.match_expression(self.self_object(), cases)]
+        } else {
+            self.fields.iter().map(|name| hash(self.field(name))).collect()
+        };
+
+        self.method(
+            "hash",
+            vec![hir::TypeParameter {
+                type_parameter_id: None,
+                name: self.constant(DERIVE_HASHER_PARAMETER),
+                requirements: vec![self.type_name(DERIVE_HASHER, Vec::new())],
+                mutable: true,
+                location: self.location.clone(),
+            }],
+            vec![self.argument(
+                "hasher",
+                hir::Type::Mut(Box::new(hir::ReferenceType {
+                    type_reference: hir::ReferrableType::Named(Box::new(
+                        self.type_name(DERIVE_HASHER_PARAMETER, Vec::new()),
+                    )),
+                    location: self.location.clone(),
+                })),
+            )],
+            None,
+            body,
+        )
+    }
+
+    fn format(&self) -> hir::DefineInstanceMethod {
+        let body = if self.is_enum {
+            let cases = self
+                .variants
+                .iter()
+                .map(|(name, members)| {
+                    let mut fmt = self.call(
+                        Some(self.variable("formatter")),
+                        "tuple",
+                        vec![self.string(name)],
+                    );
+
+                    for i in 0..*members {
+                        fmt = self.call(
+                            Some(fmt),
+                            "field",
+                            vec![self.variable(&format!("a{}", i))],
+                        );
+                    }
+
+                    self.case(
+                        self.variant_pattern(name, *members, "a"),
+                        vec![self.call(Some(fmt), "finish", Vec::new())],
+                    )
+                })
+                .collect();
+
+            self.match_expression(self.self_object(), cases)
+        } else {
+            let mut fmt = self.call(
+                Some(self.variable("formatter")),
+                "object",
+                vec![self.string(&self.class_name)],
+            );
+
+            for name in &self.fields {
+                fmt = self.call(
+                    Some(fmt),
+                    "field",
+                    vec![self.string(name), self.field(name)],
+                );
+            }
+
+            self.call(Some(fmt), "finish", Vec::new())
+        };
+
+        self.method(
+            "fmt",
+            Vec::new(),
+            vec![self.argument(
+                "formatter",
+                hir::Type::Mut(Box::new(hir::ReferenceType {
+                    type_reference: hir::ReferrableType::Named(Box::new(
+                        self.type_name(DERIVE_FORMATTER, Vec::new()),
+                    )),
+                    location: self.location.clone(),
+                })),
+            )],
+            None,
+            vec![body],
+        )
+    }
+
+    fn to_string(&self) -> hir::DefineInstanceMethod {
+        let body = if self.is_enum {
+            let cases = self
+                .variants
+                .iter()
+                .map(|(name, members)| {
+                    let mut values = vec![self.text(name)];
+
+                    for i in 0..*members {
+                        values.push(self.text(if i == 0 { "(" } else { ", " }));
+                        values.push(
+                            self.interpolate(self.variable(&format!("a{}", i))),
+                        );
+                    }
+
+                    if *members > 0 {
+                        values.push(self.text(")"));
+                    }
+
+                    self.case(
+                        self.variant_pattern(name, *members, "a"),
+                        vec![self.string_literal(values)],
+                    )
+                })
+                .collect();
+
+            self.match_expression(self.self_object(), cases)
+        } else {
+            let mut values = vec![self.text(&self.class_name)];
+
+            for (i, name) in self.fields.iter().enumerate() {
+                let start = if i == 0 { " { @" } else { ", @" };
+
+                values.push(self.text(&format!("{}{} = ", start, name)));
+                values.push(self.interpolate(self.field(name)));
+            }
+
+            if !self.fields.is_empty() {
+                values.push(self.text(" }"));
+            }
+
+            self.string_literal(values)
+        };
+
+        self.method(
+            TO_STRING_METHOD,
+            Vec::new(),
+            Vec::new(),
+            Some(self.named_type("String", Vec::new())),
+            vec![body],
+        )
+    }
+
+    fn method(
+        &self,
+        name: &str,
+        type_parameters: Vec<hir::TypeParameter>,
+        arguments: Vec<hir::MethodArgument>,
+        return_type: Option<hir::Type>,
+        body: Vec<hir::Expression>,
+    ) -> hir::DefineInstanceMethod {
+        hir::DefineInstanceMethod {
+            public: true,
+            tail: false,
+            kind: hir::MethodKind::Regular,
+            name: self.identifier(name),
+            type_parameters,
+            arguments,
+            return_type,
+            body,
+            location: self.location.clone(),
+            method_id: None,
+        }
+    }
+
+    fn argument(
+        &self,
+        name: &str,
+        value_type: hir::Type,
+    ) -> hir::MethodArgument {
+        hir::MethodArgument {
+            name: self.identifier(name),
+            value_type,
+            location: self.location.clone(),
+        }
+    }
+
+    fn trait_name(
+        &self,
+        node: &hir::TypeName,
+        arguments: Vec<hir::Type>,
+    ) -> hir::TypeName {
+        hir::TypeName {
+            source: node.source.clone(),
+            resolved_type: TypeRef::Unknown,
+            name: node.name.clone(),
+            arguments,
+            location: self.location.clone(),
+        }
+    }
+
+    fn type_arguments(&self) -> Vec<hir::Type> {
+        self.type_parameters
+            .iter()
+            .map(|(name, _)| self.named_type(name, Vec::new()))
+            .collect()
+    }
+
+    fn self_type(&self) -> hir::Type {
+        self.named_type(&self.class_name, self.type_arguments())
+    }
+
+    fn named_type(&self, name: &str, arguments: Vec<hir::Type>) -> hir::Type {
+        hir::Type::Named(Box::new(self.type_name(name, arguments)))
+    }
+
+    fn type_name(
+        &self,
+        name: &str,
+        arguments: Vec<hir::Type>,
+    ) -> hir::TypeName {
+        hir::TypeName {
+            source: None,
+            resolved_type: TypeRef::Unknown,
+            name: self.constant(name),
+            arguments,
+            location: self.location.clone(),
+        }
+    }
+
+    fn variant_pattern(
+        &self,
+        name: &str,
+        members: usize,
+        prefix: &str,
+    ) -> hir::Pattern {
+        let values = (0..members)
+            .map(|i| {
+                hir::Pattern::Identifier(Box::new(hir::IdentifierPattern {
+                    variable_id: None,
+                    name: self.identifier(&format!("{}{}", prefix, i)),
+                    mutable: false,
+                    value_type: None,
+                    location: self.location.clone(),
+                }))
+            })
+            .collect();
+
+        hir::Pattern::Variant(Box::new(hir::VariantPattern {
+            variant_id: None,
+            name: self.constant(name),
+            values,
+            location: self.location.clone(),
+        }))
+    }
+
+    fn case(
+        &self,
+        pattern: hir::Pattern,
+        body: Vec<hir::Expression>,
+    ) -> hir::MatchCase {
+        hir::MatchCase {
+            variable_ids: Vec::new(),
+            pattern,
+            guard: None,
+            body,
+            location: self.location.clone(),
+        }
+    }
+
+    fn match_expression(
+        &self,
+        expression: hir::Expression,
+        cases: Vec<hir::MatchCase>,
+    ) -> hir::Expression {
+        hir::Expression::Match(Box::new(hir::Match {
+            resolved_type: TypeRef::Unknown,
+            expression,
+            cases,
+            location: self.location.clone(),
+            write_result: true,
+        }))
+    }
+
+    /// Combines the expressions using `and`, or returns `true` if there are no
+    /// expressions.
+    fn all(
+        &self,
+        mut expressions: impl Iterator<Item = hir::Expression>,
+    ) -> hir::Expression {
+        let first = if let Some(expr) = expressions.next() {
+            expr
+        } else {
+            return hir::Expression::True(Box::new(hir::True {
+                resolved_type: TypeRef::Unknown,
+                location: self.location.clone(),
+            }));
+        };
+
+        expressions.fold(first, |left, right| {
+            hir::Expression::And(Box::new(hir::And {
+                resolved_type: TypeRef::Unknown,
+                left,
+                right,
+                location: self.location.clone(),
+            }))
+        })
+    }
+
+    fn call(
+        &self,
+        receiver: Option<hir::Expression>,
+        name: &str,
+        arguments: Vec<hir::Expression>,
+    ) -> hir::Expression {
+        hir::Expression::Call(Box::new(hir::Call {
+            kind: types::CallKind::Unknown,
+            receiver,
+            name: self.identifier(name),
+            arguments: arguments
+                .into_iter()
+                .map(|n| hir::Argument::Positional(Box::new(n)))
+                .collect(),
+            location: self.location.clone(),
+        }))
+    }
+
+    fn self_object(&self) -> hir::Expression {
+        hir::Expression::SelfObject(Box::new(hir::SelfObject {
+            resolved_type: TypeRef::Unknown,
+            location: self.location.clone(),
+        }))
+    }
+
+    fn field(&self, name: &str) -> hir::Expression {
+        hir::Expression::FieldRef(Box::new(hir::FieldRef {
+            field_id: None,
+            name: name.to_string(),
+            resolved_type: TypeRef::Unknown,
+            location: self.location.clone(),
+        }))
+    }
+
+    fn variable(&self, name: &str) -> hir::Expression {
+        hir::Expression::IdentifierRef(Box::new(hir::IdentifierRef {
+            name: name.to_string(),
+            kind: types::IdentifierKind::Unknown,
+            location: self.location.clone(),
+        }))
+    }
+
+    fn string(&self, value: &str) -> hir::Expression {
+        self.string_literal(vec![self.text(value)])
+    }
+
+    fn string_literal(&self, values: Vec<hir::StringValue>) -> hir::Expression {
+        hir::Expression::String(Box::new(hir::StringLiteral {
+            values,
+            resolved_type: TypeRef::Unknown,
+            location: self.location.clone(),
+        }))
+    }
+
+    fn text(&self, value: &str) -> hir::StringValue {
+        hir::StringValue::Text(Box::new(hir::StringText {
+            value: value.to_string(),
+            location: self.location.clone(),
+        }))
+    }
+
+    fn interpolate(&self, value: hir::Expression) -> hir::StringValue {
+        hir::StringValue::Expression(Box::new(hir::Call {
+            kind: types::CallKind::Unknown,
+            receiver: Some(value),
+            name: self.identifier(TO_STRING_METHOD),
+            arguments: Vec::new(),
+            location: self.location.clone(),
+        }))
+    }
+
+    fn identifier(&self, name: &str) -> hir::Identifier {
+        hir::Identifier {
+            name: name.to_string(),
+            location: self.location.clone(),
+        }
+    }
+
+    fn constant(&self, name: &str) -> hir::Constant {
+        hir::Constant {
+            name: name.to_string(),
+            location: self.location.clone(),
+        }
+    }
+}
+
+/// A compiler pass that generates the trait implementations requested using
+/// `derive(...)`.
+///
+/// The implementations are added to the module as regular `impl` expressions,
+/// such that the rest of the type checker processes them the same way as
+/// hand-written implementations. Because fields and variants aren't defined
+/// yet at this point, checking if their types implement the derived traits is
+/// done separately by the `CheckDerivedTraits` pass.
+pub(crate) struct DeriveTraits<'a> {
+    state: &'a mut State,
+    module: ModuleId,
+}
+
+impl<'a> DeriveTraits<'a> {
+    pub(crate) fn run_all(
+        state: &'a mut State,
+        modules: &mut Vec<hir::Module>,
+    ) -> bool {
+        for module in modules {
+            DeriveTraits { state, module: module.module_id }.run(module);
+        }
+
+        !state.diagnostics.has_errors()
+    }
+
+    fn run(mut self, module: &mut hir::Module) {
+        let mut impls = Vec::new();
+
+        for expr in module.expressions.iter_mut() {
+            if let hir::TopLevelExpression::Class(ref mut node) = expr {
+                self.define_class(node, &mut impls);
+            }
+        }
+
+        module.expressions.extend(
+            impls
+                .into_iter()
+                .map(|n| hir::TopLevelExpression::Implement(Box::new(n))),
+        );
+    }
+
+    fn define_class(
+        &mut self,
+        node: &mut hir::DefineClass,
+        impls: &mut Vec<hir::ImplementTrait>,
+    ) {
+        if node.derive.is_empty() {
+            return;
+        }
+
+        let kind = node.class_id.unwrap().kind(self.db());
+
+        if kind.is_async() {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidImplementation,
+                "traits can't be derived for async classes",
+                self.file(),
+                node.location.clone(),
+            );
+
+            return;
+        }
+
+        let mut generator = DeriveGenerator::new(node, kind.is_enum());
+
+        for name in &mut node.derive {
+            let (derive, trait_id) = if let Some(v) = self.derived_trait(name) {
+                v
+            } else {
+                continue;
+            };
+
+            match derive {
+                Derive::Hash => {
+                    self.import_hidden(trait_id, "Hasher", DERIVE_HASHER);
+                }
+                Derive::Format => {
+                    self.import_hidden(trait_id, "Formatter", DERIVE_FORMATTER);
+                }
+                _ => {}
+            }
+
+            impls.push(generator.implementation(derive, name));
+        }
+    }
+
+    fn derived_trait(
+        &mut self,
+        node: &mut hir::TypeName,
+    ) -> Option<(Derive, TraitId)> {
+        let name = &node.name.name;
+        let symbol = if let Some(source) = node.source.as_ref() {
+            if let Some(Symbol::Module(module)) =
+                self.module.symbol(self.db(), &source.name)
+            {
+                module.symbol(self.db(), name)
+            } else {
+                self.state.diagnostics.error(
+                    DiagnosticId::InvalidSymbol,
+                    format!("the symbol '{}' isn't a module", source.name),
+                    self.file(),
+                    source.location.clone(),
+                );
+
+                return None;
+            }
+        } else {
+            self.module.symbol(self.db(), name)
+        };
+
+        let id = match symbol {
+            Some(Symbol::Trait(id)) => id,
+            Some(_) => {
+                self.state.diagnostics.error(
+                    DiagnosticId::InvalidType,
+                    format!("'{}' isn't a trait", name),
+                    self.file(),
+                    node.location.clone(),
+                );
+
+                return None;
+            }
+            None => {
+                self.state.diagnostics.undefined_symbol(
+                    name,
+                    self.file(),
+                    node.location.clone(),
+                );
+
+                return None;
+            }
+        };
+
+        let derive = if let Some(v) = Derive::new(self.db(), id) {
+            v
+        } else {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidType,
+                format!(
+                    "the trait '{}' can't be derived, only the traits \
+                    'Equal', 'Clone', 'Hash', 'Format' and 'ToString' can be \
+                    derived",
+                    name
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            return None;
+        };
+
+        if !node.arguments.is_empty() {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidType,
+                "type arguments can't be specified for derived traits",
+                self.file(),
+                node.location.clone(),
+            );
+
+            return None;
+        }
+
+        node.resolved_type =
+            TypeRef::Owned(TypeId::TraitInstance(TraitInstance::new(id)));
+
+        Some((derive, id))
+    }
+
+    /// Imports a type from the module that defines the derived trait, using a
+    /// name that can't conflict with any user-defined symbols.
+    fn import_hidden(&mut self, trait_id: TraitId, name: &str, alias: &str) {
+        if self.module.symbol_exists(self.db(), alias) {
+            return;
+        }
+
+        let module = trait_id.module(self.db());
+
+        if let Some(symbol) = module.symbol(self.db(), name) {
+            self.module.new_symbol(self.db_mut(), alias.to_string(), symbol);
+        }
+    }
+
+    fn file(&self) -> PathBuf {
+        self.module.file(self.db())
+    }
+
+    fn db(&self) -> &Database {
+        &self.state.db
+    }
+
+    fn db_mut(&mut self) -> &mut Database {
+        &mut self.state.db
+    }
+}
+
 /// A compiler pass that defines the variants for an enum class.
 pub(crate) struct DefineVariants<'a> {
     state: &'a mut State,
@@ -1146,6 +2087,133 @@ impl<'a> DefineVariants<'a> {
     }
 }
 
+/// A compiler pass that checks if the fields and variant members of a class
+/// implement the traits derived for the class.
+pub(crate) struct CheckDerivedTraits<'a> {
+    state: &'a mut State,
+    module: ModuleId,
+}
+
+impl<'a> CheckDerivedTraits<'a> {
+    pub(crate) fn run_all(
+        state: &'a mut State,
+        modules: &mut Vec<hir::Module>,
+    ) -> bool {
+        for module in modules {
+            CheckDerivedTraits { state, module: module.module_id }.run(module);
+        }
+
+        !state.diagnostics.has_errors()
+    }
+
+    fn run(mut self, module: &hir::Module) {
+        for expr in &module.expressions {
+            if let hir::TopLevelExpression::Class(ref node) = expr {
+                self.check_class(node);
+            }
+        }
+    }
+
+    fn check_class(&mut self, node: &hir::DefineClass) {
+        let class_id = node.class_id.unwrap();
+
+        for name in &node.derive {
+            let trait_id = if let TypeRef::Owned(TypeId::TraitInstance(ins)) =
+                name.resolved_type
+            {
+                ins.instance_of()
+            } else {
+                continue;
+            };
+
+            for expr in &node.body {
+                match expr {
+                    hir::ClassExpression::Field(n) => {
+                        let typ = if let Some(id) = n.field_id {
+                            id.value_type(self.db())
+                        } else {
+                            continue;
+                        };
+
+                        self.check_type(
+                            typ,
+                            trait_id,
+                            format!("field '{}'", n.name.name),
+                            n.value_type.location(),
+                        );
+                    }
+                    hir::ClassExpression::Variant(n) => {
+                        let name = &n.name.name;
+                        let types = if let Some(id) =
+                            class_id.variant(self.db(), name)
+                        {
+                            id.members(self.db())
+                        } else {
+                            continue;
+                        };
+
+                        for (typ, node) in types.into_iter().zip(&n.members) {
+                            self.check_type(
+                                typ,
+                                trait_id,
+                                format!("variant '{}'", name),
+                                node.location(),
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn check_type(
+        &mut self,
+        typ: TypeRef,
+        trait_id: TraitId,
+        owner: String,
+        location: &SourceLocation,
+    ) {
+        let valid = match typ.type_id(self.db()) {
+            // Type parameters are bound to the derived trait by the generated
+            // implementation.
+            Ok(TypeId::TypeParameter(_) | TypeId::RigidTypeParameter(_)) => {
+                true
+            }
+            Ok(TypeId::ClassInstance(ins)) => ins
+                .instance_of()
+                .trait_implementation(self.db(), trait_id)
+                .is_some(),
+            _ => false,
+        };
+
+        if valid {
+            return;
+        }
+
+        self.state.diagnostics.error(
+            DiagnosticId::MissingTrait,
+            format!(
+                "the trait '{}' can't be derived, as the type '{}' of {} \
+                doesn't implement it",
+                trait_id.name(self.db()),
+                format_type(self.db(), typ),
+                owner,
+            ),
+            self.file(),
+            location.clone(),
+        );
+    }
+
+    fn file(&self) -> PathBuf {
+        self.module.file(self.db())
+    }
+
+    fn db(&self) -> &Database {
+        &self.state.db
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1256,6 +2324,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_define_inline_class() {
+        let mut state = State::new(Config::new());
+        let mut modules = parse(&mut state, "class inline A {}");
+
+        assert!(DefineTypes::run_all(&mut state, &mut modules));
+
+        let id = ClassId(FIRST_USER_CLASS_ID + 1);
+
+        assert_eq!(state.diagnostics.iter().count(), 0);
+        assert!(id.kind(&state.db).is_inline());
+    }
+
+    #[test]
+    fn test_define_inline_class_with_type_parameters() {
+        let mut state = State::new(Config::new());
+        let mut modules = parse(&mut state, "class inline A[T] {}");
+
+        assert!(!DefineTypes::run_all(&mut state, &mut modules));
+
+        let error = state.diagnostics.iter().next().unwrap();
+
+        assert_eq!(error.id(), DiagnosticId::InvalidType);
+    }
+
     #[test]
     fn test_define_empty_enum_class() {
         let mut state = State::new(Config::new());
@@ -1266,6 +2359,61 @@ mod tests {
         assert_eq!(state.diagnostics.iter().count(), 1);
     }
 
+    #[test]
+    fn test_derive_traits() {
+        let mut state = State::new(Config::new());
+        let ast = Parser::new(
+            "trait Equal[T] {}\nclass A[T] derive(Equal) {}".into(),
+            "test.inko".into(),
+        )
+        .parse()
+        .expect("failed to parse the input");
+        let module = ParsedModule { name: ModuleName::new("std.cmp"), ast };
+        let mut modules = hir::LowerToHir::run_all(&mut state, vec![module]);
+
+        assert!(DefineTypes::run_all(&mut state, &mut modules));
+        assert!(DeriveTraits::run_all(&mut state, &mut modules));
+
+        let imp = match modules[0].expressions.last() {
+            Some(hir::TopLevelExpression::Implement(node)) => node,
+            _ => panic!("expected an ImplementTrait node"),
+        };
+
+        assert_eq!(imp.class_name.name, "A");
+        assert_eq!(imp.trait_name.name.name, "Equal");
+        assert_eq!(imp.trait_name.arguments.len(), 1);
+        assert_eq!(imp.bounds.len(), 1);
+        assert_eq!(imp.body[0].name.name, "==");
+    }
+
+    #[test]
+    fn test_derive_invalid_trait() {
+        let mut state = State::new(Config::new());
+        let mut modules =
+            parse(&mut state, "trait Equal[T] {} class A derive(Equal) {}");
+
+        assert!(DefineTypes::run_all(&mut state, &mut modules));
+        assert!(!DeriveTraits::run_all(&mut state, &mut modules));
+
+        let error = state.diagnostics.iter().next().unwrap();
+
+        assert_eq!(error.id(), DiagnosticId::InvalidType);
+        assert_eq!(error.location(), &cols(34, 38));
+    }
+
+    #[test]
+    fn test_derive_for_async_class() {
+        let mut state = State::new(Config::new());
+        let mut modules = parse(&mut state, "class async A derive(B) {}");
+
+        assert!(DefineTypes::run_all(&mut state, &mut modules));
+        assert!(!DeriveTraits::run_all(&mut state, &mut modules));
+
+        let error = state.diagnostics.iter().next().unwrap();
+
+        assert_eq!(error.id(), DiagnosticId::InvalidImplementation);
+    }
+
     #[test]
     fn test_define_trait() {
         let mut state = State::new(Config::new());
@@ -1786,6 +2934,21 @@ mod tests {
         assert_eq!(error.location(), &cols(27, 30));
     }
 
+    #[test]
+    fn test_define_inline_class_field_with_invalid_type() {
+        let mut state = State::new(Config::new());
+        let mut modules = parse(&mut state, "class inline A { let @a: A }");
+
+        DefineTypes::run_all(&mut state, &mut modules);
+
+        assert!(!DefineFields::run_all(&mut state, &mut modules));
+
+        let error = state.diagnostics.iter().next().unwrap();
+
+        assert_eq!(error.id(), DiagnosticId::InvalidType);
+        assert_eq!(error.location(), &cols(26, 26));
+    }
+
     #[test]
     fn test_define_trait_type_parameter() {
         let mut state = State::new(Config::new());
@@ -11,12 +11,12 @@ use types::check::{Environment, TypeChecker};
 use types::format::{format_type, format_type_with_arguments};
 use types::resolve::TypeResolver;
 use types::{
-    Block, BuiltinCallInfo, CallInfo, CallKind, ClassId, ClassInstance,
-    Closure, ClosureCallInfo, ClosureId, ConstantKind, ConstantPatternKind,
-    Database, FieldId, FieldInfo, IdentifierKind, MethodId, MethodKind,
-    MethodLookup, MethodSource, ModuleId, Receiver, Symbol, ThrowKind, TraitId,
-    TraitInstance, TypeArguments, TypeBounds, TypeId, TypeRef, Variable,
-    VariableId, CALL_METHOD, DEREF_POINTER_FIELD,
+    Block, BuiltinCallInfo, BuiltinFunction, CallInfo, CallKind, ClassId,
+    ClassInstance, Closure, ClosureCallInfo, ClosureId, ConstantKind,
+    ConstantPatternKind, Database, FieldId, FieldInfo, ForeignType,
+    IdentifierKind, MethodId, MethodKind, MethodLookup, MethodSource, ModuleId,
+    Receiver, Symbol, ThrowKind, TypeArguments, TypeBounds, TypeId, TypeRef,
+    Variable, VariableId, CALL_METHOD, DEREF_POINTER_FIELD,
 };
 
 const IGNORE_VARIABLE: &str = "_";
@@ -464,7 +464,13 @@ impl MethodCall {
     ) {
         let given = argument.cast_according_to(expected, &state.db);
 
-        if self.require_sendable || given.is_uni_ref(&state.db) {
+        // Inline values passed to a `uni T` are checked explicitly, as
+        // resolving `uni T` to an inline type discards the `uni` ownership.
+        if self.require_sendable
+            || given.is_uni_ref(&state.db)
+            || (expected.is_uni(&state.db)
+                && given.is_inline_class_instance(&state.db))
+        {
             self.check_sendable.push((given, location.clone()));
         }
 
@@ -520,7 +526,13 @@ impl MethodCall {
         // we can't "leak" a reference through the arguments (because they too
         // are immutable), and the returned value can't refer to `self` because
         // we don't allow references anywhere in the type or its sub types.
-        let ret_sendable = if ref_safe {
+        //
+        // Async methods return their values through a future created by the
+        // caller, and the type-checker already ensures the value itself is
+        // sendable.
+        let ret_sendable = if self.method.is_async(&state.db) {
+            true
+        } else if ref_safe {
             self.return_type.is_sendable_output(&state.db)
         } else {
             self.return_type.is_sendable(&state.db)
@@ -549,6 +561,11 @@ impl MethodCall {
         )
         .with_rigid(rigid)
         .resolve(raw);
+        let typ = if self.method.returns_future(&state.db) {
+            TypeRef::future_type(&mut state.db, typ)
+        } else {
+            typ
+        };
 
         self.return_type = typ;
         typ
@@ -705,10 +722,7 @@ impl<'a> Expressions<'a> {
 
     fn define_trait(&mut self, node: &mut hir::DefineTrait) {
         self.verify_type_parameter_requirements(&node.type_parameters);
-        self.verify_required_traits(
-            &node.requirements,
-            node.trait_id.unwrap().required_traits(self.db()),
-        );
+        self.verify_required_traits(&node.requirements);
 
         for node in &mut node.body {
             if let hir::TraitExpression::InstanceMethod(ref mut n) = node {
@@ -792,7 +806,8 @@ impl<'a> Expressions<'a> {
         let stype = method.receiver_id(self.db());
         let receiver = method.receiver(self.db());
         let bounds = TypeBounds::new();
-        let returns = TypeRef::nil();
+        let returns =
+            method.return_type(self.db()).as_rigid_type(self.db_mut(), &bounds);
         let mut scope = LexicalScope::method(receiver, returns);
 
         self.verify_type_parameter_requirements(&node.type_parameters);
@@ -873,29 +888,22 @@ impl<'a> Expressions<'a> {
         nodes: &[hir::TypeParameter],
     ) {
         for param in nodes {
-            self.verify_required_traits(
-                &param.requirements,
-                param.type_parameter_id.unwrap().requirements(self.db()),
-            );
+            self.verify_required_traits(&param.requirements);
         }
     }
 
-    fn verify_required_traits(
-        &mut self,
-        nodes: &Vec<hir::TypeName>,
-        required_traits: Vec<TraitInstance>,
-    ) {
+    fn verify_required_traits(&mut self, nodes: &Vec<hir::TypeName>) {
         let mut all_methods = HashMap::new();
-        let reqs: HashMap<String, TraitId> = required_traits
-            .into_iter()
-            .map(|ins| {
-                (ins.instance_of().name(self.db()).clone(), ins.instance_of())
-            })
-            .collect();
 
         for req in nodes {
             let mut conflicts_with = None;
-            let req_id = *reqs.get(&req.name.name).unwrap();
+            let req_id = if let TypeRef::Owned(TypeId::TraitInstance(ins)) =
+                req.resolved_type
+            {
+                ins.instance_of()
+            } else {
+                continue;
+            };
             let methods = req_id
                 .required_methods(self.db())
                 .into_iter()
@@ -960,6 +968,15 @@ impl<'a> CheckConstant<'a> {
             hir::ConstExpression::Binary(ref mut n) => self.binary(n),
             hir::ConstExpression::ConstantRef(ref mut n) => self.constant(n),
             hir::ConstExpression::Array(ref mut n) => self.array(n),
+            hir::ConstExpression::Tuple(ref mut n) => self.tuple(n),
+            hir::ConstExpression::Cast(ref mut n) => self.cast(n),
+            hir::ConstExpression::Call(ref mut n) => self.call(n),
+            hir::ConstExpression::BuiltinCall(ref mut n) => {
+                self.builtin_call(n)
+            }
+            hir::ConstExpression::Interpolation(ref mut n) => {
+                self.interpolation(n)
+            }
             _ => TypeRef::Error,
         }
     }
@@ -1127,6 +1144,228 @@ impl<'a> CheckConstant<'a> {
         node.resolved_type
     }
 
+    fn tuple(&mut self, node: &mut hir::ConstTuple) -> TypeRef {
+        let types = node
+            .values
+            .iter_mut()
+            .map(|n| self.expression(n))
+            .collect::<Vec<_>>();
+
+        let class = if let Some(id) = ClassId::tuple(types.len()) {
+            id
+        } else {
+            self.state
+                .diagnostics
+                .tuple_size_error(self.file(), node.location.clone());
+
+            return TypeRef::Error;
+        };
+
+        // Similar to arrays, constant tuples are shared and thus typed as
+        // `ref (A, B)` instead of `(A, B)`.
+        let tuple = TypeRef::Ref(TypeId::ClassInstance(
+            ClassInstance::with_types(self.db_mut(), class, types),
+        ));
+
+        node.resolved_type = tuple;
+        node.resolved_type
+    }
+
+    fn cast(&mut self, node: &mut hir::ConstCast) -> TypeRef {
+        let expr_type = self.expression(&mut node.value);
+        let scope =
+            TypeScope::new(self.module, TypeId::Module(self.module), None);
+        let cast_type = DefineAndCheckTypeSignature::new(
+            self.state,
+            self.module,
+            &scope,
+            Rules::default(),
+        )
+        .define_type(&mut node.cast_to);
+
+        if expr_type.is_error(self.db()) || cast_type.is_error(self.db()) {
+            return TypeRef::Error;
+        }
+
+        let db = self.db();
+        let numeric = |typ: TypeRef| typ.is_int(db) || typ.is_float(db);
+
+        if !numeric(expr_type) || !numeric(cast_type) {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidConstExpr,
+                format!(
+                    "the type '{}' can't be cast to '{}', as constant casts \
+                    are limited to casts between Int and Float",
+                    format_type(self.db(), expr_type),
+                    format_type(self.db(), cast_type)
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            return TypeRef::Error;
+        }
+
+        node.resolved_type = cast_type;
+        node.resolved_type
+    }
+
+    fn call(&mut self, node: &mut hir::ConstCall) -> TypeRef {
+        let receiver = self.expression(&mut node.receiver);
+        let name = &node.name.name;
+        let (rec_id, method) = if let Some(found) =
+            self.lookup_method(receiver, name, &node.location)
+        {
+            found
+        } else {
+            return TypeRef::Error;
+        };
+
+        let mut call = MethodCall::new(
+            self.state,
+            self.module,
+            None,
+            receiver,
+            rec_id,
+            method,
+        );
+
+        call.check_mutability(self.state, &node.location);
+        call.check_type_bounds(self.state, &node.location);
+
+        for arg in &mut node.arguments {
+            self.positional_argument(&mut call, arg);
+        }
+
+        call.check_arguments(self.state, &node.location);
+        call.resolve_return_type(self.state);
+        call.check_sendable(self.state, &node.location);
+
+        let kind = self.call_kind(receiver, name);
+
+        if let hir::ConstCallKind::Unknown = kind {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidConstExpr,
+                format!(
+                    "the method '{}' can't be used in constant expressions",
+                    name
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            return TypeRef::Error;
+        }
+
+        node.kind = kind;
+        node.resolved_type = call.return_type;
+        node.resolved_type
+    }
+
+    /// Returns the operation to perform at compile-time for a method call.
+    ///
+    /// Only methods of which the implementation is known to the compiler can be
+    /// used, such that the result is the same as when calling the method at
+    /// runtime.
+    fn call_kind(&self, receiver: TypeRef, name: &str) -> hir::ConstCallKind {
+        let func = if receiver.is_int(self.db()) {
+            match name {
+                "opposite" => return hir::ConstCallKind::Opposite,
+                "absolute" => return hir::ConstCallKind::Absolute,
+                "not" => BuiltinFunction::IntBitNot,
+                "rotate_left" => BuiltinFunction::IntRotateLeft,
+                "rotate_right" => BuiltinFunction::IntRotateRight,
+                "wrapping_add" => BuiltinFunction::IntWrappingAdd,
+                "wrapping_sub" => BuiltinFunction::IntWrappingSub,
+                "wrapping_mul" => BuiltinFunction::IntWrappingMul,
+                _ => return hir::ConstCallKind::Unknown,
+            }
+        } else if receiver.is_float(self.db()) {
+            match name {
+                "opposite" => return hir::ConstCallKind::Opposite,
+                "absolute" => return hir::ConstCallKind::Absolute,
+                "ceil" => BuiltinFunction::FloatCeil,
+                "floor" => BuiltinFunction::FloatFloor,
+                "to_bits" => BuiltinFunction::FloatToBits,
+                "infinite?" => BuiltinFunction::FloatIsInf,
+                "not_a_number?" => BuiltinFunction::FloatIsNan,
+                _ => return hir::ConstCallKind::Unknown,
+            }
+        } else {
+            return hir::ConstCallKind::Unknown;
+        };
+
+        hir::ConstCallKind::Builtin(func)
+    }
+
+    fn builtin_call(&mut self, node: &mut hir::ConstBuiltinCall) -> TypeRef {
+        let args: Vec<_> =
+            node.arguments.iter_mut().map(|n| self.expression(n)).collect();
+
+        let id = match self.db().builtin_function(&node.name.name) {
+            Some(id) if id.is_const() => id,
+            Some(_) => {
+                self.state.diagnostics.error(
+                    DiagnosticId::InvalidConstExpr,
+                    format!(
+                        "the builtin function '{}' can't be used in constant \
+                        expressions",
+                        node.name.name
+                    ),
+                    self.file(),
+                    node.location.clone(),
+                );
+
+                return TypeRef::Error;
+            }
+            None => {
+                self.state.diagnostics.undefined_symbol(
+                    &node.name.name,
+                    self.file(),
+                    node.location.clone(),
+                );
+
+                return TypeRef::Error;
+            }
+        };
+
+        let returns = id.return_type(self.db(), &args);
+
+        node.info = Some(BuiltinCallInfo { id, returns });
+        returns
+    }
+
+    fn interpolation(&mut self, node: &mut hir::ConstInterpolation) -> TypeRef {
+        for value in &mut node.values {
+            let typ = self.expression(value);
+            let db = self.db();
+
+            if typ.is_error(db)
+                || typ.is_string(db)
+                || typ.is_int(db)
+                || typ.is_float(db)
+                || typ.is_bool(db)
+            {
+                continue;
+            }
+
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidConstExpr,
+                format!(
+                    "values of type '{}' can't be interpolated into constant \
+                    strings, only String, Int, Float and Bool values are \
+                    supported",
+                    format_type(db, typ)
+                ),
+                self.file(),
+                value.location().clone(),
+            );
+        }
+
+        node.resolved_type = TypeRef::string();
+        node.resolved_type
+    }
+
     fn lookup_method(
         &mut self,
         receiver: TypeRef,
@@ -1191,12 +1430,14 @@ impl<'a> CheckConstant<'a> {
         call: &mut MethodCall,
         node: &mut hir::ConstExpression,
     ) {
+        let index = call.arguments;
+
         call.arguments += 1;
 
         let given = self.expression(node);
 
         if let Some(expected) =
-            call.method.positional_argument_input_type(self.db(), 0)
+            call.method.positional_argument_input_type(self.db(), index)
         {
             call.check_argument(self.state, given, expected, node.location());
         }
@@ -2895,6 +3136,10 @@ impl<'a> CheckMethodBody<'a> {
             );
         }
 
+        if scope.surrounding_type.is_inline_class_instance(self.db()) {
+            self.inline_field_assignment(name, location);
+        }
+
         if scope.in_recover() && !var_type.is_sendable(self.db()) {
             self.state.diagnostics.unsendable_type_in_recover(
                 self.fmt(var_type),
@@ -2907,6 +3152,23 @@ impl<'a> CheckMethodBody<'a> {
         Some((field, var_type))
     }
 
+    fn inline_field_assignment(
+        &mut self,
+        name: &str,
+        location: &SourceLocation,
+    ) {
+        self.state.diagnostics.error(
+            DiagnosticId::InvalidAssign,
+            format!(
+                "can't assign a new value to field '{}', as fields of inline \
+                classes are immutable",
+                name
+            ),
+            self.file(),
+            location.clone(),
+        );
+    }
+
     fn loop_expression(
         &mut self,
         node: &mut hir::Loop,
@@ -3176,6 +3438,17 @@ impl<'a> CheckMethodBody<'a> {
         node: &mut hir::Mut,
         scope: &mut LexicalScope,
     ) -> TypeRef {
+        if let Some(method) = self.method_for_pointer(&node.value, scope) {
+            node.pointer_to_method = Some(method);
+            node.resolved_type = if self.check_method_pointer(method, node) {
+                TypeRef::pointer(TypeId::Foreign(ForeignType::Int(8, false)))
+            } else {
+                TypeRef::Error
+            };
+
+            return node.resolved_type;
+        }
+
         let expr = self.expression(&mut node.value, scope);
 
         if !expr.allow_as_mut(self.db()) {
@@ -3205,6 +3478,127 @@ impl<'a> CheckMethodBody<'a> {
         node.resolved_type
     }
 
+    /// Returns the module method to take the address of when using `mut` on
+    /// an identifier that doesn't refer to a variable.
+    fn method_for_pointer(
+        &mut self,
+        node: &hir::Expression,
+        scope: &LexicalScope,
+    ) -> Option<MethodId> {
+        let name = if let hir::Expression::IdentifierRef(n) = node {
+            &n.name
+        } else {
+            return None;
+        };
+
+        let mut source = Some(scope);
+
+        while let Some(current) = source {
+            if current.variables.variable(name).is_some() {
+                return None;
+            }
+
+            source = current.parent;
+        }
+
+        let module = self.module;
+
+        // Methods defined on the surrounding type take priority over module
+        // methods, the same as when calling them.
+        if let Ok(id) = scope.surrounding_type.type_id(self.db()) {
+            if !matches!(id, TypeId::Module(_))
+                && id.method(self.db(), name).is_some()
+            {
+                return None;
+            }
+        }
+
+        match TypeId::Module(module).lookup_method(
+            self.db(),
+            name,
+            module,
+            true,
+        ) {
+            MethodLookup::Ok(method) => Some(method),
+            _ => match module.symbol(self.db(), name) {
+                Some(Symbol::Method(method)) => Some(method),
+                _ => None,
+            },
+        }
+    }
+
+    fn check_method_pointer(
+        &mut self,
+        method: MethodId,
+        node: &hir::Mut,
+    ) -> bool {
+        // C functions are already defined using C types, so there's nothing
+        // to check for them.
+        if method.is_extern(self.db()) {
+            return true;
+        }
+
+        let name = method.name(self.db()).clone();
+
+        if !method.type_parameters(self.db()).is_empty() {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidType,
+                format!(
+                    "a pointer to the method '{}' can't be created, \
+                    as it defines type parameters",
+                    name
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            return false;
+        }
+
+        let mut valid = true;
+
+        for arg in method.arguments(self.db()) {
+            if arg.value_type.is_c_compatible(self.db()) {
+                continue;
+            }
+
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidType,
+                format!(
+                    "a pointer to the method '{}' can't be created, as the \
+                    type of its argument '{}' ('{}') isn't compatible with C",
+                    name,
+                    arg.name,
+                    self.fmt(arg.value_type)
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            valid = false;
+        }
+
+        let ret = method.return_type(self.db());
+
+        if !ret.is_nil(self.db()) && !ret.is_c_compatible(self.db()) {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidType,
+                format!(
+                    "a pointer to the method '{}' can't be created, as its \
+                    return type ('{}') isn't compatible with C",
+                    name,
+                    self.fmt(ret)
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            valid = false;
+        }
+
+        valid
+    }
+
     fn recover_expression(
         &mut self,
         node: &mut hir::Recover,
@@ -3220,7 +3614,20 @@ impl<'a> CheckMethodBody<'a> {
 
         let db = self.db();
 
-        let result = if last_type.is_owned(db) {
+        let result = if last_type.is_inline_class_instance(db) {
+            self.state.diagnostics.error(
+                DiagnosticId::InvalidType,
+                format!(
+                    "values of type '{}' can't be recovered, as they are \
+                    instances of an inline class",
+                    self.fmt(last_type)
+                ),
+                self.file(),
+                node.location.clone(),
+            );
+
+            return TypeRef::Error;
+        } else if last_type.is_owned(db) {
             last_type.as_uni(db)
         } else if last_type.is_uni(db) {
             last_type.as_owned(db)
@@ -3414,6 +3821,10 @@ impl<'a> CheckMethodBody<'a> {
             );
         }
 
+        if ins.instance_of().kind(self.db()).is_inline() {
+            self.inline_field_assignment(name, &node.location);
+        }
+
         let targs = TypeArguments::for_class(self.db(), ins);
         let raw_type = field.value_type(self.db());
         let bounds = self.bounds;
@@ -3884,9 +4295,11 @@ impl<'a> CheckMethodBody<'a> {
         node: &mut hir::BuiltinCall,
         scope: &mut LexicalScope,
     ) -> TypeRef {
-        for n in &mut node.arguments {
-            self.expression(n, scope);
-        }
+        let args: Vec<_> = node
+            .arguments
+            .iter_mut()
+            .map(|n| self.expression(n, scope))
+            .collect();
 
         let id = if let Some(id) = self.db().builtin_function(&node.name.name) {
             id
@@ -3900,7 +4313,7 @@ impl<'a> CheckMethodBody<'a> {
             return TypeRef::Error;
         };
 
-        let returns = id.return_type();
+        let returns = id.return_type(self.db(), &args);
 
         node.info = Some(BuiltinCallInfo { id, returns });
 
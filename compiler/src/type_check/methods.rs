@@ -296,6 +296,10 @@ impl<'a> DefineModuleMethodNames<'a> {
             MethodKind::Static,
         );
 
+        if node.tail {
+            method.set_tail_recursive(self.db_mut());
+        }
+
         if self.module.symbol_exists(self.db(), name) {
             self.state.diagnostics.duplicate_symbol(
                 name,
@@ -609,6 +613,10 @@ impl<'a> DefineMethods<'a> {
             MethodKind::Static,
         );
 
+        if node.tail {
+            method.set_tail_recursive(self.db_mut());
+        }
+
         method.set_receiver(self.db_mut(), receiver);
 
         let scope = TypeScope::new(self.module, self_type, Some(method));
@@ -682,6 +690,10 @@ impl<'a> DefineMethods<'a> {
             kind,
         );
 
+        if node.tail {
+            method.set_tail_recursive(self.db_mut());
+        }
+
         if !method.is_mutable(self.db()) {
             bounds.make_immutable(self.db_mut());
         }
@@ -799,7 +811,6 @@ impl<'a> DefineMethods<'a> {
         };
 
         method.set_receiver(self.db_mut(), receiver);
-        method.set_return_type(self.db_mut(), TypeRef::nil());
 
         let scope = TypeScope::with_bounds(
             self.module,
@@ -814,15 +825,12 @@ impl<'a> DefineMethods<'a> {
             &scope,
         );
         self.define_arguments(&mut node.arguments, method, rules, &scope);
-
-        if node.return_type.is_some() {
-            self.state.diagnostics.error(
-                DiagnosticId::InvalidMethod,
-                "async methods can't return values",
-                self.file(),
-                node.location.clone(),
-            );
-        }
+        self.define_return_type(
+            node.return_type.as_mut(),
+            method,
+            rules,
+            &scope,
+        );
 
         self.add_method_to_class(
             method,
@@ -1053,6 +1061,12 @@ impl<'a> CheckMainMethod<'a> {
     }
 
     fn check(&mut self) -> bool {
+        // Libraries don't have an entry point, instead they export methods
+        // using the DefineExportedMethods pass.
+        if self.state.config.library.is_some() {
+            return true;
+        }
+
         let main_mod = if let Some(name) = self.db().main_module() {
             name.as_str()
         } else {
@@ -1119,6 +1133,140 @@ impl<'a> CheckMainMethod<'a> {
     }
 }
 
+/// A compiler pass that defines the methods to export as C functions when
+/// compiling a library.
+///
+/// The exported methods are the public module methods of the main module.
+pub(crate) struct DefineExportedMethods<'a> {
+    state: &'a mut State,
+    module: ModuleId,
+}
+
+impl<'a> DefineExportedMethods<'a> {
+    pub(crate) fn run_all(
+        state: &'a mut State,
+        modules: &[hir::Module],
+    ) -> bool {
+        if state.config.library.is_none() {
+            return true;
+        }
+
+        let main_mod = if let Some(name) = state.db.main_module() {
+            state.db.module(name.as_str())
+        } else {
+            return true;
+        };
+
+        for module in modules.iter().filter(|m| m.module_id == main_mod) {
+            DefineExportedMethods { state, module: module.module_id }
+                .run(module);
+        }
+
+        !state.diagnostics.has_errors()
+    }
+
+    fn run(mut self, module: &hir::Module) {
+        for expr in &module.expressions {
+            match expr {
+                hir::TopLevelExpression::ModuleMethod(ref node)
+                    if node.public =>
+                {
+                    self.define_exported_method(node);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn define_exported_method(&mut self, node: &hir::DefineModuleMethod) {
+        let method = node.method_id.unwrap();
+        let name = &node.name.name;
+
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            self.invalid_export(
+                format!(
+                    "the method '{}' can't be exported to C, \
+                    as its name isn't a valid C identifier",
+                    name
+                ),
+                node.location.clone(),
+            );
+
+            return;
+        }
+
+        if !node.type_parameters.is_empty() {
+            self.invalid_export(
+                format!(
+                    "the method '{}' can't be exported to C, \
+                    as it defines type parameters",
+                    name
+                ),
+                node.location.clone(),
+            );
+
+            return;
+        }
+
+        let mut valid = true;
+
+        for arg in method.arguments(self.db()) {
+            if arg.value_type.is_c_compatible(self.db()) {
+                continue;
+            }
+
+            let typ = format_type(self.db(), arg.value_type);
+
+            self.invalid_export(
+                format!(
+                    "the method '{}' can't be exported to C, as the type of \
+                    its argument '{}' ('{}') isn't compatible with C",
+                    name, arg.name, typ
+                ),
+                node.location.clone(),
+            );
+
+            valid = false;
+        }
+
+        let ret = method.return_type(self.db());
+
+        if !ret.is_nil(self.db()) && !ret.is_c_compatible(self.db()) {
+            let typ = format_type(self.db(), ret);
+
+            self.invalid_export(
+                format!(
+                    "the method '{}' can't be exported to C, as its return \
+                    type ('{}') isn't compatible with C",
+                    name, typ
+                ),
+                node.location.clone(),
+            );
+
+            valid = false;
+        }
+
+        if valid {
+            self.state.db.add_exported_method(method);
+        }
+    }
+
+    fn invalid_export(&mut self, message: String, location: SourceLocation) {
+        let file = self.module.file(&self.state.db);
+
+        self.state.diagnostics.error(
+            DiagnosticId::InvalidMethod,
+            message,
+            file,
+            location,
+        );
+    }
+
+    fn db(&self) -> &Database {
+        &self.state.db
+    }
+}
+
 /// A compiler pass that defines methods implemented from traits
 pub(crate) struct ImplementTraitMethods<'a> {
     state: &'a mut State,
@@ -1274,6 +1422,10 @@ impl<'a> ImplementTraitMethods<'a> {
             method_kind(node.kind),
         );
 
+        if node.tail {
+            method.set_tail_recursive(self.db_mut());
+        }
+
         if !method.is_mutable(self.db()) {
             bounds.make_immutable(self.db_mut());
         }
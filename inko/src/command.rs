@@ -1,6 +1,8 @@
 pub(crate) mod build;
 pub(crate) mod check;
+pub(crate) mod init;
 pub(crate) mod main;
+pub(crate) mod new;
 pub(crate) mod pkg;
 pub(crate) mod print;
 pub(crate) mod run;
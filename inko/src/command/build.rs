@@ -7,12 +7,21 @@ use std::path::PathBuf;
 
 const USAGE: &str = "Usage: inko build [OPTIONS] [FILE]
 
-Compile a source file and its dependencies into an executable.
+Compile a source file and its dependencies into an executable or library.
+
+When building a library, the public module methods of the source file are
+exported as C functions, and a C header declaring these functions is written
+next to the library.
 
 Examples:
 
-    inko build             # Compile src/main.inko
-    inko build hello.inko  # Compile the file hello.inko";
+    inko build                          # Compile src/main.inko
+    inko build hello.inko               # Compile the file hello.inko
+    inko build --library shared a.inko  # Compile a.inko into a shared library
+    inko build -D sqlite                # Compile with the build tag 'sqlite'
+    inko build -L vendor/lib            # Search vendor/lib for C libraries
+    inko build --emit mir               # Also write the MIR to build/emit
+    inko build --emit llvm --emit-only main:Main.main";
 
 pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
@@ -25,6 +34,13 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "FORMAT",
     );
 
+    options.optmulti(
+        "D",
+        "define",
+        "Define a build tag to use in conditional imports",
+        "TAG",
+    );
+
     options.optopt(
         "t",
         "target",
@@ -53,10 +69,47 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "none,balanced,aggressive",
     );
 
+    options.optopt(
+        "",
+        "library",
+        "Compile to a library instead of an executable",
+        "static,shared",
+    );
+
+    options.optmulti(
+        "",
+        "emit",
+        "Write an intermediate representation to disk",
+        "ast,hir,mir,dot,llvm,asm,link",
+    );
+
+    options.optopt(
+        "",
+        "emit-only",
+        "Only emit the intermediate representation of a module or method",
+        "MODULE[:METHOD]",
+    );
+
+    options.optopt("", "linker", "The program to use for linking", "PATH");
+
+    options.optmulti(
+        "",
+        "linker-arg",
+        "An extra argument to pass to the linker",
+        "ARG",
+    );
+
+    options.optmulti(
+        "L",
+        "library-path",
+        "A directory to search for C libraries",
+        "DIR",
+    );
+
     options.optflag("", "static", "Statically link imported C libraries");
-    options.optflag("", "dot", "Output the MIR of every module as DOT files");
+    options.optflag("", "dot", "Deprecated, use --emit dot instead");
     options.optflag("", "verify-llvm", "Verify LLVM IR when generating code");
-    options.optflag("", "write-llvm", "Write LLVM IR files to disk");
+    options.optflag("", "write-llvm", "Deprecated, use --emit llvm instead");
 
     let matches = options.parse(arguments)?;
 
@@ -67,6 +120,12 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
 
     let mut config = Config::default();
 
+    config.load_manifest()?;
+
+    for tag in matches.opt_strs("D") {
+        config.add_build_tag(&tag)?;
+    }
+
     if let Some(val) = matches.opt_str("f") {
         config.set_presenter(&val)?;
     }
@@ -79,22 +138,44 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         config.set_opt(&val)?;
     }
 
-    if matches.opt_present("dot") {
-        config.dot = true;
+    if let Some(val) = matches.opt_str("library") {
+        config.set_library(&val)?;
     }
 
-    if matches.opt_present("verify-llvm") {
-        config.verify_llvm = true;
+    for val in matches.opt_strs("emit") {
+        config.add_emit(&val)?;
+    }
+
+    if matches.opt_present("dot") {
+        config.add_emit("dot")?;
     }
 
     if matches.opt_present("write-llvm") {
-        config.write_llvm = true;
+        config.add_emit("llvm")?;
+    }
+
+    if let Some(val) = matches.opt_str("emit-only") {
+        config.set_emit_filter(&val)?;
+    }
+
+    if matches.opt_present("verify-llvm") {
+        config.verify_llvm = true;
     }
 
     if matches.opt_present("static") {
         config.static_linking = true;
     }
 
+    if let Some(val) = matches.opt_str("linker") {
+        config.linker.linker = Some(PathBuf::from(val));
+    }
+
+    config.linker.arguments.extend(matches.opt_strs("linker-arg"));
+
+    for path in matches.opt_strs("L") {
+        config.linker.library_paths.push(PathBuf::from(path));
+    }
+
     for path in matches.opt_strs("i") {
         config.add_source_directory(path.into());
     }
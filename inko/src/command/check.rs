@@ -26,6 +26,13 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "FORMAT",
     );
 
+    options.optmulti(
+        "D",
+        "define",
+        "Define a build tag to use in conditional imports",
+        "TAG",
+    );
+
     options.optmulti(
         "i",
         "include",
@@ -42,6 +49,12 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
 
     let mut config = CompilerConfig::default();
 
+    config.load_manifest()?;
+
+    for tag in matches.opt_strs("D") {
+        config.add_build_tag(&tag)?;
+    }
+
     if let Some(format) = matches.opt_str("f") {
         config.set_presenter(&format)?;
     }
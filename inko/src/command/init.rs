@@ -0,0 +1,48 @@
+use crate::command::new::create_project;
+use crate::error::Error;
+use crate::options::print_usage;
+use getopts::Options;
+use std::env::current_dir;
+use std::path::Path;
+
+const USAGE: &str = "Usage: inko init [OPTIONS] [NAME]
+
+Create a new project in the current directory.
+
+This command generates the same files as `inko new`, but in the current
+directory. Files that already exist are left as-is. If no name is given, the
+name of the current directory is used as the project name.
+
+Examples:
+
+    inko init              # Create an executable in the current directory
+    inko init json --lib   # Create a library called 'json'";
+
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "lib", "Create a library instead of an executable");
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let dir = current_dir().map_err(|err| {
+        format!("Failed to determine the current directory: {}", err)
+    })?;
+
+    let name = if let Some(name) = matches.free.get(0) {
+        name.clone()
+    } else {
+        dir.file_name()
+            .map(|v| v.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    create_project(Path::new(""), &name, matches.opt_present("lib"))?;
+    Ok(0)
+}
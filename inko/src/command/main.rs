@@ -1,5 +1,7 @@
 use crate::command::build;
 use crate::command::check;
+use crate::command::init;
+use crate::command::new;
 use crate::command::pkg;
 use crate::command::print;
 use crate::command::run;
@@ -16,6 +18,8 @@ Commands:
     run    Compile and run Inko source code directly
     build  Compile Inko source code
     test   Run Inko unit tests
+    new    Create a new project
+    init   Create a new project in the current directory
     print  Print compiler details to STDOUT
     pkg    Manage Inko packages
 
@@ -51,6 +55,8 @@ pub(crate) fn run() -> Result<i32, Error> {
         Some("build") => build::run(&matches.free[1..]),
         Some("check") => check::run(&matches.free[1..]),
         Some("test") => test::run(&matches.free[1..]),
+        Some("new") => new::run(&matches.free[1..]),
+        Some("init") => init::run(&matches.free[1..]),
         Some("print") => print::run(&matches.free[1..]),
         Some("pkg") => pkg::run(&matches.free[1..]),
         Some(cmd) => {
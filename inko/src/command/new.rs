@@ -0,0 +1,270 @@
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::config::{BUILD, DEP, MAIN_MODULE, SOURCE, SOURCE_EXT, TESTS};
+use compiler::pkg::manifest::MANIFEST_FILE;
+use getopts::Options;
+use std::fs::{create_dir_all, write};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage: inko new [OPTIONS] NAME
+
+Create a new project in the directory NAME.
+
+The project contains a main module, a unit test, an empty package manifest, and
+a .gitignore file. By default the project is an executable, use the --lib flag
+to create a library instead. Libraries also contain an example program in
+src/main.inko, such that they can be built using `inko build`.
+
+Dashes in the name are replaced with underscores, and uppercase letters are
+converted to lowercase.
+
+Examples:
+
+    inko new hello        # Create an executable in ./hello
+    inko new json --lib   # Create a library in ./json";
+
+/// The name of the Git ignore file to generate.
+const GITIGNORE: &str = ".gitignore";
+
+pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "lib", "Create a library instead of an executable");
+
+    let matches = options.parse(arguments)?;
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let name = &matches.free[0];
+    let dir = PathBuf::from(name);
+
+    if dir.exists() {
+        return Err(Error::generic(format!(
+            "The directory {} already exists",
+            dir.display()
+        )));
+    }
+
+    create_project(&dir, name, matches.opt_present("lib"))?;
+    Ok(0)
+}
+
+/// Generates a new project in the given directory.
+///
+/// Files that already exist are left as-is.
+pub(crate) fn create_project(
+    directory: &Path,
+    name: &str,
+    library: bool,
+) -> Result<(), Error> {
+    let name = &normalize_name(name);
+
+    if !valid_name(name) {
+        return Err(Error::generic(format!(
+            "'{}' isn't a valid project name, as project names may only \
+            contain lowercase letters, digits and underscores, and can't start \
+            with a digit",
+            name
+        )));
+    }
+
+    if library && name == MAIN_MODULE {
+        return Err(Error::generic(format!(
+            "Libraries can't be named '{}'",
+            MAIN_MODULE
+        )));
+    }
+
+    for (path, contents) in project_files(name, library) {
+        let path = directory.join(path);
+
+        if path.exists() {
+            println!("Skipping {} as it already exists", path.display());
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|err| {
+                format!("Failed to create {}: {}", parent.display(), err)
+            })?;
+        }
+
+        write(&path, contents).map_err(|err| {
+            format!("Failed to write {}: {}", path.display(), err)
+        })?;
+
+        println!("Created {}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Turns a directory name such as `my-app` into a valid project name.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c == '-' { '_' } else { c.to_ascii_lowercase() })
+        .collect()
+}
+
+fn valid_name(name: &str) -> bool {
+    name.chars().next().map_or(false, |c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn source_file(directory: &str, name: &str) -> PathBuf {
+    Path::new(directory).join(format!("{}.{}", name, SOURCE_EXT))
+}
+
+/// Returns the paths and contents of the files of a new project.
+fn project_files(name: &str, library: bool) -> Vec<(PathBuf, String)> {
+    let (module, source, test) = if library {
+        (
+            name,
+            "fn pub greet(name: String) -> String {
+  \"Hello, {name}!\"
+}
+"
+            .to_string(),
+            format!(
+                "import {name}
+import std.test.Tests
+
+fn pub tests(t: mut Tests) {{
+  t.test('{name}.greet') fn (t) {{
+    t.equal({name}.greet('Alice'), 'Hello, Alice!')
+  }}
+}}
+"
+            ),
+        )
+    } else {
+        (
+            MAIN_MODULE,
+            "import std.stdio.STDOUT
+
+class async Main {
+  fn async main {
+    STDOUT.new.print('Hello, world!')
+  }
+}
+"
+            .to_string(),
+            "import std.test.Tests
+
+fn pub tests(t: mut Tests) {
+  t.test('An example test') fn (t) {
+    t.equal(1 + 1, 2)
+  }
+}
+"
+            .to_string(),
+        )
+    };
+
+    let test_module = format!("test_{}", module);
+    let test_main = format!(
+        "import std.env
+import std.test.(Filter, Tests)
+
+import {test_module}
+
+class async Main {{
+  fn async main {{
+    let tests = Tests.new
+
+    {test_module}.tests(tests)
+
+    tests.filter = Filter.from_string(env.arguments.opt(0).unwrap_or(''))
+    tests.run
+  }}
+}}
+"
+    );
+
+    let mut files = vec![(source_file(SOURCE, module), source)];
+
+    // Without a main module `inko build` has nothing to compile, so libraries
+    // come with an example program that uses the library.
+    if library {
+        files.push((
+            source_file(SOURCE, MAIN_MODULE),
+            format!(
+                "import {name}
+import std.stdio.STDOUT
+
+class async Main {{
+  fn async main {{
+    STDOUT.new.print({name}.greet('world'))
+  }}
+}}
+"
+            ),
+        ));
+    }
+
+    files.push((source_file(TESTS, MAIN_MODULE), test_main));
+    files.push((source_file(TESTS, &test_module), test));
+    files.push((PathBuf::from(MANIFEST_FILE), String::new()));
+    files.push((PathBuf::from(GITIGNORE), format!("/{}\n/{}\n", BUILD, DEP)));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_name() {
+        assert!(valid_name("foo"));
+        assert!(valid_name("foo_bar2"));
+        assert!(valid_name("_foo"));
+        assert!(!valid_name(""));
+        assert!(!valid_name("2foo"));
+        assert!(!valid_name("foo-bar"));
+        assert!(!valid_name("Foo"));
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("foo"), "foo");
+        assert_eq!(normalize_name("my-app"), "my_app");
+        assert_eq!(normalize_name("My-App"), "my_app");
+        assert!(valid_name(&normalize_name("my-app")));
+    }
+
+    #[test]
+    fn test_project_files() {
+        let exe: Vec<_> =
+            project_files("foo", false).into_iter().map(|(p, _)| p).collect();
+        let lib: Vec<_> =
+            project_files("foo", true).into_iter().map(|(p, _)| p).collect();
+
+        assert_eq!(
+            exe,
+            vec![
+                PathBuf::from("src/main.inko"),
+                PathBuf::from("test/main.inko"),
+                PathBuf::from("test/test_main.inko"),
+                PathBuf::from("inko.pkg"),
+                PathBuf::from(".gitignore"),
+            ]
+        );
+        assert_eq!(
+            lib,
+            vec![
+                PathBuf::from("src/foo.inko"),
+                PathBuf::from("src/main.inko"),
+                PathBuf::from("test/main.inko"),
+                PathBuf::from("test/test_foo.inko"),
+                PathBuf::from("inko.pkg"),
+                PathBuf::from(".gitignore"),
+            ]
+        );
+    }
+}
@@ -1,7 +1,11 @@
 mod add;
+mod outdated;
 mod remove;
 mod sync;
+mod tree;
 mod update;
+mod vendor;
+mod why;
 
 use crate::error::Error;
 use crate::options::print_usage;
@@ -13,10 +17,14 @@ Package and dependency management for Inko.
 
 Commands:
 
-    add     Add or update a dependency
-    remove  Remove a dependency
-    sync    Download and install dependencies
-    update  Update all dependencies to the latest version
+    add       Add or update a dependency
+    outdated  List dependencies for which newer versions are available
+    remove    Remove a dependency
+    sync      Download and install dependencies
+    tree      Print the dependency tree
+    update    Update all dependencies to the latest version
+    vendor    Copy all dependencies into ./vendor
+    why       Show which packages require a dependency
 
 Examples:
 
@@ -38,9 +46,13 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
 
     match matches.free.get(0).map(|s| s.as_str()) {
         Some("add") => add::run(&matches.free[1..]),
+        Some("outdated") => outdated::run(&matches.free[1..]),
         Some("remove") => remove::run(&matches.free[1..]),
         Some("sync") => sync::run(&matches.free[1..]),
+        Some("tree") => tree::run(&matches.free[1..]),
         Some("update") => update::run(&matches.free[1..]),
+        Some("vendor") => vendor::run(&matches.free[1..]),
+        Some("why") => why::run(&matches.free[1..]),
         Some(cmd) => {
             Err(Error::generic(format!("The command {:?} is invalid", cmd)))
         }
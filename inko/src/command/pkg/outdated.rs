@@ -0,0 +1,155 @@
+use crate::command::pkg::sync::download_packages;
+use crate::error::Error;
+use crate::options::print_usage;
+use crate::pkg::git::Repository;
+use crate::pkg::util::data_dir;
+use compiler::pkg::version::{select, Version};
+use getopts::Options;
+
+const USAGE: &str = "inko pkg outdated [OPTIONS]
+
+List the dependencies for which newer versions are available.
+
+For each dependency the version currently selected is shown, along with the
+latest version with the same major version, and the latest version with a newer
+major version.
+
+When using the --offline flag, the versions are determined using the local
+package cache, without fetching new versions. This means newer versions that
+aren't cached aren't shown.
+
+Examples:
+
+    inko pkg outdated
+    inko pkg outdated --offline";
+
+pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "offline", "Only use the local package cache");
+
+    let matches = options.parse(args)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let offline = matches.opt_present("offline");
+    let (packages, _) = download_packages(offline)?;
+    let mut versions = select(packages.iter().map(|p| &p.dependency));
+    let data_dir = data_dir()?;
+    let mut rows = Vec::new();
+
+    versions.sort_by(|(a_url, a_ver), (b_url, b_ver)| {
+        a_url.value.cmp(&b_url.value).then(a_ver.cmp(b_ver))
+    });
+
+    for (url, version) in versions {
+        let mut repo = Repository::open(&data_dir.join(url.directory_name()))?;
+
+        if !offline {
+            repo.fetch()?;
+        }
+
+        let (compatible, latest) =
+            newer_versions(&version, repo.version_tag_names());
+
+        if compatible.is_none() && latest.is_none() {
+            continue;
+        }
+
+        let format = |v: Option<Version>| {
+            v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+        };
+
+        rows.push([
+            url.to_string(),
+            version.to_string(),
+            format(compatible),
+            format(latest),
+        ]);
+    }
+
+    if rows.is_empty() {
+        println!("All dependencies are up to date");
+        return Ok(0);
+    }
+
+    let header = [
+        "Package".to_string(),
+        "Current".to_string(),
+        "Compatible".to_string(),
+        "Latest".to_string(),
+    ];
+    let mut widths = [0; 4];
+
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (width, col) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(col.len());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        );
+    }
+
+    Ok(0)
+}
+
+/// Returns the latest version with the same major version, and the latest
+/// version with a newer major version, if these are newer than the current
+/// version.
+fn newer_versions(
+    current: &Version,
+    tags: Vec<String>,
+) -> (Option<Version>, Option<Version>) {
+    let versions: Vec<_> = tags
+        .iter()
+        .filter_map(|tag| tag.strip_prefix('v').and_then(Version::parse))
+        .filter(|v| v > current)
+        .collect();
+
+    let compatible =
+        versions.iter().filter(|v| v.major == current.major).max().cloned();
+    let latest =
+        versions.iter().filter(|v| v.major > current.major).max().cloned();
+
+    (compatible, latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_versions() {
+        let tags = vec![
+            "v1.2.4".to_string(),
+            "v1.3.8".to_string(),
+            "v2.3.1".to_string(),
+            "v3.0.0".to_string(),
+            "foo".to_string(),
+        ];
+
+        assert_eq!(
+            newer_versions(&Version::new(1, 2, 3), tags.clone()),
+            (Some(Version::new(1, 3, 8)), Some(Version::new(3, 0, 0)))
+        );
+        assert_eq!(
+            newer_versions(&Version::new(2, 3, 1), tags.clone()),
+            (None, Some(Version::new(3, 0, 0)))
+        );
+        assert_eq!(newer_versions(&Version::new(3, 0, 0), tags), (None, None));
+    }
+}
@@ -3,13 +3,16 @@ use crate::options::print_usage;
 use crate::pkg::git::Repository;
 use crate::pkg::util::{cp_r, data_dir};
 use compiler::config::Config;
-use compiler::pkg::manifest::{Dependency, Manifest, Url, MANIFEST_FILE};
+use compiler::pkg::manifest::{
+    Checksum, Dependency, Manifest, Url, MANIFEST_FILE,
+};
 use compiler::pkg::version::{select, Version};
 use getopts::Options;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
 use std::fs::{copy, remove_dir_all};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// The name of the directory to copy source files from and into the ./dep
 /// directory.
@@ -20,19 +23,63 @@ const USAGE: &str = "inko pkg sync [OPTIONS]
 Install all necessary dependencies, and remove dependencies that are no longer
 needed.
 
+When using the --offline flag, packages are only resolved using the local
+package cache, and an error is produced if a package or version isn't cached.
+
 Examples:
 
-    inko pkg sync";
+    inko pkg sync
+    inko pkg sync --offline";
 
-struct Package {
-    repository: Repository,
-    dependency: Dependency,
+pub(crate) struct Package {
+    pub(crate) repository: Repository,
+    pub(crate) dependency: Dependency,
+}
+
+/// The source of a manifest.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) enum Source {
+    /// The manifest belongs to the project or one of its local dependencies,
+    /// located in the given directory.
+    Local(PathBuf),
+
+    /// The manifest belongs to the package with the given URL and version.
+    Remote(Url, Version),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Local(dir) => dir.join(MANIFEST_FILE).display().fmt(f),
+            Source::Remote(url, version) => write!(f, "{} {}", url, version),
+        }
+    }
+}
+
+/// The dependency graph of a project, as discovered when downloading its
+/// packages.
+#[derive(Default)]
+pub(crate) struct Graph {
+    /// The packages required, and the manifests requiring them.
+    pub(crate) packages: Vec<(Source, Dependency)>,
+
+    /// The directories of the local dependencies required, and the manifests
+    /// requiring them.
+    pub(crate) local: Vec<(Source, PathBuf)>,
+}
+
+impl Graph {
+    /// Returns the source of the manifest of the project itself.
+    pub(crate) fn root() -> Source {
+        Source::Local(PathBuf::new())
+    }
 }
 
 pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
 
     options.optflag("h", "help", "Show this help message");
+    options.optflag("", "offline", "Only use the local package cache");
 
     let matches = options.parse(args)?;
 
@@ -44,7 +91,7 @@ pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
     println!("Updating package cache");
 
     let config = Config::default();
-    let packages = download_packages()?;
+    let (packages, _) = download_packages(matches.opt_present("offline"))?;
     let versions = select(packages.iter().map(|p| &p.dependency));
 
     remove_dependencies(&config.dependencies)?;
@@ -53,25 +100,78 @@ pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
     Ok(0)
 }
 
-fn download_packages() -> Result<Vec<Package>, Error> {
+/// Downloads all the packages required by the project.
+///
+/// If `offline` is true, packages are only resolved using the package cache.
+pub(crate) fn download_packages(
+    offline: bool,
+) -> Result<(Vec<Package>, Graph), Error> {
     let data_dir = data_dir()?;
-    let mut manifests = vec![Manifest::load(&MANIFEST_FILE)?];
+    let mut manifests = vec![(Manifest::load(&MANIFEST_FILE)?, Graph::root())];
     let mut packages = Vec::new();
+    let mut graph = Graph::default();
     let mut downloaded = HashSet::new();
+    let mut local = HashSet::new();
+
+    while let Some((manifest, source)) = manifests.pop() {
+        for dep in manifest.local_dependencies() {
+            // Local dependencies only work on the machine they're defined on,
+            // so published packages aren't allowed to use them.
+            let dir = match &source {
+                Source::Local(dir) => dir.join(&dep.path),
+                Source::Remote(url, _) => {
+                    return Err(Error::generic(format!(
+                        "The package {} requires the local path {}, but \
+                        published packages can't depend on local paths",
+                        url,
+                        dep.path.display()
+                    )));
+                }
+            };
+
+            let key = dir.canonicalize().map_err(|err| {
+                format!(
+                    "The local dependency {} is invalid: {}",
+                    dir.display(),
+                    err
+                )
+            })?;
+
+            graph.local.push((source.clone(), dir.clone()));
+
+            if !local.insert(key) {
+                continue;
+            }
+
+            let manifest_path = dir.join(MANIFEST_FILE);
+
+            // Local dependencies are used as-is, but the packages they depend
+            // on still need to be installed.
+            if manifest_path.is_file() {
+                manifests.push((
+                    Manifest::load(&manifest_path)?,
+                    Source::Local(dir),
+                ));
+            }
+        }
 
-    while let Some(manifest) = manifests.pop() {
         for dep in manifest.into_dependencies() {
             let key = (dep.url.clone(), dep.version.clone());
 
+            graph.packages.push((source.clone(), dep.clone()));
+
             if downloaded.contains(&key) {
                 continue;
             } else {
                 downloaded.insert(key);
             }
 
-            match download_dependency(&data_dir, dep)? {
+            match download_dependency(&data_dir, dep, offline)? {
                 (package, Some(manifest)) => {
-                    manifests.push(manifest);
+                    let url = package.dependency.url.clone();
+                    let ver = package.dependency.version.clone();
+
+                    manifests.push((manifest, Source::Remote(url, ver)));
                     packages.push(package);
                 }
                 (package, None) => packages.push(package),
@@ -79,17 +179,24 @@ fn download_packages() -> Result<Vec<Package>, Error> {
         }
     }
 
-    Ok(packages)
+    Ok((packages, graph))
 }
 
 fn download_dependency(
     cache_dir: &Path,
     dependency: Dependency,
+    offline: bool,
 ) -> Result<(Package, Option<Manifest>), Error> {
     let dir = cache_dir.join(dependency.url.directory_name());
     let url = dependency.url.to_string();
     let (mut repo, fetch) = if dir.is_dir() {
-        (Repository::open(&dir)?, true)
+        (Repository::open(&dir)?, !offline)
+    } else if offline {
+        return Err(Error::generic(format!(
+            "The package {} isn't cached, and can't be downloaded when using \
+            --offline",
+            url
+        )));
     } else {
         println!("  Downloading {} {}", dependency.url, dependency.version);
         (Repository::clone(&url, &dir)?, false)
@@ -107,10 +214,18 @@ fn download_dependency(
     };
 
     let tag = tag.ok_or_else(|| {
-        format!(
-            "The version {} of package {} doesn't exist",
-            dependency.version, url
-        )
+        if offline {
+            format!(
+                "The version {} of package {} isn't cached, and can't be \
+                downloaded when using --offline",
+                dependency.version, url
+            )
+        } else {
+            format!(
+                "The version {} of package {} doesn't exist",
+                dependency.version, url
+            )
+        }
     })?;
 
     repo.checkout(&tag.target).map_err(|err| {
@@ -154,7 +269,7 @@ changes.",
     }
 }
 
-fn remove_dependencies(directory: &Path) -> Result<(), String> {
+pub(crate) fn remove_dependencies(directory: &Path) -> Result<(), String> {
     if directory.is_dir() {
         println!("Removing existing dependencies in {}", directory.display());
 
@@ -166,11 +281,15 @@ fn remove_dependencies(directory: &Path) -> Result<(), String> {
     Ok(())
 }
 
-fn install_packages(
+/// Installs the selected versions of the packages into the given directory.
+///
+/// The return value is a list of the installed dependencies.
+pub(crate) fn install_packages(
     packages: Vec<Package>,
     versions: Vec<(Url, Version)>,
     directory: &Path,
-) -> Result<(), String> {
+) -> Result<Vec<Dependency>, String> {
+    let mut installed = Vec::new();
     let repos = packages
         .into_iter()
         .map(|pkg| (pkg.dependency.url, pkg.repository))
@@ -199,7 +318,28 @@ fn install_packages(
             copy(&manifest_src, &base_dir.join(MANIFEST_FILE))
                 .map_err(|e| format!("Failed to copy inko.pkg: {}", e))?;
         }
+
+        installed.push(Dependency {
+            name: url.import_name(),
+            url,
+            version: ver,
+            checksum: Checksum::new(tag.target),
+        });
     }
 
-    Ok(())
+    Ok(installed)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn dep(url: &str, version: Version) -> Dependency {
+        Dependency {
+            url: Url::new(url),
+            name: url.to_string(),
+            version,
+            checksum: Checksum::new("abc"),
+        }
+    }
 }
@@ -0,0 +1,179 @@
+use crate::command::pkg::sync::{download_packages, Graph, Source};
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::pkg::manifest::{Dependency, Url};
+use compiler::pkg::version::{select, Version};
+use getopts::Options;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+const USAGE: &str = "inko pkg tree [OPTIONS]
+
+Print the dependency tree of the current project.
+
+For each package the selected version is shown. If this version differs from
+the version required by the parent package, the required version is shown as
+well. Packages of which the dependencies are already shown are marked with (*).
+
+Packages are resolved using the local package cache, and packages or versions
+that aren't cached are downloaded. When using the --offline flag, an error is
+produced instead.
+
+Examples:
+
+    inko pkg tree
+    inko pkg tree --offline";
+
+pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "offline", "Only use the local package cache");
+
+    let matches = options.parse(args)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let (packages, graph) = download_packages(matches.opt_present("offline"))?;
+    let versions = select(packages.iter().map(|p| &p.dependency));
+
+    print!("{}", tree(&graph, &versions));
+    Ok(0)
+}
+
+/// Returns the version selected for a dependency.
+pub(crate) fn selected_version(
+    versions: &[(Url, Version)],
+    dependency: &Dependency,
+) -> Version {
+    versions
+        .iter()
+        .find(|(url, ver)| {
+            url == &dependency.url && ver.major == dependency.version.major
+        })
+        .map(|(_, ver)| ver.clone())
+        .unwrap_or_else(|| dependency.version.clone())
+}
+
+fn tree(graph: &Graph, versions: &[(Url, Version)]) -> String {
+    let mut buffer = String::new();
+    let mut expanded = HashSet::new();
+
+    add_children(
+        graph,
+        versions,
+        &Graph::root(),
+        0,
+        &mut expanded,
+        &mut buffer,
+    );
+
+    buffer
+}
+
+fn add_children(
+    graph: &Graph,
+    versions: &[(Url, Version)],
+    source: &Source,
+    depth: usize,
+    expanded: &mut HashSet<Source>,
+    buffer: &mut String,
+) {
+    let indent = "  ".repeat(depth);
+
+    for (_, dir) in graph.local.iter().filter(|(src, _)| src == source) {
+        let _ = write!(buffer, "{}{} (local)", indent, dir.display());
+
+        add_node(
+            graph,
+            versions,
+            Source::Local(dir.clone()),
+            depth,
+            expanded,
+            buffer,
+        );
+    }
+
+    for (_, dep) in graph.packages.iter().filter(|(src, _)| src == source) {
+        let version = selected_version(versions, dep);
+
+        let _ = write!(buffer, "{}{} {}", indent, dep.url, version);
+
+        if version != dep.version {
+            let _ = write!(buffer, " (required: {})", dep.version);
+        }
+
+        add_node(
+            graph,
+            versions,
+            Source::Remote(dep.url.clone(), version),
+            depth,
+            expanded,
+            buffer,
+        );
+    }
+}
+
+fn add_node(
+    graph: &Graph,
+    versions: &[(Url, Version)],
+    source: Source,
+    depth: usize,
+    expanded: &mut HashSet<Source>,
+    buffer: &mut String,
+) {
+    let has_children = graph.local.iter().any(|(src, _)| src == &source)
+        || graph.packages.iter().any(|(src, _)| src == &source);
+
+    if !has_children {
+        buffer.push('\n');
+    } else if expanded.insert(source.clone()) {
+        buffer.push('\n');
+        add_children(graph, versions, &source, depth + 1, expanded, buffer);
+    } else {
+        buffer.push_str(" (*)\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::pkg::sync::tests::dep;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_tree() {
+        let mut graph = Graph::default();
+        let json = Source::Remote(Url::new("json"), Version::new(1, 2, 0));
+        let local = Source::Local(PathBuf::from("../shared"));
+
+        graph.local.push((Graph::root(), PathBuf::from("../shared")));
+        graph
+            .packages
+            .push((Graph::root(), dep("json", Version::new(1, 1, 0))));
+        graph
+            .packages
+            .push((Graph::root(), dep("http", Version::new(2, 0, 0))));
+        graph.packages.push((json, dep("utf8", Version::new(1, 0, 0))));
+        graph.packages.push((local, dep("json", Version::new(1, 2, 0))));
+
+        let versions = vec![
+            (Url::new("json"), Version::new(1, 2, 0)),
+            (Url::new("http"), Version::new(2, 0, 0)),
+            (Url::new("utf8"), Version::new(1, 0, 0)),
+        ];
+
+        assert_eq!(
+            tree(&graph, &versions),
+            "../shared (local)
+  json 1.2.0
+    utf8 1.0.0
+json 1.2.0 (required: 1.1.0) (*)
+http 2.0.0
+"
+        );
+    }
+}
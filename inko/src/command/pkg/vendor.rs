@@ -0,0 +1,62 @@
+use crate::command::pkg::sync::{
+    download_packages, install_packages, remove_dependencies,
+};
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::config::Config;
+use compiler::pkg::manifest::{Entry, Manifest, MANIFEST_FILE};
+use compiler::pkg::version::select;
+use getopts::Options;
+
+const USAGE: &str = "inko pkg vendor [OPTIONS]
+
+Copy the source code of all dependencies into the ./vendor directory.
+
+The ./vendor directory is meant to be committed to version control, allowing
+the project to be compiled without access to the network. The versions and
+checksums of the vendored packages are listed in ./vendor/inko.pkg.
+
+When the ./vendor directory exists, the compiler uses it instead of the ./dep
+directory.
+
+Examples:
+
+    inko pkg vendor
+    inko pkg vendor --offline";
+
+pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "offline", "Only use the local package cache");
+
+    let matches = options.parse(args)?;
+
+    if matches.opt_present("h") {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    println!("Updating package cache");
+
+    let config = Config::default();
+    let (packages, _) = download_packages(matches.opt_present("offline"))?;
+    let versions = select(packages.iter().map(|p| &p.dependency));
+
+    remove_dependencies(&config.vendor)?;
+    println!("Vendoring");
+
+    let mut manifest = Manifest {
+        entries: vec![Entry::Comment(
+            " This file is generated by `inko pkg vendor`, don't edit it."
+                .to_string(),
+        )],
+    };
+
+    for dep in install_packages(packages, versions, &config.vendor)? {
+        manifest.add_dependency(dep.url, dep.name, dep.version, dep.checksum);
+    }
+
+    manifest.save(&config.vendor.join(MANIFEST_FILE))?;
+    Ok(0)
+}
@@ -0,0 +1,149 @@
+use crate::command::pkg::sync::{download_packages, Graph};
+use crate::command::pkg::tree::selected_version;
+use crate::error::Error;
+use crate::options::print_usage;
+use compiler::pkg::manifest::Url;
+use compiler::pkg::version::{select, Version};
+use getopts::Options;
+use std::fmt::Write as _;
+
+const USAGE: &str = "inko pkg why [OPTIONS] URI [VERSION]
+
+Show which packages require the given package.
+
+For each requirement the required version is shown. The requirement that
+resulted in a version being selected is marked with (selected). If a version is
+given, only the requirements for the same major version are shown.
+
+Packages are resolved using the local package cache, and packages or versions
+that aren't cached are downloaded. When using the --offline flag, an error is
+produced instead.
+
+Examples:
+
+    inko pkg why github.com/inko-lang/example
+    inko pkg why github.com/inko-lang/example 1.2.3
+    inko pkg why --offline github.com/inko-lang/example";
+
+pub(crate) fn run(args: &[String]) -> Result<i32, Error> {
+    let mut options = Options::new();
+
+    options.optflag("h", "help", "Show this help message");
+    options.optflag("", "offline", "Only use the local package cache");
+
+    let matches = options.parse(args)?;
+
+    if matches.opt_present("h") || matches.free.is_empty() {
+        print_usage(&options, USAGE);
+        return Ok(0);
+    }
+
+    let url = matches
+        .free
+        .get(0)
+        .and_then(|uri| Url::parse(uri))
+        .ok_or_else(|| "The package URL is invalid".to_string())?;
+
+    let version = if let Some(val) = matches.free.get(1) {
+        Some(
+            Version::parse(val)
+                .ok_or_else(|| format!("The version '{}' is invalid", val))?,
+        )
+    } else {
+        None
+    };
+
+    let (packages, graph) = download_packages(matches.opt_present("offline"))?;
+    let versions = select(packages.iter().map(|p| &p.dependency));
+
+    if let Some(output) = why(&graph, &versions, &url, version.as_ref()) {
+        print!("{}", output);
+        Ok(0)
+    } else {
+        Err(Error::generic(format!(
+            "The package {} isn't required by any package",
+            url
+        )))
+    }
+}
+
+fn why(
+    graph: &Graph,
+    versions: &[(Url, Version)],
+    url: &Url,
+    version: Option<&Version>,
+) -> Option<String> {
+    let mut buffer = String::new();
+
+    for (source, dep) in &graph.packages {
+        if &dep.url != url
+            || version.map_or(false, |v| v.major != dep.version.major)
+        {
+            continue;
+        }
+
+        let _ = write!(buffer, "{} requires {} {}", source, url, dep.version);
+
+        if selected_version(versions, dep) == dep.version {
+            buffer.push_str(" (selected)");
+        }
+
+        buffer.push('\n');
+    }
+
+    if buffer.is_empty() {
+        None
+    } else {
+        Some(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::pkg::sync::tests::dep;
+    use crate::command::pkg::sync::Source;
+
+    #[test]
+    fn test_why() {
+        let mut graph = Graph::default();
+        let http = Source::Remote(Url::new("http"), Version::new(2, 0, 0));
+        let json = Url::new("json");
+
+        graph
+            .packages
+            .push((Graph::root(), dep("json", Version::new(1, 1, 0))));
+        graph
+            .packages
+            .push((Graph::root(), dep("http", Version::new(2, 0, 0))));
+        graph.packages.push((http.clone(), dep("json", Version::new(1, 2, 0))));
+        graph.packages.push((http, dep("json", Version::new(2, 0, 0))));
+
+        let versions = vec![
+            (Url::new("json"), Version::new(1, 2, 0)),
+            (Url::new("json"), Version::new(2, 0, 0)),
+            (Url::new("http"), Version::new(2, 0, 0)),
+        ];
+
+        assert_eq!(
+            why(&graph, &versions, &json, None),
+            Some(
+                "inko.pkg requires json 1.1.0
+http 2.0.0 requires json 1.2.0 (selected)
+http 2.0.0 requires json 2.0.0 (selected)
+"
+                .to_string()
+            )
+        );
+        assert_eq!(
+            why(&graph, &versions, &json, Some(&Version::new(1, 0, 0))),
+            Some(
+                "inko.pkg requires json 1.1.0
+http 2.0.0 requires json 1.2.0 (selected)
+"
+                .to_string()
+            )
+        );
+        assert_eq!(why(&graph, &versions, &Url::new("foo"), None), None);
+    }
+}
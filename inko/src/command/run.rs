@@ -1,29 +1,28 @@
 use crate::error::Error;
 use crate::options::print_usage;
+use crate::pkg::util::cache_dir;
 use compiler::compiler::{CompileError, Compiler};
 use compiler::config::Config;
 use getopts::{Options, ParsingStyle};
-use std::env::temp_dir;
-use std::fs::{create_dir, remove_dir_all};
 use std::path::PathBuf;
 use std::process::Command;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const USAGE: &str = "Usage: inko run [OPTIONS] [FILE] [ARGS]
 
 Compile a source file and its dependencies into an executable, then run it.
 
-Running source files is meant for development and scripting purposes, as it
-requires compiling source code from scratch every time. When distributing or
-deploying your Inko software, you should build it ahead of time using the
-\"inko build\" command.
+Running source files is meant for development and scripting purposes. The
+resulting executable is cached, and reused as long as the source code and
+compiler settings don't change. When distributing or deploying your Inko
+software, you should build it ahead of time using the \"inko build\" command.
 
 Arguments passed _after_ the file to run are passed to the resulting executable.
 
 Examples:
 
     inko run hello.inko        # Compile and run the file hello.inko
-    inko run hello.inko --foo  # Passes --foo to the resulting executable";
+    inko run hello.inko --foo  # Passes --foo to the resulting executable
+    inko run --profile cpu.txt hello.inko  # Write a CPU profile to cpu.txt";
 
 pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
@@ -37,6 +36,13 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         "FORMAT",
     );
 
+    options.optmulti(
+        "D",
+        "define",
+        "Define a build tag to use in conditional imports",
+        "TAG",
+    );
+
     options.optmulti(
         "i",
         "include",
@@ -46,6 +52,28 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
 
     options.optflag("", "static", "Statically link imported C libraries");
 
+    options.optopt("", "linker", "The program to use for linking", "PATH");
+
+    options.optmulti(
+        "",
+        "linker-arg",
+        "An extra argument to pass to the linker",
+        "ARG",
+    );
+
+    options.optmulti(
+        "L",
+        "library-path",
+        "A directory to search for C libraries",
+        "DIR",
+    );
+    options.optopt(
+        "",
+        "profile",
+        "Write a CPU profile of the executable to the given path",
+        "FILE",
+    );
+
     let matches = options.parse(arguments)?;
 
     if matches.opt_present("h") {
@@ -57,6 +85,12 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let arguments =
         if matches.free.len() > 1 { &matches.free[1..] } else { &[] };
 
+    config.load_manifest()?;
+
+    for tag in matches.opt_strs("D") {
+        config.add_build_tag(&tag)?;
+    }
+
     if let Some(format) = matches.opt_str("f") {
         config.set_presenter(&format)?;
     }
@@ -69,35 +103,34 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
         config.static_linking = true;
     }
 
-    let time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs();
-    let build_dir = temp_dir().join(format!("inko-run-{}", time));
-
-    if !build_dir.is_dir() {
-        create_dir(&build_dir).map_err(|err| {
-            Error::generic(format!(
-                "Failed to create {}: {}",
-                build_dir.display(),
-                err
-            ))
-        })?;
+    if let Some(val) = matches.opt_str("linker") {
+        config.linker.linker = Some(PathBuf::from(val));
     }
 
-    config.build = build_dir.clone();
+    config.linker.arguments.extend(matches.opt_strs("linker-arg"));
 
+    for path in matches.opt_strs("L") {
+        config.linker.library_paths.push(PathBuf::from(path));
+    }
+
+    let cache = cache_dir().map_err(Error::generic)?.join("run");
     let mut compiler = Compiler::new(config);
     let file = matches.free.get(0).map(PathBuf::from);
-    let result = compiler.build(file);
+    let result = compiler.build_cached(file, &cache);
 
     compiler.print_diagnostics();
 
     match result {
         Ok(exe) => {
-            let status = Command::new(exe)
-                .args(arguments)
-                .spawn()
+            let mut cmd = Command::new(exe);
+
+            cmd.args(arguments);
+
+            if let Some(path) = matches.opt_str("profile") {
+                cmd.env("INKO_CPU_PROFILE", path);
+            }
+
+            cmd.spawn()
                 .and_then(|mut child| child.wait())
                 .map_err(|err| {
                     Error::generic(format!(
@@ -105,15 +138,7 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
                         err
                     ))
                 })
-                .map(|status| status.code().unwrap_or(0));
-
-            if build_dir.is_dir() {
-                // If this fails that dosen't matter because temporary files are
-                // removed upon shutdown anyway.
-                let _ = remove_dir_all(&build_dir);
-            }
-
-            status
+                .map(|status| status.code().unwrap_or(0))
         }
         Err(CompileError::Invalid) => Ok(1),
         Err(CompileError::Internal(msg)) => Err(Error::generic(msg)),
@@ -3,6 +3,7 @@ use crate::options::print_usage;
 use compiler::compiler::{CompileError, Compiler};
 use compiler::config::{Config, Output};
 use getopts::Options;
+use std::path::PathBuf;
 use std::process::Command;
 
 const USAGE: &str = "Usage: inko test [OPTIONS]
@@ -21,6 +22,28 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut options = Options::new();
 
     options.optflag("h", "help", "Show this help message");
+    options.optmulti(
+        "D",
+        "define",
+        "Define a build tag to use in conditional imports",
+        "TAG",
+    );
+
+    options.optopt("", "linker", "The program to use for linking", "PATH");
+
+    options.optmulti(
+        "",
+        "linker-arg",
+        "An extra argument to pass to the linker",
+        "ARG",
+    );
+
+    options.optmulti(
+        "L",
+        "library-path",
+        "A directory to search for C libraries",
+        "DIR",
+    );
 
     let matches = options.parse(arguments)?;
 
@@ -32,6 +55,22 @@ pub(crate) fn run(arguments: &[String]) -> Result<i32, Error> {
     let mut config = Config::default();
     let input = config.main_test_module();
 
+    config.load_manifest()?;
+
+    for tag in matches.opt_strs("D") {
+        config.add_build_tag(&tag)?;
+    }
+
+    if let Some(val) = matches.opt_str("linker") {
+        config.linker.linker = Some(PathBuf::from(val));
+    }
+
+    config.linker.arguments.extend(matches.opt_strs("linker-arg"));
+
+    for path in matches.opt_strs("L") {
+        config.linker.library_paths.push(PathBuf::from(path));
+    }
+
     if !config.tests.is_dir() {
         return Err(Error::generic(format!(
             "The tests directory {:?} doesn't exist",
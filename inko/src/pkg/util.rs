@@ -20,6 +20,20 @@ pub(crate) fn data_dir() -> Result<PathBuf, String> {
         .ok_or_else(|| "No data directory could be determined".to_string())
 }
 
+pub(crate) fn cache_dir() -> Result<PathBuf, String> {
+    let base = if cfg!(target_os = "macos") {
+        home_dir().map(|h| h.join("Library").join("Caches"))
+    } else {
+        env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".cache")))
+    };
+
+    base.map(|p| p.join("inko"))
+        .ok_or_else(|| "No cache directory could be determined".to_string())
+}
+
 pub(crate) fn cp_r(source: &Path, target: &Path) -> Result<(), String> {
     create_dir_all(target).map_err(|e| e.to_string())?;
 
@@ -0,0 +1,77 @@
+//! Tracking of the time elapsed since the runtime started.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// A monotonic clock that starts when the runtime starts.
+///
+/// A clock either follows the system's monotonic clock, or is simulated. A
+/// simulated clock only moves forward when explicitly advanced, which is used
+/// by the deterministic scheduler to make timeouts expire without waiting for
+/// them.
+pub(crate) struct Clock {
+    /// The time at which the clock started.
+    start: Instant,
+
+    /// The time (in nanoseconds since the start) of a simulated clock.
+    simulated: Option<AtomicU64>,
+}
+
+impl Clock {
+    pub(crate) fn new() -> Clock {
+        Clock { start: Instant::now(), simulated: None }
+    }
+
+    pub(crate) fn simulated() -> Clock {
+        Clock { start: Instant::now(), simulated: Some(AtomicU64::new(0)) }
+    }
+
+    /// Returns the time elapsed since the clock started.
+    pub(crate) fn elapsed(&self) -> Duration {
+        if let Some(time) = &self.simulated {
+            Duration::from_nanos(time.load(Ordering::Acquire))
+        } else {
+            self.start.elapsed()
+        }
+    }
+
+    /// Moves a simulated clock forward by the given amount of time.
+    ///
+    /// For a clock that follows the system's clock this method does nothing.
+    pub(crate) fn advance(&self, time: Duration) {
+        if let Some(current) = &self.simulated {
+            current.fetch_add(time.as_nanos() as u64, Ordering::AcqRel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elapsed() {
+        let clock = Clock::new();
+        let time = clock.elapsed();
+
+        assert!(clock.elapsed() >= time);
+    }
+
+    #[test]
+    fn test_elapsed_with_simulated_clock() {
+        let clock = Clock::simulated();
+
+        assert_eq!(clock.elapsed(), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn test_advance() {
+        let real = Clock::new();
+        let simulated = Clock::simulated();
+
+        real.advance(Duration::from_secs(3600));
+        simulated.advance(Duration::from_secs(2));
+
+        assert!(real.elapsed() < Duration::from_secs(3600));
+        assert_eq!(simulated.elapsed(), Duration::from_secs(2));
+    }
+}
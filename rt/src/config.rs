@@ -3,7 +3,9 @@
 //! Various virtual machine settings that can be changed by the user, such as
 //! the number of threads to run.
 use crate::scheduler::number_of_cores;
+use rand::{thread_rng, Rng};
 use std::env::var;
+use std::path::PathBuf;
 
 /// Sets a configuration field based on an environment variable.
 macro_rules! set_from_env {
@@ -33,6 +35,9 @@ const MAX_NETPOLL_THREADS: u8 = 127;
 /// cases, and to ensure foreign function calls don't overflow the stack.
 const DEFAULT_STACK_SIZE: u32 = 1024 * 1024;
 
+/// The default number of samples per second taken by the CPU profiler.
+const DEFAULT_CPU_PROFILE_FREQUENCY: u32 = 100;
+
 /// Structure containing the configuration settings for the virtual machine.
 pub struct Config {
     /// The number of process threads to run.
@@ -51,6 +56,42 @@ pub struct Config {
     /// and use the value -1 to signal a file descriptor isn't registered with
     /// any poller.
     pub netpoll_threads: u8,
+
+    /// The path to write a heap profile to.
+    ///
+    /// When set, the number of allocations and releases are counted for every
+    /// class, and a report is written to this path when the program
+    /// terminates.
+    pub heap_profile: Option<PathBuf>,
+
+    /// The number of allocations after which to record the stack trace of an
+    /// allocation, when heap profiling is enabled.
+    ///
+    /// A value of zero means no stack traces are recorded.
+    pub heap_profile_sample_rate: u32,
+
+    /// The path to write a CPU profile to.
+    ///
+    /// When set, the stacks of running processes are sampled periodically,
+    /// and written to this path when the program terminates.
+    pub cpu_profile: Option<PathBuf>,
+
+    /// The number of times per second to sample the stacks of running
+    /// processes, when CPU profiling is enabled.
+    pub cpu_profile_frequency: u32,
+
+    /// A flag indicating if processes should be scheduled deterministically.
+    ///
+    /// When enabled, all processes run on a single thread, in an order
+    /// determined by `seed`. Timeouts don't depend on the system's clock,
+    /// instead they expire when no other processes are able to run.
+    pub deterministic: bool,
+
+    /// The seed to use for deterministic scheduling.
+    ///
+    /// If deterministic scheduling is enabled and no seed is given, a random
+    /// seed is used.
+    pub seed: u64,
 }
 
 impl Config {
@@ -62,6 +103,12 @@ impl Config {
             backup_threads: cpu_count * 4,
             netpoll_threads: DEFAULT_NETPOLL_THREADS,
             stack_size: DEFAULT_STACK_SIZE,
+            heap_profile: None,
+            heap_profile_sample_rate: 0,
+            cpu_profile: None,
+            cpu_profile_frequency: DEFAULT_CPU_PROFILE_FREQUENCY,
+            deterministic: false,
+            seed: 0,
         }
     }
 
@@ -72,6 +119,37 @@ impl Config {
         set_from_env!(config, backup_threads, "BACKUP_THREADS", u16);
         set_from_env!(config, netpoll_threads, "NETPOLL_THREADS", u8);
         set_from_env!(config, stack_size, "STACK_SIZE", u32);
+        set_from_env!(
+            config,
+            heap_profile_sample_rate,
+            "HEAP_PROFILE_SAMPLE_RATE",
+            u32
+        );
+
+        set_from_env!(
+            config,
+            cpu_profile_frequency,
+            "CPU_PROFILE_FREQUENCY",
+            u32
+        );
+
+        set_from_env!(config, seed, "SEED", u64);
+
+        if let Ok(value) = var("INKO_DETERMINISTIC") {
+            config.deterministic = value == "1" || value == "true";
+        }
+
+        if let Ok(path) = var("INKO_HEAP_PROFILE") {
+            if !path.is_empty() {
+                config.heap_profile = Some(PathBuf::from(path));
+            }
+        }
+
+        if let Ok(path) = var("INKO_CPU_PROFILE") {
+            if !path.is_empty() {
+                config.cpu_profile = Some(PathBuf::from(path));
+            }
+        }
 
         config.verify();
         config
@@ -81,6 +159,20 @@ impl Config {
         if self.netpoll_threads > MAX_NETPOLL_THREADS {
             self.netpoll_threads = MAX_NETPOLL_THREADS;
         }
+
+        // Setting a seed implies the use of deterministic scheduling.
+        if self.seed > 0 {
+            self.deterministic = true;
+        }
+
+        if self.deterministic {
+            self.process_threads = 1;
+            self.backup_threads = 0;
+
+            if self.seed == 0 {
+                self.seed = thread_rng().gen_range(1..u64::MAX);
+            }
+        }
     }
 }
 
@@ -125,4 +217,27 @@ mod tests {
         cfg.verify();
         assert_eq!(cfg.netpoll_threads, MAX_NETPOLL_THREADS);
     }
+
+    #[test]
+    fn test_verify_with_seed() {
+        let mut cfg = Config::new();
+
+        cfg.seed = 42;
+        cfg.verify();
+
+        assert!(cfg.deterministic);
+        assert_eq!(cfg.seed, 42);
+        assert_eq!(cfg.process_threads, 1);
+        assert_eq!(cfg.backup_threads, 0);
+    }
+
+    #[test]
+    fn test_verify_deterministic_without_seed() {
+        let mut cfg = Config::new();
+
+        cfg.deterministic = true;
+        cfg.verify();
+
+        assert!(cfg.seed > 0);
+    }
 }
@@ -6,6 +6,7 @@
 pub mod macros;
 
 pub mod arc_without_weak;
+pub mod clock;
 pub mod config;
 pub mod context;
 pub mod mem;
@@ -13,9 +14,11 @@ pub mod memory_map;
 pub mod network_poller;
 pub mod page;
 pub mod process;
+pub mod profiler;
 pub mod result;
 pub mod runtime;
 pub mod scheduler;
+pub mod slab;
 pub mod socket;
 pub mod stack;
 pub mod state;
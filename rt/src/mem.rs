@@ -1,3 +1,5 @@
+use crate::profiler::heap;
+use crate::slab;
 use std::alloc::{alloc, alloc_zeroed, dealloc, handle_alloc_error, Layout};
 use std::mem::{align_of, forget, size_of, swap};
 use std::ops::Deref;
@@ -9,7 +11,16 @@ use std::string::String as RustString;
 /// The alignment to use for Inko objects.
 const ALIGNMENT: usize = align_of::<usize>();
 
+/// Allocates memory for an object using the given layout.
+///
+/// Small objects are allocated using a size-class based allocator (see the
+/// `slab` module), while larger objects use the system allocator. Memory
+/// allocated using this function must be released using `free()`.
 pub(crate) fn allocate(layout: Layout) -> *mut u8 {
+    if layout.size() <= slab::MAX_SIZE {
+        return slab::allocate(layout.size());
+    }
+
     unsafe {
         let ptr = alloc(layout);
 
@@ -28,7 +39,11 @@ pub(crate) unsafe fn header_of<'a, T>(ptr: *const T) -> &'a mut Header {
 pub(crate) unsafe fn free<T>(ptr: *mut T) {
     let layout = header_of(ptr).class.instance_layout();
 
-    dealloc(ptr as *mut u8, layout);
+    if layout.size() <= slab::MAX_SIZE {
+        slab::free(ptr as *mut u8);
+    } else {
+        dealloc(ptr as *mut u8, layout);
+    }
 }
 
 /// The header used by heap allocated objects.
@@ -182,9 +197,14 @@ impl Class {
 
 /// A pointer to a class.
 #[repr(transparent)]
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
 pub struct ClassPointer(*mut Class);
 
+// Classes are immutable once created, so it's safe to share pointers to them
+// between threads.
+unsafe impl Sync for ClassPointer {}
+unsafe impl Send for ClassPointer {}
+
 impl Deref for ClassPointer {
     type Target = Class;
 
@@ -211,6 +231,7 @@ impl ByteArray {
 
         obj.header.init(class);
         init!(obj.value => value);
+        heap::allocated(ptr as _, class);
         ptr
     }
 
@@ -284,6 +305,7 @@ impl String {
         obj.header.init_atomic(class);
         init!(obj.size => len as u64);
         init!(obj.bytes => buffer);
+        heap::allocated(ptr as _, class);
         ptr as _
     }
 
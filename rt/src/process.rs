@@ -7,6 +7,7 @@ use backtrace;
 use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
 use std::cell::UnsafeCell;
 use std::collections::VecDeque;
+use std::ffi::c_void;
 use std::mem::{align_of, forget, size_of, ManuallyDrop};
 use std::ops::Drop;
 use std::ops::{Deref, DerefMut};
@@ -40,6 +41,68 @@ pub struct StackFrame {
     pub line: i64,
 }
 
+/// Resolves the Inko stack frames of an instruction pointer, adding them to
+/// the given list of frames.
+///
+/// Frames for code not written in Inko are ignored.
+pub(crate) fn resolve_frame(ip: *mut c_void, frames: &mut Vec<StackFrame>) {
+    backtrace::resolve(ip, |symbol| {
+        let name = if let Some(sym_name) = symbol.name() {
+            // We only want to include frames for Inko source code, not
+            // any additional frames introduced by the runtime library
+            // and its dependencies.
+            let base = if let Some(name) = sym_name
+                .as_str()
+                .unwrap_or("")
+                .strip_prefix(INKO_SYMBOL_IDENTIFIER)
+            {
+                name
+            } else {
+                return;
+            };
+
+            // Methods include the type IDs to prevent name conflicts.
+            // We get rid of these to ensure the stacktraces are easier
+            // to understand.
+            if let Some(idx) = base.find('#') {
+                base[0..idx].to_string()
+            } else {
+                base.to_string()
+            }
+        } else {
+            String::new()
+        };
+
+        let path = symbol
+            .filename()
+            .map(|v| v.to_string_lossy().into_owned())
+            .unwrap_or_else(String::new);
+
+        let line = symbol.lineno().unwrap_or(0) as i64;
+
+        frames.push(StackFrame { name, path, line });
+    });
+}
+
+/// Returns the Inko stack frames of the stack of the current thread.
+///
+/// The current thread is expected to be running an Inko process, and frames
+/// for code outside of Inko (e.g. the runtime library) are excluded.
+pub(crate) fn stacktrace() -> Vec<StackFrame> {
+    let mut frames = Vec::new();
+
+    // We don't use backtrace::trace() so we can avoid the frames introduced
+    // by calling this function (and any functions it may call).
+    let trace = backtrace::Backtrace::new();
+
+    for frame in trace.frames() {
+        resolve_frame(frame.ip(), &mut frames);
+    }
+
+    frames.reverse();
+    frames
+}
+
 /// A message sent between two processes.
 #[repr(C)]
 pub struct Message {
@@ -72,6 +135,14 @@ impl Message {
         }
     }
 
+    /// Sets the argument at the given index.
+    ///
+    /// The index must be less than the number of arguments the message is
+    /// allocated for.
+    pub(crate) unsafe fn set_argument(&mut self, index: usize, value: *mut u8) {
+        *self.arguments.as_mut_ptr().add(index) = value;
+    }
+
     unsafe fn layout(length: u8) -> Layout {
         let size = size_of::<Self>() + (length as usize * size_of::<*mut u8>());
 
@@ -638,53 +709,7 @@ impl Process {
     }
 
     pub(crate) fn stacktrace(&self) -> Vec<StackFrame> {
-        let mut frames = Vec::new();
-
-        // We don't use backtrace::trace() so we can avoid the frames introduced
-        // by calling this function (and any functions it may call).
-        let trace = backtrace::Backtrace::new();
-
-        for frame in trace.frames() {
-            backtrace::resolve(frame.ip(), |symbol| {
-                let name = if let Some(sym_name) = symbol.name() {
-                    // We only want to include frames for Inko source code, not
-                    // any additional frames introduced by the runtime library
-                    // and its dependencies.
-                    let base = if let Some(name) = sym_name
-                        .as_str()
-                        .unwrap_or("")
-                        .strip_prefix(INKO_SYMBOL_IDENTIFIER)
-                    {
-                        name
-                    } else {
-                        return;
-                    };
-
-                    // Methods include the type IDs to prevent name conflicts.
-                    // We get rid of these to ensure the stacktraces are easier
-                    // to understand.
-                    if let Some(idx) = base.find('#') {
-                        base[0..idx].to_string()
-                    } else {
-                        base.to_string()
-                    }
-                } else {
-                    String::new()
-                };
-
-                let path = symbol
-                    .filename()
-                    .map(|v| v.to_string_lossy().into_owned())
-                    .unwrap_or_else(String::new);
-
-                let line = symbol.lineno().unwrap_or(0) as i64;
-
-                frames.push(StackFrame { name, path, line });
-            });
-        }
-
-        frames.reverse();
-        frames
+        stacktrace()
     }
 }
 
@@ -750,6 +775,16 @@ pub(crate) enum ReceiveResult {
     Reschedule(*mut u8, ProcessPointer),
 }
 
+#[derive(Eq, PartialEq, Debug)]
+pub(crate) enum SelectResult {
+    /// None of the channels have a message, and the receiver is waiting for a
+    /// message to be sent to any of them.
+    None,
+
+    /// A message is received from the channel at the given index.
+    Some(usize, ReceiveResult),
+}
+
 /// The internal (synchronised) state of a channel.
 pub(crate) struct ChannelState {
     /// The index into the ring buffer to use for sending a new value.
@@ -874,24 +909,26 @@ impl Channel {
             return SendResult::Full;
         }
 
-        if let Some(receiver) = state.waiting_for_message.pop() {
-            // We don't need to keep the lock any longer than necessary.
-            drop(state);
-
-            // The process may be waiting for more than one channel to receive a
-            // message. In this case it's possible that multiple different
-            // processes try to reschedule the same waiting process, so we have
-            // to acquire the rescheduling rights first.
+        // The process may be waiting for more than one channel to receive a
+        // message. In this case it's possible that multiple different
+        // processes try to reschedule the same waiting process, so we have to
+        // acquire the rescheduling rights first. If this fails, the process is
+        // already rescheduled and its entry is stale, so we try the next
+        // process. Stopping at the stale entry would leave the other processes
+        // waiting, even though a message is available.
+        while let Some(receiver) = state.waiting_for_message.pop() {
             match receiver.state().try_reschedule_for_channel() {
-                RescheduleRights::Failed => SendResult::Sent,
-                RescheduleRights::Acquired => SendResult::Reschedule(receiver),
+                RescheduleRights::Failed => continue,
+                RescheduleRights::Acquired => {
+                    return SendResult::Reschedule(receiver);
+                }
                 RescheduleRights::AcquiredWithTimeout => {
-                    SendResult::RescheduleWithTimeout(receiver)
+                    return SendResult::RescheduleWithTimeout(receiver);
                 }
             }
-        } else {
-            SendResult::Sent
         }
+
+        SendResult::Sent
     }
 
     pub(crate) fn receive(
@@ -914,6 +951,68 @@ impl Channel {
         }
     }
 
+    /// Receives a message from the first of the given channels that has one.
+    ///
+    /// If none of the channels have a message, the receiver is registered as
+    /// waiting for a message on all of them.
+    ///
+    /// All channels are locked for the duration of this method, such that
+    /// checking for messages and registering the receiver is atomic. This
+    /// ensures the receiver's status is only updated once, and only when it
+    /// is registered with the channels.
+    pub(crate) fn select(
+        channels: &[&Channel],
+        receiver: ProcessPointer,
+        timeout: Option<ArcWithoutWeak<Timeout>>,
+    ) -> SelectResult {
+        // The locks are acquired in a fixed order to prevent deadlocks when
+        // multiple processes select the same channels in a different order.
+        // The same channel may be given more than once, in which case we only
+        // lock it once.
+        let mut order: Vec<usize> = (0..channels.len()).collect();
+
+        order.sort_by_key(|&index| channels[index] as *const Channel);
+        order.dedup_by_key(|index| channels[*index] as *const Channel);
+
+        let mut states: Vec<(usize, MutexGuard<ChannelState>)> = order
+            .into_iter()
+            .map(|index| (index, channels[index].state.lock().unwrap()))
+            .collect();
+
+        // Channels are checked in the order they are given in, not the order
+        // in which they are locked.
+        states.sort_by_key(|(index, _)| *index);
+
+        for (index, state) in &mut states {
+            if let Some(msg) = state.receive() {
+                let result = if let Some(proc) = state.waiting_for_space.pop() {
+                    ReceiveResult::Reschedule(msg, proc)
+                } else {
+                    ReceiveResult::Some(msg)
+                };
+
+                return SelectResult::Some(*index, result);
+            }
+        }
+
+        receiver.state().waiting_for_channel(timeout);
+
+        for (_, state) in &mut states {
+            state.waiting_for_message.push(receiver);
+        }
+
+        SelectResult::None
+    }
+
+    /// Removes a process from the list of processes waiting for a message.
+    pub(crate) fn unsubscribe(&self, receiver: ProcessPointer) {
+        self.state
+            .lock()
+            .unwrap()
+            .waiting_for_message
+            .retain(|&process| process != receiver);
+    }
+
     pub(crate) fn try_receive(&self) -> ReceiveResult {
         let mut state = self.state.lock().unwrap();
 
@@ -929,6 +1028,114 @@ impl Channel {
     }
 }
 
+/// The internal (synchronised) state of a future.
+pub(crate) struct FutureState {
+    /// The value the future is resolved to, if it's not yet taken.
+    ///
+    /// NULL is a valid value, as Inko uses 0x0/0 for `nil` and `false`.
+    value: Option<*mut u8>,
+
+    /// The process waiting for the future to be resolved.
+    waiting: Option<ProcessPointer>,
+
+    /// The number of handles to this future.
+    handles: usize,
+}
+
+/// A value that's computed by another process.
+///
+/// A future is shared between the process that waits for its value, and the
+/// process that resolves it. Both processes have their own handle to the
+/// future, and the future is released when the last handle is dropped.
+///
+/// Futures are used to return values from async methods: the sender creates the
+/// future and passes a handle to the receiver, which resolves the future when
+/// it finishes running the method.
+#[repr(C)]
+pub struct Future {
+    pub(crate) state: Mutex<FutureState>,
+}
+
+impl Future {
+    pub(crate) fn new() -> Future {
+        Future {
+            state: Mutex::new(FutureState {
+                value: None,
+                waiting: None,
+                handles: 1,
+            }),
+        }
+    }
+
+    /// Releases a handle to the future.
+    ///
+    /// If this was the last handle, the future is dropped and the value it was
+    /// resolved to (if any) is returned, such that the caller can drop it.
+    pub(crate) unsafe fn release(ptr: *mut Future) -> Option<*mut u8> {
+        let value = {
+            let mut state = (*ptr).state.lock().unwrap();
+
+            state.handles -= 1;
+
+            if state.handles > 0 {
+                return None;
+            }
+
+            state.value.take()
+        };
+
+        drop(Box::from_raw(ptr));
+        value
+    }
+
+    /// Adds a new handle to the future.
+    pub(crate) fn retain(&self) {
+        self.state.lock().unwrap().handles += 1;
+    }
+
+    /// Resolves the future to the given value.
+    pub(crate) fn set(&self, value: *mut u8) -> SendResult {
+        let mut state = self.state.lock().unwrap();
+
+        state.value = Some(value);
+
+        if let Some(receiver) = state.waiting.take() {
+            drop(state);
+
+            // Waiting for a future is treated the same as waiting for a
+            // channel, allowing us to reuse the logic for rescheduling
+            // processes after a timeout.
+            match receiver.state().try_reschedule_for_channel() {
+                RescheduleRights::Failed => SendResult::Sent,
+                RescheduleRights::Acquired => SendResult::Reschedule(receiver),
+                RescheduleRights::AcquiredWithTimeout => {
+                    SendResult::RescheduleWithTimeout(receiver)
+                }
+            }
+        } else {
+            SendResult::Sent
+        }
+    }
+
+    /// Takes the value of the future, or marks the process as waiting for the
+    /// future if it isn't resolved yet.
+    pub(crate) fn get(
+        &self,
+        receiver: ProcessPointer,
+        timeout: Option<ArcWithoutWeak<Timeout>>,
+    ) -> ReceiveResult {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(value) = state.value.take() {
+            ReceiveResult::Some(value)
+        } else {
+            receiver.state().waiting_for_channel(timeout);
+            state.waiting = Some(receiver);
+            ReceiveResult::None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1368,6 +1575,208 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_channel_select_empty() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan1 = Channel::new(1);
+        let chan2 = Channel::new(1);
+
+        assert_eq!(
+            Channel::select(&[&chan1, &chan2], *process, None),
+            SelectResult::None
+        );
+        assert!(process.state().status.is_waiting_for_channel());
+        assert_eq!(
+            chan1.state.lock().unwrap().waiting_for_message,
+            vec![*process]
+        );
+        assert_eq!(
+            chan2.state.lock().unwrap().waiting_for_message,
+            vec![*process]
+        );
+    }
+
+    #[test]
+    fn test_channel_select_with_messages() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan1 = Channel::new(1);
+        let chan2 = Channel::new(1);
+
+        chan2.send(*process, 42 as _);
+
+        assert_eq!(
+            Channel::select(&[&chan1, &chan2], *process, None),
+            SelectResult::Some(1, ReceiveResult::Some(42 as _))
+        );
+        assert!(!process.state().status.is_waiting_for_channel());
+        assert!(chan1.state.lock().unwrap().waiting_for_message.is_empty());
+
+        chan1.send(*process, 10 as _);
+        chan2.send(*process, 20 as _);
+
+        assert_eq!(
+            Channel::select(&[&chan1, &chan2], *process, None),
+            SelectResult::Some(0, ReceiveResult::Some(10 as _))
+        );
+    }
+
+    #[test]
+    fn test_channel_select_same_channel() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan = Channel::new(1);
+
+        assert_eq!(
+            Channel::select(&[&chan, &chan], *process, None),
+            SelectResult::None
+        );
+        assert_eq!(
+            chan.state.lock().unwrap().waiting_for_message,
+            vec![*process]
+        );
+
+        chan.unsubscribe(*process);
+        chan.send(*process, 42 as _);
+
+        assert_eq!(
+            Channel::select(&[&chan, &chan], *process, None),
+            SelectResult::Some(0, ReceiveResult::Some(42 as _))
+        );
+    }
+
+    #[test]
+    fn test_channel_select_reschedule() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan1 = Channel::new(1);
+        let chan2 = Channel::new(1);
+        let state = setup();
+        let timeout = Timeout::duration(&state, Duration::from_secs(10));
+
+        assert_eq!(
+            Channel::select(&[&chan1, &chan2], *process, Some(timeout)),
+            SelectResult::None
+        );
+        assert_eq!(
+            chan1.send(*process, 10 as _),
+            SendResult::RescheduleWithTimeout(*process)
+        );
+        assert_eq!(chan2.send(*process, 20 as _), SendResult::Sent);
+        assert!(!process.state().status.is_waiting());
+        assert!(process.state().timeout.is_none());
+
+        chan1.unsubscribe(*process);
+        chan2.unsubscribe(*process);
+
+        assert_eq!(
+            Channel::select(&[&chan1, &chan2], *process, None),
+            SelectResult::Some(0, ReceiveResult::Some(10 as _))
+        );
+        assert!(!process.state().status.is_waiting());
+    }
+
+    #[test]
+    fn test_channel_send_with_stale_selector() {
+        let process_class = empty_process_class("A");
+        let selector =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let receiver =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan1 = Channel::new(1);
+        let chan2 = Channel::new(1);
+
+        assert_eq!(
+            Channel::select(&[&chan1, &chan2], *selector, None),
+            SelectResult::None
+        );
+        assert_eq!(chan2.receive(*receiver, None), ReceiveResult::None);
+
+        // The selector is woken up by the first channel, but remains
+        // registered with the second channel until it runs again.
+        assert_eq!(
+            chan1.send(*selector, 10 as _),
+            SendResult::Reschedule(*selector)
+        );
+
+        // The selector is already rescheduled, so the receiver must be woken
+        // up instead.
+        assert_eq!(
+            chan2.send(*selector, 20 as _),
+            SendResult::Reschedule(*receiver)
+        );
+        assert!(!receiver.state().status.is_waiting());
+        assert_eq!(
+            chan2.state.lock().unwrap().waiting_for_message,
+            vec![*selector]
+        );
+    }
+
+    #[test]
+    fn test_channel_unsubscribe() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let chan = Channel::new(1);
+
+        chan.receive(*process, None);
+        chan.unsubscribe(*process);
+
+        assert!(chan.state.lock().unwrap().waiting_for_message.is_empty());
+    }
+
+    #[test]
+    fn test_future_set_without_waiting() {
+        let future = Future::new();
+
+        assert_eq!(future.set(42 as _), SendResult::Sent);
+        assert_eq!(future.state.lock().unwrap().value, Some(42 as _));
+    }
+
+    #[test]
+    fn test_future_set_with_waiting() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let future = Future::new();
+
+        assert_eq!(future.get(*process, None), ReceiveResult::None);
+        assert!(process.state().status.is_waiting_for_channel());
+        assert_eq!(future.set(42 as _), SendResult::Reschedule(*process));
+        assert_eq!(future.get(*process, None), ReceiveResult::Some(42 as _));
+    }
+
+    #[test]
+    fn test_future_get_resolved() {
+        let process_class = empty_process_class("A");
+        let process =
+            OwnedProcess::new(Process::alloc(*process_class, Stack::new(32)));
+        let future = Future::new();
+
+        future.set(42 as _);
+
+        assert_eq!(future.get(*process, None), ReceiveResult::Some(42 as _));
+        assert!(!process.state().status.is_waiting_for_channel());
+    }
+
+    #[test]
+    fn test_future_release() {
+        let future = Box::into_raw(Box::new(Future::new()));
+
+        unsafe {
+            (*future).retain();
+            (*future).set(42 as _);
+
+            assert_eq!(Future::release(future), None);
+            assert_eq!(Future::release(future), Some(42 as _));
+        }
+    }
+
     #[test]
     fn test_message_new() {
         let message = Message::alloc(method, 2);
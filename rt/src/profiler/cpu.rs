@@ -0,0 +1,710 @@
+//! Sampling of the stacks of running processes.
+//!
+//! When enabled, a dedicated thread periodically interrupts every process
+//! thread using a signal. The signal handler records the instruction pointers
+//! of the interrupted thread's stack, which the profiler thread then aggregates.
+//! When the program terminates, the samples are resolved into Inko stack frames
+//! and written to a file using the "folded stacks" format, as used by tools
+//! such as [FlameGraph](https://github.com/brendangregg/FlameGraph) and
+//! [inferno](https://github.com/jonhoo/inferno).
+//!
+//! Threads are sampled one at a time, such that we only need a single buffer
+//! to store the instruction pointers in.
+//!
+//! The signal handler may interrupt a thread at any point, including while it
+//! holds a lock (e.g. in `malloc()`), so it must only use async-signal-safe
+//! functions. For this reason we don't use a regular unwinder (which acquires
+//! locks), and instead walk the chain of frame pointers starting at the
+//! registers of the interrupted code. Frame pointers may be invalid (e.g. when
+//! interrupting code compiled without them), so memory is read by writing it to
+//! a pipe and reading it back, which fails for invalid addresses instead of
+//! crashing the program.
+use crate::process::{resolve_frame, StackFrame};
+use crate::runtime::errno_location;
+use libc::{
+    c_void, fcntl, pipe, pthread_kill, pthread_self, pthread_t, read,
+    sigaction, sigemptyset, write, F_GETFL, F_SETFL, O_NONBLOCK, SA_RESTART,
+    SA_SIGINFO, SIGPROF,
+};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::mem::{size_of, zeroed};
+use std::path::PathBuf;
+use std::ptr::null_mut;
+use std::sync::atomic::{
+    AtomicBool, AtomicI32, AtomicPtr, AtomicUsize, Ordering,
+};
+use std::sync::{Mutex, Once};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The maximum number of stack frames to record per sample.
+const MAX_FRAMES: usize = 128;
+
+/// The maximum distance between two frame pointers.
+///
+/// If the distance is greater, we assume the frame pointer is invalid and stop
+/// walking the stack.
+const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+/// The maximum amount of time to wait for a thread to record its stack.
+///
+/// If a thread doesn't finish recording its stack within this time, its sample
+/// is skipped.
+const SAMPLE_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// The bits of the buffer state that store its status, the remaining bits store
+/// the ID of the current request.
+const STATUS_MASK: usize = 0b111;
+
+/// The number of bits used for the status of the buffer.
+const STATUS_BITS: u32 = 3;
+
+/// The sample buffer isn't in use.
+const IDLE: usize = 0;
+
+/// A thread is asked to record its stack.
+const REQUESTED: usize = 1;
+
+/// A thread is recording its stack.
+const WRITING: usize = 2;
+
+/// A thread finished recording its stack.
+const DONE: usize = 3;
+
+/// A thread is recording its stack, but the profiler stopped waiting for it.
+///
+/// Once the thread finishes, it resets the buffer to IDLE.
+const ABANDONED: usize = 4;
+
+/// The file descriptors of the pipe used for reading memory, in the form
+/// `[reader, writer]`.
+static PIPE: [AtomicI32; 2] = [AtomicI32::new(-1), AtomicI32::new(-1)];
+
+/// Ensures the signal handler and pipe are only set up once.
+static SETUP: Once = Once::new();
+
+/// A flag indicating if the profiler is sampling threads.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The profiler to use, if any.
+///
+/// Similar to the heap profiler, the profiler is never dropped once started.
+static PROFILER: AtomicPtr<Profiler> = AtomicPtr::new(null_mut());
+
+/// The buffer the signal handler writes the instruction pointers to.
+static BUFFER: Buffer = Buffer {
+    thread: AtomicUsize::new(0),
+    state: AtomicUsize::new(IDLE),
+    len: AtomicUsize::new(0),
+    frames: UnsafeCell::new([0; MAX_FRAMES]),
+};
+
+struct Buffer {
+    /// The thread that is to record its stack.
+    thread: AtomicUsize,
+
+    /// The state of the buffer.
+    ///
+    /// The lower bits store the status (e.g. `REQUESTED`), while the remaining
+    /// bits store the ID of the current request. The ID is incremented for
+    /// every request, such that a signal handler that runs too late can't
+    /// claim a request meant for another thread.
+    state: AtomicUsize,
+
+    /// The number of instruction pointers recorded.
+    len: AtomicUsize,
+
+    /// The instruction pointers of the stack, starting with the most recent
+    /// call.
+    frames: UnsafeCell<[usize; MAX_FRAMES]>,
+}
+
+// The frames are only written to while the status is WRITING or ABANDONED, and
+// only read while the status is DONE.
+unsafe impl Sync for Buffer {}
+
+/// A sampling CPU profiler.
+pub(crate) struct Profiler {
+    /// The path to write the report to.
+    path: PathBuf,
+
+    /// The time between two samples.
+    interval: Duration,
+
+    /// The threads to sample.
+    threads: Mutex<Vec<usize>>,
+
+    /// The thread that samples the process threads.
+    sampler: Mutex<Option<JoinHandle<()>>>,
+
+    /// The recorded stacks (starting with the most recent call) and the
+    /// number of times they were recorded.
+    samples: Mutex<HashMap<Vec<usize>, u64>>,
+}
+
+impl Profiler {
+    fn new(path: PathBuf, frequency: u32) -> Profiler {
+        Profiler {
+            path,
+            interval: Duration::from_secs(1) / frequency.max(1),
+            threads: Mutex::new(Vec::new()),
+            sampler: Mutex::new(None),
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn run(&self) {
+        let mut next = Instant::now() + self.interval;
+
+        while ENABLED.load(Ordering::Acquire) {
+            let now = Instant::now();
+
+            if next > now {
+                thread::sleep(next - now);
+            }
+
+            next += self.interval;
+
+            let threads = self.threads.lock().unwrap().clone();
+
+            for thread in threads {
+                if !ENABLED.load(Ordering::Acquire) {
+                    return;
+                }
+
+                // The lock is held while sampling, such that a thread can't
+                // unregister itself and terminate while we're signalling it.
+                let registered = self.threads.lock().unwrap();
+
+                if !registered.contains(&thread) {
+                    continue;
+                }
+
+                let sample = self.sample(thread);
+
+                drop(registered);
+
+                if let Some(stack) = sample {
+                    *self.samples.lock().unwrap().entry(stack).or_insert(0) +=
+                        1;
+                }
+            }
+        }
+    }
+
+    /// Records the stack of a single thread.
+    fn sample(&self, thread: usize) -> Option<Vec<usize>> {
+        let current = BUFFER.state.load(Ordering::Acquire);
+
+        // A thread we stopped waiting for may still be writing to the buffer,
+        // in which case we can't use it yet.
+        if status(current) != IDLE {
+            return None;
+        }
+
+        let request = (current & !STATUS_MASK).wrapping_add(1 << STATUS_BITS);
+
+        // The thread must be stored before publishing the request, such that
+        // the signal handler observes the thread that belongs to the request.
+        BUFFER.thread.store(thread, Ordering::Release);
+        BUFFER.state.store(request | REQUESTED, Ordering::Release);
+
+        if unsafe { pthread_kill(thread as pthread_t, SIGPROF) } != 0 {
+            BUFFER.state.store(request | IDLE, Ordering::Release);
+            return None;
+        }
+
+        let start = Instant::now();
+
+        loop {
+            let state = BUFFER.state.load(Ordering::Acquire);
+
+            match status(state) {
+                DONE => break,
+                REQUESTED | WRITING if start.elapsed() >= SAMPLE_TIMEOUT => {
+                    // If the thread didn't start writing yet, the buffer can be
+                    // reused right away. If it's still writing, it resets the
+                    // buffer once it's done.
+                    let new = if status(state) == REQUESTED {
+                        request | IDLE
+                    } else {
+                        request | ABANDONED
+                    };
+
+                    // If this fails, the status changed in the mean time and
+                    // we'll check again.
+                    if BUFFER
+                        .state
+                        .compare_exchange(
+                            state,
+                            new,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        )
+                        .is_ok()
+                    {
+                        return None;
+                    }
+                }
+                _ => thread::yield_now(),
+            }
+        }
+
+        let len = BUFFER.len.load(Ordering::Acquire);
+        let stack = unsafe { (&*BUFFER.frames.get())[0..len].to_vec() };
+
+        BUFFER.state.store(request | IDLE, Ordering::Release);
+        Some(stack)
+    }
+
+    /// Returns the report of this profiler in the folded stacks format.
+    fn report(&self) -> String {
+        let samples = self.samples.lock().unwrap();
+        let mut symbols: HashMap<usize, Vec<StackFrame>> = HashMap::new();
+        let mut stacks: HashMap<String, u64> = HashMap::new();
+
+        for (ips, &count) in samples.iter() {
+            let mut names = Vec::new();
+
+            for &ip in ips.iter().rev() {
+                let frames = symbols.entry(ip).or_insert_with(|| {
+                    let mut frames = Vec::new();
+
+                    // The instruction pointers of the callers point to the
+                    // instruction _after_ the call, which may belong to a
+                    // different line or function. Subtracting one ensures we
+                    // resolve the call instruction instead.
+                    resolve_frame(ip.saturating_sub(1) as _, &mut frames);
+
+                    // When inlining is involved we get the innermost frame
+                    // first, but we want the outermost frame first.
+                    frames.reverse();
+                    frames
+                });
+
+                names.extend(
+                    frames
+                        .iter()
+                        .filter(|f| !f.name.is_empty())
+                        .map(|f| f.name.clone()),
+                );
+            }
+
+            // Samples taken while not running any Inko code (e.g. when the
+            // scheduler is looking for work) are ignored.
+            if names.is_empty() {
+                continue;
+            }
+
+            *stacks.entry(names.join(";")).or_insert(0) += count;
+        }
+
+        let mut lines: Vec<_> = stacks.into_iter().collect();
+        let mut buf = String::new();
+
+        lines.sort();
+
+        for (stack, count) in lines {
+            let _ = writeln!(buf, "{} {}", stack, count);
+        }
+
+        buf
+    }
+}
+
+fn status(state: usize) -> usize {
+    state & STATUS_MASK
+}
+
+/// Returns the instruction pointer and frame pointer of the interrupted code.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+unsafe fn registers(context: *mut c_void) -> (usize, usize) {
+    let regs = &(*(context as *const libc::ucontext_t)).uc_mcontext.gregs;
+
+    (
+        regs[libc::REG_RIP as usize] as usize,
+        regs[libc::REG_RBP as usize] as usize,
+    )
+}
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+unsafe fn registers(context: *mut c_void) -> (usize, usize) {
+    let ctx = &(*(context as *const libc::ucontext_t)).uc_mcontext;
+
+    (ctx.pc as usize, ctx.regs[29] as usize)
+}
+
+#[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+unsafe fn registers(context: *mut c_void) -> (usize, usize) {
+    let ctx = &(*(*(context as *const libc::ucontext_t)).uc_mcontext).__ss;
+
+    (ctx.__rip as usize, ctx.__rbp as usize)
+}
+
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+unsafe fn registers(context: *mut c_void) -> (usize, usize) {
+    let ctx = &(*(*(context as *const libc::ucontext_t)).uc_mcontext).__ss;
+
+    (ctx.__pc as usize, ctx.__fp as usize)
+}
+
+#[cfg(all(target_os = "freebsd", target_arch = "x86_64"))]
+unsafe fn registers(context: *mut c_void) -> (usize, usize) {
+    let ctx = &(*(context as *const libc::ucontext_t)).uc_mcontext;
+
+    (ctx.mc_rip as usize, ctx.mc_rbp as usize)
+}
+
+#[cfg(all(target_os = "freebsd", target_arch = "aarch64"))]
+unsafe fn registers(context: *mut c_void) -> (usize, usize) {
+    let ctx = &(*(context as *const libc::ucontext_t)).uc_mcontext.mc_gpregs;
+
+    (ctx.gp_elr as usize, ctx.gp_x[29] as usize)
+}
+
+/// Reads the frame record (the caller's frame pointer and the return address)
+/// stored at the given frame pointer.
+///
+/// If the memory can't be read, a None is returned.
+unsafe fn read_frame(pointer: usize) -> Option<[usize; 2]> {
+    let mut record = [0_usize; 2];
+    let size = size_of::<[usize; 2]>();
+    let reader = PIPE[0].load(Ordering::Acquire);
+    let writer = PIPE[1].load(Ordering::Acquire);
+    let written = write(writer, pointer as *const c_void, size);
+
+    // If only part of the record could be read, we still need to remove the
+    // part that was written from the pipe.
+    if written > 0 {
+        read(reader, record.as_mut_ptr() as *mut c_void, written as usize);
+    }
+
+    if written == size as isize {
+        Some(record)
+    } else {
+        None
+    }
+}
+
+/// Records the instruction pointers of the stack, starting with the given
+/// instruction and frame pointer.
+///
+/// The return value is the number of instruction pointers recorded.
+unsafe fn walk_stack(
+    pc: usize,
+    mut fp: usize,
+    frames: &mut [usize; MAX_FRAMES],
+) -> usize {
+    if pc == 0 {
+        return 0;
+    }
+
+    let mut len = 1;
+
+    frames[0] = pc;
+
+    while len < MAX_FRAMES && fp != 0 && fp % size_of::<usize>() == 0 {
+        let [next, ret] = match read_frame(fp) {
+            Some(record) if record[1] != 0 => record,
+            _ => break,
+        };
+
+        frames[len] = ret;
+        len += 1;
+
+        // Stacks grow downwards, so the caller's frame must reside at a
+        // higher address. If not, the frame pointer is invalid.
+        if next <= fp || next - fp > MAX_FRAME_SIZE {
+            break;
+        }
+
+        fp = next;
+    }
+
+    len
+}
+
+extern "C" fn handle_signal(
+    _signal: i32,
+    _info: *mut libc::siginfo_t,
+    context: *mut c_void,
+) {
+    let state = BUFFER.state.load(Ordering::Acquire);
+
+    // The thread is only changed before publishing a new request, and the
+    // exchange fails if a new request is published after we loaded the state.
+    // This way we don't claim a request meant for another thread, even if the
+    // signal arrives late.
+    if status(state) != REQUESTED
+        || BUFFER.thread.load(Ordering::Acquire)
+            != unsafe { pthread_self() } as usize
+        || BUFFER
+            .state
+            .compare_exchange(
+                state,
+                state - REQUESTED + WRITING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_err()
+    {
+        return;
+    }
+
+    // Reading memory using the pipe may change errno, which would confuse the
+    // code we interrupted.
+    let errno = unsafe { *errno_location() };
+    let frames = unsafe { &mut *BUFFER.frames.get() };
+    let len = unsafe {
+        let (pc, fp) = registers(context);
+        let len = walk_stack(pc, fp, frames);
+
+        *errno_location() = errno;
+        len
+    };
+
+    BUFFER.len.store(len, Ordering::Release);
+
+    let writing = state - REQUESTED + WRITING;
+    let done = state - REQUESTED + DONE;
+
+    // If the profiler stopped waiting for us, it's our job to release the
+    // buffer.
+    if BUFFER
+        .state
+        .compare_exchange(writing, done, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        BUFFER.state.store(state - REQUESTED + IDLE, Ordering::Release);
+    }
+}
+
+fn install_signal_handler() {
+    SETUP.call_once(|| unsafe {
+        let mut fds = [-1, -1];
+
+        // The pipe must never block, as that would block the signal handler.
+        if pipe(fds.as_mut_ptr()) == 0 {
+            for fd in fds {
+                fcntl(fd, F_SETFL, fcntl(fd, F_GETFL) | O_NONBLOCK);
+            }
+
+            PIPE[0].store(fds[0], Ordering::Release);
+            PIPE[1].store(fds[1], Ordering::Release);
+        }
+
+        let mut action: sigaction = zeroed();
+
+        action.sa_sigaction = handle_signal as *const () as usize;
+        action.sa_flags = SA_SIGINFO | SA_RESTART;
+        sigemptyset(&mut action.sa_mask);
+        sigaction(SIGPROF, &action, null_mut());
+    });
+}
+
+/// Starts the profiler, sampling at the given frequency (in Hz).
+pub(crate) fn start(path: PathBuf, frequency: u32) {
+    let profiler: &'static Profiler =
+        Box::leak(Box::new(Profiler::new(path, frequency)));
+
+    install_signal_handler();
+    PROFILER.store(profiler as *const _ as *mut _, Ordering::Release);
+    ENABLED.store(true, Ordering::Release);
+
+    let handle = thread::Builder::new()
+        .name("profiler".to_string())
+        .spawn(move || profiler.run())
+        .unwrap();
+
+    *profiler.sampler.lock().unwrap() = Some(handle);
+}
+
+/// Registers the current thread as a thread to sample.
+pub(crate) fn register_thread() {
+    if !ENABLED.load(Ordering::Acquire) {
+        return;
+    }
+
+    let profiler = unsafe { &*PROFILER.load(Ordering::Acquire) };
+
+    profiler.threads.lock().unwrap().push(unsafe { pthread_self() } as usize);
+}
+
+/// Removes the current thread from the threads to sample.
+///
+/// This must be called before the thread terminates, as signalling a thread
+/// that no longer exists is undefined behaviour.
+pub(crate) fn unregister_thread() {
+    let ptr = PROFILER.load(Ordering::Acquire);
+
+    if ptr.is_null() {
+        return;
+    }
+
+    let thread = unsafe { pthread_self() } as usize;
+
+    unsafe { &*ptr }.threads.lock().unwrap().retain(|&t| t != thread);
+}
+
+/// Stops sampling threads, waiting for the sampling thread to finish.
+///
+/// This must be called before the runtime waits for its threads to terminate.
+pub(crate) fn stop_sampling() {
+    ENABLED.store(false, Ordering::Release);
+
+    let ptr = PROFILER.load(Ordering::Acquire);
+
+    if ptr.is_null() {
+        return;
+    }
+
+    if let Some(handle) = unsafe { &*ptr }.sampler.lock().unwrap().take() {
+        let _ = handle.join();
+    }
+}
+
+/// Stops the profiler (if enabled) and writes its report.
+pub(crate) fn stop() {
+    stop_sampling();
+
+    let ptr = PROFILER.swap(null_mut(), Ordering::AcqRel);
+
+    if ptr.is_null() {
+        return;
+    }
+
+    let profiler = unsafe { &*ptr };
+
+    if let Err(err) = fs::write(&profiler.path, profiler.report()) {
+        eprintln!(
+            "Failed to write the CPU profile to {}: {}",
+            profiler.path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+
+    /// The sample buffer and pipe are shared by all tests, so tests using them
+    /// must not run in parallel.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_profiler_new() {
+        let profiler = Profiler::new(PathBuf::from("cpu.txt"), 100);
+
+        assert_eq!(profiler.interval, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_profiler_sample() {
+        let _lock = LOCK.lock().unwrap();
+        let profiler = Profiler::new(PathBuf::from("cpu.txt"), 100);
+        let (send, receive) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_thread = stop.clone();
+        let handle = thread::spawn(move || {
+            send.send(unsafe { pthread_self() } as usize).unwrap();
+
+            while !stop_thread.load(Ordering::Acquire) {
+                thread::yield_now();
+            }
+        });
+
+        install_signal_handler();
+
+        let stack = profiler.sample(receive.recv().unwrap());
+
+        stop.store(true, Ordering::Release);
+        handle.join().unwrap();
+
+        assert!(!stack.unwrap().is_empty());
+        assert_eq!(status(BUFFER.state.load(Ordering::Acquire)), IDLE);
+    }
+
+    #[test]
+    fn test_read_frame() {
+        let _lock = LOCK.lock().unwrap();
+        install_signal_handler();
+
+        let record = [10_usize, 20_usize];
+
+        assert_eq!(
+            unsafe { read_frame(record.as_ptr() as usize) },
+            Some([10, 20])
+        );
+        assert_eq!(unsafe { read_frame(8) }, None);
+    }
+
+    #[test]
+    fn test_walk_stack() {
+        let _lock = LOCK.lock().unwrap();
+        install_signal_handler();
+
+        let mut stack = [0_usize; 4];
+        let mut frames = [0_usize; MAX_FRAMES];
+        let ptr = stack.as_mut_ptr();
+        let base = ptr as usize;
+
+        // The values are written through the same pointer the stack walker
+        // reads from, otherwise the writes may be considered unused.
+        unsafe {
+            ptr.write(base + (2 * size_of::<usize>()));
+            ptr.add(1).write(0x10);
+            ptr.add(3).write(0x20);
+        }
+
+        assert_eq!(unsafe { walk_stack(0x1, base, &mut frames) }, 3);
+        assert_eq!(frames[0..3], [0x1, 0x10, 0x20]);
+
+        // Invalid frame pointers are ignored.
+        assert_eq!(unsafe { walk_stack(0x1, 8, &mut frames) }, 1);
+        assert_eq!(unsafe { walk_stack(0x1, 3, &mut frames) }, 1);
+        assert_eq!(unsafe { walk_stack(0, base, &mut frames) }, 0);
+    }
+
+    #[test]
+    fn test_start_and_stop() {
+        let _lock = LOCK.lock().unwrap();
+        let path = std::env::temp_dir().join("inko-cpu-profile-test.txt");
+
+        start(path.clone(), 1000);
+        register_thread();
+        thread::sleep(Duration::from_millis(10));
+
+        let profiler = unsafe { &*PROFILER.load(Ordering::Acquire) };
+
+        unregister_thread();
+        assert!(profiler.threads.lock().unwrap().is_empty());
+
+        stop_sampling();
+        assert!(!ENABLED.load(Ordering::Acquire));
+        assert!(profiler.sampler.lock().unwrap().is_none());
+
+        stop();
+        assert!(PROFILER.load(Ordering::Acquire).is_null());
+        assert!(path.is_file());
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_report_without_inko_frames() {
+        let profiler = Profiler::new(PathBuf::from("cpu.txt"), 100);
+
+        profiler.samples.lock().unwrap().insert(
+            vec![test_report_without_inko_frames as *const () as usize + 1],
+            4,
+        );
+
+        assert_eq!(profiler.report(), "");
+    }
+}
@@ -0,0 +1,349 @@
+//! Counting of object allocations per class.
+//!
+//! When enabled, the runtime counts the number of objects allocated and
+//! released for every class. In addition, the stack trace of every N-th
+//! allocation may be recorded, along with whether or not the object is still
+//! alive. Combined this makes it possible to find out which objects are leaking
+//! and where they are allocated.
+//!
+//! The profiler is a global singleton, as the runtime functions used for
+//! allocating and releasing objects don't have access to the runtime's state.
+//! When the profiler is disabled, the only cost is a single atomic load per
+//! allocation and release.
+use crate::mem::{header_of, ClassPointer};
+use crate::process::{stacktrace, StackFrame};
+use crate::profiler::write_json_string;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// A flag indicating if the profiler is enabled.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The profiler to use, if any.
+///
+/// Once started the profiler is never dropped, as other threads may still be
+/// using it while the program terminates.
+static PROFILER: AtomicPtr<Profiler> = AtomicPtr::new(null_mut());
+
+/// The statistics of a single class.
+#[derive(Default)]
+struct ClassStats {
+    allocations: u64,
+    frees: u64,
+}
+
+/// A unique stack trace at which objects of a class are allocated.
+struct Site {
+    class: ClassPointer,
+    frames: Vec<StackFrame>,
+
+    /// The number of sampled allocations of this site.
+    allocations: u64,
+
+    /// The number of sampled allocations of this site that are still alive.
+    live: u64,
+}
+
+#[derive(Default)]
+struct Data {
+    classes: HashMap<ClassPointer, ClassStats>,
+    sites: Vec<Site>,
+
+    /// A mapping of the class and (folded) stack trace to the index of the
+    /// allocation site in `sites`.
+    site_ids: HashMap<(ClassPointer, String), usize>,
+
+    /// The sampled objects that are still alive, and their allocation sites.
+    samples: HashMap<usize, usize>,
+}
+
+impl Data {
+    fn sample(
+        &mut self,
+        pointer: *mut u8,
+        class: ClassPointer,
+        frames: Vec<StackFrame>,
+    ) {
+        let mut key = String::new();
+
+        for frame in &frames {
+            let _ =
+                write!(key, "{} {}:{};", frame.name, frame.path, frame.line);
+        }
+
+        let sites = &mut self.sites;
+        let id = *self.site_ids.entry((class, key)).or_insert_with(|| {
+            sites.push(Site { class, frames, allocations: 0, live: 0 });
+            sites.len() - 1
+        });
+        let site = &mut self.sites[id];
+
+        site.allocations += 1;
+        site.live += 1;
+        self.samples.insert(pointer as usize, id);
+    }
+}
+
+/// A profiler that counts allocations per class.
+pub(crate) struct Profiler {
+    /// The path to write the report to.
+    path: PathBuf,
+
+    /// The number of allocations after which to record the stack trace of an
+    /// allocation.
+    ///
+    /// A value of zero disables the recording of stack traces.
+    sample_rate: u64,
+
+    /// The total number of allocations, used for sampling allocations.
+    allocations: AtomicU64,
+
+    data: Mutex<Data>,
+}
+
+impl Profiler {
+    fn new(path: PathBuf, sample_rate: u32) -> Profiler {
+        Profiler {
+            path,
+            sample_rate: sample_rate as u64,
+            allocations: AtomicU64::new(0),
+            data: Mutex::new(Data::default()),
+        }
+    }
+
+    fn allocated(&self, pointer: *mut u8, class: ClassPointer) {
+        let sample = self.sample_rate > 0
+            && (self.allocations.fetch_add(1, Ordering::Relaxed) + 1)
+                % self.sample_rate
+                == 0;
+
+        // Obtaining a stack trace is expensive, so we do this before acquiring
+        // the lock.
+        let frames = if sample { Some(stacktrace()) } else { None };
+        let mut data = self.data.lock().unwrap();
+
+        data.classes.entry(class).or_default().allocations += 1;
+
+        if let Some(frames) = frames {
+            data.sample(pointer, class, frames);
+        }
+    }
+
+    fn released(&self, pointer: *mut u8, class: ClassPointer) {
+        let mut data = self.data.lock().unwrap();
+
+        data.classes.entry(class).or_default().frees += 1;
+
+        if let Some(id) = data.samples.remove(&(pointer as usize)) {
+            let site = &mut data.sites[id];
+
+            site.live = site.live.saturating_sub(1);
+        }
+    }
+
+    /// Returns the report of this profiler as a JSON document.
+    fn report(&self) -> String {
+        let data = self.data.lock().unwrap();
+        let mut classes: Vec<_> = data
+            .classes
+            .iter()
+            .map(|(&class, stats)| {
+                let live = stats.allocations.saturating_sub(stats.frees);
+
+                (class, stats, live, live * class.instance_size as u64)
+            })
+            .collect();
+        let mut sites: Vec<_> = data.sites.iter().collect();
+
+        classes.sort_by(|a, b| {
+            b.3.cmp(&a.3).then_with(|| a.0.name.cmp(&b.0.name))
+        });
+        sites.sort_by(|a, b| {
+            b.live.cmp(&a.live).then_with(|| b.allocations.cmp(&a.allocations))
+        });
+
+        let mut buf = String::new();
+
+        buf.push_str("{\n  \"classes\": [");
+
+        for (index, (class, stats, live, live_bytes)) in
+            classes.into_iter().enumerate()
+        {
+            buf.push_str(if index == 0 { "\n" } else { ",\n" });
+            buf.push_str("    {\"name\": ");
+            write_json_string(&mut buf, &class.name);
+            let _ = write!(
+                buf,
+                ", \"size\": {}, \"allocations\": {}, \"frees\": {}, \
+                \"live\": {}, \"live_bytes\": {}}}",
+                class.instance_size,
+                stats.allocations,
+                stats.frees,
+                live,
+                live_bytes
+            );
+        }
+
+        buf.push_str("\n  ],\n  \"samples\": [");
+
+        for (index, site) in sites.into_iter().enumerate() {
+            buf.push_str(if index == 0 { "\n" } else { ",\n" });
+            buf.push_str("    {\"class\": ");
+            write_json_string(&mut buf, &site.class.name);
+            let _ = write!(
+                buf,
+                ", \"allocations\": {}, \"live\": {}, \"stack\": [",
+                site.allocations, site.live
+            );
+
+            for (index, frame) in site.frames.iter().enumerate() {
+                if index > 0 {
+                    buf.push_str(", ");
+                }
+
+                buf.push_str("{\"name\": ");
+                write_json_string(&mut buf, &frame.name);
+                buf.push_str(", \"path\": ");
+                write_json_string(&mut buf, &frame.path);
+                let _ = write!(buf, ", \"line\": {}}}", frame.line);
+            }
+
+            buf.push_str("]}");
+        }
+
+        buf.push_str("\n  ]\n}\n");
+        buf
+    }
+}
+
+/// Starts the profiler, writing its report to the given path.
+pub(crate) fn start(path: PathBuf, sample_rate: u32) {
+    let profiler = Box::into_raw(Box::new(Profiler::new(path, sample_rate)));
+
+    PROFILER.store(profiler, Ordering::Release);
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Stops the profiler (if enabled) and writes its report.
+pub(crate) fn stop() {
+    if !ENABLED.swap(false, Ordering::AcqRel) {
+        return;
+    }
+
+    let profiler = unsafe { &*PROFILER.load(Ordering::Acquire) };
+
+    if let Err(err) = fs::write(&profiler.path, profiler.report()) {
+        eprintln!(
+            "Failed to write the heap profile to {}: {}",
+            profiler.path.display(),
+            err
+        );
+    }
+}
+
+/// Records the allocation of an object.
+#[inline(always)]
+pub(crate) fn allocated(pointer: *mut u8, class: ClassPointer) {
+    if ENABLED.load(Ordering::Relaxed) {
+        unsafe { &*PROFILER.load(Ordering::Acquire) }.allocated(pointer, class);
+    }
+}
+
+/// Records the release of an object.
+///
+/// This function must be called _before_ the object's memory is released.
+#[inline(always)]
+pub(crate) unsafe fn released(pointer: *mut u8) {
+    if ENABLED.load(Ordering::Relaxed) {
+        let class = header_of(pointer).class;
+
+        (*PROFILER.load(Ordering::Acquire)).released(pointer, class);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Class;
+
+    fn frame(name: &str, line: i64) -> StackFrame {
+        StackFrame { name: name.to_string(), path: "a.inko".to_string(), line }
+    }
+
+    #[test]
+    fn test_allocated_and_released() {
+        let class = Class::object("A".to_string(), 24, 0);
+        let profiler = Profiler::new(PathBuf::from("heap.json"), 0);
+
+        profiler.allocated(0x10 as _, class);
+        profiler.allocated(0x20 as _, class);
+        profiler.released(0x10 as _, class);
+
+        {
+            let data = profiler.data.lock().unwrap();
+            let stats = &data.classes[&class];
+
+            assert_eq!(stats.allocations, 2);
+            assert_eq!(stats.frees, 1);
+            assert!(data.sites.is_empty());
+        }
+
+        unsafe { Class::drop(class) };
+    }
+
+    #[test]
+    fn test_data_sample() {
+        let class = Class::object("A".to_string(), 24, 0);
+        let mut data = Data::default();
+
+        data.sample(0x10 as _, class, vec![frame("foo", 1)]);
+        data.sample(0x20 as _, class, vec![frame("foo", 1)]);
+        data.sample(0x30 as _, class, vec![frame("bar", 2)]);
+
+        assert_eq!(data.sites.len(), 2);
+        assert_eq!(data.sites[0].allocations, 2);
+        assert_eq!(data.sites[1].allocations, 1);
+        assert_eq!(data.samples[&0x20], 0);
+        assert_eq!(data.samples[&0x30], 1);
+
+        unsafe { Class::drop(class) };
+    }
+
+    #[test]
+    fn test_report() {
+        let class = Class::object("A".to_string(), 24, 0);
+        let profiler = Profiler::new(PathBuf::from("heap.json"), 0);
+
+        profiler.allocated(0x10 as _, class);
+        profiler.allocated(0x20 as _, class);
+        profiler.data.lock().unwrap().sample(
+            0x20 as _,
+            class,
+            vec![frame("foo", 1), frame("bar", 2)],
+        );
+        profiler.released(0x10 as _, class);
+
+        assert_eq!(
+            profiler.report(),
+            "{
+  \"classes\": [
+    {\"name\": \"A\", \"size\": 24, \"allocations\": 2, \"frees\": 1, \
+\"live\": 1, \"live_bytes\": 24}
+  ],
+  \"samples\": [
+    {\"class\": \"A\", \"allocations\": 1, \"live\": 1, \"stack\": [\
+{\"name\": \"foo\", \"path\": \"a.inko\", \"line\": 1}, \
+{\"name\": \"bar\", \"path\": \"a.inko\", \"line\": 2}]}
+  ]
+}
+"
+        );
+
+        unsafe { Class::drop(class) };
+    }
+}
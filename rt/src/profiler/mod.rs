@@ -0,0 +1,42 @@
+//! Profiling of Inko programs.
+//!
+//! Profilers are enabled using the runtime's configuration, and write their
+//! reports when the program terminates.
+pub mod cpu;
+pub mod heap;
+
+use std::fmt::Write as _;
+
+/// Appends a string to a buffer as a JSON string literal.
+pub(crate) fn write_json_string(buffer: &mut String, value: &str) {
+    buffer.push('"');
+
+    for chr in value.chars() {
+        match chr {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => {
+                let _ = write!(buffer, "\\u{:04x}", chr as u32);
+            }
+            chr => buffer.push(chr),
+        }
+    }
+
+    buffer.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_json_string() {
+        let mut buffer = String::new();
+
+        write_json_string(&mut buffer, "a\"b\\c\nd\u{1}");
+        assert_eq!(buffer, "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+}
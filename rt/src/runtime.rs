@@ -14,22 +14,37 @@ mod string;
 mod sys;
 mod time;
 
+pub(crate) use general::errno_location;
+
 use crate::config::Config;
-use crate::mem::ClassPointer;
+use crate::context::Context;
+use crate::mem::{Class, ClassPointer};
 use crate::network_poller::Worker as NetworkPollerWorker;
-use crate::process::{NativeAsyncMethod, Process};
+use crate::process::{Message, NativeAsyncMethod, Process, ProcessPointer};
+use crate::profiler::{cpu, heap};
+use crate::runtime::process::inko_process_finish_message;
 use crate::scheduler::{number_of_cores, pin_thread_to_core};
 use crate::stack::Stack;
 use crate::state::{MethodCounts, RcState, State};
 use std::ffi::CStr;
 use std::io::{stdout, Write as _};
+use std::mem::size_of;
 use std::process::exit as rust_exit;
 use std::slice;
-use std::thread;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
 
 const SIGPIPE: i32 = 13;
 const SIG_IGN: usize = 1;
 
+/// The seed used for deterministic scheduling, or zero if deterministic
+/// scheduling isn't used.
+///
+/// This value is stored separately from the configuration, as we need it when
+/// exiting, at which point we may not have access to the runtime state.
+static SEED: AtomicU64 = AtomicU64::new(0);
+
 extern "C" {
     // Broken pipe errors default to terminating the entire program, making it
     // impossible to handle such errors. This is especially problematic for
@@ -79,9 +94,35 @@ pub unsafe extern "system" fn inko_runtime_start(
 ) {
     signal(SIGPIPE, SIG_IGN);
     (*runtime).start(class, method);
+    cpu::stop();
+    heap::stop();
     flush_stdout();
 }
 
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_spawn(runtime: *mut Runtime) {
+    signal(SIGPIPE, SIG_IGN);
+    (*runtime).spawn();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_call(
+    runtime: *mut Runtime,
+    function: CallFunction,
+    data: *mut u8,
+) {
+    (*runtime).call(function, data);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_runtime_shutdown(runtime: *mut Runtime) {
+    (*runtime).shutdown();
+    cpu::stop();
+    heap::stop();
+    flush_stdout();
+    drop(Box::from_raw(runtime));
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn inko_runtime_state(
     runtime: *mut Runtime,
@@ -96,14 +137,68 @@ fn flush_stdout() {
 }
 
 pub(crate) fn exit(status: i32) -> ! {
+    cpu::stop();
+    heap::stop();
     flush_stdout();
+
+    let seed = SEED.load(Ordering::Acquire);
+
+    if status != 0 && seed > 0 {
+        eprintln!(
+            "The program used deterministic scheduling with seed {}, \
+            set INKO_SEED={} to run it again using the same seed",
+            seed, seed
+        );
+    }
+
     rust_exit(status);
 }
 
+/// A function generated by the compiler that calls an Inko method using the
+/// arguments stored in `data`, storing the return value (if any) in `data`.
+type CallFunction = unsafe extern "system" fn(
+    state: *const State,
+    process: ProcessPointer,
+    data: *mut u8,
+);
+
+/// A call of an Inko method, performed on behalf of C code.
+struct Call {
+    function: CallFunction,
+    data: *mut u8,
+    done: Mutex<bool>,
+    cvar: Condvar,
+}
+
+// The data pointer is only used by the process performing the call, while the
+// thread that started the call waits for it to finish.
+unsafe impl Send for Call {}
+unsafe impl Sync for Call {}
+
+/// The native async method used for running a `Call`.
+unsafe extern "system" fn run_call(context: *mut u8) {
+    let context = &*(context as *const Context);
+    let call = Arc::from_raw(*(context.arguments as *const *const Call));
+
+    (call.function)(context.state, context.process, call.data);
+    *call.done.lock().unwrap() = true;
+    call.cvar.notify_one();
+
+    // The call must be dropped first, as this function doesn't return.
+    drop(call);
+    inko_process_finish_message(context.process, true);
+}
+
 /// An Inko runtime along with all its state.
 #[repr(C)]
 pub struct Runtime {
     state: RcState,
+
+    /// The class of the processes used for calling Inko methods from C.
+    call_class: ClassPointer,
+
+    /// The thread running the scheduler, when using `Runtime::spawn()`.
+    scheduler: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl Runtime {
@@ -112,7 +207,25 @@ impl Runtime {
     /// This method sets up the runtime and allocates the core classes, but
     /// doesn't start any threads.
     fn new(counts: &MethodCounts, args: Vec<String>) -> Self {
-        Self { state: State::new(Config::from_env(), counts, args) }
+        let config = Config::from_env();
+
+        if let Some(path) = config.heap_profile.clone() {
+            heap::start(path, config.heap_profile_sample_rate);
+        }
+
+        if config.deterministic {
+            SEED.store(config.seed, Ordering::Release);
+        }
+
+        Self {
+            state: State::new(config, counts, args),
+            call_class: Class::process(
+                "$Call".to_string(),
+                size_of::<Process>() as _,
+                0,
+            ),
+            scheduler: Mutex::new(None),
+        }
     }
 
     /// Starts the runtime using the given process and method as the entry
@@ -122,16 +235,82 @@ impl Runtime {
     /// though this thread itself doesn't run any processes (= it just
     /// waits/blocks until completion).
     fn start(&self, main_class: ClassPointer, main_method: NativeAsyncMethod) {
+        self.start_threads();
+
+        let stack = Stack::new(self.state.config.stack_size as usize);
+        let main_proc = Process::main(main_class, main_method, stack);
+
+        self.state.scheduler.run(&self.state, Some(main_proc));
+    }
+
+    /// Starts the runtime without an entry point, returning immediately.
+    ///
+    /// This is used when embedding Inko code in another program, with the
+    /// program using `Runtime::call()` to run Inko methods.
+    fn spawn(&self) {
+        self.start_threads();
+
+        let state = self.state.clone();
+        let handle = thread::Builder::new()
+            .name("scheduler".to_string())
+            .spawn(move || state.scheduler.run(&state, None))
+            .unwrap();
+
+        *self.scheduler.lock().unwrap() = Some(handle);
+    }
+
+    /// Calls an Inko method on a new process, blocking the calling thread
+    /// until the call finishes.
+    fn call(&self, function: CallFunction, data: *mut u8) {
+        let call = Arc::new(Call {
+            function,
+            data,
+            done: Mutex::new(false),
+            cvar: Condvar::new(),
+        });
+        let stack = Stack::new(self.state.config.stack_size as usize);
+        let mut process = Process::alloc(self.call_class, stack);
+        let mut message = Message::alloc(run_call, 1);
+
+        unsafe {
+            message.set_argument(0, Arc::into_raw(call.clone()) as _);
+        }
+
+        process.send_message(message);
+        self.state.scheduler.schedule(process);
+
+        let mut done = call.done.lock().unwrap();
+
+        while !*done {
+            done = call.cvar.wait(done).unwrap();
+        }
+    }
+
+    /// Terminates a runtime started using `Runtime::spawn()`, waiting for its
+    /// threads to stop.
+    fn shutdown(&self) {
+        self.state.terminate();
+
+        if let Some(handle) = self.scheduler.lock().unwrap().take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn start_threads(&self) {
         let state = self.state.clone();
         let cores = number_of_cores();
 
-        thread::Builder::new()
-            .name("timeout".to_string())
-            .spawn(move || {
-                pin_thread_to_core(0);
-                state.timeout_worker.run(&state)
-            })
-            .unwrap();
+        // When using deterministic scheduling, timeouts are handled by the
+        // thread running the processes.
+        if !self.state.config.deterministic {
+            thread::Builder::new()
+                .name("timeout".to_string())
+                .spawn(move || {
+                    pin_thread_to_core(0);
+                    state.timeout_worker.run(&state)
+                })
+                .unwrap();
+        }
 
         for id in 0..self.state.network_pollers.len() {
             let state = self.state.clone();
@@ -145,9 +324,41 @@ impl Runtime {
                 .unwrap();
         }
 
-        let stack = Stack::new(self.state.config.stack_size as usize);
-        let main_proc = Process::main(main_class, main_method, stack);
+        if let Some(path) = self.state.config.cpu_profile.clone() {
+            cpu::start(path, self.state.config.cpu_profile_frequency);
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        unsafe { Class::drop(self.call_class) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "system" fn add(
+        _state: *const State,
+        _process: ProcessPointer,
+        data: *mut u8,
+    ) {
+        let data = &mut *(data as *mut [i64; 3]);
+
+        data[2] = data[0] + data[1];
+    }
+
+    #[test]
+    fn test_runtime_call() {
+        let runtime = Runtime::new(&MethodCounts::default(), Vec::new());
+        let mut data = [10_i64, 20, 0];
+
+        runtime.spawn();
+        runtime.call(add, data.as_mut_ptr() as _);
+        runtime.shutdown();
 
-        self.state.scheduler.run(&self.state, main_proc);
+        assert_eq!(data[2], 30);
     }
 }
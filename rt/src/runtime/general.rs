@@ -1,9 +1,9 @@
 use crate::context;
-use crate::mem::{free, header_of, ClassPointer};
+use crate::mem::{allocate, free, header_of, ClassPointer};
 use crate::process::ProcessPointer;
+use crate::profiler::heap;
 use crate::runtime::exit;
 use crate::runtime::process::panic;
-use std::alloc::alloc;
 use std::io::Error;
 
 // Taken from Rust's standard library, with some removals of platforms we don't
@@ -24,7 +24,7 @@ extern "C" {
         ),
         link_name = "__error"
     )]
-    fn errno_location() -> *mut i32;
+    pub(crate) fn errno_location() -> *mut i32;
 }
 
 #[no_mangle]
@@ -55,6 +55,7 @@ pub unsafe extern "system" fn inko_check_refs(
 
 #[no_mangle]
 pub unsafe extern "system" fn inko_free(pointer: *mut u8) {
+    heap::released(pointer);
     free(pointer);
 }
 
@@ -76,9 +77,10 @@ pub unsafe extern "system" fn inko_reduce(
 
 #[no_mangle]
 pub unsafe extern "system" fn inko_alloc(class: ClassPointer) -> *mut u8 {
-    let ptr = alloc(class.instance_layout());
+    let ptr = allocate(class.instance_layout());
 
     header_of(ptr).init(class);
+    heap::allocated(ptr, class);
     ptr
 }
 
@@ -86,9 +88,10 @@ pub unsafe extern "system" fn inko_alloc(class: ClassPointer) -> *mut u8 {
 pub unsafe extern "system" fn inko_alloc_atomic(
     class: ClassPointer,
 ) -> *mut u8 {
-    let ptr = alloc(class.instance_layout());
+    let ptr = allocate(class.instance_layout());
 
     header_of(ptr).init_atomic(class);
+    heap::allocated(ptr, class);
     ptr
 }
 
@@ -1,16 +1,20 @@
 use crate::context;
 use crate::mem::{ClassPointer, String as InkoString};
 use crate::process::{
-    Channel, Message, NativeAsyncMethod, OwnedMessage, Process, ProcessPointer,
-    ReceiveResult, RescheduleRights, SendResult, StackFrame,
+    Channel, Future, Message, NativeAsyncMethod, OwnedMessage, Process,
+    ProcessPointer, ReceiveResult, RescheduleRights, SelectResult, SendResult,
+    StackFrame,
 };
 use crate::result::Result as InkoResult;
 use crate::runtime::exit;
+use crate::scheduler;
 use crate::scheduler::process::Action;
 use crate::scheduler::timeouts::Timeout;
 use crate::state::State;
 use std::cmp::max;
 use std::fmt::Write as _;
+use std::ptr::null_mut;
+use std::slice;
 use std::str;
 use std::time::Duration;
 
@@ -122,6 +126,30 @@ pub unsafe extern "system" fn inko_process_finish_message(
     context::switch(process);
 }
 
+/// Returns the state and process of the process running on the current thread,
+/// terminating the program if there's no such process.
+fn current() -> (*const State, ProcessPointer) {
+    if let Some(current) = scheduler::process::current() {
+        return current;
+    }
+
+    eprintln!(
+        "C callbacks can only be called by the thread running the Inko \
+        process that called into C"
+    );
+    exit(101);
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_process_current() -> ProcessPointer {
+    current().1
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_process_current_state() -> *const State {
+    current().0
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn inko_process_yield(mut process: ProcessPointer) {
     // Safety: the current thread is holding on to the run lock
@@ -307,7 +335,168 @@ pub unsafe extern "system" fn inko_channel_receive_until(
     }
 }
 
+/// A message received from one of many channels.
+#[repr(C)]
+pub struct SelectedMessage {
+    /// The index of the channel the message is received from, or -1 if the
+    /// deadline expired.
+    index: i64,
+
+    /// The message that is received.
+    message: *mut u8,
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_channel_select(
+    state: *const State,
+    mut process: ProcessPointer,
+    channels: *const *const Channel,
+    length: i64,
+    deadline: i64,
+) -> SelectedMessage {
+    let state = &(*state);
+    let channels: Vec<&Channel> = slice::from_raw_parts(channels, length as _)
+        .iter()
+        .map(|&chan| &*chan)
+        .collect();
+
+    // A deadline of -1 signals that we should wait indefinitely.
+    let timeout = if deadline >= 0 {
+        Some(Timeout::until(deadline as u64))
+    } else {
+        None
+    };
+
+    loop {
+        let (index, result) =
+            match Channel::select(&channels, process, timeout.clone()) {
+                SelectResult::Some(index, result) => (index, result),
+                SelectResult::None => {
+                    if let Some(time) = timeout.as_ref() {
+                        // Safety: the current thread is holding on to the run
+                        // lock
+                        state.timeout_worker.suspend(process, time.clone());
+                    }
+
+                    context::switch(process);
+
+                    // Only one channel is able to reschedule us, so we're
+                    // still registered as waiting for the other channels,
+                    // which we have to undo before trying again.
+                    for chan in &channels {
+                        chan.unsubscribe(process);
+                    }
+
+                    if process.timeout_expired() {
+                        return SelectedMessage {
+                            index: -1,
+                            message: null_mut(),
+                        };
+                    }
+
+                    // It's possible another process received the message
+                    // before we got a chance to, in which case we continue
+                    // waiting.
+                    continue;
+                }
+            };
+
+        let message = match result {
+            ReceiveResult::Some(msg) => msg,
+            ReceiveResult::Reschedule(msg, sender) => {
+                // We schedule onto the global queue because the current process
+                // wants to do something with the message.
+                process.thread().schedule_global(sender);
+                msg
+            }
+            ReceiveResult::None => unreachable!(),
+        };
+
+        return SelectedMessage { index: index as _, message };
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn inko_channel_drop(channel: *mut Channel) {
     Channel::drop(channel);
 }
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_future_new() -> *mut Future {
+    Box::into_raw(Box::new(Future::new()))
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_future_retain(future: *const Future) {
+    (*future).retain();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_future_set(
+    state: *const State,
+    mut process: ProcessPointer,
+    future: *const Future,
+    value: *mut u8,
+) {
+    let state = &*state;
+
+    match (*future).set(value) {
+        SendResult::Reschedule(receiver) => {
+            process.thread().schedule_global(receiver);
+        }
+        SendResult::RescheduleWithTimeout(receiver) => {
+            state.timeout_worker.increase_expired_timeouts();
+            process.thread().schedule_global(receiver);
+        }
+        _ => {}
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_future_get(
+    process: ProcessPointer,
+    future: *const Future,
+) -> *const u8 {
+    loop {
+        match (*future).get(process, None) {
+            ReceiveResult::Some(value) => return value,
+            _ => context::switch(process),
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_future_get_until(
+    state: *const State,
+    process: ProcessPointer,
+    future: *const Future,
+    nanos: u64,
+) -> InkoResult {
+    let state = &(*state);
+    let deadline = Timeout::until(nanos);
+
+    loop {
+        match (*future).get(process, Some(deadline.clone())) {
+            ReceiveResult::Some(value) => return InkoResult::ok(value as _),
+            _ => {
+                // Safety: the current thread is holding on to the run lock
+                state.timeout_worker.suspend(process, deadline.clone());
+                context::switch(process);
+
+                if process.timeout_expired() {
+                    return InkoResult::none();
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn inko_future_drop(
+    future: *mut Future,
+) -> InkoResult {
+    match Future::release(future) {
+        Some(value) => InkoResult::ok(value as _),
+        None => InkoResult::none(),
+    }
+}
@@ -38,7 +38,7 @@ pub unsafe extern "system" fn inko_time_monotonic(state: *const State) -> i64 {
     // that long.
     let state = &*state;
 
-    state.start_time.elapsed().as_nanos() as i64
+    state.clock.elapsed().as_nanos() as i64
 }
 
 #[no_mangle]
@@ -2,14 +2,17 @@
 use crate::arc_without_weak::ArcWithoutWeak;
 use crate::context;
 use crate::process::{Process, ProcessPointer, Task};
+use crate::profiler::cpu;
 use crate::scheduler::{number_of_cores, pin_thread_to_core};
+use crate::slab;
 use crate::stack::StackPool;
 use crate::state::State;
 use crossbeam_queue::ArrayQueue;
 use crossbeam_utils::atomic::AtomicCell;
 use crossbeam_utils::thread::scope;
-use rand::rngs::ThreadRng;
-use rand::thread_rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::Cell;
 use std::cmp::min;
 use std::collections::VecDeque;
 use std::mem::{size_of, swap};
@@ -115,6 +118,21 @@ const MONITOR_INTERVAL: u64 = 100;
 /// we perform a number of regular cycles before entering a deep sleep.
 const MAX_IDLE_CYCLES: u64 = 1_000_000 / MONITOR_INTERVAL;
 
+thread_local! {
+    /// The state and process of the process running on the current thread.
+    ///
+    /// C callbacks don't have access to the state and process of the Inko code
+    /// that called into C, so they use this value to obtain them.
+    static CURRENT: Cell<Option<(*const State, ProcessPointer)>> =
+        const { Cell::new(None) };
+}
+
+/// Returns the state and process of the process running on the current thread,
+/// if any.
+pub(crate) fn current() -> Option<(*const State, ProcessPointer)> {
+    CURRENT.with(|current| current.get())
+}
+
 /// The shared half of a thread.
 struct Shared {
     /// The queue threads can steal work from.
@@ -161,7 +179,10 @@ pub struct Thread {
     pub(crate) network_poller: usize,
 
     /// A random number generator to use for the current thread.
-    pub(crate) rng: ThreadRng,
+    ///
+    /// When using deterministic scheduling, this generator is also used for
+    /// deciding what process to run next.
+    pub(crate) rng: StdRng,
 
     /// The pool of stacks to use.
     pub(crate) stacks: StackPool,
@@ -188,7 +209,7 @@ impl Thread {
             backup: false,
             blocked_at: NOT_BLOCKING,
             network_poller,
-            rng: thread_rng(),
+            rng: pool.rng(),
             stacks: StackPool::new(pool.stack_size),
             action: Action::Ignore,
             reductions: REDUCTIONS,
@@ -205,7 +226,7 @@ impl Thread {
             backup: true,
             blocked_at: NOT_BLOCKING,
             network_poller,
-            rng: thread_rng(),
+            rng: pool.rng(),
             stacks: StackPool::new(pool.stack_size),
             action: Action::Ignore,
             reductions: REDUCTIONS,
@@ -338,6 +359,11 @@ impl Thread {
     }
 
     fn run(&mut self, state: &State) {
+        if self.pool.seed.is_some() {
+            self.run_deterministic(state);
+            return;
+        }
+
         while self.pool.is_alive() {
             if self.backup {
                 let mut blocked = self.pool.blocked_threads.lock().unwrap();
@@ -376,6 +402,10 @@ impl Thread {
             // up shrinking the stack pool.
             self.stacks.shrink();
 
+            // For the same reason we release excess memory of the object
+            // allocator here.
+            slab::trim();
+
             if let Some(process) = self.steal_from_global() {
                 self.run_process(state, process);
                 continue;
@@ -385,6 +415,45 @@ impl Thread {
         }
     }
 
+    /// Runs processes in an order determined by the pool's seed.
+    ///
+    /// Deterministic pools only have a single thread, so instead of stealing
+    /// work we pick a random process from all the processes that are ready to
+    /// run. When no process is ready, we advance the clock such that the
+    /// first pending timeout expires.
+    fn run_deterministic(&mut self, state: &State) {
+        while self.pool.is_alive() {
+            if let Some(process) = self.next_random_process() {
+                self.run_process(state, process);
+                continue;
+            }
+
+            if state.timeout_worker.advance(state) {
+                continue;
+            }
+
+            // At this point the only way for processes to become ready is
+            // through the network pollers.
+            self.sleep();
+        }
+    }
+
+    fn next_random_process(&mut self) -> Option<ProcessPointer> {
+        let mut global = self.pool.global.lock().unwrap();
+
+        while let Some(process) = self.work.pop() {
+            global.push(process);
+        }
+
+        if global.is_empty() {
+            return None;
+        }
+
+        let index = self.rng.gen_range(0..global.len());
+
+        Some(global.swap_remove(index))
+    }
+
     fn next_local_process(&mut self) -> Option<ProcessPointer> {
         self.work.pop()
     }
@@ -500,15 +569,18 @@ impl Thread {
             match process.next_task() {
                 Task::Resume => {
                     process.set_thread(self);
+                    CURRENT.with(|c| c.set(Some((state as _, process))));
                     unsafe { context::switch(process) }
                 }
                 Task::Start(func, args) => {
                     process.set_thread(self);
+                    CURRENT.with(|c| c.set(Some((state as _, process))));
                     unsafe { context::start(state, process, func, args) }
                 }
                 Task::Wait => return,
             }
 
+            CURRENT.with(|c| c.set(None));
             process.unset_thread();
         }
 
@@ -788,6 +860,12 @@ struct Pool {
 
     /// The size of each stack to allocate for a process.
     stack_size: usize,
+
+    /// The seed to use for deterministic scheduling.
+    ///
+    /// If a seed is present the pool only uses a single thread, which runs
+    /// processes in an order determined by the seed.
+    seed: Option<u64>,
 }
 
 impl Pool {
@@ -834,6 +912,14 @@ impl Pool {
     fn current_epoch(&self) -> u64 {
         self.epoch.load(Ordering::Acquire)
     }
+
+    fn rng(&self) -> StdRng {
+        if let Some(seed) = self.seed {
+            StdRng::seed_from_u64(seed)
+        } else {
+            StdRng::from_entropy()
+        }
+    }
 }
 
 impl Drop for Pool {
@@ -855,6 +941,21 @@ impl Scheduler {
         size: usize,
         backup: usize,
         stack_size: usize,
+    ) -> Scheduler {
+        Self::with_seed(size, backup, stack_size, None)
+    }
+
+    /// Returns a scheduler that runs all processes on a single thread, in an
+    /// order determined by the given seed.
+    pub(crate) fn deterministic(stack_size: usize, seed: u64) -> Scheduler {
+        Self::with_seed(1, 0, stack_size, Some(seed))
+    }
+
+    fn with_seed(
+        size: usize,
+        backup: usize,
+        stack_size: usize,
+        seed: Option<u64>,
     ) -> Scheduler {
         let mut shared = Vec::with_capacity(size);
 
@@ -880,6 +981,7 @@ impl Scheduler {
                 cvar: Condvar::new(),
             },
             stack_size,
+            seed,
         });
 
         Self { primary: size, backup, pool: shared }
@@ -889,6 +991,10 @@ impl Scheduler {
         self.pool.is_alive()
     }
 
+    pub(crate) fn schedule(&self, process: ProcessPointer) {
+        self.pool.schedule(process);
+    }
+
     pub(crate) fn schedule_multiple(&self, processes: Vec<ProcessPointer>) {
         self.pool.schedule_multiple(processes);
     }
@@ -906,20 +1012,28 @@ impl Scheduler {
         self.pool.monitor.cvar.notify_one();
     }
 
-    pub(crate) fn run(&self, state: &State, process: ProcessPointer) {
+    /// Runs the scheduler until it's terminated.
+    ///
+    /// If a process is given, it's scheduled once all threads are started.
+    pub(crate) fn run(&self, state: &State, process: Option<ProcessPointer>) {
         let pollers = state.network_pollers.len();
         let cores = number_of_cores();
         let _ = scope(move |s| {
-            s.builder()
-                .name("proc monitor".to_string())
-                .spawn(move |_| {
-                    // Cores 0 and 1 are used for the timeout and network poller
-                    // threads. Since we may be running quite often we'll pin
-                    // this thread to a different core.
-                    pin_thread_to_core(2 % cores);
-                    Monitor::new(&self.pool).run()
-                })
-                .unwrap();
+            // Deterministic pools don't replace blocking threads, as this would
+            // result in processes running on different threads in a
+            // non-deterministic order.
+            if self.pool.seed.is_none() {
+                s.builder()
+                    .name("proc monitor".to_string())
+                    .spawn(move |_| {
+                        // Cores 0 and 1 are used for the timeout and network
+                        // poller threads. Since we may be running quite often
+                        // we'll pin this thread to a different core.
+                        pin_thread_to_core(2 % cores);
+                        Monitor::new(&self.pool).run()
+                    })
+                    .unwrap();
+            }
 
             for id in 0..self.primary {
                 let poll_id = id % pollers;
@@ -928,7 +1042,9 @@ impl Scheduler {
                     .name(format!("proc {}", id))
                     .spawn(move |_| {
                         pin_thread_to_core(id % cores);
-                        Thread::new(id, poll_id, self.pool.clone()).run(state)
+                        cpu::register_thread();
+                        Thread::new(id, poll_id, self.pool.clone()).run(state);
+                        cpu::unregister_thread();
                     })
                     .unwrap();
             }
@@ -940,12 +1056,16 @@ impl Scheduler {
                     .name(format!("backup {}", id))
                     .spawn(move |_| {
                         pin_thread_to_core(id % cores);
-                        Thread::backup(poll_id, self.pool.clone()).run(state)
+                        cpu::register_thread();
+                        Thread::backup(poll_id, self.pool.clone()).run(state);
+                        cpu::unregister_thread();
                     })
                     .unwrap();
             }
 
-            self.pool.schedule(process);
+            if let Some(process) = process {
+                self.pool.schedule(process);
+            }
         });
     }
 }
@@ -966,6 +1086,19 @@ mod tests {
         context::switch(ctx.process);
     }
 
+    static CURRENT_MATCHES: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "system" fn current_method(ctx: *mut u8) {
+        let ctx = &mut *(ctx as *mut Context);
+        let matches = current().map_or(false, |(state, process)| {
+            state == ctx.state && process == ctx.process
+        });
+
+        CURRENT_MATCHES.store(matches, Ordering::Release);
+        ctx.process.thread().action = Action::Terminate;
+        context::switch(ctx.process);
+    }
+
     #[test]
     fn test_thread_schedule() {
         let class = empty_process_class("A");
@@ -1016,6 +1149,21 @@ mod tests {
         assert_eq!(thread.work.len(), 0);
     }
 
+    #[test]
+    fn test_thread_run_process_sets_current() {
+        let class = empty_process_class("A");
+        let process =
+            new_main_process(*class, current_method).take_and_forget();
+        let state = setup();
+        let mut thread = Thread::new(0, 0, state.scheduler.pool.clone());
+
+        thread.schedule(process);
+        thread.run(&state);
+
+        assert!(CURRENT_MATCHES.load(Ordering::Acquire));
+        assert!(current().is_none());
+    }
+
     #[test]
     fn test_thread_run_with_stolen_job() {
         let class = empty_process_class("A");
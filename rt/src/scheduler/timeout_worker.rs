@@ -108,6 +108,34 @@ impl TimeoutWorker {
         self.cvar.notify_one();
     }
 
+    /// Reschedules the processes of the timeouts that expire first, advancing
+    /// the clock up to the point at which they expire.
+    ///
+    /// This method is used by the deterministic scheduler, which doesn't use a
+    /// separate thread for rescheduling processes. The return value is `true`
+    /// if one or more processes are rescheduled.
+    pub(crate) fn advance(&self, state: &State) -> bool {
+        self.move_messages();
+        self.defragment_heap();
+        self.handle_pending_messages();
+
+        loop {
+            let (expired, time_until_expiration) =
+                self.inner_mut().timeouts.processes_to_reschedule(state);
+
+            if !expired.is_empty() {
+                state.scheduler.schedule_multiple(expired);
+                return true;
+            }
+
+            if let Some(time) = time_until_expiration {
+                state.clock.advance(time);
+            } else {
+                return false;
+            }
+        }
+    }
+
     fn run_iteration(&self, state: &State) -> Option<Duration> {
         self.move_messages();
         self.defragment_heap();
@@ -5,7 +5,7 @@ use crate::state::State;
 use std::cmp;
 use std::collections::BinaryHeap;
 use std::ops::Drop;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 /// An process that should be resumed after a certain point in time.
 pub(crate) struct Timeout {
@@ -28,15 +28,15 @@ impl Timeout {
         state: &State,
         duration: Duration,
     ) -> ArcWithoutWeak<Self> {
-        let deadline =
-            (Instant::now() - state.start_time + duration).as_nanos() as u64;
+        let deadline = (state.clock.elapsed() + duration).as_nanos() as u64;
 
         Timeout::until(deadline)
     }
 
     pub(crate) fn remaining_time(&self, state: &State) -> Option<Duration> {
-        (state.start_time + Duration::from_nanos(self.resume_after))
-            .checked_duration_since(Instant::now())
+        Duration::from_nanos(self.resume_after)
+            .checked_sub(state.clock.elapsed())
+            .filter(|time| time.as_nanos() > 0)
     }
 }
 
@@ -0,0 +1,652 @@
+//! Size-class based allocation of small objects.
+//!
+//! Most Inko objects are small, and the size of an instance is fixed and known
+//! when its class is created. Instead of using the system allocator for every
+//! such object, each thread allocates these objects from "slabs": aligned
+//! chunks of memory divided into blocks of the same size class. Blocks are
+//! recycled when objects are released, and slabs that no longer contain any
+//! objects are eventually handed back to the system allocator.
+//!
+//! Processes move between threads, so an object may be released by a thread
+//! other than the one that allocated it. Such "remote" releases are pushed onto
+//! an atomic list stored in the slab, and the slab is queued onto a list owned
+//! by the heap of the thread that owns the slab. The owning thread processes
+//! this queue when it runs out of blocks, or when it's asked to trim its memory
+//! usage.
+//!
+//! Objects larger than `MAX_SIZE` aren't handled by this module, and instead
+//! use the system allocator directly.
+use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use std::cell::UnsafeCell;
+use std::mem::size_of;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// The difference (in bytes) between two consecutive size classes.
+///
+/// This value also determines the alignment of the blocks.
+const SIZE_STEP: usize = 16;
+
+/// The size (in bytes) of the largest object allocated using a slab.
+pub(crate) const MAX_SIZE: usize = 512;
+
+/// The number of size classes.
+const SIZE_CLASSES: usize = MAX_SIZE / SIZE_STEP;
+
+/// The size and alignment (in bytes) of a single slab.
+///
+/// Slabs are aligned to their size, allowing us to get the slab of a block by
+/// just masking the block's address.
+const SLAB_SIZE: usize = 64 * 1024;
+
+/// The maximum number of empty slabs a thread keeps around for reuse.
+///
+/// Empty slabs beyond this limit are released immediately.
+const MAX_EMPTY_SLABS: usize = 8;
+
+/// A bit set in `Slab.remote` when the slab is queued (or about to be queued)
+/// onto the queue of its owner.
+///
+/// Blocks are aligned to `SIZE_STEP` bytes, so the lowest bit of a block's
+/// address is always available for this purpose.
+const QUEUED: usize = 0b1;
+
+/// Returns the index of the size class for an object of the given size.
+fn size_class(size: usize) -> usize {
+    (size + (SIZE_STEP - 1)) / SIZE_STEP - 1
+}
+
+/// Returns the size of the blocks of the given size class.
+fn block_size(class: usize) -> usize {
+    (class + 1) * SIZE_STEP
+}
+
+fn slab_layout() -> Layout {
+    unsafe { Layout::from_size_align_unchecked(SLAB_SIZE, SLAB_SIZE) }
+}
+
+/// A free block of memory.
+struct Block {
+    next: *mut Block,
+}
+
+/// The part of a heap that other threads are allowed to access.
+struct Shared {
+    /// The slabs containing blocks released by other threads.
+    ///
+    /// The slabs in this list are linked together using `Slab.next_queued`.
+    queue: AtomicPtr<Slab>,
+}
+
+/// A chunk of memory divided into blocks of the same size.
+///
+/// The slab's data is stored at the start of the chunk, followed by the
+/// blocks.
+#[repr(C)]
+struct Slab {
+    /// The heap that owns this slab.
+    owner: *const Shared,
+
+    /// The previous slab in the list of the size class or empty slabs.
+    prev: *mut Slab,
+
+    /// The next slab in the list of the size class or empty slabs.
+    next: *mut Slab,
+
+    /// The next slab in the queue of the owner.
+    next_queued: *mut Slab,
+
+    /// Blocks released by the owning thread, available for reuse.
+    free: *mut Block,
+
+    /// Blocks released by other threads, tagged with the `QUEUED` bit.
+    remote: AtomicUsize,
+
+    /// The address of the first block that hasn't been handed out yet.
+    bump: *mut u8,
+
+    /// The number of blocks handed out and not yet reclaimed by the owner.
+    used: usize,
+
+    /// The index of the size class of this slab.
+    class: usize,
+
+    /// A flag indicating this slab is part of a list of its owning heap.
+    listed: bool,
+}
+
+impl Slab {
+    /// Returns the slab the given block belongs to.
+    fn of(pointer: *mut u8) -> *mut Slab {
+        (pointer as usize & !(SLAB_SIZE - 1)) as *mut Slab
+    }
+
+    /// Returns the address of the first block of the slab.
+    unsafe fn start(slab: *mut Slab) -> *mut u8 {
+        let offset = (size_of::<Slab>() + (SIZE_STEP - 1)) & !(SIZE_STEP - 1);
+
+        (slab as *mut u8).add(offset)
+    }
+
+    unsafe fn init(slab: *mut Slab, owner: *const Shared, class: usize) {
+        let obj = &mut *slab;
+
+        obj.owner = owner;
+        obj.prev = null_mut();
+        obj.next = null_mut();
+        obj.next_queued = null_mut();
+        obj.free = null_mut();
+        obj.remote = AtomicUsize::new(0);
+        obj.bump = Slab::start(slab);
+        obj.used = 0;
+        obj.class = class;
+        obj.listed = false;
+    }
+
+    /// Returns a block to use for a new object, if any are available.
+    unsafe fn take(&mut self) -> Option<*mut u8> {
+        if !self.free.is_null() {
+            let block = self.free;
+
+            self.free = (*block).next;
+            self.used += 1;
+            return Some(block as *mut u8);
+        }
+
+        let size = block_size(self.class);
+        let end = (self as *mut Slab as *mut u8).add(SLAB_SIZE);
+
+        if self.bump as usize + size <= end as usize {
+            let block = self.bump;
+
+            self.bump = block.add(size);
+            self.used += 1;
+            return Some(block);
+        }
+
+        None
+    }
+
+    /// Moves the blocks released by other threads into the local free list,
+    /// returning the number of blocks reclaimed.
+    ///
+    /// The `QUEUED` bit is left as-is, as the slab may still be (or is about to
+    /// be) in the queue of its owner.
+    unsafe fn reclaim(&mut self) -> usize {
+        let mut old = self.remote.load(Ordering::Acquire);
+
+        loop {
+            if old & !QUEUED == 0 {
+                return 0;
+            }
+
+            match self.remote.compare_exchange_weak(
+                old,
+                old & QUEUED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(val) => old = val,
+            }
+        }
+
+        self.push_free((old & !QUEUED) as *mut Block)
+    }
+
+    /// Moves the blocks released by other threads into the local free list,
+    /// and clears the `QUEUED` bit.
+    unsafe fn reclaim_queued(&mut self) -> usize {
+        let old = self.remote.swap(0, Ordering::AcqRel);
+
+        self.push_free((old & !QUEUED) as *mut Block)
+    }
+
+    unsafe fn push_free(&mut self, mut block: *mut Block) -> usize {
+        let mut amount = 0;
+
+        while !block.is_null() {
+            let next = (*block).next;
+
+            (*block).next = self.free;
+            self.free = block;
+            block = next;
+            amount += 1;
+        }
+
+        self.used -= amount;
+        amount
+    }
+
+    /// Releases a block from a thread that doesn't own this slab.
+    unsafe fn release_remote(slab: *mut Slab, block: *mut Block) {
+        let remote = &(*slab).remote;
+        let mut old = remote.load(Ordering::Acquire);
+
+        loop {
+            (*block).next = (old & !QUEUED) as *mut Block;
+
+            match remote.compare_exchange_weak(
+                old,
+                block as usize | QUEUED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(val) => old = val,
+            }
+        }
+
+        // If the slab is already queued there's nothing left to do. If not, we
+        // must queue it ourselves. The owner won't release the slab while the
+        // QUEUED bit is set, so it's safe to keep using the slab here.
+        if old & QUEUED == QUEUED {
+            return;
+        }
+
+        let queue = &(*(*slab).owner).queue;
+        let mut head = queue.load(Ordering::Acquire);
+
+        loop {
+            (*slab).next_queued = head;
+
+            match queue.compare_exchange_weak(
+                head,
+                slab,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(val) => head = val,
+            }
+        }
+    }
+}
+
+/// The slabs owned by a single thread.
+struct Heap {
+    /// The data shared with other threads.
+    ///
+    /// This data is allocated when first needed and never released, as other
+    /// threads may still refer to it after our thread stops.
+    shared: *const Shared,
+
+    /// For every size class, the slabs that may have blocks available.
+    ///
+    /// Slabs that run out of blocks are removed from these lists, and added
+    /// back once blocks are released.
+    classes: [*mut Slab; SIZE_CLASSES],
+
+    /// Slabs that don't contain any objects, ready to be reused.
+    empty: *mut Slab,
+
+    /// The number of slabs in the `empty` list.
+    empty_slabs: usize,
+}
+
+impl Heap {
+    fn new() -> Heap {
+        Heap {
+            shared: null_mut(),
+            classes: [null_mut(); SIZE_CLASSES],
+            empty: null_mut(),
+            empty_slabs: 0,
+        }
+    }
+
+    fn shared(&mut self) -> *const Shared {
+        if self.shared.is_null() {
+            self.shared = Box::into_raw(Box::new(Shared {
+                queue: AtomicPtr::new(null_mut()),
+            }));
+        }
+
+        self.shared
+    }
+
+    unsafe fn allocate(&mut self, class: usize) -> *mut u8 {
+        loop {
+            let slab = self.classes[class];
+
+            if slab.is_null() {
+                break;
+            }
+
+            if let Some(ptr) = (*slab).take() {
+                return ptr;
+            }
+
+            if (*slab).reclaim() > 0 {
+                continue;
+            }
+
+            // The slab is full, so we remove it from the list until some of its
+            // blocks are released.
+            self.unlink(slab);
+        }
+
+        // Before allocating a new slab we check if other threads released any
+        // blocks, as this may result in slabs becoming available again.
+        self.process_queue();
+
+        let slab = if self.classes[class].is_null() {
+            let slab = self.new_slab(class);
+
+            self.link(slab);
+            slab
+        } else {
+            self.classes[class]
+        };
+
+        // Both the existing and the new slab are guaranteed to have at least
+        // one block available at this point.
+        (*slab).take().unwrap()
+    }
+
+    unsafe fn release(&mut self, slab: *mut Slab, pointer: *mut u8) {
+        let block = pointer as *mut Block;
+
+        if (*slab).owner != self.shared {
+            Slab::release_remote(slab, block);
+            return;
+        }
+
+        let obj = &mut *slab;
+
+        (*block).next = obj.free;
+        obj.free = block;
+        obj.used -= 1;
+
+        self.update(slab);
+    }
+
+    /// Processes the slabs containing blocks released by other threads.
+    unsafe fn process_queue(&mut self) {
+        if self.shared.is_null() {
+            return;
+        }
+
+        let mut slab = (*self.shared).queue.swap(null_mut(), Ordering::AcqRel);
+
+        while !slab.is_null() {
+            // We must read the next slab _before_ clearing the QUEUED bit, as
+            // another thread may queue the slab again after that point.
+            let next = (*slab).next_queued;
+
+            (*slab).reclaim_queued();
+            self.update(slab);
+            slab = next;
+        }
+    }
+
+    /// Moves a slab to the right list after one or more of its blocks are
+    /// released.
+    unsafe fn update(&mut self, slab: *mut Slab) {
+        let obj = &mut *slab;
+
+        if obj.used > 0 {
+            if !obj.listed {
+                self.link(slab);
+            }
+
+            return;
+        }
+
+        // If the QUEUED bit is set, another thread may still be in the process
+        // of queueing the slab. Such slabs are released when processing the
+        // queue.
+        if obj.remote.load(Ordering::Acquire) & QUEUED == QUEUED {
+            return;
+        }
+
+        if obj.listed {
+            self.unlink(slab);
+        }
+
+        if self.empty_slabs < MAX_EMPTY_SLABS {
+            obj.next = self.empty;
+            self.empty = slab;
+            self.empty_slabs += 1;
+        } else {
+            dealloc(slab as *mut u8, slab_layout());
+        }
+    }
+
+    unsafe fn new_slab(&mut self, class: usize) -> *mut Slab {
+        let slab = if self.empty.is_null() {
+            let layout = slab_layout();
+            let ptr = alloc(layout) as *mut Slab;
+
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
+
+            ptr
+        } else {
+            let slab = self.empty;
+
+            self.empty = (*slab).next;
+            self.empty_slabs -= 1;
+            slab
+        };
+
+        Slab::init(slab, self.shared(), class);
+        slab
+    }
+
+    /// Adds a slab to the front of the list of its size class.
+    unsafe fn link(&mut self, slab: *mut Slab) {
+        let obj = &mut *slab;
+        let head = self.classes[obj.class];
+
+        obj.prev = null_mut();
+        obj.next = head;
+        obj.listed = true;
+
+        if !head.is_null() {
+            (*head).prev = slab;
+        }
+
+        self.classes[obj.class] = slab;
+    }
+
+    /// Removes a slab from the list of its size class.
+    unsafe fn unlink(&mut self, slab: *mut Slab) {
+        let obj = &mut *slab;
+
+        if obj.prev.is_null() {
+            self.classes[obj.class] = obj.next;
+        } else {
+            (*obj.prev).next = obj.next;
+        }
+
+        if !obj.next.is_null() {
+            (*obj.next).prev = obj.prev;
+        }
+
+        obj.prev = null_mut();
+        obj.next = null_mut();
+        obj.listed = false;
+    }
+
+    /// Reduces the amount of memory retained by this heap.
+    ///
+    /// Similar to `StackPool::shrink()`, this releases at most half of the
+    /// empty slabs, so we don't immediately need to allocate new slabs again
+    /// when allocating many objects shortly after.
+    unsafe fn trim(&mut self) {
+        self.process_queue();
+
+        let mut remove = (self.empty_slabs + 1) / 2;
+
+        while remove > 0 {
+            let slab = self.empty;
+
+            self.empty = (*slab).next;
+            self.empty_slabs -= 1;
+            remove -= 1;
+            dealloc(slab as *mut u8, slab_layout());
+        }
+    }
+}
+
+thread_local! {
+    // Heaps are never dropped, as objects allocated by a thread may outlive
+    // the thread. Since `Heap` doesn't implement `Drop`, accessing the heap
+    // never fails, even while the thread is shutting down.
+    static HEAP: UnsafeCell<Heap> = UnsafeCell::new(Heap::new());
+}
+
+/// Allocates memory for an object of the given size.
+///
+/// The size must be greater than zero and not greater than `MAX_SIZE`. The
+/// returned memory is aligned to at least 16 bytes.
+pub(crate) fn allocate(size: usize) -> *mut u8 {
+    debug_assert!(size > 0 && size <= MAX_SIZE);
+
+    HEAP.with(|heap| unsafe { (*heap.get()).allocate(size_class(size)) })
+}
+
+/// Releases memory allocated using `allocate()`.
+///
+/// The memory may be released by any thread, not just the thread that
+/// allocated it.
+pub(crate) unsafe fn free(pointer: *mut u8) {
+    let slab = Slab::of(pointer);
+
+    HEAP.with(|heap| (*heap.get()).release(slab, pointer));
+}
+
+/// Reduces the amount of memory retained by the current thread.
+///
+/// This is meant to be called when a thread runs out of work, such that memory
+/// no longer needed is returned to the system allocator.
+pub(crate) fn trim() {
+    HEAP.with(|heap| unsafe { (*heap.get()).trim() });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn with_heap<R, F: FnOnce(&mut Heap) -> R>(func: F) -> R {
+        HEAP.with(|heap| func(unsafe { &mut *heap.get() }))
+    }
+
+    #[test]
+    fn test_size_class() {
+        assert_eq!(size_class(1), 0);
+        assert_eq!(size_class(16), 0);
+        assert_eq!(size_class(17), 1);
+        assert_eq!(size_class(32), 1);
+        assert_eq!(size_class(MAX_SIZE), SIZE_CLASSES - 1);
+    }
+
+    #[test]
+    fn test_block_size() {
+        assert_eq!(block_size(0), 16);
+        assert_eq!(block_size(1), 32);
+        assert_eq!(block_size(SIZE_CLASSES - 1), MAX_SIZE);
+    }
+
+    #[test]
+    fn test_allocate() {
+        let ptr1 = allocate(16);
+        let ptr2 = allocate(16);
+        let ptr3 = allocate(48);
+
+        assert_eq!(ptr1 as usize % SIZE_STEP, 0);
+        assert_eq!(ptr2 as usize - ptr1 as usize, 16);
+        assert_ne!(Slab::of(ptr1), Slab::of(ptr3));
+        assert_eq!(unsafe { (*Slab::of(ptr1)).used }, 2);
+
+        unsafe {
+            free(ptr1);
+            free(ptr2);
+            free(ptr3);
+        }
+    }
+
+    #[test]
+    fn test_free_reuses_blocks() {
+        let ptr1 = allocate(64);
+        let ptr2 = allocate(64);
+
+        unsafe { free(ptr2) };
+
+        assert_eq!(allocate(64), ptr2);
+
+        unsafe {
+            free(ptr1);
+            free(ptr2);
+        }
+    }
+
+    #[test]
+    fn test_free_empty_slab() {
+        let ptr = allocate(512);
+        let slab = Slab::of(ptr);
+
+        unsafe { free(ptr) };
+
+        with_heap(|heap| {
+            assert!(heap.classes[size_class(512)].is_null());
+            assert_eq!(heap.empty, slab);
+            assert_eq!(heap.empty_slabs, 1);
+        });
+
+        trim();
+        with_heap(|heap| {
+            assert!(heap.empty.is_null());
+            assert_eq!(heap.empty_slabs, 0);
+        });
+    }
+
+    #[test]
+    fn test_allocate_with_full_slab() {
+        let size = 256;
+        let ptrs: Vec<_> =
+            (0..(SLAB_SIZE / size) + 1).map(|_| allocate(size)).collect();
+        let first = Slab::of(ptrs[0]);
+        let last = Slab::of(*ptrs.last().unwrap());
+
+        assert_ne!(first, last);
+        assert!(unsafe { !(*first).listed });
+        assert!(unsafe { (*last).listed });
+
+        unsafe { free(ptrs[0]) };
+
+        assert!(unsafe { (*first).listed });
+
+        for ptr in ptrs.into_iter().skip(1) {
+            unsafe { free(ptr) };
+        }
+    }
+
+    #[test]
+    fn test_free_from_other_thread() {
+        let ptr1 = allocate(32) as usize;
+        let ptr2 = allocate(32) as usize;
+        let slab = Slab::of(ptr1 as _);
+
+        thread::spawn(move || unsafe { free(ptr1 as _) }).join().unwrap();
+
+        unsafe {
+            assert_eq!((*slab).used, 2);
+            assert_eq!((*slab).remote.load(Ordering::Acquire), ptr1 | QUEUED);
+        }
+
+        with_heap(|heap| unsafe {
+            assert_eq!((*heap.shared).queue.load(Ordering::Acquire), slab);
+            heap.process_queue();
+        });
+
+        unsafe {
+            assert_eq!((*slab).used, 1);
+            assert_eq!((*slab).remote.load(Ordering::Acquire), 0);
+            assert_eq!((*slab).free as usize, ptr1);
+            free(ptr2 as _);
+        }
+    }
+}
@@ -1,15 +1,17 @@
 use crate::arc_without_weak::ArcWithoutWeak;
+use crate::clock::Clock;
 use crate::config::Config;
 use crate::mem::{ByteArray, Class, ClassPointer, String as InkoString};
 use crate::network_poller::NetworkPoller;
+use crate::profiler::cpu;
 use crate::scheduler::process::Scheduler;
 use crate::scheduler::timeout_worker::TimeoutWorker;
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
 use std::env;
 use std::mem::size_of;
 use std::panic::RefUnwindSafe;
-use std::time;
 
 /// Allocates a new class, returning a tuple containing the owned pointer and a
 /// permanent reference pointer.
@@ -86,8 +88,8 @@ pub struct State {
     /// The runtime's configuration.
     pub(crate) config: Config,
 
-    /// The start time of the VM (more or less).
-    pub(crate) start_time: time::Instant,
+    /// The clock used for measuring the time since the VM started.
+    pub(crate) clock: Clock,
 
     /// The commandline arguments passed to an Inko program.
     pub(crate) arguments: Vec<String>,
@@ -123,15 +125,29 @@ impl State {
         let byte_array_class =
             class!("ByteArray", counts.byte_array_class, ByteArray);
 
-        let mut rng = thread_rng();
+        let environment = Env::new();
+        let (mut rng, scheduler, clock) = if config.deterministic {
+            (
+                StdRng::seed_from_u64(config.seed),
+                Scheduler::deterministic(
+                    config.stack_size as usize,
+                    config.seed,
+                ),
+                Clock::simulated(),
+            )
+        } else {
+            (
+                StdRng::from_entropy(),
+                Scheduler::new(
+                    config.process_threads as usize,
+                    config.backup_threads as usize,
+                    config.stack_size as usize,
+                ),
+                Clock::new(),
+            )
+        };
         let hash_key0 = rng.gen();
         let hash_key1 = rng.gen();
-        let environment = Env::new();
-        let scheduler = Scheduler::new(
-            config.process_threads as usize,
-            config.backup_threads as usize,
-            config.stack_size as usize,
-        );
 
         let network_pollers =
             (0..config.netpoll_threads).map(|_| NetworkPoller::new()).collect();
@@ -142,7 +158,7 @@ impl State {
             scheduler,
             environment,
             config,
-            start_time: time::Instant::now(),
+            clock,
             timeout_worker: TimeoutWorker::new(),
             arguments,
             network_pollers,
@@ -154,6 +170,9 @@ impl State {
     }
 
     pub(crate) fn terminate(&self) {
+        // The profiler signals the process threads, so it must stop doing so
+        // before these threads terminate.
+        cpu::stop_sampling();
         self.scheduler.terminate();
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
     Arguments, ClassInstance, Database, ForeignType, MethodId, TraitInstance,
     TypeArguments, TypeBounds, TypeId, TypeParameterId, TypePlaceholderId,
-    TypeRef, FLOAT_ID, INT_ID,
+    TypeRef, INT_ID,
 };
 use std::collections::HashSet;
 
@@ -338,6 +338,9 @@ impl<'a> TypeChecker<'a> {
                 TypeRef::Owned(right_id) | TypeRef::Infer(right_id) => {
                     self.check_type_id(left_id, right_id, env, rules)
                 }
+                TypeRef::Uni(_) if left.is_inline_class_instance(self.db) => {
+                    false
+                }
                 TypeRef::Ref(right_id)
                 | TypeRef::Mut(right_id)
                 | TypeRef::Uni(right_id)
@@ -567,8 +570,13 @@ impl<'a> TypeChecker<'a> {
                         },
                     )
                 }
+                // Inline and extern values don't have an object header, so
+                // they can't be turned into trait objects.
                 TypeId::TraitInstance(rhs)
-                    if !lhs.instance_of().kind(self.db).is_extern() =>
+                    if !lhs
+                        .instance_of()
+                        .kind(self.db)
+                        .is_stack_allocated() =>
                 {
                     if rules.type_cast && !lhs.instance_of().allow_cast(self.db)
                     {
@@ -593,7 +601,11 @@ impl<'a> TypeChecker<'a> {
                         )
                     })
                 }
-                TypeId::Foreign(_) => rules.type_cast,
+                TypeId::Foreign(rhs) => {
+                    // Fixed-width numeric types are compatible with their
+                    // C counterparts, so they can be passed to C functions.
+                    rules.type_cast || lhs.instance_of.sized_type() == Some(rhs)
+                }
                 _ => false,
             },
             TypeId::TraitInstance(lhs) => match right_id {
@@ -653,8 +665,7 @@ impl<'a> TypeChecker<'a> {
                         TypeId::ClassInstance(ins) => {
                             // 64-bits integers can be cast to Inko objects, as
                             // this is needed when interfacing with C.
-                            matches!(ins.instance_of().0, INT_ID | FLOAT_ID)
-                                || lsize == 64
+                            ins.instance_of().is_numeric() || lsize == 64
                         }
                         _ => lsize == 64,
                     }
@@ -663,6 +674,10 @@ impl<'a> TypeChecker<'a> {
                         TypeId::Foreign(ForeignType::Int(rsize, rsigned)) => {
                             lsize == rsize && lsigned == rsigned
                         }
+                        TypeId::ClassInstance(ins) => {
+                            ins.instance_of().sized_type()
+                                == Some(ForeignType::Int(lsize, lsigned))
+                        }
                         _ => false,
                     }
                 }
@@ -672,7 +687,7 @@ impl<'a> TypeChecker<'a> {
                     match right_id {
                         TypeId::Foreign(_) => true,
                         TypeId::ClassInstance(ins) => {
-                            matches!(ins.instance_of().0, INT_ID | FLOAT_ID)
+                            ins.instance_of().is_numeric()
                         }
                         _ => false,
                     }
@@ -681,6 +696,10 @@ impl<'a> TypeChecker<'a> {
                         TypeId::Foreign(ForeignType::Float(rsize)) => {
                             lsize == rsize
                         }
+                        TypeId::ClassInstance(ins) => {
+                            ins.instance_of().sized_type()
+                                == Some(ForeignType::Float(lsize))
+                        }
                         _ => false,
                     }
                 }
@@ -1045,9 +1064,9 @@ mod tests {
     use crate::test::{
         closure, generic_instance_id, generic_trait_instance,
         generic_trait_instance_id, immutable, implement, infer, instance,
-        mutable, new_class, new_extern_class, new_parameter, new_trait, owned,
-        parameter, placeholder, pointer, rigid, trait_instance,
-        trait_instance_id, type_arguments, type_bounds, uni,
+        mutable, new_class, new_extern_class, new_inline_class, new_parameter,
+        new_trait, owned, parameter, placeholder, pointer, rigid,
+        trait_instance, trait_instance_id, type_arguments, type_bounds, uni,
     };
     use crate::{
         Block, Class, ClassId, ClassKind, Closure, ModuleId,
@@ -1158,6 +1177,38 @@ mod tests {
         check_err(&db, owned(instance(foo)), TypeRef::Never);
     }
 
+    #[test]
+    fn test_inline_class_instance() {
+        let mut db = Database::new();
+        let foo = new_inline_class(&mut db, "Foo");
+        let bar = new_inline_class(&mut db, "Bar");
+        let to_string = new_trait(&mut db, "ToString");
+
+        implement(&mut db, trait_instance(to_string), foo);
+
+        check_ok(&db, owned(instance(foo)), owned(instance(foo)));
+        check_ok(&db, owned(instance(foo)), immutable(instance(foo)));
+        check_ok(&db, owned(instance(foo)), mutable(instance(foo)));
+
+        check_err(&db, owned(instance(foo)), owned(instance(bar)));
+        check_err(&db, owned(instance(foo)), uni(instance(foo)));
+        check_err(
+            &db,
+            owned(instance(foo)),
+            owned(trait_instance_id(to_string)),
+        );
+        check_err(
+            &db,
+            owned(instance(foo)),
+            immutable(trait_instance_id(to_string)),
+        );
+        check_err_cast(
+            &db,
+            owned(instance(foo)),
+            owned(trait_instance_id(to_string)),
+        );
+    }
+
     #[test]
     fn test_extern_class_instance() {
         let mut db = Database::new();
@@ -40,8 +40,16 @@ const TUPLE7_ID: u32 = 13;
 const TUPLE8_ID: u32 = 14;
 const ARRAY_ID: u32 = 15;
 const CHECKED_INT_RESULT_ID: u32 = 16;
+const INT8_ID: u32 = 17;
+const INT16_ID: u32 = 18;
+const INT32_ID: u32 = 19;
+const UINT8_ID: u32 = 20;
+const UINT16_ID: u32 = 21;
+const UINT32_ID: u32 = 22;
+const UINT64_ID: u32 = 23;
+const FLOAT32_ID: u32 = 24;
 
-pub const FIRST_USER_CLASS_ID: u32 = CHECKED_INT_RESULT_ID + 1;
+pub const FIRST_USER_CLASS_ID: u32 = FLOAT32_ID + 1;
 
 /// The default module ID to assign to builtin types.
 ///
@@ -65,6 +73,14 @@ const TUPLE6_NAME: &str = "Tuple6";
 const TUPLE7_NAME: &str = "Tuple7";
 const TUPLE8_NAME: &str = "Tuple8";
 const CHECKED_INT_RESULT_NAME: &str = "CheckedIntResult";
+const INT8_NAME: &str = "Int8";
+const INT16_NAME: &str = "Int16";
+const INT32_NAME: &str = "Int32";
+const UINT8_NAME: &str = "UInt8";
+const UINT16_NAME: &str = "UInt16";
+const UINT32_NAME: &str = "UInt32";
+const UINT64_NAME: &str = "UInt64";
+const FLOAT32_NAME: &str = "Float32";
 
 pub const STRING_MODULE: &str = "std.string";
 pub const TO_STRING_TRAIT: &str = "ToString";
@@ -86,6 +102,11 @@ pub const OPTION_SOME: &str = "Some";
 pub const OPTION_NONE: &str = "None";
 pub const RESULT_OK: &str = "Ok";
 pub const RESULT_ERROR: &str = "Error";
+pub const FUTURE_MODULE: &str = "std.future";
+pub const FUTURE_CLASS: &str = "Future";
+pub const FUTURE_NEW: &str = "new";
+pub const FUTURE_HANDLE: &str = "handle";
+pub const FUTURE_SET: &str = "set";
 pub const ARRAY_WITH_CAPACITY: &str = "with_capacity";
 pub const ARRAY_PUSH: &str = "push";
 pub const ARRAY_INTERNAL_NAME: &str = "$Array";
@@ -616,7 +637,7 @@ impl TraitId {
             .map(|&id| Symbol::TypeParameter(id))
     }
 
-    fn module(self, db: &Database) -> ModuleId {
+    pub fn module(self, db: &Database) -> ModuleId {
         self.get(db).module
     }
 
@@ -937,6 +958,7 @@ pub enum ClassKind {
     Closure,
     Enum,
     Extern,
+    Inline,
     Module,
     Regular,
     Tuple,
@@ -972,8 +994,21 @@ impl ClassKind {
         matches!(self, ClassKind::Extern)
     }
 
+    pub fn is_inline(self) -> bool {
+        matches!(self, ClassKind::Inline)
+    }
+
+    /// Returns `true` if instances of this kind are stored by value (i.e. on
+    /// the stack or in-place) instead of being heap allocated.
+    pub fn is_stack_allocated(self) -> bool {
+        matches!(self, ClassKind::Extern | ClassKind::Inline)
+    }
+
     pub fn allow_pattern_matching(self) -> bool {
-        matches!(self, ClassKind::Regular | ClassKind::Extern)
+        matches!(
+            self,
+            ClassKind::Regular | ClassKind::Extern | ClassKind::Inline
+        )
     }
 
     fn is_atomic(self) -> bool {
@@ -986,6 +1021,7 @@ impl ClassKind {
             ClassKind::Async
                 | ClassKind::Atomic
                 | ClassKind::Extern
+                | ClassKind::Inline
                 | ClassKind::ValueType
         )
     }
@@ -1170,6 +1206,38 @@ impl ClassId {
         ClassId(CHECKED_INT_RESULT_ID)
     }
 
+    pub fn int8() -> ClassId {
+        ClassId(INT8_ID)
+    }
+
+    pub fn int16() -> ClassId {
+        ClassId(INT16_ID)
+    }
+
+    pub fn int32() -> ClassId {
+        ClassId(INT32_ID)
+    }
+
+    pub fn uint8() -> ClassId {
+        ClassId(UINT8_ID)
+    }
+
+    pub fn uint16() -> ClassId {
+        ClassId(UINT16_ID)
+    }
+
+    pub fn uint32() -> ClassId {
+        ClassId(UINT32_ID)
+    }
+
+    pub fn uint64() -> ClassId {
+        ClassId(UINT64_ID)
+    }
+
+    pub fn float32() -> ClassId {
+        ClassId(FLOAT32_ID)
+    }
+
     pub fn tuple(len: usize) -> Option<ClassId> {
         match len {
             1 => Some(ClassId::tuple1()),
@@ -1404,7 +1472,7 @@ impl ClassId {
     }
 
     pub fn is_builtin(self) -> bool {
-        self.0 <= CHANNEL_ID
+        self.0 <= CHANNEL_ID || self.is_sized()
     }
 
     pub fn is_value_type(self, db: &Database) -> bool {
@@ -1416,13 +1484,36 @@ impl ClassId {
     }
 
     pub fn is_numeric(self) -> bool {
-        matches!(self.0, INT_ID | FLOAT_ID)
+        matches!(self.0, INT_ID | FLOAT_ID) || self.is_sized()
+    }
+
+    /// Returns `true` if `self` is one of the fixed-width numeric types, such
+    /// as `Int8` or `Float32`.
+    pub fn is_sized(self) -> bool {
+        self.sized_type().is_some()
+    }
+
+    /// Returns the machine type of a fixed-width numeric type.
+    pub fn sized_type(self) -> Option<ForeignType> {
+        match self.0 {
+            INT8_ID => Some(ForeignType::Int(8, true)),
+            INT16_ID => Some(ForeignType::Int(16, true)),
+            INT32_ID => Some(ForeignType::Int(32, true)),
+            UINT8_ID => Some(ForeignType::Int(8, false)),
+            UINT16_ID => Some(ForeignType::Int(16, false)),
+            UINT32_ID => Some(ForeignType::Int(32, false)),
+            UINT64_ID => Some(ForeignType::Int(64, false)),
+            FLOAT32_ID => Some(ForeignType::Float(32)),
+            _ => None,
+        }
     }
 
     pub fn allow_cast(self, db: &Database) -> bool {
         match self.0 {
             INT_ID | FLOAT_ID | BOOL_ID | NIL_ID | STRING_ID => false,
+            _ if self.is_sized() => false,
             _ if self.kind(db).is_atomic() => false,
+            _ if self.kind(db).is_inline() => false,
             _ => true,
         }
     }
@@ -1434,7 +1525,9 @@ impl ClassId {
             BOOL_ID => Shape::Boolean,
             NIL_ID => Shape::Nil,
             STRING_ID => Shape::String,
+            _ if self.is_sized() => Shape::Sized(self),
             _ if self.kind(db).is_atomic() => Shape::Atomic,
+            _ if self.kind(db).is_inline() => Shape::Inline(self),
             _ => default,
         }
     }
@@ -1694,6 +1787,22 @@ pub enum BuiltinFunction {
     IntCheckedAdd,
     IntCheckedMul,
     IntCheckedSub,
+    PointerValueSize,
+    SizedAdd,
+    SizedSub,
+    SizedMul,
+    SizedDiv,
+    SizedRem,
+    SizedEq,
+    SizedLt,
+    SizedLe,
+    SizedGt,
+    SizedGe,
+    SizedBitAnd,
+    SizedBitOr,
+    SizedBitXor,
+    SizedShl,
+    SizedShr,
 }
 
 impl BuiltinFunction {
@@ -1744,6 +1853,22 @@ impl BuiltinFunction {
             BuiltinFunction::Process,
             BuiltinFunction::FloatRound,
             BuiltinFunction::FloatPowi,
+            BuiltinFunction::PointerValueSize,
+            BuiltinFunction::SizedAdd,
+            BuiltinFunction::SizedSub,
+            BuiltinFunction::SizedMul,
+            BuiltinFunction::SizedDiv,
+            BuiltinFunction::SizedRem,
+            BuiltinFunction::SizedEq,
+            BuiltinFunction::SizedLt,
+            BuiltinFunction::SizedLe,
+            BuiltinFunction::SizedGt,
+            BuiltinFunction::SizedGe,
+            BuiltinFunction::SizedBitAnd,
+            BuiltinFunction::SizedBitOr,
+            BuiltinFunction::SizedBitXor,
+            BuiltinFunction::SizedShl,
+            BuiltinFunction::SizedShr,
         ]
         .into_iter()
         .fold(HashMap::new(), |mut map, func| {
@@ -1797,12 +1922,55 @@ impl BuiltinFunction {
             BuiltinFunction::StringConcat => "string_concat",
             BuiltinFunction::State => "state",
             BuiltinFunction::Process => "process",
+            BuiltinFunction::PointerValueSize => "pointer_value_size",
             BuiltinFunction::FloatRound => "float_round",
             BuiltinFunction::FloatPowi => "float_powi",
+            BuiltinFunction::SizedAdd => "sized_add",
+            BuiltinFunction::SizedSub => "sized_sub",
+            BuiltinFunction::SizedMul => "sized_mul",
+            BuiltinFunction::SizedDiv => "sized_div",
+            BuiltinFunction::SizedRem => "sized_rem",
+            BuiltinFunction::SizedEq => "sized_eq",
+            BuiltinFunction::SizedLt => "sized_lt",
+            BuiltinFunction::SizedLe => "sized_le",
+            BuiltinFunction::SizedGt => "sized_gt",
+            BuiltinFunction::SizedGe => "sized_ge",
+            BuiltinFunction::SizedBitAnd => "sized_bit_and",
+            BuiltinFunction::SizedBitOr => "sized_bit_or",
+            BuiltinFunction::SizedBitXor => "sized_bit_xor",
+            BuiltinFunction::SizedShl => "sized_shl",
+            BuiltinFunction::SizedShr => "sized_shr",
         }
     }
 
-    pub fn return_type(self) -> TypeRef {
+    /// Returns `true` if calls to this function can be evaluated at
+    /// compile-time, such as when used in a constant.
+    pub fn is_const(self) -> bool {
+        matches!(
+            self,
+            BuiltinFunction::FloatCeil
+                | BuiltinFunction::FloatFloor
+                | BuiltinFunction::FloatFromBits
+                | BuiltinFunction::FloatIsInf
+                | BuiltinFunction::FloatIsNan
+                | BuiltinFunction::FloatRound
+                | BuiltinFunction::FloatToBits
+                | BuiltinFunction::IntBitNot
+                | BuiltinFunction::IntRotateLeft
+                | BuiltinFunction::IntRotateRight
+                | BuiltinFunction::IntWrappingAdd
+                | BuiltinFunction::IntWrappingMul
+                | BuiltinFunction::IntWrappingSub
+        )
+    }
+
+    /// Returns the type of the value returned by this function.
+    ///
+    /// Functions operating on fixed-width numeric types support all such types,
+    /// and return a value of the same type as their first argument.
+    pub fn return_type(self, db: &Database, arguments: &[TypeRef]) -> TypeRef {
+        let same_as_argument =
+            arguments.first().map_or(TypeRef::Error, |&typ| typ.as_owned(db));
         let checked_result = TypeRef::Owned(TypeId::ClassInstance(
             ClassInstance::new(ClassId::checked_int_result()),
         ));
@@ -1855,8 +2023,24 @@ impl BuiltinFunction {
             BuiltinFunction::Process => {
                 TypeRef::pointer(TypeId::Foreign(ForeignType::Int(8, false)))
             }
+            BuiltinFunction::PointerValueSize => TypeRef::int(),
             BuiltinFunction::FloatRound => TypeRef::float(),
             BuiltinFunction::FloatPowi => TypeRef::float(),
+            BuiltinFunction::SizedAdd => same_as_argument,
+            BuiltinFunction::SizedSub => same_as_argument,
+            BuiltinFunction::SizedMul => same_as_argument,
+            BuiltinFunction::SizedDiv => same_as_argument,
+            BuiltinFunction::SizedRem => same_as_argument,
+            BuiltinFunction::SizedEq => TypeRef::boolean(),
+            BuiltinFunction::SizedLt => TypeRef::boolean(),
+            BuiltinFunction::SizedLe => TypeRef::boolean(),
+            BuiltinFunction::SizedGt => TypeRef::boolean(),
+            BuiltinFunction::SizedGe => TypeRef::boolean(),
+            BuiltinFunction::SizedBitAnd => same_as_argument,
+            BuiltinFunction::SizedBitOr => same_as_argument,
+            BuiltinFunction::SizedBitXor => same_as_argument,
+            BuiltinFunction::SizedShl => same_as_argument,
+            BuiltinFunction::SizedShr => same_as_argument,
         }
     }
 }
@@ -1965,6 +2149,9 @@ pub struct Method {
     main: bool,
     variadic: bool,
 
+    /// A flag indicating the method is marked as tail recursive.
+    tail_recursive: bool,
+
     /// The type of the receiver of the method.
     receiver: TypeRef,
 
@@ -2007,6 +2194,7 @@ impl Method {
             field_types: HashMap::new(),
             main: false,
             variadic: false,
+            tail_recursive: false,
             specializations: HashMap::new(),
             shapes: Vec::new(),
         };
@@ -2149,6 +2337,14 @@ impl MethodId {
         self.get(db).variadic
     }
 
+    pub fn set_tail_recursive(self, db: &mut Database) {
+        self.get_mut(db).tail_recursive = true;
+    }
+
+    pub fn is_tail_recursive(self, db: &Database) -> bool {
+        self.get(db).tail_recursive
+    }
+
     pub fn positional_argument_input_type(
         self,
         db: &Database,
@@ -2273,6 +2469,12 @@ impl MethodId {
         self.has_return_type(db) && !self.return_type(db).is_never(db)
     }
 
+    /// Returns `true` if this is an async method that returns its value
+    /// through a future.
+    pub fn returns_future(self, db: &Database) -> bool {
+        self.is_async(db) && !self.ignore_return_value(db)
+    }
+
     pub fn add_specialization(
         self,
         db: &mut Database,
@@ -2299,15 +2501,23 @@ impl MethodId {
     }
 
     pub fn clone_for_specialization(self, db: &mut Database) -> MethodId {
-        let (module, name, vis, kind, source) = {
+        let (module, name, vis, kind, source, tail) = {
             let old = self.get(db);
 
-            (old.module, old.name.clone(), old.visibility, old.kind, old.source)
+            (
+                old.module,
+                old.name.clone(),
+                old.visibility,
+                old.kind,
+                old.source,
+                old.tail_recursive,
+            )
         };
 
         let new = Method::alloc(db, module, name, vis, kind);
 
         new.set_source(db, source);
+        new.get_mut(db).tail_recursive = tail;
         new
     }
 
@@ -3006,20 +3216,34 @@ pub enum Shape {
 
     /// The value is an owned value that uses atomic reference counting.
     Atomic,
+
+    /// The value is an instance of an inline class, stored and passed around
+    /// by value.
+    ///
+    /// Inline classes differ in size, so each inline class has its own shape.
+    Inline(ClassId),
+
+    /// An unboxed fixed-width integer or float, such as `Int8` or `Float32`.
+    ///
+    /// These values are passed around using a simple copy. The types differ in
+    /// size, so each type has its own shape.
+    Sized(ClassId),
 }
 
 impl Shape {
-    pub fn identifier(&self) -> &'static str {
+    pub fn identifier(&self) -> String {
         match self {
-            Shape::Owned => "o",
-            Shape::Mut => "m",
-            Shape::Ref => "r",
-            Shape::Int => "i",
-            Shape::Float => "f",
-            Shape::Boolean => "b",
-            Shape::String => "s",
-            Shape::Atomic => "a",
-            Shape::Nil => "n",
+            Shape::Owned => "o".to_string(),
+            Shape::Mut => "m".to_string(),
+            Shape::Ref => "r".to_string(),
+            Shape::Int => "i".to_string(),
+            Shape::Float => "f".to_string(),
+            Shape::Boolean => "b".to_string(),
+            Shape::String => "s".to_string(),
+            Shape::Atomic => "a".to_string(),
+            Shape::Nil => "n".to_string(),
+            Shape::Inline(id) => format!("v{}", id.0),
+            Shape::Sized(id) => format!("z{}", id.0),
         }
     }
 }
@@ -3200,12 +3424,41 @@ impl TypeRef {
         }
     }
 
-    /// Returns `true` if `self` is an instance of a class that's allocated on
-    /// and passed around using the stack.
+    /// Returns `true` if `self` is an instance of an extern class, which is
+    /// allocated on and passed around using the stack.
     pub fn is_stack_class_instance(self, db: &Database) -> bool {
         self.class_id(db).map_or(false, |c| c.kind(db).is_extern())
     }
 
+    /// Returns `true` if `self` is an instance of an inline class.
+    pub fn is_inline_class_instance(self, db: &Database) -> bool {
+        self.class_id(db).map_or(false, |c| c.kind(db).is_inline())
+    }
+
+    /// Returns `true` if a value of this type can be stored in a field of an
+    /// inline class.
+    ///
+    /// Instances of inline classes are copied, so their fields can only store
+    /// values that can be copied without the need for reference counting or
+    /// running destructors.
+    pub fn allow_in_inline_class(self, db: &Database) -> bool {
+        match self {
+            TypeRef::Owned(TypeId::ClassInstance(ins)) => {
+                match ins.instance_of.0 {
+                    INT_ID | FLOAT_ID | BOOL_ID | NIL_ID => true,
+                    _ if ins.instance_of.is_sized() => true,
+                    _ => ins.instance_of.kind(db).is_stack_allocated(),
+                }
+            }
+            TypeRef::Owned(TypeId::Foreign(_)) => true,
+            TypeRef::Pointer(_) => true,
+            TypeRef::Placeholder(id) => {
+                id.value(db).map_or(false, |v| v.allow_in_inline_class(db))
+            }
+            _ => false,
+        }
+    }
+
     pub fn is_pointer(self, db: &Database) -> bool {
         match self {
             TypeRef::Pointer(_) => true,
@@ -3451,6 +3704,10 @@ impl TypeRef {
         self.is_instance_of(db, ClassId::int())
     }
 
+    pub fn is_float(self, db: &Database) -> bool {
+        self.is_instance_of(db, ClassId::float())
+    }
+
     pub fn is_string(self, db: &Database) -> bool {
         self.is_instance_of(db, ClassId::string())
     }
@@ -3459,6 +3716,22 @@ impl TypeRef {
         self.is_instance_of(db, ClassId::nil())
     }
 
+    /// Returns `true` if values of this type can be passed to and returned
+    /// from C functions as-is.
+    pub fn is_c_compatible(self, db: &Database) -> bool {
+        match self {
+            TypeRef::Owned(TypeId::Foreign(_)) | TypeRef::Pointer(_) => true,
+            TypeRef::Placeholder(id) => {
+                id.value(db).map_or(false, |v| v.is_c_compatible(db))
+            }
+            _ => {
+                self.is_instance_of(db, ClassId::int())
+                    || self.is_instance_of(db, ClassId::float())
+                    || self.class_id(db).map_or(false, |id| id.is_sized())
+            }
+        }
+    }
+
     pub fn allow_moving(self) -> bool {
         matches!(self, TypeRef::Owned(_) | TypeRef::Uni(_))
     }
@@ -3493,6 +3766,13 @@ impl TypeRef {
     }
 
     pub fn is_sendable(self, db: &Database) -> bool {
+        // Inline values are copied, but the runtime only supports sending
+        // values that are the size of a pointer, so they can't be sent
+        // between processes.
+        if self.is_inline_class_instance(db) {
+            return false;
+        }
+
         if self.is_value_type(db) {
             return true;
         }
@@ -3936,6 +4216,20 @@ impl TypeRef {
         )))
     }
 
+    /// Returns the type of the future produced by calling an async method
+    /// that returns a value of the given type.
+    pub fn future_type(db: &mut Database, value: TypeRef) -> TypeRef {
+        let class = db.class_in_module(FUTURE_MODULE, FUTURE_CLASS);
+        let params = class.type_parameters(db);
+        let mut args = TypeArguments::new();
+
+        args.assign(params[0], value);
+
+        TypeRef::Owned(TypeId::ClassInstance(ClassInstance::generic(
+            db, class, args,
+        )))
+    }
+
     pub fn shape(
         self,
         db: &Database,
@@ -4160,6 +4454,9 @@ pub struct Database {
     main_module: Option<ModuleName>,
     main_method: Option<MethodId>,
     main_class: Option<ClassId>,
+
+    /// The methods to export as C functions when compiling a library.
+    exported_methods: Vec<MethodId>,
 }
 
 impl Database {
@@ -4191,6 +4488,14 @@ impl Database {
                     Visibility::Private,
                     ModuleId(DEFAULT_BUILTIN_MODULE_ID),
                 ),
+                Class::value_type(INT8_NAME.to_string()),
+                Class::value_type(INT16_NAME.to_string()),
+                Class::value_type(INT32_NAME.to_string()),
+                Class::value_type(UINT8_NAME.to_string()),
+                Class::value_type(UINT16_NAME.to_string()),
+                Class::value_type(UINT32_NAME.to_string()),
+                Class::value_type(UINT64_NAME.to_string()),
+                Class::value_type(FLOAT32_NAME.to_string()),
             ],
             type_parameters: Vec::new(),
             type_arguments: Vec::new(),
@@ -4206,6 +4511,7 @@ impl Database {
             main_module: None,
             main_method: None,
             main_class: None,
+            exported_methods: Vec::new(),
         }
     }
 
@@ -4228,6 +4534,14 @@ impl Database {
             TUPLE7_NAME => Some(ClassId::tuple7()),
             TUPLE8_NAME => Some(ClassId::tuple8()),
             CHECKED_INT_RESULT_NAME => Some(ClassId::checked_int_result()),
+            INT8_NAME => Some(ClassId::int8()),
+            INT16_NAME => Some(ClassId::int16()),
+            INT32_NAME => Some(ClassId::int32()),
+            UINT8_NAME => Some(ClassId::uint8()),
+            UINT16_NAME => Some(ClassId::uint16()),
+            UINT32_NAME => Some(ClassId::uint32()),
+            UINT64_NAME => Some(ClassId::uint64()),
+            FLOAT32_NAME => Some(ClassId::float32()),
             _ => None,
         }
     }
@@ -4309,6 +4623,14 @@ impl Database {
     pub fn main_class(&self) -> Option<ClassId> {
         self.main_class
     }
+
+    pub fn add_exported_method(&mut self, id: MethodId) {
+        self.exported_methods.push(id);
+    }
+
+    pub fn exported_methods(&self) -> &[MethodId] {
+        &self.exported_methods
+    }
 }
 
 #[cfg(test)]
@@ -4316,8 +4638,9 @@ mod tests {
     use super::*;
     use crate::test::{
         closure, generic_instance_id, generic_trait_instance, immutable, infer,
-        instance, mutable, new_async_class, new_class, new_parameter,
-        new_trait, owned, parameter, placeholder, rigid, uni,
+        instance, mutable, new_async_class, new_class, new_extern_class,
+        new_inline_class, new_parameter, new_trait, owned, parameter,
+        placeholder, rigid, uni,
     };
     use std::mem::size_of;
 
@@ -4860,6 +5183,9 @@ mod tests {
         assert_eq!(&db.classes[NIL_ID as usize].name, NIL_NAME);
         assert_eq!(&db.classes[BYTE_ARRAY_ID as usize].name, BYTE_ARRAY_NAME);
         assert_eq!(&db.classes[CHANNEL_ID as usize].name, CHANNEL_NAME);
+        assert_eq!(&db.classes[INT8_ID as usize].name, INT8_NAME);
+        assert_eq!(&db.classes[UINT64_ID as usize].name, UINT64_NAME);
+        assert_eq!(&db.classes[FLOAT32_ID as usize].name, FLOAT32_NAME);
     }
 
     #[test]
@@ -4882,6 +5208,7 @@ mod tests {
     #[test]
     fn test_class_id_is_builtin() {
         assert!(ClassId::int().is_builtin());
+        assert!(ClassId::uint8().is_builtin());
         assert!(!ClassId::tuple8().is_builtin());
         assert!(!ClassId(42).is_builtin());
     }
@@ -5005,6 +5332,31 @@ mod tests {
         assert!(!owned(closure(func2)).is_sendable(&db));
     }
 
+    #[test]
+    fn test_type_ref_is_sendable_with_inline_class() {
+        let mut db = Database::new();
+        let class = new_inline_class(&mut db, "Point");
+
+        assert!(!owned(instance(class)).is_sendable(&db));
+        assert!(!uni(instance(class)).is_sendable(&db));
+    }
+
+    #[test]
+    fn test_type_ref_is_c_compatible() {
+        let mut db = Database::new();
+        let thing = new_class(&mut db, "Thing");
+
+        assert!(owned(instance(ClassId::int())).is_c_compatible(&db));
+        assert!(owned(instance(ClassId::float())).is_c_compatible(&db));
+        assert!(TypeRef::foreign_signed_int(32).is_c_compatible(&db));
+        assert!(TypeRef::foreign_float(32).is_c_compatible(&db));
+        assert!(TypeRef::pointer(instance(thing)).is_c_compatible(&db));
+        assert!(owned(instance(ClassId::uint8())).is_c_compatible(&db));
+        assert!(!owned(instance(ClassId::string())).is_c_compatible(&db));
+        assert!(!owned(instance(ClassId::boolean())).is_c_compatible(&db));
+        assert!(!owned(instance(thing)).is_c_compatible(&db));
+    }
+
     #[test]
     fn test_type_ref_as_uni_ref() {
         let db = Database::new();
@@ -5178,6 +5530,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_type_ref_shape_with_inline_class() {
+        let mut db = Database::new();
+        let class = new_inline_class(&mut db, "Point");
+        let shapes = HashMap::new();
+
+        assert_eq!(
+            owned(instance(class)).shape(&db, &shapes),
+            Shape::Inline(class)
+        );
+        assert_eq!(
+            immutable(instance(class)).shape(&db, &shapes),
+            Shape::Inline(class)
+        );
+        assert_eq!(
+            mutable(instance(class)).shape(&db, &shapes),
+            Shape::Inline(class)
+        );
+    }
+
+    #[test]
+    fn test_type_ref_shape_with_sized_class() {
+        let db = Database::new();
+        let shapes = HashMap::new();
+        let class = ClassId::uint8();
+
+        assert_eq!(
+            owned(instance(class)).shape(&db, &shapes),
+            Shape::Sized(class)
+        );
+        assert_eq!(
+            immutable(instance(class)).shape(&db, &shapes),
+            Shape::Sized(class)
+        );
+    }
+
+    #[test]
+    fn test_class_id_sized_type() {
+        assert_eq!(
+            ClassId::int8().sized_type(),
+            Some(ForeignType::Int(8, true))
+        );
+        assert_eq!(
+            ClassId::uint32().sized_type(),
+            Some(ForeignType::Int(32, false))
+        );
+        assert_eq!(
+            ClassId::float32().sized_type(),
+            Some(ForeignType::Float(32))
+        );
+        assert_eq!(ClassId::int().sized_type(), None);
+    }
+
+    #[test]
+    fn test_type_ref_allow_in_inline_class() {
+        let mut db = Database::new();
+        let inline = new_inline_class(&mut db, "Point");
+        let ext = new_extern_class(&mut db, "Foo");
+        let regular = new_class(&mut db, "Thing");
+
+        assert!(TypeRef::int().allow_in_inline_class(&db));
+        assert!(TypeRef::float().allow_in_inline_class(&db));
+        assert!(TypeRef::boolean().allow_in_inline_class(&db));
+        assert!(TypeRef::nil().allow_in_inline_class(&db));
+        assert!(owned(instance(inline)).allow_in_inline_class(&db));
+        assert!(owned(instance(ext)).allow_in_inline_class(&db));
+        assert!(TypeRef::pointer(instance(ext)).allow_in_inline_class(&db));
+        assert!(!TypeRef::string().allow_in_inline_class(&db));
+        assert!(!owned(instance(regular)).allow_in_inline_class(&db));
+        assert!(!immutable(instance(inline)).allow_in_inline_class(&db));
+    }
+
     #[test]
     fn test_type_ref_class_id() {
         let db = Database::new();
@@ -68,6 +68,9 @@ impl<'a, 'b, 'c> TypeSpecializer<'a, 'b, 'c> {
                 Some(Shape::Boolean) => TypeRef::boolean(),
                 Some(Shape::String) => TypeRef::string(),
                 Some(Shape::Nil) => TypeRef::nil(),
+                Some(Shape::Inline(id) | Shape::Sized(id)) => TypeRef::Owned(
+                    TypeId::ClassInstance(ClassInstance::new(*id)),
+                ),
                 Some(Shape::Ref) => value.as_ref(self.db),
                 Some(Shape::Mut) => value.force_as_mut(self.db),
                 Some(Shape::Atomic) => {
@@ -86,6 +89,9 @@ impl<'a, 'b, 'c> TypeSpecializer<'a, 'b, 'c> {
                 Some(Shape::Boolean) => TypeRef::boolean(),
                 Some(Shape::String) => TypeRef::string(),
                 Some(Shape::Nil) => TypeRef::nil(),
+                Some(Shape::Inline(id) | Shape::Sized(id)) => TypeRef::Owned(
+                    TypeId::ClassInstance(ClassInstance::new(*id)),
+                ),
                 Some(Shape::Atomic) => {
                     TypeRef::Ref(TypeId::AtomicTypeParameter(id))
                 }
@@ -102,6 +108,9 @@ impl<'a, 'b, 'c> TypeSpecializer<'a, 'b, 'c> {
                 Some(Shape::Boolean) => TypeRef::boolean(),
                 Some(Shape::String) => TypeRef::string(),
                 Some(Shape::Nil) => TypeRef::nil(),
+                Some(Shape::Inline(id) | Shape::Sized(id)) => TypeRef::Owned(
+                    TypeId::ClassInstance(ClassInstance::new(*id)),
+                ),
                 Some(Shape::Ref) => value.as_ref(self.db),
                 Some(Shape::Atomic) => {
                     TypeRef::Mut(TypeId::AtomicTypeParameter(id))
@@ -133,6 +142,17 @@ impl<'a, 'b, 'c> TypeSpecializer<'a, 'b, 'c> {
             TypeRef::Placeholder(id) => {
                 id.value(self.db).map_or(value, |v| self.specialize(v))
             }
+            // Pointers to inline and fixed-width types must point to these
+            // types and not to the type parameter, as the size of the values
+            // pointed to depends on the type.
+            TypeRef::Pointer(
+                TypeId::TypeParameter(pid) | TypeId::RigidTypeParameter(pid),
+            ) => match self.shapes.get(&pid) {
+                Some(Shape::Inline(id) | Shape::Sized(id)) => TypeRef::Pointer(
+                    TypeId::ClassInstance(ClassInstance::new(*id)),
+                ),
+                _ => value,
+            },
             TypeRef::Pointer(id) => {
                 TypeRef::Pointer(self.specialize_type_id(id))
             }
@@ -349,7 +369,8 @@ mod tests {
     use crate::format::format_type;
     use crate::test::{
         generic_instance_id, immutable, infer, instance, mutable,
-        new_enum_class, new_parameter, owned, parameter, rigid, uni,
+        new_enum_class, new_inline_class, new_parameter, owned, parameter,
+        rigid, uni,
     };
     use crate::{ClassId, ModuleId, Visibility};
 
@@ -631,4 +652,32 @@ mod tests {
         assert_eq!(immutable, TypeRef::Ref(TypeId::TypeParameter(param)));
         assert_eq!(mutable, TypeRef::Mut(TypeId::TypeParameter(param)));
     }
+
+    #[test]
+    fn test_specialize_inline_type_parameter() {
+        let mut db = Database::new();
+        let mut shapes = HashMap::new();
+        let mut classes = Vec::new();
+        let class = new_inline_class(&mut db, "Point");
+        let param = new_parameter(&mut db, "A");
+
+        shapes.insert(param, Shape::Inline(class));
+
+        let owned = TypeSpecializer::new(&mut db, &shapes, &mut classes)
+            .specialize(owned(parameter(param)));
+
+        let immutable = TypeSpecializer::new(&mut db, &shapes, &mut classes)
+            .specialize(immutable(parameter(param)));
+
+        let mutable = TypeSpecializer::new(&mut db, &shapes, &mut classes)
+            .specialize(mutable(parameter(param)));
+
+        let pointer = TypeSpecializer::new(&mut db, &shapes, &mut classes)
+            .specialize(TypeRef::Pointer(parameter(param)));
+
+        assert_eq!(owned, TypeRef::Owned(instance(class)));
+        assert_eq!(immutable, owned);
+        assert_eq!(mutable, owned);
+        assert_eq!(pointer, TypeRef::Pointer(instance(class)));
+    }
 }
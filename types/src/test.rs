@@ -45,6 +45,16 @@ pub(crate) fn new_extern_class(db: &mut Database, name: &str) -> ClassId {
     )
 }
 
+pub(crate) fn new_inline_class(db: &mut Database, name: &str) -> ClassId {
+    Class::alloc(
+        db,
+        name.to_string(),
+        ClassKind::Inline,
+        Visibility::Public,
+        ModuleId(0),
+    )
+}
+
 pub(crate) fn new_trait(db: &mut Database, name: &str) -> TraitId {
     Trait::alloc(db, name.to_string(), Visibility::Public, ModuleId(0))
 }